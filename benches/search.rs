@@ -0,0 +1,40 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use triangular_arbitrage_demo::math::{arbitrage_profit, find_best_input, seeded_pool_paths};
+
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+const PATHS: usize = 64;
+const FEE: f64 = 0.003;
+const ITERATIONS: usize = 100;
+
+fn bench_find_best_input(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_best_input");
+    for max_hops in [3, 5] {
+        let paths = seeded_pool_paths(SEED, PATHS, max_hops);
+        group.bench_with_input(BenchmarkId::new("max_hops", max_hops), &paths, |b, paths| {
+            b.iter(|| {
+                for pools in paths {
+                    black_box(find_best_input(black_box(pools), FEE, ITERATIONS));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_arbitrage_profit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("arbitrage_profit");
+    for max_hops in [3, 5] {
+        let paths = seeded_pool_paths(SEED, PATHS, max_hops);
+        group.bench_with_input(BenchmarkId::new("max_hops", max_hops), &paths, |b, paths| {
+            b.iter(|| {
+                for pools in paths {
+                    black_box(arbitrage_profit(black_box(100.0), black_box(pools), FEE));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_find_best_input, bench_arbitrage_profit);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+use std::error::Error;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use serde::Serialize;
+use crate::shutdown::Shutdown;
+use crate::types::MultiPathOpportunity;
+
+/// Latest monitor state served by the HTTP API
+#[derive(Debug, Default)]
+pub struct ApiState {
+    block_number: u64,
+    last_fetch: Option<Instant>,
+    latest: Option<MultiPathOpportunity>,
+}
+
+/// State shared between the monitor loop (writer) and the API handlers (readers)
+pub type SharedApiState = Arc<RwLock<ApiState>>;
+
+/// Body of `GET /health`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Health {
+    /// `starting` until the first reserve fetch succeeds, then `ok`
+    pub status: &'static str,
+    pub block_number: u64,
+    pub last_fetch_age_secs: Option<f64>,
+}
+
+impl ApiState {
+    /// Empty state ready to share with the server
+    pub fn shared() -> SharedApiState {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    /// Reserves were fetched for `block_number`
+    pub fn record_fetch(&mut self, block_number: u64) {
+        self.block_number = block_number;
+        self.last_fetch = Some(Instant::now());
+    }
+
+    /// Replace the published analysis result
+    pub fn publish(&mut self, multi_opportunity: MultiPathOpportunity) {
+        self.latest = Some(multi_opportunity);
+    }
+
+    /// Most recent analysis result, if any block was analyzed
+    pub fn latest(&self) -> Option<&MultiPathOpportunity> {
+        self.latest.as_ref()
+    }
+
+    /// Health as of `now`
+    pub fn health_at(&self, now: Instant) -> Health {
+        Health {
+            status: if self.last_fetch.is_some() { "ok" } else { "starting" },
+            block_number: self.block_number,
+            last_fetch_age_secs: self.last_fetch.map(|fetched| now.saturating_duration_since(fetched).as_secs_f64()),
+        }
+    }
+}
+
+/// Serve `GET /opportunities` and `GET /health` on `address` until `shutdown` fires
+/// (requires the `http-api` feature)
+pub async fn start_api_server(state: SharedApiState, address: &str, shutdown: Shutdown) -> Result<(), Box<dyn Error>> {
+    #[cfg(feature = "http-api")]
+    {
+        let local_addr = crate::api_server::serve(state, address, shutdown).await?;
+        log::info!("🌐 HTTP API on http://{}/opportunities", local_addr);
+        Ok(())
+    }
+    #[cfg(not(feature = "http-api"))]
+    {
+        drop((state, shutdown));
+        Err(format!("API_ADDRESS={} requires building with --features http-api", address).into())
+    }
+}
@@ -0,0 +1,131 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use std::time::Instant;
+use log::warn;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use crate::api::SharedApiState;
+use crate::shutdown::Shutdown;
+
+/// Largest request head read before answering
+const MAX_REQUEST_BYTES: usize = 8192;
+
+/// Serve the JSON API on `address` in a background task that stops with `shutdown`; returns the bound address
+pub async fn serve(state: SharedApiState, address: &str, shutdown: Shutdown) -> Result<SocketAddr, Box<dyn Error>> {
+    let listener = TcpListener::bind(address).await?;
+    let local_addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        loop {
+            let accepted = tokio::select! {
+                biased;
+                _ = shutdown.wait() => break,
+                accepted = listener.accept() => accepted,
+            };
+            match accepted {
+                Ok((socket, _)) => {
+                    tokio::spawn(answer_request(socket, state.clone()));
+                }
+                Err(e) => warn!("⚠️ API accept failed: {}", e),
+            }
+        }
+    });
+    Ok(local_addr)
+}
+
+/// Status line and JSON body for a request path
+fn route(state: &SharedApiState, path: &str) -> (&'static str, String) {
+    let state = state.read().unwrap_or_else(|e| e.into_inner());
+    let path = path.split('?').next().unwrap_or("");
+    match path {
+        "/health" => ("200 OK", serde_json::to_string(&state.health_at(Instant::now())).unwrap_or_default()),
+        "/opportunities" => match state.latest().map(serde_json::to_string) {
+            Some(Ok(body)) => ("200 OK", body),
+            Some(Err(e)) => ("500 Internal Server Error", serde_json::json!({ "error": e.to_string() }).to_string()),
+            None => ("503 Service Unavailable", r#"{"error":"no block analyzed yet"}"#.to_string()),
+        },
+        _ => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+    }
+}
+
+/// Answer one HTTP request and close the connection
+async fn answer_request(mut socket: TcpStream, state: SharedApiState) {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        match socket.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => request.extend_from_slice(&buffer[..n]),
+        }
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => route(&state, path),
+        _ => ("405 Method Not Allowed", r#"{"error":"only GET is supported"}"#.to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, body.len(), body
+    );
+    if let Err(e) = socket.write_all(response.as_bytes()).await {
+        warn!("⚠️ Failed to answer API request: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ApiState;
+    use crate::types::MultiPathOpportunity;
+    use tokio::time::{timeout, Duration};
+
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).await.unwrap();
+        let mut response = String::new();
+        timeout(Duration::from_secs(5), client.read_to_string(&mut response)).await.unwrap().unwrap();
+        response
+    }
+
+    fn body(response: &str) -> serde_json::Value {
+        serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_serves_latest_opportunities_and_health() {
+        let state = ApiState::shared();
+        let shutdown = Shutdown::new();
+        let addr = serve(state.clone(), "127.0.0.1:0", shutdown.clone()).await.unwrap();
+
+        // Nothing analyzed yet
+        assert!(get(addr, "/opportunities").await.starts_with("HTTP/1.1 503"));
+        assert_eq!(body(&get(addr, "/health").await)["status"], "starting");
+
+        // The monitor publishes a block's result
+        {
+            let mut state = state.write().unwrap();
+            state.record_fetch(1234);
+            state.publish(MultiPathOpportunity::new(Vec::new(), 7));
+        }
+        let health = body(&get(addr, "/health").await);
+        assert_eq!(health["status"], "ok");
+        assert_eq!(health["block_number"], 1234);
+        assert!(health["last_fetch_age_secs"].as_f64().unwrap() >= 0.0);
+
+        let response = get(addr, "/opportunities").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert_eq!(body(&response)["analysis_time_ms"], 7);
+        assert!(get(addr, "/").await.starts_with("HTTP/1.1 404"));
+
+        // Shutdown closes the listener
+        shutdown.trigger();
+        let refused = async {
+            while TcpStream::connect(addr).await.is_ok() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        timeout(Duration::from_secs(5), refused).await.unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 use alloy::primitives::U256;
-use crate::types::{Token, PoolReserves, ArbitrageOpportunity};
+use crate::types::{Token, PoolReserves, ArbitrageOpportunity, InputConstraint};
 use crate::config::Config;
-use crate::math::{u256_to_f64, find_best_input, get_amount_out};
+use crate::math::{find_best_input_in_range, find_best_input_within_impact, get_amount_out, hop_outputs, max_price_impact, min_path_depth, realized_profit, swap};
 use crate::constants::{GAS_UNITS_3_HOPS, DEFAULT_GAS_PRICE_GWEI, GWEI_TO_MNT_MULTIPLIER};
 
 /// Extract and normalize pool reserves for ternary search algorithm
@@ -11,23 +11,23 @@ fn prepare_pools_for_search(
     joe_wmnt: &PoolReserves,
 ) -> Option<Vec<(f64, f64)>> {
     // Pool 1: WMNT -> MOE in MOE-WMNT pool
-    let (wmnt_reserve1, moe_reserve1) = match (moe_wmnt.token_a, moe_wmnt.token_b) {
-        (Token::WMNT(_), Token::MOE(_)) => (u256_to_f64(moe_wmnt.reserve_a), u256_to_f64(moe_wmnt.reserve_b)),
-        (Token::MOE(_), Token::WMNT(_)) => (u256_to_f64(moe_wmnt.reserve_b), u256_to_f64(moe_wmnt.reserve_a)),
+    let (wmnt_reserve1, moe_reserve1) = match (moe_wmnt.token_a.symbol(), moe_wmnt.token_b.symbol()) {
+        ("WMNT", "MOE") => (moe_wmnt.reserve_a_units(), moe_wmnt.reserve_b_units()),
+        ("MOE", "WMNT") => (moe_wmnt.reserve_b_units(), moe_wmnt.reserve_a_units()),
         _ => return None,
     };
 
     // Pool 2: MOE -> JOE in JOE-MOE pool
-    let (moe_reserve2, joe_reserve2) = match (joe_moe.token_a, joe_moe.token_b) {
-        (Token::MOE(_), Token::JOE(_)) => (u256_to_f64(joe_moe.reserve_a), u256_to_f64(joe_moe.reserve_b)),
-        (Token::JOE(_), Token::MOE(_)) => (u256_to_f64(joe_moe.reserve_b), u256_to_f64(joe_moe.reserve_a)),
+    let (moe_reserve2, joe_reserve2) = match (joe_moe.token_a.symbol(), joe_moe.token_b.symbol()) {
+        ("MOE", "JOE") => (joe_moe.reserve_a_units(), joe_moe.reserve_b_units()),
+        ("JOE", "MOE") => (joe_moe.reserve_b_units(), joe_moe.reserve_a_units()),
         _ => return None,
     };
 
     // Pool 3: JOE -> WMNT in JOE-WMNT pool
-    let (joe_reserve3, wmnt_reserve3) = match (joe_wmnt.token_a, joe_wmnt.token_b) {
-        (Token::JOE(_), Token::WMNT(_)) => (u256_to_f64(joe_wmnt.reserve_a), u256_to_f64(joe_wmnt.reserve_b)),
-        (Token::WMNT(_), Token::JOE(_)) => (u256_to_f64(joe_wmnt.reserve_b), u256_to_f64(joe_wmnt.reserve_a)),
+    let (joe_reserve3, wmnt_reserve3) = match (joe_wmnt.token_a.symbol(), joe_wmnt.token_b.symbol()) {
+        ("JOE", "WMNT") => (joe_wmnt.reserve_a_units(), joe_wmnt.reserve_b_units()),
+        ("WMNT", "JOE") => (joe_wmnt.reserve_b_units(), joe_wmnt.reserve_a_units()),
         _ => return None,
     };
 
@@ -47,14 +47,26 @@ pub fn find_optimal_arbitrage(
 ) -> Option<ArbitrageOpportunity> {
     let pools = prepare_pools_for_search(moe_wmnt, joe_moe, joe_wmnt)?;
     
-    let (best_input, gross_profit) = find_best_input(&pools, config.dex_fee, config.ternary_search_iterations);
+    let fees = [config.dex_fee; 3];
+    let iterations = config.search_iterations(pools.len());
+    // MAX_PRICE_IMPACT_PCT limits the search itself, so the result is the best input under the cap
+    let max_impact = config.max_price_impact_pct / 100.0;
+    let (best_input, gross_profit, robustness) =
+        find_best_input_within_impact(&pools, &fees, config.input_range(), max_impact, iterations);
+    let unconstrained_input = if max_impact > 0.0 {
+        find_best_input_in_range(&pools, &fees, config.input_range(), iterations).0
+    } else {
+        best_input
+    };
+    let binding_constraint = (best_input < unconstrained_input).then_some(InputConstraint::PriceImpact);
     
     // Calculate final output amount
     let final_output = best_input + gross_profit;
     
     // Calculate net profit after precise gas costs (3-hops for triangular arbitrage)
     let gas_cost = config.calculate_gas_cost(GAS_UNITS_3_HOPS);
-    let net_profit = gross_profit - gas_cost;
+    let flash_loan_fee = config.flash_loan_terms().fee_for(best_input);
+    let net_profit = gross_profit - gas_cost - flash_loan_fee.unwrap_or(0.0);
     
     // Calculate profit percentage
     let profit_percentage = if best_input > 0.0 { 
@@ -71,6 +83,16 @@ pub fn find_optimal_arbitrage(
             profit_percentage,
             search_method: "ternary_search".to_string(),
             path: None, // Legacy triangular arbitrage doesn't use path structure
+            robustness,
+            unconstrained_input,
+            binding_constraint,
+            price_impact_percent: max_price_impact(best_input, &pools, &[config.dex_fee; 3]) * 100.0,
+            requires_flash_loan: flash_loan_fee.is_some(),
+            flash_loan_fee: flash_loan_fee.unwrap_or(0.0),
+            min_pool_depth: min_path_depth(&pools),
+            expected_realized_profit: realized_profit(gross_profit, gas_cost + flash_loan_fee.unwrap_or(0.0), config.capture_ratio),
+            hop_outputs: hop_outputs(best_input, &pools, &fees),
+            confidence: 1.0,
         })
 }
 
@@ -84,25 +106,25 @@ pub fn check_arbitrage_legacy(
     // Path: WMNT -> MOE -> JOE -> WMNT
 
     // Step 1: WMNT -> MOE in MOE-WMNT pool
-    let (wmnt_reserve, moe_reserve) = match (moe_wmnt.token_a, moe_wmnt.token_b) {
-        (Token::WMNT(_), Token::MOE(_)) => (moe_wmnt.reserve_a, moe_wmnt.reserve_b),
-        (Token::MOE(_), Token::WMNT(_)) => (moe_wmnt.reserve_b, moe_wmnt.reserve_a),
+    let (wmnt_reserve, moe_reserve) = match (moe_wmnt.token_a.symbol(), moe_wmnt.token_b.symbol()) {
+        ("WMNT", "MOE") => (moe_wmnt.reserve_a, moe_wmnt.reserve_b),
+        ("MOE", "WMNT") => (moe_wmnt.reserve_b, moe_wmnt.reserve_a),
         _ => return (false, U256::ZERO, U256::ZERO),
     };
     let moe_out = get_amount_out(start_amount, wmnt_reserve, moe_reserve);
 
     // Step 2: MOE -> JOE in JOE-MOE pool
-    let (moe_reserve, joe_reserve) = match (joe_moe.token_a, joe_moe.token_b) {
-        (Token::MOE(_), Token::JOE(_)) => (joe_moe.reserve_a, joe_moe.reserve_b),
-        (Token::JOE(_), Token::MOE(_)) => (joe_moe.reserve_b, joe_moe.reserve_a),
+    let (moe_reserve, joe_reserve) = match (joe_moe.token_a.symbol(), joe_moe.token_b.symbol()) {
+        ("MOE", "JOE") => (joe_moe.reserve_a, joe_moe.reserve_b),
+        ("JOE", "MOE") => (joe_moe.reserve_b, joe_moe.reserve_a),
         _ => return (false, U256::ZERO, U256::ZERO),
     };
     let joe_out = get_amount_out(moe_out, moe_reserve, joe_reserve);
 
     // Step 3: JOE -> WMNT in JOE-WMNT pool
-    let (joe_reserve, wmnt_reserve) = match (joe_wmnt.token_a, joe_wmnt.token_b) {
-        (Token::JOE(_), Token::WMNT(_)) => (joe_wmnt.reserve_a, joe_wmnt.reserve_b),
-        (Token::WMNT(_), Token::JOE(_)) => (joe_wmnt.reserve_b, joe_wmnt.reserve_a),
+    let (joe_reserve, wmnt_reserve) = match (joe_wmnt.token_a.symbol(), joe_wmnt.token_b.symbol()) {
+        ("JOE", "WMNT") => (joe_wmnt.reserve_a, joe_wmnt.reserve_b),
+        ("WMNT", "JOE") => (joe_wmnt.reserve_b, joe_wmnt.reserve_a),
         _ => return (false, U256::ZERO, U256::ZERO),
     };
     let wmnt_out = get_amount_out(joe_out, joe_reserve, wmnt_reserve);
@@ -131,14 +153,33 @@ pub trait ArbitragePath {
 
 /// Standard 3-pool triangular arbitrage path
 pub struct TriangularPath {
+    /// Tokens in swap order; the cycle returns to the first token
     pub path: Vec<Token>,
+    /// Swap fee charged by every pool (0.003 = 0.3%)
+    pub fee: f64,
 }
 
 impl ArbitragePath for TriangularPath {
-    fn calculate_output(&self, _input_amount: f64, _reserves: &[&PoolReserves]) -> Option<f64> {
-        // Implementation would go here for generic path calculation
-        // This is a placeholder for future expansion
-        None
+    /// Chain swaps path[0] -> path[1] -> path[2] -> path[0], where `reserves[i]` is the pool
+    /// for hop i in either token order. None if a pool does not hold the hop's tokens.
+    fn calculate_output(&self, input_amount: f64, reserves: &[&PoolReserves]) -> Option<f64> {
+        if self.path.len() != 3 || reserves.len() != 3 {
+            return None;
+        }
+
+        let mut amount = input_amount;
+        for (hop, pool) in reserves.iter().enumerate() {
+            let (token_in, token_out) = (&self.path[hop], &self.path[(hop + 1) % 3]);
+            let (reserve_in, reserve_out) = if pool.token_a == *token_in && pool.token_b == *token_out {
+                (pool.reserve_a_units(), pool.reserve_b_units())
+            } else if pool.token_b == *token_in && pool.token_a == *token_out {
+                (pool.reserve_b_units(), pool.reserve_a_units())
+            } else {
+                return None;
+            };
+            amount = swap(reserve_in, reserve_out, amount, self.fee);
+        }
+        Some(amount)
     }
 
     fn get_path_description(&self) -> String {
@@ -153,6 +194,7 @@ impl ArbitragePath for TriangularPath {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::find_best_input;
     use crate::types::Token;
     use alloy::primitives::Address;
     use chrono::Utc;
@@ -175,9 +217,9 @@ mod tests {
         let moe_addr = Address::from([1u8; 20]);
         let joe_addr = Address::from([2u8; 20]);
 
-        let moe_wmnt = create_test_reserves(Token::WMNT(wmnt_addr), 1000, Token::MOE(moe_addr), 1000);
-        let joe_moe = create_test_reserves(Token::MOE(moe_addr), 1000, Token::JOE(joe_addr), 1000);
-        let joe_wmnt = create_test_reserves(Token::JOE(joe_addr), 1000, Token::WMNT(wmnt_addr), 1000);
+        let moe_wmnt = create_test_reserves(Token::new(wmnt_addr, "WMNT", 18), 1000, Token::new(moe_addr, "MOE", 18), 1000);
+        let joe_moe = create_test_reserves(Token::new(moe_addr, "MOE", 18), 1000, Token::new(joe_addr, "JOE", 18), 1000);
+        let joe_wmnt = create_test_reserves(Token::new(joe_addr, "JOE", 18), 1000, Token::new(wmnt_addr, "WMNT", 18), 1000);
 
         let pools = prepare_pools_for_search(&moe_wmnt, &joe_moe, &joe_wmnt);
         assert!(pools.is_some());
@@ -189,6 +231,39 @@ mod tests {
         assert_eq!(pools[2], (1000.0, 1000.0)); // JOE -> WMNT
     }
 
+    #[test]
+    fn test_reserve_scaling_applies_to_display_and_analysis() {
+        let wmnt = Token::new(Address::ZERO, "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let (moe_wmnt_addr, joe_moe_addr, joe_wmnt_addr) =
+            (Address::from([3u8; 20]), Address::from([4u8; 20]), Address::from([5u8; 20]));
+        let factor = 0.001;
+
+        let mut reserves_map = std::collections::HashMap::new();
+        reserves_map.insert(moe_wmnt_addr, create_test_reserves(wmnt.clone(), 1_000_000, moe.clone(), 900_000));
+        reserves_map.insert(joe_moe_addr, create_test_reserves(moe, 1_000_000, joe.clone(), 1_100_000));
+        reserves_map.insert(joe_wmnt_addr, create_test_reserves(joe, 1_000_000, wmnt, 1_200_000));
+        let scaled_map: std::collections::HashMap<_, _> = reserves_map
+            .iter()
+            .map(|(addr, r)| (*addr, r.scaled(factor)))
+            .collect();
+
+        // Display shows the scaled amounts
+        let display = crate::display::format_pool_reserves(moe_wmnt_addr, joe_moe_addr, joe_wmnt_addr, &scaled_map);
+        assert!(display.contains("1000.00 WMNT / 900.00 MOE"));
+
+        // Analysis sees the same scaled pools; x*y=k is homogeneous so the optimum scales linearly
+        let original = prepare_pools_for_search(&reserves_map[&moe_wmnt_addr], &reserves_map[&joe_moe_addr], &reserves_map[&joe_wmnt_addr]).unwrap();
+        let scaled = prepare_pools_for_search(&scaled_map[&moe_wmnt_addr], &scaled_map[&joe_moe_addr], &scaled_map[&joe_wmnt_addr]).unwrap();
+        assert_eq!(scaled[0], (1000.0, 900.0));
+
+        let (input, profit, _) = find_best_input(&original, 0.003, 100);
+        let (scaled_input, scaled_profit, _) = find_best_input(&scaled, 0.003, 100);
+        assert!((scaled_input - input * factor).abs() < 1e-6);
+        assert!((scaled_profit - profit * factor).abs() < 1e-6);
+    }
+
     #[test]
     fn test_triangular_path() {
         let wmnt_addr = Address::ZERO;
@@ -196,9 +271,35 @@ mod tests {
         let joe_addr = Address::from([2u8; 20]);
 
         let path = TriangularPath {
-            path: vec![Token::WMNT(wmnt_addr), Token::MOE(moe_addr), Token::JOE(joe_addr)],
+            path: vec![Token::new(wmnt_addr, "WMNT", 18), Token::new(moe_addr, "MOE", 18), Token::new(joe_addr, "JOE", 18)],
+            fee: 0.003,
         };
 
         assert_eq!(path.get_path_description(), "WMNT -> MOE -> JOE");
     }
+
+    #[test]
+    fn test_triangular_path_output() {
+        let wmnt = Token::new(Address::ZERO, "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+
+        // MOE-WMNT is stored in reverse order and must be flipped
+        let moe_wmnt = create_test_reserves(moe.clone(), 900, wmnt.clone(), 1000);
+        let joe_moe = create_test_reserves(moe.clone(), 1000, joe.clone(), 1100);
+        let joe_wmnt = create_test_reserves(joe.clone(), 1000, wmnt.clone(), 1200);
+        let path = TriangularPath { path: vec![wmnt, moe, joe], fee: 0.003 };
+
+        // 10 WMNT -> 8.884422 MOE -> 9.657998 JOE -> 11.444628 WMNT (x*y=k with 0.3% fee)
+        let output = path.calculate_output(10.0, &[&moe_wmnt, &joe_moe, &joe_wmnt]).unwrap();
+        assert!((output - 11.444627778407751).abs() < 1e-9);
+
+        // Matches the profit the ternary search evaluates on the same pools
+        let pools = prepare_pools_for_search(&moe_wmnt, &joe_moe, &joe_wmnt).unwrap();
+        assert!((output - 10.0 - crate::math::arbitrage_profit(10.0, &pools, 0.003)).abs() < 1e-9);
+
+        // Pools out of hop order cannot be oriented
+        assert!(path.calculate_output(10.0, &[&joe_moe, &moe_wmnt, &joe_wmnt]).is_none());
+        assert!(path.calculate_output(10.0, &[&moe_wmnt, &joe_moe]).is_none());
+    }
 }
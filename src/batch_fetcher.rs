@@ -1,41 +1,62 @@
-use std::collections::HashMap;
-use alloy::primitives::Address;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use alloy::primitives::{Address, U256};
 use alloy::providers::Provider;
-use futures::future::join_all;
-use crate::types::PoolReserves;
-use crate::blockchain::fetch_all_reserves_with_retry;
+use log::{debug, info, warn};
+use crate::types::{PoolReserves, Token};
+use crate::blockchain::{fetch_sync_reserves, ReserveFetchOutcome};
+use crate::constants::MAX_SYNC_LOG_BLOCKS;
+use crate::error::ArbError;
+use crate::reserve_reader::ReserveReader;
 
-/// Batch fetcher for pool reserves with parallel processing
+/// Batch fetcher for pool reserves with parallel processing.
+/// This is the only `BatchReservesFetcher`; `multi_path` does not define its own:
+///
+/// ```compile_fail
+/// use triangular_arbitrage_demo::multi_path::BatchReservesFetcher;
+/// ```
 pub struct BatchReservesFetcher {
     pool_addresses: Vec<Address>,
     max_retries: usize,
     batch_size: usize,
+    inter_batch_delay: Duration,
+    /// Per-pool calls in flight at once within a batch (0 = unbounded)
+    max_concurrent_requests: usize,
+    /// Share of pools a snapshot needs before it is analyzed (0 = any)
+    min_pool_coverage: f64,
+    /// Pools never added to the fetch list
+    excluded_pools: HashSet<Address>,
 }
 
 impl BatchReservesFetcher {
-    /// Create new batch fetcher
-    pub fn new(max_retries: usize) -> Self {
+    /// Create new batch fetcher, fetching `batch_size` pools at a time with a pause between batches
+    pub fn new(max_retries: usize, batch_size: usize, inter_batch_delay: Duration) -> Self {
         Self {
             pool_addresses: Vec::new(),
             max_retries,
-            batch_size: 50, // Process 50 pools per batch
+            batch_size: batch_size.max(1),
+            inter_batch_delay,
+            max_concurrent_requests: 0,
+            min_pool_coverage: 0.0,
+            excluded_pools: HashSet::new(),
         }
     }
 
     /// Load pool addresses from CSV
-    pub fn load_pool_addresses_from_csv(&mut self, csv_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn load_pool_addresses_from_csv(&mut self, csv_path: &str) -> Result<(), ArbError> {
         let mut reader = csv::Reader::from_path(csv_path)?;
         
         for result in reader.records() {
             let record = result?;
-            if record.len() >= 3 {
-                if let Ok(pool_addr) = record[2].parse::<Address>() {
-                    self.pool_addresses.push(pool_addr);
-                }
+            if record.len() >= 3
+                && let Ok(pool_addr) = record[2].parse::<Address>()
+                && !self.excluded_pools.contains(&pool_addr)
+            {
+                self.pool_addresses.push(pool_addr);
             }
         }
         
-        println!("📊 Loaded {} pool addresses from CSV", self.pool_addresses.len());
+        info!("📊 Loaded {} pool addresses from CSV", self.pool_addresses.len());
         Ok(())
     }
 
@@ -44,67 +65,88 @@ impl BatchReservesFetcher {
         &self.pool_addresses
     }
 
-    /// Add individual pool address
+    /// Add individual pool address (excluded pools are ignored)
     pub fn add_pool_address(&mut self, address: Address) {
-        if !self.pool_addresses.contains(&address) {
+        if !self.pool_addresses.contains(&address) && !self.excluded_pools.contains(&address) {
             self.pool_addresses.push(address);
         }
     }
 
-    /// Fetch all reserves in parallel batches
-    pub async fn fetch_all_reserves<P: Provider>(
+    /// Fetch all reserves in parallel batches. Pools that fail every attempt are left out;
+    /// below `min_pool_coverage` (or with no pool fetched) the block is an error.
+    pub async fn fetch_all_reserves<R: ReserveReader + ?Sized>(
         &self,
-        provider: &P,
+        reader: &R,
         current_block: u64,
-    ) -> Result<HashMap<Address, PoolReserves>, Box<dyn std::error::Error>> {
-        let mut all_reserves = HashMap::new();
+    ) -> Result<HashMap<Address, PoolReserves>, ArbError> {
+        let mut all_reserves = ReserveFetchOutcome::default();
         
         // Process pools in batches to avoid overwhelming the RPC
-        for chunk in self.pool_addresses.chunks(self.batch_size) {
+        for (i, chunk) in self.pool_addresses.chunks(self.batch_size).enumerate() {
+            // Pause between batches to be gentle on RPC (not before the first one)
+            if i > 0 && !self.inter_batch_delay.is_zero() {
+                tokio::time::sleep(self.inter_batch_delay).await;
+            }
+
             let chunk_vec: Vec<Address> = chunk.to_vec();
-            let batch_result = fetch_all_reserves_with_retry(provider, &chunk_vec, current_block, self.max_retries as u32).await;
-            
-            match batch_result {
-                Ok(reserves_map) => {
-                    // Merge all results from this batch
-                    for (addr, reserves) in reserves_map {
-                        all_reserves.insert(addr, reserves);
-                    }
-                }
-                Err(e) => {
-                    eprintln!("⚠️ Failed to fetch reserves for batch: {}", e);
-                }
+            let outcome = reader.fetch_reserves(&chunk_vec, current_block, self.max_retries as u32, self.max_concurrent_requests).await;
+
+            // Keep whatever succeeded; failed pools are simply missing this block
+            if !outcome.failed.is_empty() {
+                warn!("⚠️ Skipping {} pools that failed after {} attempts: {:?}",
+                         outcome.failed.len(), self.max_retries, outcome.failed);
             }
-            
-            // Small delay between batches to be gentle on RPC
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            all_reserves.merge(outcome);
         }
         
-        println!("✅ Successfully fetched reserves for {}/{} pools", 
-                all_reserves.len(), self.pool_addresses.len());
+        debug!("✅ Successfully fetched reserves for {}/{} pools", 
+                all_reserves.reserves.len(), self.pool_addresses.len());
         
-        Ok(all_reserves)
+        all_reserves.into_usable(self.min_pool_coverage)
     }
 
-    /// Filter pools by minimum liquidity threshold
-    pub fn filter_by_liquidity(&self, reserves_map: &HashMap<Address, PoolReserves>, min_liquidity_usd: f64) -> Vec<Address> {
-        reserves_map
-            .iter()
-            .filter_map(|(&addr, reserves)| {
-                // Estimate liquidity in USD (simplified calculation)
-                let reserve_a_f64 = crate::math::u256_to_f64(reserves.reserve_a);
-                let reserve_b_f64 = crate::math::u256_to_f64(reserves.reserve_b);
-                
-                // Rough estimate: assume both tokens have similar value
-                let estimated_liquidity = (reserve_a_f64 + reserve_b_f64) * 0.5;
-                
-                if estimated_liquidity >= min_liquidity_usd {
-                    Some(addr)
-                } else {
-                    None
+    /// Reserves at `current_block` from `Sync` logs since the last snapshot. Takes a full
+    /// `getReserves` snapshot first when there is none, the gap exceeds `MAX_SYNC_LOG_BLOCKS`,
+    /// or the log query fails.
+    pub async fn fetch_reserves_via_sync<P: Provider + ReserveReader>(
+        &self,
+        provider: &P,
+        synced: &mut SyncedReserves,
+        current_block: u64,
+    ) -> Result<HashMap<Address, PoolReserves>, ArbError> {
+        if let Some(synced_block) = synced.synced_block()
+            && current_block > synced_block
+            && current_block - synced_block <= MAX_SYNC_LOG_BLOCKS
+        {
+            match fetch_sync_reserves(provider, &self.pool_addresses, synced_block + 1, current_block).await {
+                Ok(updates) => {
+                    let updated = updates
+                        .into_iter()
+                        .filter(|(pool, (reserve0, reserve1))| synced.apply_sync(*pool, *reserve0, *reserve1))
+                        .count();
+                    synced.advance_to(current_block);
+                    debug!("📡 Applied Sync logs for {} pools (blocks {}-{})", updated, synced_block + 1, current_block);
+                    return Ok(synced.reserves().clone());
                 }
-            })
-            .collect()
+                Err(e) => warn!("⚠️ Sync log query failed ({}), taking a getReserves snapshot", e),
+            }
+        } else if synced.synced_block() == Some(current_block) {
+            return Ok(synced.reserves().clone());
+        }
+
+        let snapshot = self.fetch_all_reserves(provider, current_block).await?;
+        synced.reset(snapshot, current_block);
+        Ok(synced.reserves().clone())
+    }
+
+    /// Filter pools by minimum WMNT-denominated liquidity (see `LiquidityAnalyzer::wmnt_prices`)
+    pub fn filter_by_liquidity(
+        &self,
+        reserves_map: &HashMap<Address, PoolReserves>,
+        prices: &HashMap<Token, f64>,
+        min_liquidity_wmnt: f64,
+    ) -> Vec<Address> {
+        LiquidityAnalyzer::get_arbitrage_ready_pools(reserves_map, prices, min_liquidity_wmnt)
     }
 
     /// Get pool count
@@ -112,29 +154,154 @@ impl BatchReservesFetcher {
         self.pool_addresses.len()
     }
 
+    /// Cap the per-pool calls in flight at once (0 = unbounded)
+    pub fn set_max_concurrent_requests(&mut self, max_concurrent_requests: usize) {
+        self.max_concurrent_requests = max_concurrent_requests;
+    }
+
+    /// Require at least this share of the pools (0-1) before a snapshot is used
+    pub fn set_min_pool_coverage(&mut self, min_pool_coverage: f64) {
+        self.min_pool_coverage = min_pool_coverage;
+    }
+
+    /// Skip these pools when loading or adding addresses, dropping any already listed
+    pub fn set_excluded_pools(&mut self, pools: &[Address]) {
+        self.excluded_pools = pools.iter().copied().collect();
+        self.pool_addresses.retain(|pool| !self.excluded_pools.contains(pool));
+    }
+
     /// Set batch size for parallel processing
     pub fn set_batch_size(&mut self, batch_size: usize) {
         self.batch_size = batch_size.max(1);
     }
+
+    /// Set the pause between batches (zero disables it)
+    pub fn set_inter_batch_delay(&mut self, delay: Duration) {
+        self.inter_batch_delay = delay;
+    }
+}
+
+/// Reserves kept current from `Sync` logs on top of a `getReserves` snapshot
+#[derive(Debug, Default)]
+pub struct SyncedReserves {
+    reserves: HashMap<Address, PoolReserves>,
+    synced_block: Option<u64>,
 }
 
-/// Pool liquidity analyzer
+impl SyncedReserves {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Block the reserves are current as of (`None` before the first snapshot)
+    pub fn synced_block(&self) -> Option<u64> {
+        self.synced_block
+    }
+
+    /// Current reserves of every snapshotted pool
+    pub fn reserves(&self) -> &HashMap<Address, PoolReserves> {
+        &self.reserves
+    }
+
+    /// Replace everything with a fresh snapshot taken at `block_number`
+    pub fn reset(&mut self, reserves: HashMap<Address, PoolReserves>, block_number: u64) {
+        self.reserves = reserves;
+        self.synced_block = Some(block_number);
+    }
+
+    /// Drop the snapshot so the next fetch takes a new one (e.g. after a reconnect)
+    pub fn invalidate(&mut self) {
+        self.synced_block = None;
+    }
+
+    /// Apply a `Sync` (reserve0 is token_a, as fetched on chain); false for pools not in the
+    /// snapshot and for implausible reserves, which are ignored with a warning
+    pub fn apply_sync(&mut self, pool: Address, reserve0: U256, reserve1: U256) -> bool {
+        let Some(reserves) = self.reserves.get_mut(&pool) else {
+            return false;
+        };
+        let updated = PoolReserves { reserve_a: reserve0, reserve_b: reserve1, ..reserves.clone() };
+        if let Err(issue) = updated.sanity_check() {
+            warn!("⚠️ Ignoring Sync for pool {}: {}", pool, issue);
+            return false;
+        }
+        *reserves = updated;
+        true
+    }
+
+    /// Mark every pool as current at `block_number` (pools without a `Sync` did not change)
+    pub fn advance_to(&mut self, block_number: u64) {
+        for reserves in self.reserves.values_mut() {
+            reserves.block_number = block_number;
+        }
+        self.synced_block = Some(block_number);
+    }
+}
+
+/// Pool liquidity analyzer; liquidity is the WMNT value of both reserves
 pub struct LiquidityAnalyzer;
 
 impl LiquidityAnalyzer {
-    /// Analyze liquidity distribution across pools
-    pub fn analyze_liquidity_distribution(reserves_map: &HashMap<Address, PoolReserves>) -> LiquidityStats {
+    /// WMNT price of every token reachable from WMNT through the given pools.
+    /// Each token is priced at the spot rate of its deepest pool with an already priced token.
+    pub fn wmnt_prices(reserves_map: &HashMap<Address, PoolReserves>, wmnt: &Token) -> HashMap<Token, f64> {
+        let mut prices = HashMap::from([(wmnt.clone(), 1.0)]);
+        loop {
+            // (token, price, WMNT depth of the pricing side) for each newly reachable token
+            let mut candidates: HashMap<Token, (f64, f64)> = HashMap::new();
+            for reserves in reserves_map.values() {
+                let sides = [
+                    (&reserves.token_a, reserves.reserve_a_units(), &reserves.token_b, reserves.reserve_b_units()),
+                    (&reserves.token_b, reserves.reserve_b_units(), &reserves.token_a, reserves.reserve_a_units()),
+                ];
+                for (known, known_reserve, unknown, unknown_reserve) in sides {
+                    let Some(&known_price) = prices.get(known) else { continue };
+                    if prices.contains_key(unknown) || unknown_reserve <= 0.0 {
+                        continue;
+                    }
+                    let depth = known_reserve * known_price;
+                    let price = depth / unknown_reserve;
+                    if candidates.get(unknown).is_none_or(|&(_, best)| depth > best) {
+                        candidates.insert(unknown.clone(), (price, depth));
+                    }
+                }
+            }
+            if candidates.is_empty() {
+                return prices;
+            }
+            prices.extend(candidates.into_iter().map(|(token, (price, _))| (token, price)));
+        }
+    }
+
+    /// Pool liquidity in WMNT, or None when neither token has a price.
+    /// Uses both sides when both are priced, otherwise twice the priced side (the pool holds equal value on each).
+    pub fn pool_liquidity_wmnt(reserves: &PoolReserves, prices: &HashMap<Token, f64>) -> Option<f64> {
+        let value_a = prices.get(&reserves.token_a).map(|p| reserves.reserve_a_units() * p);
+        let value_b = prices.get(&reserves.token_b).map(|p| reserves.reserve_b_units() * p);
+        match (value_a, value_b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (Some(side), None) | (None, Some(side)) => Some(2.0 * side),
+            (None, None) => None,
+        }
+    }
+
+    /// Analyze liquidity distribution across pools (WMNT-denominated)
+    pub fn analyze_liquidity_distribution(
+        reserves_map: &HashMap<Address, PoolReserves>,
+        prices: &HashMap<Token, f64>,
+    ) -> LiquidityStats {
         let mut total_liquidity = 0.0;
         let mut pool_liquidities = Vec::new();
+        let mut unpriced_pools = 0;
         
         for reserves in reserves_map.values() {
-            let reserve_a = crate::math::u256_to_f64(reserves.reserve_a);
-            let reserve_b = crate::math::u256_to_f64(reserves.reserve_b);
-            
-            // Simple liquidity calculation (sum of reserves)
-            let liquidity = reserve_a + reserve_b;
-            pool_liquidities.push(liquidity);
-            total_liquidity += liquidity;
+            match Self::pool_liquidity_wmnt(reserves, prices) {
+                Some(liquidity) => {
+                    pool_liquidities.push(liquidity);
+                    total_liquidity += liquidity;
+                }
+                None => unpriced_pools += 1,
+            }
         }
         
         pool_liquidities.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
@@ -155,7 +322,8 @@ impl LiquidityAnalyzer {
         let min_liquidity = pool_liquidities.last().copied().unwrap_or(0.0);
 
         LiquidityStats {
-            total_pools: count,
+            total_pools: reserves_map.len(),
+            unpriced_pools,
             total_liquidity,
             mean_liquidity: mean,
             median_liquidity: median,
@@ -165,24 +333,18 @@ impl LiquidityAnalyzer {
         }
     }
 
-    /// Get pools with sufficient liquidity for arbitrage
+    /// Get pools with at least `min_liquidity_wmnt` of liquidity (unpriced pools never qualify)
     pub fn get_arbitrage_ready_pools(
         reserves_map: &HashMap<Address, PoolReserves>,
-        min_liquidity: f64,
+        prices: &HashMap<Token, f64>,
+        min_liquidity_wmnt: f64,
     ) -> Vec<Address> {
         reserves_map
             .iter()
-            .filter_map(|(&addr, reserves)| {
-                let reserve_a = crate::math::u256_to_f64(reserves.reserve_a);
-                let reserve_b = crate::math::u256_to_f64(reserves.reserve_b);
-                
-                // Check if both reserves meet minimum threshold
-                if reserve_a >= min_liquidity && reserve_b >= min_liquidity {
-                    Some(addr)
-                } else {
-                    None
-                }
+            .filter(|(_, reserves)| {
+                Self::pool_liquidity_wmnt(reserves, prices).is_some_and(|liquidity| liquidity >= min_liquidity_wmnt)
             })
+            .map(|(&addr, _)| addr)
             .collect()
     }
 }
@@ -191,6 +353,8 @@ impl LiquidityAnalyzer {
 #[derive(Debug, Clone)]
 pub struct LiquidityStats {
     pub total_pools: usize,
+    /// Pools with no route to a WMNT price, left out of the statistics
+    pub unpriced_pools: usize,
     pub total_liquidity: f64,
     pub mean_liquidity: f64,
     pub median_liquidity: f64,
@@ -202,18 +366,18 @@ pub struct LiquidityStats {
 impl LiquidityStats {
     /// Print liquidity analysis
     pub fn print_analysis(&self) {
-        println!("\n📊 Liquidity Analysis:");
-        println!("├─ Total Pools: {}", self.total_pools);
-        println!("├─ Total Liquidity: {:.2}", self.total_liquidity);
-        println!("├─ Mean Liquidity: {:.2}", self.mean_liquidity);
-        println!("├─ Median Liquidity: {:.2}", self.median_liquidity);
-        println!("├─ Max Liquidity: {:.2}", self.max_liquidity);
-        println!("├─ Min Liquidity: {:.2}", self.min_liquidity);
-        println!("└─ Top 10 Pools by Liquidity:");
+        info!("\n📊 Liquidity Analysis:");
+        info!("├─ Total Pools: {} ({} without a WMNT price)", self.total_pools, self.unpriced_pools);
+        info!("├─ Total Liquidity: {:.2} WMNT", self.total_liquidity);
+        info!("├─ Mean Liquidity: {:.2} WMNT", self.mean_liquidity);
+        info!("├─ Median Liquidity: {:.2} WMNT", self.median_liquidity);
+        info!("├─ Max Liquidity: {:.2} WMNT", self.max_liquidity);
+        info!("├─ Min Liquidity: {:.2} WMNT", self.min_liquidity);
+        info!("└─ Top 10 Pools by Liquidity (WMNT):");
         
         for (i, liquidity) in self.top_10_pools.iter().enumerate() {
             let prefix = if i == self.top_10_pools.len() - 1 { "   └─" } else { "   ├─" };
-            println!("{}  #{}: {:.2}", prefix, i + 1, liquidity);
+            info!("{}  #{}: {:.2} WMNT", prefix, i + 1, liquidity);
         }
     }
 }
@@ -221,17 +385,57 @@ impl LiquidityStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::f64_to_u256_with_decimals;
+
+    fn pool(id: u8, token_a: &Token, reserve_a: f64, token_b: &Token, reserve_b: f64) -> (Address, PoolReserves) {
+        let address = Address::from([id; 20]);
+        let reserves = PoolReserves::new(
+            token_a.clone(),
+            f64_to_u256_with_decimals(reserve_a, token_a.decimals()),
+            token_b.clone(),
+            f64_to_u256_with_decimals(reserve_b, token_b.decimals()),
+            1,
+            address,
+        );
+        (address, reserves)
+    }
+
+    #[test]
+    fn test_sync_updates_snapshot() {
+        let wmnt = Token::new(Address::from([0xa0; 20]), "WMNT", 18);
+        let usdc = Token::new(Address::from([0xa1; 20]), "USDC", 6);
+        let (address, reserves) = pool(1, &wmnt, 100.0, &usdc, 200.0);
+
+        let mut synced = SyncedReserves::new();
+        assert_eq!(synced.synced_block(), None);
+        synced.reset(HashMap::from([(address, reserves)]), 10);
+
+        assert!(synced.apply_sync(address, U256::from(5u64), U256::from(6u64)));
+        assert!(!synced.apply_sync(Address::from([9; 20]), U256::from(1u64), U256::from(1u64)));
+        // Zero or overflowing reserves are ignored, keeping the last good values
+        assert!(!synced.apply_sync(address, U256::ZERO, U256::from(6u64)));
+        assert!(!synced.apply_sync(address, U256::from(5u64), U256::MAX));
+        synced.advance_to(12);
+        let updated = &synced.reserves()[&address];
+        assert_eq!((updated.reserve_a, updated.reserve_b, updated.block_number), (U256::from(5u64), U256::from(6u64), 12));
+        assert_eq!(synced.synced_block(), Some(12));
+
+        synced.invalidate();
+        assert_eq!(synced.synced_block(), None);
+        assert_eq!(crate::config::ReserveSource::parse("Sync"), Some(crate::config::ReserveSource::SyncEvents));
+        assert_eq!(crate::config::ReserveSource::parse("logs"), None);
+    }
 
     #[test]
     fn test_batch_fetcher_creation() {
-        let fetcher = BatchReservesFetcher::new(3);
+        let fetcher = BatchReservesFetcher::new(3, 50, Duration::from_millis(100));
         assert_eq!(fetcher.pool_count(), 0);
         assert_eq!(fetcher.max_retries, 3);
     }
 
     #[test]
     fn test_pool_address_management() {
-        let mut fetcher = BatchReservesFetcher::new(3);
+        let mut fetcher = BatchReservesFetcher::new(3, 50, Duration::from_millis(100));
         
         let addr1 = Address::from([1u8; 20]);
         let addr2 = Address::from([2u8; 20]);
@@ -243,16 +447,67 @@ mod tests {
         assert_eq!(fetcher.pool_count(), 2);
         assert!(fetcher.get_pool_addresses().contains(&addr1));
         assert!(fetcher.get_pool_addresses().contains(&addr2));
+
+        // Excluded pools are dropped and not re-added
+        fetcher.set_excluded_pools(&[addr2]);
+        fetcher.add_pool_address(addr2);
+        assert_eq!(fetcher.get_pool_addresses(), &[addr1]);
     }
 
     #[test]
     fn test_batch_size_setting() {
-        let mut fetcher = BatchReservesFetcher::new(3);
+        let mut fetcher = BatchReservesFetcher::new(3, 50, Duration::from_millis(100));
         
         fetcher.set_batch_size(100);
         assert_eq!(fetcher.batch_size, 100);
         
         fetcher.set_batch_size(0); // Should be clamped to minimum 1
         assert_eq!(fetcher.batch_size, 1);
+
+        fetcher.set_inter_batch_delay(Duration::ZERO);
+        assert!(fetcher.inter_batch_delay.is_zero());
+    }
+
+    #[test]
+    fn test_liquidity_is_valued_in_wmnt() {
+        let wmnt = Token::parse("WMNT").unwrap();
+        let moe = Token::parse("MOE").unwrap();
+        let joe = Token::parse("JOE").unwrap();
+        let usdc = Token::new(Address::from([0xa1; 20]), "USDC", 6);
+        let orphan_x = Token::new(Address::from([0xa2; 20]), "X", 18);
+        let orphan_y = Token::new(Address::from([0xa3; 20]), "Y", 18);
+
+        let reserves_map = HashMap::from([
+            pool(1, &wmnt, 1000.0, &moe, 4000.0), // MOE = 0.25 WMNT
+            pool(2, &wmnt, 10.0, &moe, 20.0),     // Shallower pool quotes MOE differently; ignored for pricing
+            pool(3, &usdc, 500.0, &wmnt, 1000.0), // USDC = 2 WMNT despite 6 decimals
+            pool(4, &moe, 400.0, &usdc, 40.0),
+            pool(5, &joe, 100.0, &moe, 4000.0),   // JOE priced two hops out: 10 WMNT
+            pool(6, &orphan_x, 1e6, &orphan_y, 1e6),
+        ]);
+
+        let prices = LiquidityAnalyzer::wmnt_prices(&reserves_map, &wmnt);
+        assert!((prices[&moe] - 0.25).abs() < 1e-9);
+        assert!((prices[&usdc] - 2.0).abs() < 1e-9);
+        assert!((prices[&joe] - 10.0).abs() < 1e-9);
+        assert!(!prices.contains_key(&orphan_x));
+
+        // Raw unit sums would rank the orphan pool first and the USDC pool below the MOE one
+        let liquidity = |id: u8| LiquidityAnalyzer::pool_liquidity_wmnt(&reserves_map[&Address::from([id; 20])], &prices);
+        assert!((liquidity(1).unwrap() - 2000.0).abs() < 1e-6);
+        assert!((liquidity(3).unwrap() - 2000.0).abs() < 1e-6);
+        assert!((liquidity(4).unwrap() - 180.0).abs() < 1e-6);
+        assert_eq!(liquidity(6), None);
+
+        let mut ready = LiquidityAnalyzer::get_arbitrage_ready_pools(&reserves_map, &prices, 1000.0);
+        ready.sort();
+        assert_eq!(ready, [1u8, 3, 5].map(|id| Address::from([id; 20])));
+
+        let stats = LiquidityAnalyzer::analyze_liquidity_distribution(&reserves_map, &prices);
+        assert_eq!(stats.total_pools, 6);
+        assert_eq!(stats.unpriced_pools, 1);
+        assert!((stats.total_liquidity - 6195.0).abs() < 1e-6);
+        assert!((stats.max_liquidity - 2000.0).abs() < 1e-6);
+        assert!((stats.min_liquidity - 15.0).abs() < 1e-6);
     }
 }
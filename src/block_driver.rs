@@ -0,0 +1,101 @@
+use std::error::Error;
+use log::warn;
+use tokio::time::{sleep, Duration};
+use crate::config::MonitorDriver;
+use crate::constants::{BLOCK_POLL_BUFFER_MS, MIN_BLOCK_POLL_INTERVAL_MS};
+use crate::error::ArbError;
+use crate::reserve_reader::ReserveReader;
+
+/// Source of block numbers for the monitor loops, shared by both drivers
+pub struct BlockTicker {
+    driver: MonitorDriver,
+    poll_interval: Duration,
+    polled: bool,
+}
+
+impl BlockTicker {
+    /// Start the requested driver, falling back to polling when no WebSocket endpoint is usable
+    pub fn start(driver: MonitorDriver, ws_url: Option<&str>, poll_interval: Duration) -> Self {
+        let driver = match (driver, ws_url) {
+            (MonitorDriver::WebSocket, Some(url)) if is_websocket_url(url) => match subscribe_new_heads(url) {
+                Ok(()) => MonitorDriver::WebSocket,
+                Err(e) => {
+                    warn!("⚠️ WebSocket block subscription unavailable ({}), falling back to polling", e);
+                    MonitorDriver::Polling
+                }
+            },
+            (MonitorDriver::WebSocket, _) => {
+                warn!("⚠️ WebSocket driver needs a ws:// or wss:// WS_RPC_URL, falling back to polling");
+                MonitorDriver::Polling
+            }
+            (MonitorDriver::Polling, _) => MonitorDriver::Polling,
+        };
+
+        Self {
+            driver,
+            poll_interval,
+            polled: false,
+        }
+    }
+
+    /// Driver actually in use after any fallback
+    pub fn driver(&self) -> MonitorDriver {
+        self.driver
+    }
+
+    /// Wait for the next block to process and return the chain head.
+    /// Polling sleeps the poll interval before every poll but the first, including after errors.
+    pub async fn next_block<R: ReserveReader + ?Sized>(&mut self, reader: &R) -> Result<u64, ArbError> {
+        if self.polled {
+            sleep(self.poll_interval).await;
+        }
+        self.polled = true;
+        reader.block_number().await
+    }
+}
+
+/// Polling interval for a block time: the next expected block minus a small buffer,
+/// never shorter than the minimum (block times below the buffer would otherwise underflow)
+pub fn poll_interval_for_block_time(block_time_seconds: u64) -> Duration {
+    let interval_ms = block_time_seconds
+        .saturating_mul(1000)
+        .saturating_sub(BLOCK_POLL_BUFFER_MS)
+        .max(MIN_BLOCK_POLL_INTERVAL_MS);
+    Duration::from_millis(interval_ms)
+}
+
+/// Whether `url` can carry a block subscription
+pub fn is_websocket_url(url: &str) -> bool {
+    let url = url.to_lowercase();
+    url.starts_with("ws://") || url.starts_with("wss://")
+}
+
+/// `eth_subscribe("newHeads")` needs alloy's `provider-ws` transport, which this build does not include
+fn subscribe_new_heads(_url: &str) -> Result<(), Box<dyn Error>> {
+    Err("this build has no WebSocket transport (alloy `provider-ws` feature)".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_driver_falls_back_to_polling() {
+        let interval = Duration::from_millis(10);
+        assert!(is_websocket_url("WSS://rpc.mantle.xyz"));
+        assert!(!is_websocket_url("https://rpc.mantle.xyz"));
+
+        assert_eq!(BlockTicker::start(MonitorDriver::Polling, None, interval).driver(), MonitorDriver::Polling);
+        assert_eq!(BlockTicker::start(MonitorDriver::WebSocket, None, interval).driver(), MonitorDriver::Polling);
+        assert_eq!(BlockTicker::start(MonitorDriver::WebSocket, Some("https://rpc.mantle.xyz"), interval).driver(), MonitorDriver::Polling);
+        assert_eq!(MonitorDriver::parse("WebSocket"), Some(MonitorDriver::WebSocket));
+        assert_eq!(MonitorDriver::parse("push"), None);
+    }
+
+    #[test]
+    fn test_poll_interval_never_underflows() {
+        assert_eq!(poll_interval_for_block_time(2), Duration::from_millis(1800));
+        assert_eq!(poll_interval_for_block_time(0), Duration::from_millis(MIN_BLOCK_POLL_INTERVAL_MS));
+        assert_eq!(poll_interval_for_block_time(u64::MAX), Duration::from_millis(u64::MAX - BLOCK_POLL_BUFFER_MS));
+    }
+}
@@ -1,9 +1,22 @@
-use std::collections::HashMap;
-use std::error::Error;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::future::IntoFuture;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{LazyLock, RwLock};
 use alloy::providers::Provider;
-use alloy::primitives::{Address, U256};
+use alloy::primitives::{Address, Bytes, U256};
+use alloy::rpc::types::{BlockId, BlockNumberOrTag, Filter, Log};
+use alloy::sol_types::{SolCall, SolEvent};
+use futures::StreamExt;
+use log::{error, warn};
 use tokio::time::{sleep, Duration};
-use crate::types::{Token, PoolReserves};
+use crate::cache::GasPriceOracle;
+use crate::constants::{DEFAULT_RPC_TIMEOUT_MS, MULTICALL3_ADDRESS};
+use crate::error::ArbError;
+use crate::math::{f64_to_u256_with_decimals, u256_to_f64, v3_virtual_reserves};
+use crate::types::{Token, PoolReserves, SwapSample, V3PoolState};
 
 // Define the MoePair interface using alloy's sol! macro
 alloy::sol!(
@@ -12,111 +25,1006 @@ alloy::sol!(
         function token0() external view returns (address);
         function token1() external view returns (address);
         function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast);
+
+        event Swap(address indexed sender, uint256 amount0In, uint256 amount1In, uint256 amount0Out, uint256 amount1Out, address indexed to);
+        event Sync(uint112 reserve0, uint112 reserve1);
+    }
+);
+
+// Concentrated-liquidity pool (Uniswap V3 and forks)
+alloy::sol!(
+    #[sol(rpc)]
+    interface IUniswapV3Pool {
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+        function fee() external view returns (uint24);
+        function liquidity() external view returns (uint128);
+        // feeProtocol is uint8 upstream; some forks widen it, and uint32 decodes both
+        function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint32 feeProtocol, bool unlocked);
+    }
+);
+
+alloy::sol!(
+    #[sol(rpc)]
+    interface IERC20Metadata {
+        function symbol() external view returns (string);
+        function decimals() external view returns (uint8);
+    }
+);
+
+alloy::sol!(
+    #[sol(rpc)]
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Result[] memory returnData);
+
+        function getBlockNumber() external view returns (uint256 blockNumber);
     }
 );
 
-/// Fetch reserves for a single pool
+/// token0/token1 per pool; pair tokens never change, so they are fetched once
+static POOL_TOKENS: LazyLock<RwLock<HashMap<Address, (Token, Token)>>> = LazyLock::new(Default::default);
+
+/// Set when `POOL_TOKENS` gains or loses an entry since the last load or save
+static POOL_TOKENS_CHANGED: AtomicBool = AtomicBool::new(false);
+
+fn cached_pool_tokens(pool: Address) -> Option<(Token, Token)> {
+    POOL_TOKENS.read().unwrap_or_else(|e| e.into_inner()).get(&pool).cloned()
+}
+
+fn cache_pool_tokens(pool: Address, tokens: (Token, Token)) {
+    POOL_TOKENS.write().unwrap_or_else(|e| e.into_inner()).insert(pool, tokens);
+    POOL_TOKENS_CHANGED.store(true, Ordering::Relaxed);
+}
+
+/// Forget a pool's tokens so the next fetch reads token0/token1 again
+fn evict_pool_tokens(pool: Address) {
+    if POOL_TOKENS.write().unwrap_or_else(|e| e.into_inner()).remove(&pool).is_some() {
+        POOL_TOKENS_CHANGED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Limit on each RPC call made by this module, in milliseconds (0 = wait indefinitely)
+static RPC_TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_RPC_TIMEOUT_MS);
+
+/// Set the per-call RPC timeout (`RPC_TIMEOUT_MS`); zero waits indefinitely
+pub fn set_rpc_timeout(timeout: Duration) {
+    RPC_TIMEOUT_MS.store(timeout.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// Await an RPC call under the configured timeout
+async fn rpc<F, T, E>(call: F) -> Result<T, ArbError>
+where
+    F: IntoFuture<Output = Result<T, E>>,
+    E: std::fmt::Display,
+{
+    with_timeout(call, Duration::from_millis(RPC_TIMEOUT_MS.load(Ordering::Relaxed))).await
+}
+
+/// Await `call` for at most `timeout` (zero waits indefinitely). A timeout is an ordinary
+/// error, so a hung endpoint takes the same retry/skip path as a failed call.
+async fn with_timeout<F, T, E>(call: F, timeout: Duration) -> Result<T, ArbError>
+where
+    F: IntoFuture<Output = Result<T, E>>,
+    E: std::fmt::Display,
+{
+    if timeout.is_zero() {
+        return call.await.map_err(|e| ArbError::Rpc(e.to_string()));
+    }
+    match tokio::time::timeout(timeout, call).await {
+        Ok(result) => result.map_err(|e| ArbError::Rpc(e.to_string())),
+        Err(_) => Err(ArbError::Rpc(format!("call timed out after {} ms", timeout.as_millis()))),
+    }
+}
+
+/// Seed the pool token cache from a JSON file written by `save_pool_tokens` (a missing file is an
+/// empty cache). Unknown tokens are registered, so cached pools need no metadata calls either.
+pub fn load_pool_tokens(path: &str) -> Result<usize, ArbError> {
+    if !Path::new(path).exists() {
+        return Ok(0);
+    }
+    let entries: BTreeMap<Address, (Token, Token)> = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    for (token0, token1) in entries.values() {
+        for token in [token0, token1] {
+            if Token::from_address(token.address()).is_none() {
+                crate::token_registry::register(token.clone());
+            }
+        }
+    }
+
+    let count = entries.len();
+    POOL_TOKENS.write().unwrap_or_else(|e| e.into_inner()).extend(entries);
+    POOL_TOKENS_CHANGED.store(false, Ordering::Relaxed);
+    Ok(count)
+}
+
+/// Write the pool token cache to `path` if it changed since the last load or save
+pub fn save_pool_tokens(path: &str) -> Result<bool, ArbError> {
+    if !POOL_TOKENS_CHANGED.swap(false, Ordering::Relaxed) {
+        return Ok(false);
+    }
+    let entries: BTreeMap<Address, (Token, Token)> = POOL_TOKENS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|(pool, tokens)| (*pool, tokens.clone()))
+        .collect();
+    serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), &entries)?;
+    Ok(true)
+}
+
+/// Resolve a token from the registry, registering it from its on-chain metadata if unknown
+pub async fn resolve_token<P: Provider>(provider: &P, address: Address) -> Result<Token, ArbError> {
+    if let Some(token) = Token::from_address(address) {
+        return Ok(token);
+    }
+
+    let contract = IERC20Metadata::new(address, provider);
+    let symbol = rpc(contract.symbol().call()).await?;
+    let decimals = rpc(contract.decimals().call()).await?;
+    let token = Token::new(address, symbol, decimals);
+    crate::token_registry::register(token.clone());
+    Ok(token)
+}
+
+/// Fetch reserves for a single pool, with every call pinned to `block_number`
 pub async fn fetch_pool_reserves<P: Provider + Clone>(
     provider: P,
     pool_address: Address,
     block_number: u64,
-) -> Result<PoolReserves, Box<dyn Error>> {
-    let contract = IMoePair::new(pool_address, provider.clone());
+) -> Result<PoolReserves, ArbError> {
+    fetch_pool_reserves_at_block(&provider, pool_address, block_number).await
+}
 
-    // Fetch token0 and token1
-    let token0_addr = contract.token0().call().await?;
-    let token1_addr = contract.token1().call().await?;
+/// Fetch reserves for a few pools as of `block_number`, one pool at a time
+pub async fn fetch_pools_at_block<P: Provider>(
+    provider: &P,
+    pool_addresses: &[Address],
+    block_number: u64,
+) -> Result<HashMap<Address, PoolReserves>, ArbError> {
+    let mut reserves_map = HashMap::new();
+    for &pool_address in pool_addresses {
+        reserves_map.insert(pool_address, fetch_pool_reserves_at_block(provider, pool_address, block_number).await?);
+    }
+    Ok(reserves_map)
+}
 
-    // Map to our Token enum
-    let token0 = Token::from_address(token0_addr)
-        .ok_or("Unknown token0")?;
-    let token1 = Token::from_address(token1_addr)
-        .ok_or("Unknown token1")?;
+/// Fetch reserves for a single pool as of `block_number` (older blocks need an archive-capable RPC)
+pub async fn fetch_pool_reserves_at_block<P: Provider>(
+    provider: &P,
+    pool_address: Address,
+    block_number: u64,
+) -> Result<PoolReserves, ArbError> {
+    let contract = IMoePair::new(pool_address, provider);
+    let block = BlockId::number(block_number);
 
-    // Fetch reserves
-    let reserves = contract.getReserves().call().await?;
-    let reserve0 = U256::from(reserves.reserve0);
-    let reserve1 = U256::from(reserves.reserve1);
+    // Once a pool's tokens are known only getReserves hits the chain
+    let (token0, token1) = match cached_pool_tokens(pool_address) {
+        Some(tokens) => tokens,
+        None => {
+            let token0 = resolve_token(provider, rpc(contract.token0().block(block).call()).await?).await?;
+            let token1 = resolve_token(provider, rpc(contract.token1().block(block).call()).await?).await?;
+            cache_pool_tokens(pool_address, (token0.clone(), token1.clone()));
+            (token0, token1)
+        }
+    };
+    let reserves = match rpc(contract.getReserves().block(block).call()).await {
+        Ok(reserves) => reserves,
+        Err(e) => {
+            evict_pool_tokens(pool_address);
+            return Err(e);
+        }
+    };
 
     Ok(PoolReserves::new(
+        token0,
+        U256::from(reserves.reserve0),
+        token1,
+        U256::from(reserves.reserve1),
+        block_number,
+        pool_address,
+    ))
+}
+
+/// Read a V3 pool at a block: its virtual reserves (as `PoolReserves`), price/liquidity state
+/// and fee as a fraction. The virtual reserves only describe the active tick range.
+/// Library only: the monitors do not load or refresh V3 pools yet.
+pub async fn fetch_v3_pool_at_block<P: Provider>(
+    provider: &P,
+    pool_address: Address,
+    block_number: u64,
+) -> Result<(PoolReserves, V3PoolState, f64), ArbError> {
+    let contract = IUniswapV3Pool::new(pool_address, provider);
+    let block = BlockId::number(block_number);
+
+    let token0 = resolve_token(provider, rpc(contract.token0().block(block).call()).await?).await?;
+    let token1 = resolve_token(provider, rpc(contract.token1().block(block).call()).await?).await?;
+    let slot0 = rpc(contract.slot0().block(block).call()).await?;
+    let liquidity = rpc(contract.liquidity().block(block).call()).await?;
+    let fee = rpc(contract.fee().block(block).call()).await?;
+
+    let state = V3PoolState {
+        sqrt_price_x96: U256::from(slot0.sqrtPriceX96),
+        liquidity,
+        tick: slot0.tick.as_i32(),
+    };
+    let (virtual0, virtual1) = v3_virtual_reserves(&state, token0.decimals(), token1.decimals());
+    let (reserve0, reserve1) = (
+        f64_to_u256_with_decimals(virtual0, token0.decimals()),
+        f64_to_u256_with_decimals(virtual1, token1.decimals()),
+    );
+    let reserves = PoolReserves::new(
         token0,
         reserve0,
         token1,
         reserve1,
         block_number,
         pool_address,
-    ))
+    );
+
+    // V3 fees are in hundredths of a basis point
+    Ok((reserves, state, fee.to::<u32>() as f64 / 1_000_000.0))
+}
+
+/// How much of a pool set a fetch returned
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchStatus {
+    /// Every pool was fetched
+    Complete,
+    /// Some pools failed every attempt; the snapshot is degraded
+    Partial { missing: Vec<Address> },
+    /// No pool could be fetched
+    Failed,
+}
+
+/// Reserves fetched for a pool set, plus the pools that failed every attempt
+#[derive(Debug, Default)]
+pub struct ReserveFetchOutcome {
+    pub reserves: HashMap<Address, PoolReserves>,
+    pub failed: Vec<Address>,
+}
+
+impl ReserveFetchOutcome {
+    /// Error if any fetched pool reports a block other than `block_number`. Multicall reads carry the
+    /// block the chain executed at; per-pool fallback reads only carry the block they were pinned to.
+    pub fn check_block_consistency(&self, block_number: u64) -> Result<(), ArbError> {
+        let mismatched: Vec<String> = self.reserves
+            .values()
+            .filter(|reserves| reserves.block_number != block_number)
+            .map(|reserves| format!("{} (block {})", reserves.pool_address, reserves.block_number))
+            .collect();
+        if mismatched.is_empty() {
+            Ok(())
+        } else {
+            Err(ArbError::Rpc(format!("pools read outside block {}: {}", block_number, mismatched.join(", "))))
+        }
+    }
+
+    /// Move pools whose reserves fail `PoolReserves::sanity_check` to `failed`, with a warning,
+    /// so zero or absurd values never reach the graph
+    pub fn reject_implausible(&mut self) {
+        let mut rejected: Vec<Address> = self.reserves
+            .iter()
+            .filter_map(|(address, reserves)| {
+                let issue = reserves.sanity_check().err()?;
+                warn!("⚠️ Excluding pool {}: {}", address, issue);
+                Some(*address)
+            })
+            .collect();
+        rejected.sort();
+        for address in &rejected {
+            self.reserves.remove(address);
+        }
+        self.failed.extend(rejected);
+    }
+
+    /// Whether the snapshot is complete, partial or empty
+    pub fn status(&self) -> FetchStatus {
+        match (self.reserves.is_empty(), self.failed.is_empty()) {
+            (_, true) => FetchStatus::Complete,
+            (true, false) => FetchStatus::Failed,
+            (false, false) => FetchStatus::Partial { missing: self.failed.clone() },
+        }
+    }
+
+    /// Share of the requested pools that were fetched (1 for an empty pool set)
+    pub fn coverage(&self) -> f64 {
+        let requested = self.reserves.len() + self.failed.len();
+        if requested == 0 {
+            return 1.0;
+        }
+        self.reserves.len() as f64 / requested as f64
+    }
+
+    /// Add the pools of another fetch (e.g. the next batch)
+    pub fn merge(&mut self, other: ReserveFetchOutcome) {
+        self.reserves.extend(other.reserves);
+        self.failed.extend(other.failed);
+    }
+
+    /// The fetched reserves if at least `min_coverage` of the pools came back (warning
+    /// about the missing ones), an error otherwise or when nothing was fetched
+    pub fn into_usable(self, min_coverage: f64) -> Result<HashMap<Address, PoolReserves>, ArbError> {
+        let requested = self.reserves.len() + self.failed.len();
+        match self.status() {
+            FetchStatus::Complete => Ok(self.reserves),
+            FetchStatus::Failed => Err(ArbError::Rpc(format!("none of the {} pools could be fetched", requested))),
+            FetchStatus::Partial { missing } => {
+                let coverage = self.coverage();
+                if coverage < min_coverage {
+                    return Err(ArbError::Rpc(format!(
+                        "only {}/{} pools fetched ({:.1}% < {:.1}% minimum coverage), skipping the block",
+                        self.reserves.len(), requested, coverage * 100.0, min_coverage * 100.0
+                    )));
+                }
+                warn!("⚠️ Degraded snapshot: {}/{} pools fetched, missing {:?}", self.reserves.len(), requested, missing);
+                Ok(self.reserves)
+            }
+        }
+    }
+
+    /// All reserves, or an error naming the pools that could not be fetched
+    pub fn into_complete(self) -> Result<HashMap<Address, PoolReserves>, ArbError> {
+        if self.failed.is_empty() {
+            return Ok(self.reserves);
+        }
+        let failed: Vec<String> = self.failed.iter().map(|addr| addr.to_string()).collect();
+        Err(ArbError::Rpc(format!("Failed to fetch reserves after maximum retries for pools: {}", failed.join(", "))))
+    }
+}
+
+/// Fetch reserves for every pool in one Multicall3 `aggregate3` call pinned to `block_number`.
+/// Pools whose tokens are not cached yet cost one extra aggregated call for token0/token1.
+/// The reserves carry the block Multicall3 reports it executed at, which a node that ignores
+/// the block tag gets wrong.
+pub async fn fetch_reserves_multicall<P: Provider>(
+    provider: &P,
+    pool_addresses: &[Address],
+    block_number: u64,
+) -> Result<ReserveFetchOutcome, ArbError> {
+    let multicall_address = MULTICALL3_ADDRESS.parse().map_err(|e| ArbError::Config(format!("invalid Multicall3 address: {}", e)))?;
+    let multicall = IMulticall3::new(multicall_address, provider);
+    let block = BlockId::number(block_number);
+    let mut failed = Vec::new();
+
+    let uncached: Vec<Address> = pool_addresses.iter().copied().filter(|&addr| cached_pool_tokens(addr).is_none()).collect();
+    if !uncached.is_empty() {
+        let calls = uncached
+            .iter()
+            .flat_map(|&pool| [
+                multicall_call(pool, IMoePair::token0Call {}.abi_encode()),
+                multicall_call(pool, IMoePair::token1Call {}.abi_encode()),
+            ])
+            .collect();
+        let results = rpc(multicall.aggregate3(calls).block(block).call()).await?;
+
+        for (&pool, pair) in uncached.iter().zip(results.chunks(2)) {
+            let addresses = match pair {
+                [token0, token1] => decode_token_address(token0).zip(decode_token_address(token1)),
+                _ => None,
+            };
+            let Some((token0, token1)) = addresses else {
+                failed.push(pool);
+                continue;
+            };
+            match (resolve_token(provider, token0).await, resolve_token(provider, token1).await) {
+                (Ok(token0), Ok(token1)) => cache_pool_tokens(pool, (token0, token1)),
+                _ => failed.push(pool),
+            }
+        }
+    }
+
+    let pools: Vec<(Address, (Token, Token))> = pool_addresses
+        .iter()
+        .filter_map(|&addr| cached_pool_tokens(addr).map(|tokens| (addr, tokens)))
+        .collect();
+    // The block number rides in the same call, so reserves carry the block the node actually executed at
+    let calls = pools
+        .iter()
+        .map(|(pool, _)| multicall_call(*pool, IMoePair::getReservesCall {}.abi_encode()))
+        .chain([multicall_call(multicall_address, IMulticall3::getBlockNumberCall {}.abi_encode())])
+        .collect();
+    let mut results = rpc(multicall.aggregate3(calls).block(block).call()).await?;
+    let served_block = results
+        .pop()
+        .as_ref()
+        .and_then(decode_block_number)
+        .ok_or_else(|| ArbError::Rpc("Multicall3 did not report the block it executed at".to_string()))?;
+
+    let mut outcome = decode_reserves(pools, &results, served_block);
+    outcome.failed.extend(failed);
+    Ok(outcome)
+}
+
+/// Multicall first, then per-pool calls for anything it could not return.
+/// Falls back to per-pool calls entirely when the multicall itself fails (e.g. no Multicall3 on the chain).
+/// At most `max_concurrent` per-pool calls are in flight at once (0 = unbounded).
+pub async fn fetch_all_reserves<P: Provider + Clone>(
+    provider: P,
+    pool_addresses: &[Address],
+    block_number: u64,
+    max_retries: u32,
+    max_concurrent: usize,
+) -> ReserveFetchOutcome {
+    let mut outcome = fetch_all_reserves_unchecked(provider, pool_addresses, block_number, max_retries, max_concurrent).await;
+    outcome.reject_implausible();
+
+    // Debug builds reject a snapshot that mixes blocks rather than analyze phantom arbitrage
+    if cfg!(debug_assertions) && let Err(e) = outcome.check_block_consistency(block_number) {
+        error!("❌ Rejecting reserve batch: {}", e);
+        return ReserveFetchOutcome {
+            reserves: HashMap::new(),
+            failed: pool_addresses.to_vec(),
+        };
+    }
+    outcome
+}
+
+async fn fetch_all_reserves_unchecked<P: Provider + Clone>(
+    provider: P,
+    pool_addresses: &[Address],
+    block_number: u64,
+    max_retries: u32,
+    max_concurrent: usize,
+) -> ReserveFetchOutcome {
+    match fetch_reserves_multicall(&provider, pool_addresses, block_number).await {
+        Ok(mut outcome) => {
+            if !outcome.failed.is_empty() {
+                let retried = fetch_all_reserves_with_retry(provider, &outcome.failed, block_number, max_retries, max_concurrent).await;
+                outcome.reserves.extend(retried.reserves);
+                outcome.failed = retried.failed;
+            }
+            outcome
+        }
+        Err(e) => {
+            warn!("⚠️ Multicall failed, falling back to per-pool calls: {}", e);
+            fetch_all_reserves_with_retry(provider, pool_addresses, block_number, max_retries, max_concurrent).await
+        }
+    }
+}
+
+fn multicall_call(target: Address, call_data: Vec<u8>) -> IMulticall3::Call3 {
+    IMulticall3::Call3 {
+        target,
+        allowFailure: true,
+        callData: Bytes::from(call_data),
+    }
+}
+
+fn decode_block_number(result: &IMulticall3::Result) -> Option<u64> {
+    if !result.success {
+        return None;
+    }
+    IMulticall3::getBlockNumberCall::abi_decode_returns(&result.returnData).ok()?.try_into().ok()
 }
 
-/// Parallel fetch all pool reserves with retry mechanism
+fn decode_token_address(result: &IMulticall3::Result) -> Option<Address> {
+    if !result.success {
+        return None;
+    }
+    IMoePair::token0Call::abi_decode_returns(&result.returnData).ok()
+}
+
+/// Pair each pool with its `getReserves` result; failed or undecodable calls are reported as failed.
+/// A pool whose cached tokens no longer answer `getReserves` is evicted so its tokens are re-read.
+fn decode_reserves(
+    pools: Vec<(Address, (Token, Token))>,
+    results: &[IMulticall3::Result],
+    block_number: u64,
+) -> ReserveFetchOutcome {
+    let mut outcome = ReserveFetchOutcome::default();
+    for (i, (pool, (token0, token1))) in pools.into_iter().enumerate() {
+        let decoded = results
+            .get(i)
+            .filter(|result| result.success)
+            .and_then(|result| IMoePair::getReservesCall::abi_decode_returns(&result.returnData).ok());
+        match decoded {
+            Some(reserves) => {
+                outcome.reserves.insert(pool, PoolReserves::new(
+                    token0,
+                    U256::from(reserves.reserve0),
+                    token1,
+                    U256::from(reserves.reserve1),
+                    block_number,
+                    pool,
+                ));
+            }
+            None => {
+                evict_pool_tokens(pool);
+                outcome.failed.push(pool);
+            }
+        }
+    }
+    outcome
+}
+
+/// Parallel fetch all pool reserves, retrying each failed pool independently, with at most
+/// `max_concurrent` calls in flight (0 = unbounded)
 pub async fn fetch_all_reserves_with_retry<P: Provider + Clone>(
     provider: P,
     pool_addresses: &[Address],
     block_number: u64,
     max_retries: u32,
-) -> Result<HashMap<Address, PoolReserves>, Box<dyn Error>> {
+    max_concurrent: usize,
+) -> ReserveFetchOutcome {
+    retry_per_pool(pool_addresses, max_retries, max_concurrent, Duration::from_secs(1), |addr| {
+        fetch_pool_reserves(provider.clone(), addr, block_number)
+    }).await
+}
+
+/// Run `fetch` for every pool in parallel, at most `max_concurrent` at a time (0 = unbounded);
+/// only pools that failed are retried, up to `max_retries` attempts
+async fn retry_per_pool<F, Fut>(
+    pool_addresses: &[Address],
+    max_retries: u32,
+    max_concurrent: usize,
+    retry_delay: Duration,
+    fetch: F,
+) -> ReserveFetchOutcome
+where
+    F: Fn(Address) -> Fut,
+    Fut: Future<Output = Result<PoolReserves, ArbError>>,
+{
+    let mut outcome = ReserveFetchOutcome::default();
+    let mut pending = pool_addresses.to_vec();
     let mut attempts = 0;
-    
-    while attempts < max_retries {
-        let futures: Vec<_> = pool_addresses.iter().map(|&addr| {
-            let provider_clone = provider.clone();
-            async move {
-                (addr, fetch_pool_reserves(provider_clone, addr, block_number).await)
-            }
-        }).collect();
 
-        let results = futures::future::join_all(futures).await;
-        let mut reserves_map = HashMap::new();
-        let mut all_succeeded = true;
+    while !pending.is_empty() && attempts < max_retries {
+        // Futures are lazy, so only `limit` of them run at a time
+        let futures: Vec<_> = pending.iter().map(|&addr| {
+            let fetch = &fetch;
+            async move { (addr, fetch(addr).await) }
+        }).collect();
+        let limit = if max_concurrent == 0 { futures.len() } else { max_concurrent };
+        let results: Vec<_> = futures::stream::iter(futures).buffered(limit).collect().await;
 
+        let mut still_failing = Vec::new();
         for (addr, result) in results {
             match result {
                 Ok(reserves) => {
-                    reserves_map.insert(addr, reserves);
+                    outcome.reserves.insert(addr, reserves);
                 }
                 Err(e) => {
-                    println!("Error fetching reserves for pool {}: {}", addr, e);
-                    all_succeeded = false;
-                    break;
+                    warn!("Error fetching reserves for pool {}: {}", addr, e);
+                    still_failing.push(addr);
                 }
             }
         }
-
-        if all_succeeded {
-            return Ok(reserves_map);
-        }
+        pending = still_failing;
 
         attempts += 1;
-        if attempts < max_retries {
-            println!("Retrying... attempt {} of {}", attempts + 1, max_retries);
-            sleep(Duration::from_secs(1)).await;
+        if !pending.is_empty() && attempts < max_retries {
+            warn!("Retrying {} pools... attempt {} of {}", pending.len(), attempts + 1, max_retries);
+            sleep(retry_delay).await;
         }
     }
 
-    Err("Failed to fetch reserves after maximum retries".into())
+    outcome.failed = pending;
+    outcome
 }
 
 /// Get current block number from provider
-pub async fn get_current_block<P: Provider>(provider: &P) -> Result<u64, Box<dyn Error>> {
-    Ok(provider.get_block_number().await?)
+pub async fn get_current_block<P: Provider>(provider: &P) -> Result<u64, ArbError> {
+    rpc(provider.get_block_number()).await
+}
+
+/// Collect swap samples for a pool from `Sync`/`Swap` event pairs in a block range.
+/// `Sync` carries post-swap reserves, so pre-swap reserves are recovered by undoing the swap.
+pub async fn fetch_swap_samples<P: Provider>(
+    provider: &P,
+    pool_address: Address,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<SwapSample>, ArbError> {
+    let filter = Filter::new()
+        .address(pool_address)
+        .from_block(from_block)
+        .to_block(to_block)
+        .event_signature(vec![IMoePair::Swap::SIGNATURE_HASH, IMoePair::Sync::SIGNATURE_HASH]);
+    let logs = rpc(provider.get_logs(&filter)).await?;
+
+    let mut samples = Vec::new();
+    let mut last_sync = None;
+    for log in logs {
+        if let Ok(sync) = log.log_decode::<IMoePair::Sync>() {
+            last_sync = Some((log.transaction_hash, sync.inner.data));
+            continue;
+        }
+
+        let Ok(swap) = log.log_decode::<IMoePair::Swap>() else {
+            continue;
+        };
+        let Some((sync_tx, reserves)) = last_sync.take() else {
+            continue;
+        };
+        if sync_tx != log.transaction_hash {
+            continue;
+        }
+
+        let swap = swap.inner.data;
+        let reserve0 = u256_to_f64(U256::from(reserves.reserve0));
+        let reserve1 = u256_to_f64(U256::from(reserves.reserve1));
+        let (amount0_in, amount1_in) = (u256_to_f64(swap.amount0In), u256_to_f64(swap.amount1In));
+        let (amount0_out, amount1_out) = (u256_to_f64(swap.amount0Out), u256_to_f64(swap.amount1Out));
+
+        // Only single-direction swaps can be inverted cleanly
+        let sample = if amount0_in > 0.0 && amount1_out > 0.0 && amount1_in == 0.0 {
+            SwapSample {
+                reserve_in: reserve0 - amount0_in,
+                reserve_out: reserve1 + amount1_out,
+                amount_in: amount0_in,
+                amount_out: amount1_out,
+            }
+        } else if amount1_in > 0.0 && amount0_out > 0.0 && amount0_in == 0.0 {
+            SwapSample {
+                reserve_in: reserve1 - amount1_in,
+                reserve_out: reserve0 + amount0_out,
+                amount_in: amount1_in,
+                amount_out: amount0_out,
+            }
+        } else {
+            continue;
+        };
+        samples.push(sample);
+    }
+
+    Ok(samples)
+}
+
+/// Pool and new (reserve0, reserve1) carried by a V2 `Sync` log; `None` for any other log
+pub fn decode_sync_log(log: &Log) -> Option<(Address, U256, U256)> {
+    let sync = log.log_decode::<IMoePair::Sync>().ok()?;
+    Some((sync.address(), U256::from(sync.inner.data.reserve0), U256::from(sync.inner.data.reserve1)))
+}
+
+/// Latest `Sync` reserves of each listed pool over a block range (one `eth_getLogs` call).
+/// Logs come back in chain order, so a pool's last `Sync` wins.
+pub async fn fetch_sync_reserves<P: Provider>(
+    provider: &P,
+    pool_addresses: &[Address],
+    from_block: u64,
+    to_block: u64,
+) -> Result<HashMap<Address, (U256, U256)>, ArbError> {
+    let filter = Filter::new()
+        .address(pool_addresses.to_vec())
+        .from_block(from_block)
+        .to_block(to_block)
+        .event_signature(IMoePair::Sync::SIGNATURE_HASH);
+    let logs = rpc(provider.get_logs(&filter)).await?;
+    Ok(logs
+        .iter()
+        .filter_map(decode_sync_log)
+        .map(|(pool, reserve0, reserve1)| (pool, (reserve0, reserve1)))
+        .collect())
+}
+
+/// Price from the oracle, refetched from the chain when stale; failures fall back to the static price
+pub async fn refresh_gas_price<P: Provider>(oracle: &mut GasPriceOracle, provider: &P) -> f64 {
+    if !oracle.is_stale_at(std::time::Instant::now()) {
+        return oracle.gas_price_gwei();
+    }
+    let fetched = fetch_gas_price(provider).await;
+    if let Err(e) = &fetched {
+        warn!("⚠️ Failed to fetch gas price, using the static price: {}", e);
+    }
+    oracle.record_at(fetched, std::time::Instant::now())
+}
+
+/// Current gas price in gwei from `eth_gasPrice`, or the latest block's base fee if that call fails
+pub async fn fetch_gas_price<P: Provider>(provider: &P) -> Result<f64, ArbError> {
+    let gas_price_error = match rpc(provider.get_gas_price()).await {
+        Ok(wei) => return Ok(wei as f64 / 1e9),
+        Err(e) => e,
+    };
+    let block = rpc(provider.get_block_by_number(BlockNumberOrTag::Latest))
+        .await?
+        .ok_or_else(|| ArbError::Rpc(format!("eth_gasPrice failed ({}) and the latest block is unavailable", gas_price_error)))?;
+    let base_fee = block.header.base_fee_per_gas
+        .ok_or_else(|| ArbError::Rpc(format!("eth_gasPrice failed ({}) and the latest block has no base fee", gas_price_error)))?;
+    Ok(base_fee as f64 / 1e9)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
-    // Note: These tests would require a mock provider or integration test setup
-    // For now, we'll just test the basic functionality
-    
+
+    // Loop-level reads against a fake chain are covered by `reserve_reader::MockReserveReader`
+
     #[test]
     fn test_token_from_address() {
         let wmnt_addr: Address = crate::constants::WMNT_ADDRESS.parse().unwrap();
         let token = Token::from_address(wmnt_addr);
         assert!(token.is_some());
         
-        match token.unwrap() {
-            Token::WMNT(_) => {},
-            _ => panic!("Expected WMNT token"),
-        }
+        let token = token.unwrap();
+        assert_eq!(token.symbol(), "WMNT");
+        assert_eq!(token.decimals(), 18);
+    }
+
+    #[test]
+    fn test_pool_tokens_persist_and_evict() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pool_tokens.json");
+        let path = path.to_str().unwrap();
+        let pool = Address::with_last_byte(0xe1);
+        let wmnt = Token::parse("WMNT").unwrap();
+        let exotic = Token::new(Address::repeat_byte(0xe2), "EXO", 6);
+
+        assert_eq!(load_pool_tokens(path).unwrap(), 0);
+        cache_pool_tokens(pool, (wmnt.clone(), exotic.clone()));
+        assert!(save_pool_tokens(path).unwrap());
+
+        // A fresh run reloads the pair, registering the unknown token with its metadata
+        evict_pool_tokens(pool);
+        assert!(cached_pool_tokens(pool).is_none());
+        assert!(load_pool_tokens(path).unwrap() >= 1);
+        let (token0, token1) = cached_pool_tokens(pool).unwrap();
+        assert_eq!((token0, token1.decimals()), (wmnt.clone(), 6));
+        assert_eq!(Token::from_address(exotic.address()).unwrap().symbol(), "EXO");
+
+        // A pool whose getReserves call fails is evicted so its tokens are re-read next time
+        let failed = IMulticall3::Result { success: false, returnData: Bytes::new() };
+        let outcome = decode_reserves(vec![(pool, (wmnt, exotic))], &[failed], 1);
+        assert_eq!(outcome.failed, vec![pool]);
+        assert!(cached_pool_tokens(pool).is_none());
+    }
+
+    #[test]
+    fn test_block_consistency_check() {
+        let token = Token::new(Address::ZERO, "TKN", 18);
+        let reserves_at = |pool: u8, block: u64| {
+            let pool = Address::with_last_byte(pool);
+            (pool, PoolReserves::new(token.clone(), U256::from(1u64), token.clone(), U256::from(1u64), block, pool))
+        };
+
+        let mut outcome = ReserveFetchOutcome {
+            reserves: HashMap::from([reserves_at(1, 100), reserves_at(2, 100)]),
+            failed: Vec::new(),
+        };
+        assert!(outcome.check_block_consistency(100).is_ok());
+
+        outcome.reserves.extend([reserves_at(3, 101)]);
+        let err = outcome.check_block_consistency(100).unwrap_err().to_string();
+        assert!(err.contains(&Address::with_last_byte(3).to_string()) && err.contains("block 101"));
+    }
+
+    #[test]
+    fn test_decode_multicall_reserves() {
+        let ok_pool = Address::with_last_byte(1);
+        let reverted_pool = Address::with_last_byte(2);
+        let garbage_pool = Address::with_last_byte(3);
+        let tokens = (Token::new(Address::with_last_byte(10), "AAA", 18), Token::new(Address::with_last_byte(11), "BBB", 6));
+
+        let encoded = IMoePair::getReservesCall::abi_encode_returns(&IMoePair::getReservesReturn {
+            reserve0: alloy::primitives::Uint::from(1_000u64),
+            reserve1: alloy::primitives::Uint::from(2_000u64),
+            blockTimestampLast: 0,
+        });
+        let results = vec![
+            IMulticall3::Result { success: true, returnData: encoded.into() },
+            IMulticall3::Result { success: false, returnData: Bytes::new() },
+            IMulticall3::Result { success: true, returnData: Bytes::from(vec![1, 2, 3]) },
+        ];
+        let pools = vec![
+            (ok_pool, tokens.clone()),
+            (reverted_pool, tokens.clone()),
+            (garbage_pool, tokens),
+        ];
+
+        let outcome = decode_reserves(pools.clone(), &results, 42);
+        assert_eq!(outcome.failed, vec![reverted_pool, garbage_pool]);
+        let reserves = &outcome.reserves[&ok_pool];
+        assert_eq!((reserves.reserve_a, reserves.reserve_b), (U256::from(1_000u64), U256::from(2_000u64)));
+        assert_eq!((reserves.block_number, reserves.token_b.decimals()), (42, 6));
+        assert!(outcome.check_block_consistency(42).is_ok());
+
+        // A node that served a later block than the one pinned gets the batch rejected
+        let served = IMulticall3::Result {
+            success: true,
+            returnData: IMulticall3::getBlockNumberCall::abi_encode_returns(&U256::from(43u64)).into(),
+        };
+        let served_block = decode_block_number(&served).unwrap();
+        assert_eq!(served_block, 43);
+        let err = decode_reserves(pools, &results, served_block).check_block_consistency(42).unwrap_err().to_string();
+        assert!(err.contains(&ok_pool.to_string()) && err.contains("block 43"), "{}", err);
+        assert_eq!(decode_block_number(&IMulticall3::Result { success: false, returnData: Bytes::new() }), None);
+    }
+
+    #[test]
+    fn test_reject_implausible_reserves() {
+        let wmnt = Token::new(Address::from([0xa0; 20]), "WMNT", 18);
+        let usdc = Token::new(Address::from([0xa1; 20]), "USDC", 6);
+        let reserves = |last_byte, reserve_a, reserve_b| {
+            let address = Address::with_last_byte(last_byte);
+            (address, PoolReserves::new(wmnt.clone(), reserve_a, usdc.clone(), reserve_b, 7, address))
+        };
+        let healthy = reserves(1, U256::from(10u64).pow(U256::from(21u64)), U256::from(2_000_000_000u64));
+        let drained = reserves(2, U256::ZERO, U256::ZERO);
+        let one_sided = reserves(3, U256::from(10u64).pow(U256::from(21u64)), U256::ZERO);
+        let overflowing = reserves(4, U256::MAX, U256::from(2_000_000_000u64));
+        assert!(healthy.1.sanity_check().is_ok());
+        assert!(one_sided.1.sanity_check().unwrap_err().contains("zero USDC"));
+        assert!(overflowing.1.sanity_check().unwrap_err().contains("WMNT reserve"));
+
+        let mut outcome = ReserveFetchOutcome {
+            reserves: HashMap::from([healthy.clone(), drained.clone(), one_sided.clone(), overflowing.clone()]),
+            failed: Vec::new(),
+        };
+        outcome.reject_implausible();
+        assert_eq!(outcome.reserves.keys().collect::<Vec<_>>(), vec![&healthy.0]);
+        assert_eq!(outcome.failed, vec![drained.0, one_sided.0, overflowing.0]);
+        assert!(outcome.into_complete().is_err());
+    }
+
+    #[test]
+    fn test_fetch_status_and_coverage() {
+        let token = Token::new(Address::ZERO, "TKN", 18);
+        let pools: Vec<Address> = (1..=4).map(Address::with_last_byte).collect();
+        let outcome = |fetched: usize| ReserveFetchOutcome {
+            reserves: pools[..fetched]
+                .iter()
+                .map(|&pool| (pool, PoolReserves::new(token.clone(), U256::from(1u64), token.clone(), U256::from(1u64), 7, pool)))
+                .collect(),
+            failed: pools[fetched..].to_vec(),
+        };
+
+        // Complete
+        assert_eq!(outcome(4).status(), FetchStatus::Complete);
+        assert_eq!(outcome(4).coverage(), 1.0);
+        assert_eq!(outcome(4).into_usable(1.0).unwrap().len(), 4);
+
+        // Partial: usable down to the minimum coverage, skipped below it
+        assert_eq!(outcome(3).status(), FetchStatus::Partial { missing: vec![pools[3]] });
+        assert_eq!(outcome(3).coverage(), 0.75);
+        assert_eq!(outcome(3).into_usable(0.75).unwrap().len(), 3);
+        let error = outcome(3).into_usable(0.8).unwrap_err().to_string();
+        assert!(error.contains("only 3/4 pools fetched"), "{}", error);
+
+        // Failed, even with no minimum
+        assert_eq!(outcome(0).status(), FetchStatus::Failed);
+        assert_eq!(outcome(0).coverage(), 0.0);
+        assert!(outcome(0).into_usable(0.0).is_err());
+
+        // Batches merge into one snapshot; an empty pool set counts as complete
+        let mut merged = ReserveFetchOutcome::default();
+        assert_eq!((merged.status(), merged.coverage()), (FetchStatus::Complete, 1.0));
+        merged.merge(outcome(3));
+        merged.merge(ReserveFetchOutcome { reserves: HashMap::new(), failed: vec![Address::with_last_byte(9)] });
+        assert_eq!(merged.status(), FetchStatus::Partial { missing: vec![pools[3], Address::with_last_byte(9)] });
+        assert_eq!(merged.coverage(), 0.6);
+    }
+
+    #[test]
+    fn test_decode_sync_log() {
+        assert_eq!(IMoePair::Sync::SIGNATURE, "Sync(uint112,uint112)");
+        assert_eq!(IMoePair::Sync::SIGNATURE_HASH, alloy::primitives::keccak256("Sync(uint112,uint112)"));
+        assert_eq!(
+            IMoePair::Sync::SIGNATURE_HASH.to_string(),
+            "0x1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1"
+        );
+
+        let pool = Address::with_last_byte(7);
+        let rpc_log = |data| Log { inner: alloy::primitives::Log { address: pool, data }, ..Default::default() };
+        let sync = IMoePair::Sync {
+            reserve0: alloy::primitives::Uint::from(1_500u64),
+            reserve1: alloy::primitives::Uint::from(3_000u64),
+        };
+        assert_eq!(
+            decode_sync_log(&rpc_log(sync.encode_log_data())),
+            Some((pool, U256::from(1_500u64), U256::from(3_000u64)))
+        );
+
+        // Other pair events do not decode as Sync
+        let swap = IMoePair::Swap {
+            sender: Address::ZERO,
+            amount0In: U256::from(1u64),
+            amount1In: U256::ZERO,
+            amount0Out: U256::ZERO,
+            amount1Out: U256::from(2u64),
+            to: Address::ZERO,
+        };
+        assert_eq!(decode_sync_log(&rpc_log(swap.encode_log_data())), None);
+    }
+
+    #[tokio::test]
+    async fn test_retry_per_pool_isolates_failures() {
+        use std::sync::Mutex;
+
+        let healthy = Address::with_last_byte(1);
+        let flaky = Address::with_last_byte(2);
+        let broken = Address::with_last_byte(3);
+        let calls: Mutex<HashMap<Address, u32>> = Mutex::new(HashMap::new());
+
+        let outcome = retry_per_pool(&[healthy, flaky, broken], 3, 0, Duration::ZERO, |addr| {
+            let attempt = {
+                let mut calls = calls.lock().unwrap();
+                let count = calls.entry(addr).or_insert(0);
+                *count += 1;
+                *count
+            };
+            async move {
+                if addr == broken || (addr == flaky && attempt < 2) {
+                    return Err(ArbError::Rpc("rpc error".to_string()));
+                }
+                let token = Token::new(Address::ZERO, "TKN", 18);
+                Ok(PoolReserves::new(token.clone(), U256::from(1u64), token, U256::from(1u64), 7, addr))
+            }
+        }).await;
+
+        assert_eq!(outcome.reserves.len(), 2);
+        assert!(outcome.reserves.contains_key(&healthy) && outcome.reserves.contains_key(&flaky));
+        assert_eq!(outcome.failed, vec![broken]);
+
+        // Successful pools are not refetched; failing ones use every attempt
+        let calls = calls.into_inner().unwrap();
+        assert_eq!((calls[&healthy], calls[&flaky], calls[&broken]), (1, 2, 3));
+        assert!(outcome.into_complete().unwrap_err().to_string().contains(&broken.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_retry_per_pool_caps_calls_in_flight() {
+        use std::sync::atomic::AtomicUsize;
+
+        let pools: Vec<Address> = (1..=20).map(Address::with_last_byte).collect();
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let fetch = |addr| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                sleep(Duration::from_millis(2)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                let token = Token::new(Address::ZERO, "TKN", 18);
+                Ok(PoolReserves::new(token.clone(), U256::from(1u64), token, U256::from(1u64), 7, addr))
+            }
+        };
+
+        let outcome = retry_per_pool(&pools, 1, 4, Duration::ZERO, fetch).await;
+        assert_eq!(outcome.reserves.len(), pools.len());
+        assert_eq!(peak.load(Ordering::SeqCst), 4);
+
+        // 0 leaves the calls unbounded
+        peak.store(0, Ordering::SeqCst);
+        retry_per_pool(&pools, 1, 0, Duration::ZERO, fetch).await;
+        assert_eq!(peak.load(Ordering::SeqCst), pools.len());
+    }
+
+    #[tokio::test]
+    async fn test_rpc_timeout_fails_hung_call() {
+        let slow_call = || async {
+            sleep(Duration::from_secs(5)).await;
+            Ok::<u64, ArbError>(100)
+        };
+
+        let started = std::time::Instant::now();
+        let error = with_timeout(slow_call(), Duration::from_millis(20)).await.unwrap_err();
+        assert!(error.is_transient());
+        assert_eq!(error.to_string(), "RPC error: call timed out after 20 ms");
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // Calls that answer in time are unaffected
+        let quick = with_timeout(async { Ok::<u64, ArbError>(7) }, Duration::from_millis(20)).await;
+        assert_eq!(quick.unwrap(), 7);
+
+        // A timed-out pool goes through the normal retry path and ends up failed
+        let hung = Address::with_last_byte(1);
+        let outcome = retry_per_pool(&[hung], 2, 0, Duration::ZERO, |_| async {
+            with_timeout(async {
+                sleep(Duration::from_secs(5)).await;
+                Err::<PoolReserves, ArbError>(ArbError::Rpc("unreachable".to_string()))
+            }, Duration::from_millis(10)).await
+        }).await;
+        assert_eq!(outcome.failed, vec![hung]);
     }
 }
@@ -1,11 +1,19 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 use alloy::primitives::Address;
 use crate::types::PoolReserves;
 
+/// Cached reserves of one pool and when they were stored
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    reserves: PoolReserves,
+    inserted_at: Instant,
+}
+
 /// Cache structure for pool reserves to avoid unnecessary refetching
 #[derive(Debug, Clone)]
 pub struct ReservesCache {
-    data: HashMap<Address, PoolReserves>,
+    data: HashMap<Address, CacheEntry>,
     last_block: u64,
 }
 
@@ -20,13 +28,43 @@ impl ReservesCache {
 
     /// Get cached reserves for a pool address
     pub fn get(&self, address: &Address) -> Option<&PoolReserves> {
-        self.data.get(address)
+        self.data.get(address).map(|entry| &entry.reserves)
+    }
+
+    /// Cached reserves for a pool, unless they were stored more than `ttl` ago
+    pub fn get_fresh(&self, address: &Address, ttl: Duration) -> Option<&PoolReserves> {
+        self.get_fresh_at(address, ttl, Instant::now())
+    }
+
+    /// `get_fresh` measured against `now`
+    pub fn get_fresh_at(&self, address: &Address, ttl: Duration, now: Instant) -> Option<&PoolReserves> {
+        self.data
+            .get(address)
+            .filter(|entry| now.saturating_duration_since(entry.inserted_at) <= ttl)
+            .map(|entry| &entry.reserves)
     }
 
     /// Update the cache with new reserves
     pub fn update(&mut self, address: Address, reserves: PoolReserves) {
+        self.update_at(address, reserves, Instant::now());
+    }
+
+    /// `update` stamped with `now`
+    pub fn update_at(&mut self, address: Address, reserves: PoolReserves, now: Instant) {
         self.last_block = reserves.block_number;
-        self.data.insert(address, reserves);
+        self.data.insert(address, CacheEntry { reserves, inserted_at: now });
+    }
+
+    /// Drop entries stored more than `ttl` ago (pools no longer being fetched); returns how many
+    pub fn prune_older_than(&mut self, ttl: Duration) -> usize {
+        self.prune_older_than_at(ttl, Instant::now())
+    }
+
+    /// `prune_older_than` measured against `now`
+    pub fn prune_older_than_at(&mut self, ttl: Duration, now: Instant) -> usize {
+        let before = self.data.len();
+        self.data.retain(|_, entry| now.saturating_duration_since(entry.inserted_at) <= ttl);
+        before - self.data.len()
     }
 
     /// Check if the block has changed since last update
@@ -36,22 +74,24 @@ impl ReservesCache {
 
     /// Check if any reserves have actually changed compared to cache
     pub fn reserves_changed(&self, new_reserves: &HashMap<Address, PoolReserves>) -> bool {
-        if self.data.len() != new_reserves.len() {
-            return true;
-        }
-        
-        for (addr, new_reserve) in new_reserves {
-            match self.data.get(addr) {
+        self.data.len() != new_reserves.len() || !self.changed_pools(new_reserves).is_empty()
+    }
+
+    /// Pools whose reserves differ from the cache (or are not cached yet), sorted by address
+    pub fn changed_pools(&self, new_reserves: &HashMap<Address, PoolReserves>) -> Vec<Address> {
+        let mut changed: Vec<Address> = new_reserves
+            .iter()
+            .filter(|(addr, new_reserve)| match self.get(addr) {
                 Some(cached_reserve) => {
-                    if cached_reserve.reserve_a != new_reserve.reserve_a 
-                        || cached_reserve.reserve_b != new_reserve.reserve_b {
-                        return true;
-                    }
+                    cached_reserve.reserve_a != new_reserve.reserve_a
+                        || cached_reserve.reserve_b != new_reserve.reserve_b
                 }
-                None => return true,
-            }
-        }
-        false
+                None => true,
+            })
+            .map(|(addr, _)| *addr)
+            .collect();
+        changed.sort();
+        changed
     }
 
     /// Update the last block number (used when reserves didn't change but block advanced)
@@ -71,8 +111,8 @@ impl ReservesCache {
     }
 
     /// Get all cached reserves
-    pub fn get_all(&self) -> &HashMap<Address, PoolReserves> {
-        &self.data
+    pub fn get_all(&self) -> HashMap<Address, PoolReserves> {
+        self.data.iter().map(|(addr, entry)| (*addr, entry.reserves.clone())).collect()
     }
 }
 
@@ -81,3 +121,393 @@ impl Default for ReservesCache {
         Self::new()
     }
 }
+
+/// Reserve amounts (a, b) per pool in token units
+type ReserveSnapshot = HashMap<Address, (f64, f64)>;
+
+/// Throttles reserve-change logging independently of analysis
+#[derive(Debug, Clone)]
+pub struct ReserveLogGate {
+    min_change_pct: f64,
+    min_interval: Duration,
+    last_logged: Option<(Instant, ReserveSnapshot)>,
+}
+
+impl ReserveLogGate {
+    /// Log only when some reserve moved at least `min_change_pct` percent since the
+    /// last logged snapshot, and at most once per `min_interval` (zeros disable each check)
+    pub fn new(min_change_pct: f64, min_interval: Duration) -> Self {
+        Self {
+            min_change_pct,
+            min_interval,
+            last_logged: None,
+        }
+    }
+
+    /// Decide whether a reserve change should be logged, remembering the snapshot if so
+    pub fn should_log(&mut self, reserves_map: &HashMap<Address, PoolReserves>, now: Instant) -> bool {
+        let snapshot: ReserveSnapshot = reserves_map
+            .iter()
+            .map(|(addr, r)| (*addr, (r.reserve_a_units(), r.reserve_b_units())))
+            .collect();
+
+        let log = match &self.last_logged {
+            None => true,
+            Some((logged_at, logged)) => {
+                now.duration_since(*logged_at) >= self.min_interval
+                    && max_change_pct(logged, &snapshot) >= self.min_change_pct
+            }
+        };
+
+        if log {
+            self.last_logged = Some((now, snapshot));
+        }
+        log
+    }
+}
+
+/// Largest relative reserve move (percent) between two snapshots; new pools count as 100%
+fn max_change_pct(before: &ReserveSnapshot, after: &ReserveSnapshot) -> f64 {
+    let pct = |old: f64, new: f64| if old > 0.0 { ((new - old) / old).abs() * 100.0 } else { 100.0 };
+
+    after
+        .iter()
+        .map(|(addr, (a, b))| match before.get(addr) {
+            Some((old_a, old_b)) => pct(*old_a, *a).max(pct(*old_b, *b)),
+            None => 100.0,
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Sliding window of recent reserve ratios per pool, used to rank pools by activity
+#[derive(Debug, Clone)]
+pub struct ReserveHistory {
+    window: usize,
+    ratios: HashMap<Address, VecDeque<f64>>,
+}
+
+impl ReserveHistory {
+    /// Create a history keeping the last `window` snapshots per pool
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(2),
+            ratios: HashMap::new(),
+        }
+    }
+
+    /// Record a snapshot of reserves, evicting the oldest entry once the window is full
+    pub fn record(&mut self, reserves_map: &HashMap<Address, PoolReserves>) {
+        for (addr, reserves) in reserves_map {
+            let reserve_a = reserves.reserve_a_units();
+            let reserve_b = reserves.reserve_b_units();
+            if reserve_a <= 0.0 || reserve_b <= 0.0 {
+                continue;
+            }
+
+            let buffer = self.ratios.entry(*addr).or_insert_with(|| VecDeque::with_capacity(self.window));
+            if buffer.len() == self.window {
+                buffer.pop_front();
+            }
+            buffer.push_back(reserve_b / reserve_a);
+        }
+    }
+
+    /// Volatility of a pool's reserve ratio over the window
+    /// (standard deviation divided by the mean, so pools at different price levels are comparable)
+    pub fn volatility(&self, address: &Address) -> Option<f64> {
+        coefficient_of_variation(self.ratios.get(address)?)
+    }
+
+    /// Pools ranked by volatility, most volatile first
+    pub fn most_volatile(&self, limit: usize) -> Vec<(Address, f64)> {
+        let mut ranked: Vec<(Address, f64)> = self.ratios
+            .keys()
+            .filter_map(|addr| self.volatility(addr).map(|v| (*addr, v)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked.truncate(limit);
+        ranked
+    }
+
+    /// Typical relative move of a pool's reserve ratio from one snapshot to the next
+    /// (root mean square of the step changes)
+    pub fn drift_per_block(&self, address: &Address) -> Option<f64> {
+        let ratios = self.ratios.get(address)?;
+        if ratios.len() < 2 {
+            return None;
+        }
+        let steps = ratios.iter().zip(ratios.iter().skip(1)).map(|(before, after)| (after - before) / before);
+        Some((steps.map(|step| step * step).sum::<f64>() / (ratios.len() - 1) as f64).sqrt())
+    }
+
+    /// Expected relative drift of a path's exchange rate after `blocks` blocks, treating each
+    /// pool as an independent random walk; pools without history count as stable
+    pub fn path_drift(&self, pools: &[Address], blocks: u64) -> f64 {
+        let variance: f64 = pools.iter().filter_map(|addr| self.drift_per_block(addr)).map(|d| d * d).sum();
+        (variance * blocks as f64).sqrt()
+    }
+
+    /// Number of snapshots held for a pool
+    pub fn len(&self, address: &Address) -> usize {
+        self.ratios.get(address).map(|b| b.len()).unwrap_or(0)
+    }
+}
+
+/// Rolling window of observed gas prices (gwei)
+pub struct GasPriceHistory {
+    window: usize,
+    samples: VecDeque<f64>,
+}
+
+impl GasPriceHistory {
+    /// Create a history keeping the last `window` gas price samples
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(2),
+            samples: VecDeque::with_capacity(window.max(2)),
+        }
+    }
+
+    /// Record a gas price sample, evicting the oldest once the window is full
+    pub fn record(&mut self, gas_price_gwei: f64) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(gas_price_gwei);
+    }
+
+    /// Most recent gas price sample
+    pub fn latest(&self) -> Option<f64> {
+        self.samples.back().copied()
+    }
+
+    /// Gas price volatility over the window (standard deviation divided by the mean)
+    pub fn volatility(&self) -> Option<f64> {
+        coefficient_of_variation(&self.samples)
+    }
+}
+
+/// Last on-chain gas price, refetched once older than the refresh interval.
+/// A failed fetch falls back to the static `GAS_PRICE_GWEI` and is retried next block.
+pub struct GasPriceOracle {
+    fallback_gwei: f64,
+    refresh_interval: Duration,
+    current_gwei: f64,
+    fetched_at: Option<Instant>,
+}
+
+impl GasPriceOracle {
+    /// Start from the static price until the first successful fetch
+    pub fn new(fallback_gwei: f64, refresh_interval: Duration) -> Self {
+        Self {
+            fallback_gwei,
+            refresh_interval,
+            current_gwei: fallback_gwei,
+            fetched_at: None,
+        }
+    }
+
+    /// Gas price to cost routes with (gwei)
+    pub fn gas_price_gwei(&self) -> f64 {
+        self.current_gwei
+    }
+
+    /// Whether the price should be refetched at `now`
+    pub fn is_stale_at(&self, now: Instant) -> bool {
+        self.fetched_at
+            .is_none_or(|fetched_at| now.saturating_duration_since(fetched_at) >= self.refresh_interval)
+    }
+
+    /// Record a fetch made at `now`; returns the price now in effect
+    pub fn record_at<E>(&mut self, fetched: Result<f64, E>, now: Instant) -> f64 {
+        match fetched {
+            Ok(gwei) if gwei.is_finite() && gwei > 0.0 => {
+                self.current_gwei = gwei;
+                self.fetched_at = Some(now);
+            }
+            _ => {
+                self.current_gwei = self.fallback_gwei;
+                self.fetched_at = None;
+            }
+        }
+        self.current_gwei
+    }
+}
+
+/// Standard deviation over mean for a window of positive samples
+fn coefficient_of_variation(samples: &VecDeque<f64>) -> Option<f64> {
+    if samples.len() < 2 {
+        return None;
+    }
+
+    let count = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / count;
+    if mean <= 0.0 {
+        return None;
+    }
+    let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count;
+    Some(variance.sqrt() / mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U256;
+    use chrono::Utc;
+    use crate::types::Token;
+
+    fn create_reserves(pool: Address, reserve_a: u128, reserve_b: u128) -> PoolReserves {
+        PoolReserves {
+            token_a: Token::new(Address::ZERO, "WMNT", 18),
+            reserve_a: U256::from(reserve_a * 1_000_000_000_000_000_000u128),
+            token_b: Token::new(Address::from([1u8; 20]), "MOE", 18),
+            reserve_b: U256::from(reserve_b * 1_000_000_000_000_000_000u128),
+            block_number: 1,
+            timestamp: Utc::now(),
+            pool_address: pool,
+        }
+    }
+
+    #[test]
+    fn test_reserve_log_gate_skips_small_changes() {
+        let pool = Address::from([7u8; 20]);
+        let snapshot = |reserve_b: u128| {
+            let mut map = HashMap::new();
+            map.insert(pool, create_reserves(pool, 1000, reserve_b));
+            map
+        };
+
+        let mut cache = ReservesCache::new();
+        let mut gate = ReserveLogGate::new(1.0, Duration::ZERO);
+        let start = Instant::now();
+
+        assert!(gate.should_log(&snapshot(1000), start));
+        for (addr, reserves) in snapshot(1000) {
+            cache.update(addr, reserves);
+        }
+
+        // A 0.5% move still triggers analysis but is not logged
+        let small_move = snapshot(1005);
+        assert!(cache.reserves_changed(&small_move));
+        assert!(!gate.should_log(&small_move, start));
+
+        // Changes accumulate against the last logged snapshot
+        assert!(gate.should_log(&snapshot(1012), start));
+
+        // Time-based throttling
+        let mut throttled = ReserveLogGate::new(0.0, Duration::from_secs(10));
+        assert!(throttled.should_log(&snapshot(1000), start));
+        assert!(!throttled.should_log(&snapshot(2000), start + Duration::from_secs(5)));
+        assert!(throttled.should_log(&snapshot(2000), start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_changed_pools_lists_only_moved_pools() {
+        let pools: Vec<Address> = (1..=4).map(|i| Address::from([i; 20])).collect();
+        let snapshot = |reserves: &[(u128, u128)]| -> HashMap<Address, PoolReserves> {
+            pools.iter().zip(reserves).map(|(pool, &(a, b))| (*pool, create_reserves(*pool, a, b))).collect()
+        };
+
+        let mut cache = ReservesCache::new();
+        let first = snapshot(&[(1000, 1000), (500, 700), (20, 30), (9, 9)]);
+        assert_eq!(cache.changed_pools(&first), pools);
+        for (addr, reserves) in first.clone() {
+            cache.update(addr, reserves);
+        }
+        assert!(cache.changed_pools(&first).is_empty());
+        assert!(!cache.reserves_changed(&first));
+
+        // Only the pools whose a or b reserve moved are reported
+        let next = snapshot(&[(1000, 1000), (501, 700), (20, 30), (9, 10)]);
+        assert_eq!(cache.changed_pools(&next), vec![pools[1], pools[3]]);
+        assert!(cache.reserves_changed(&next));
+
+        // A pool dropped from the fetch still counts as a change for the boolean check
+        let mut shrunk = first.clone();
+        shrunk.remove(&pools[0]);
+        assert!(cache.changed_pools(&shrunk).is_empty());
+        assert!(cache.reserves_changed(&shrunk));
+    }
+
+    #[test]
+    fn test_stale_entries_expire() {
+        let kept = Address::from([1u8; 20]);
+        let dropped = Address::from([2u8; 20]);
+        let ttl = Duration::from_secs(30);
+        let start = Instant::now();
+
+        let mut cache = ReservesCache::new();
+        cache.update_at(kept, create_reserves(kept, 1000, 1000), start);
+        cache.update_at(dropped, create_reserves(dropped, 500, 500), start);
+
+        // Only `kept` is still being fetched
+        let later = start + Duration::from_secs(20);
+        cache.update_at(kept, create_reserves(kept, 1000, 1001), later);
+
+        let now = start + Duration::from_secs(31);
+        assert!(cache.get_fresh_at(&kept, ttl, now).is_some());
+        assert!(cache.get_fresh_at(&dropped, ttl, now).is_none());
+        // Stale entries stay readable until pruned
+        assert!(cache.get(&dropped).is_some());
+
+        assert_eq!(cache.prune_older_than_at(ttl, now), 1);
+        assert!(cache.get(&dropped).is_none());
+        assert_eq!(cache.get_all().len(), 1);
+
+        assert_eq!(cache.prune_older_than_at(ttl, later + ttl + Duration::from_secs(1)), 1);
+        assert!(cache.get_all().is_empty());
+    }
+
+    #[test]
+    fn test_gas_price_oracle_caches_and_falls_back() {
+        let start = Instant::now();
+        let mut oracle = GasPriceOracle::new(0.02, Duration::from_secs(10));
+        assert_eq!(oracle.gas_price_gwei(), 0.02);
+        assert!(oracle.is_stale_at(start));
+
+        // A fetched price is reused until the interval passes
+        assert_eq!(oracle.record_at(Ok::<f64, ()>(0.05), start), 0.05);
+        assert!(!oracle.is_stale_at(start + Duration::from_secs(9)));
+        assert!(oracle.is_stale_at(start + Duration::from_secs(10)));
+
+        // A failed or nonsensical fetch uses the static price and retries next time
+        let later = start + Duration::from_secs(10);
+        assert_eq!(oracle.record_at(Err("rpc down"), later), 0.02);
+        assert!(oracle.is_stale_at(later));
+        assert_eq!(oracle.record_at(Ok::<f64, ()>(f64::NAN), later), 0.02);
+        assert_eq!(oracle.record_at(Ok::<f64, ()>(0.03), later), 0.03);
+        assert_eq!(oracle.gas_price_gwei(), 0.03);
+    }
+
+    #[test]
+    fn test_volatility_ranking() {
+        let volatile_pool = Address::from([1u8; 20]);
+        let stable_pool = Address::from([2u8; 20]);
+        let mut history = ReserveHistory::new(5);
+
+        let volatile_b = [1000, 1300, 800, 1250, 900, 1400];
+        for (i, reserve_b) in volatile_b.iter().enumerate() {
+            let mut snapshot = HashMap::new();
+            snapshot.insert(volatile_pool, create_reserves(volatile_pool, 1000, *reserve_b));
+            snapshot.insert(stable_pool, create_reserves(stable_pool, 1000, 1000 + i as u128));
+            history.record(&snapshot);
+        }
+
+        // Window caps the number of snapshots kept
+        assert_eq!(history.len(&volatile_pool), 5);
+
+        let ranked = history.most_volatile(2);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].0, volatile_pool);
+        assert_eq!(ranked[1].0, stable_pool);
+        assert!(ranked[0].1 > ranked[1].1 * 10.0);
+
+        // Step drift follows the block-to-block moves and grows with the square root of the delay
+        assert!(history.drift_per_block(&volatile_pool).unwrap() > 0.3);
+        assert!(history.drift_per_block(&stable_pool).unwrap() < 0.002);
+        assert_eq!(history.drift_per_block(&Address::ZERO), None);
+        let one_block = history.path_drift(&[volatile_pool, stable_pool], 1);
+        assert!((history.path_drift(&[volatile_pool, stable_pool], 4) - 2.0 * one_block).abs() < 1e-12);
+        assert_eq!(history.path_drift(&[volatile_pool], 0), 0.0);
+    }
+}
@@ -0,0 +1,129 @@
+use clap::Parser;
+
+/// `--once` found an actionable opportunity (also the normal exit of a continuous run);
+/// errors exit with 1
+pub const EXIT_PROFITABLE: u8 = 0;
+/// `--once` analyzed its block without finding an actionable opportunity
+pub const EXIT_NO_OPPORTUNITY: u8 = 2;
+
+/// Command-line options of the monitor binary
+#[derive(Debug, Clone, PartialEq, Parser)]
+#[command(version, about = "Triangular and multi-path arbitrage monitor for Mantle AMM pools")]
+pub struct Cli {
+    /// Mode (triangular, multi, diff, replay, synthetic) followed by its arguments;
+    /// ARBITRAGE_MODE takes precedence when set
+    pub args: Vec<String>,
+
+    /// TOML config file, instead of CONFIG_FILE
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<String>,
+
+    /// Run the multi-path monitor (same as the `multi` mode)
+    #[arg(long)]
+    pub multi_path: bool,
+
+    /// Fetch and analyze a single block, then exit (0 = actionable opportunity, 2 = none, 1 = error)
+    #[arg(long)]
+    pub once: bool,
+
+    /// Comma-separated pool addresses to monitor instead of the pool CSV (multi-path, or diff)
+    #[arg(long, value_name = "ADDRESSES")]
+    pub pools: Option<String>,
+
+    /// Append every block's reserves of all monitored pools to PATH as JSON lines (multi-path; replay reads it back)
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<String>,
+
+    /// Print every cycle the pool CSV can form, with its gas units, and exit (no RPC)
+    #[arg(long)]
+    pub list_paths: bool,
+
+    /// Fail if any pool CSV row is skipped (unknown symbol, bad address or reserves), like STRICT_CSV=true
+    #[arg(long)]
+    pub strict_csv: bool,
+}
+
+/// How long a monitor loop runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RunMode {
+    /// Until Ctrl-C
+    #[default]
+    Continuous,
+    /// One fetch and analysis cycle
+    Once,
+}
+
+impl Cli {
+    /// Selected mode, lowercased: `--multi-path`, then `env_mode` (ARBITRAGE_MODE),
+    /// then the first positional argument; with none of them `multi` when `--pools` or `--record`
+    /// is given, else `triangular`
+    pub fn mode(&self, env_mode: Option<String>) -> String {
+        if self.multi_path {
+            return "multi".to_string();
+        }
+        let default_mode = if self.pools.is_some() || self.record.is_some() { "multi" } else { "triangular" };
+        env_mode
+            .or_else(|| self.args.first().cloned())
+            .unwrap_or_else(|| default_mode.to_string())
+            .to_lowercase()
+    }
+
+    /// Loop length requested by `--once`
+    pub fn run_mode(&self) -> RunMode {
+        if self.once { RunMode::Once } else { RunMode::Continuous }
+    }
+}
+
+/// Process exit code for a finished monitor run
+pub fn exit_code(run_mode: RunMode, found_actionable: bool) -> u8 {
+    match run_mode {
+        RunMode::Once if !found_actionable => EXIT_NO_OPPORTUNITY,
+        _ => EXIT_PROFITABLE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("triangular-arbitrage-demo").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn test_parse_cli() {
+        let cli = parse(&[]);
+        assert_eq!(cli.mode(None), "triangular");
+        assert_eq!(cli.run_mode(), RunMode::Continuous);
+
+        let cli = parse(&["--once", "--config", "config.toml", "--multi-path", "--record", "reserves.jsonl", "--strict-csv"]);
+        assert!(!cli.list_paths);
+        assert!(parse(&["--list-paths"]).list_paths);
+        assert_eq!(cli.config.as_deref(), Some("config.toml"));
+        assert!(cli.strict_csv);
+        assert_eq!(cli.record.as_deref(), Some("reserves.jsonl"));
+        assert_eq!(cli.run_mode(), RunMode::Once);
+        assert_eq!(cli.mode(None), "multi");
+
+        // Positional mode arguments pass through
+        let cli = parse(&["diff", "100", "200", "--pools=0x01"]);
+        assert_eq!(cli.args, vec!["diff", "100", "200"]);
+        assert_eq!(cli.pools.as_deref(), Some("0x01"));
+        assert_eq!(cli.mode(None), "diff");
+        assert_eq!(parse(&["--pools=0x01"]).mode(None), "multi");
+        assert_eq!(parse(&["--pools=0x01"]).mode(Some("replay".to_string())), "replay");
+        assert_eq!(parse(&["--record", "out.jsonl"]).mode(None), "multi");
+        assert_eq!(parse(&["Replay"]).mode(None), "replay");
+        assert_eq!(parse(&["replay"]).mode(Some("MULTI".to_string())), "multi");
+
+        assert!(Cli::try_parse_from(["triangular-arbitrage-demo", "--config"]).is_err());
+        assert!(Cli::try_parse_from(["triangular-arbitrage-demo", "--bogus"]).is_err());
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(exit_code(RunMode::Once, true), EXIT_PROFITABLE);
+        assert_eq!(exit_code(RunMode::Once, false), EXIT_NO_OPPORTUNITY);
+        assert_eq!(exit_code(RunMode::Continuous, false), EXIT_PROFITABLE);
+    }
+}
@@ -1,80 +1,1090 @@
-use std::env;
-use std::error::Error;
+use std::collections::HashMap;
+use std::env::{self, VarError};
+use alloy::primitives::Address;
 use dotenv::dotenv;
+use serde::Deserialize;
 use crate::constants::*;
+use crate::error::ArbError;
+use crate::multi_path::{validate_input_range, OptimizationStrategy};
+use crate::price_source::{PriceSource, StaticPriceSource};
+use crate::types::{FlashLoanTerms, GasOverhead, InputLimits, PathType, PoolReserves, Token};
+
+/// Output format for recorded opportunities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Csv,
+    Parquet,
+    /// SQLite database (requires the `sqlite` feature)
+    Sqlite,
+}
+
+impl OutputFormat {
+    /// Parse an output format name (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "csv" => Some(OutputFormat::Csv),
+            "parquet" => Some(OutputFormat::Parquet),
+            "sqlite" => Some(OutputFormat::Sqlite),
+            _ => None,
+        }
+    }
+}
+
+/// Structured log output written next to the console output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Console (emoji) output only
+    Console,
+    /// Console output plus one JSON object per event
+    Json,
+}
+
+impl LogFormat {
+    /// Parse a log format name (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "console" | "text" => Some(LogFormat::Console),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// How the monitor loops learn about new blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorDriver {
+    /// Poll the block number, sleeping a block time between polls
+    Polling,
+    /// React to each new head from a WebSocket subscription (no transport in this build yet,
+    /// so `Config::validate` rejects it)
+    WebSocket,
+}
+
+impl MonitorDriver {
+    /// Parse a driver name (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "polling" | "poll" => Some(MonitorDriver::Polling),
+            "websocket" | "ws" => Some(MonitorDriver::WebSocket),
+            _ => None,
+        }
+    }
+}
+
+/// How the multi-path monitor keeps pool reserves current
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReserveSource {
+    /// Call `getReserves` on every pool each block
+    GetReserves,
+    /// Apply `Sync` logs to a `getReserves` snapshot taken at startup and on reconnect
+    SyncEvents,
+}
+
+impl ReserveSource {
+    /// Parse a reserve source name (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "getreserves" | "poll" => Some(ReserveSource::GetReserves),
+            "sync" | "events" => Some(ReserveSource::SyncEvents),
+            _ => None,
+        }
+    }
+}
+
+/// Objective used to pick the input amount for a cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfitObjective {
+    /// Input that maximizes profit
+    MaxProfit,
+    /// Smallest input that still clears the target net profit
+    MinCapital,
+}
+
+impl ProfitObjective {
+    /// Parse an objective name (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "max_profit" => Some(ProfitObjective::MaxProfit),
+            "min_capital" => Some(ProfitObjective::MinCapital),
+            _ => None,
+        }
+    }
+}
 
 /// Configuration structure for runtime settings
 #[derive(Debug, Clone)]
 pub struct Config {
     pub rpc_url: String,
+    /// Backup endpoint the provider switches to after repeated failures
+    pub rpc_url_fallback: Option<String>,
+    /// Consecutive RPC failures before the provider reconnects (0 disables)
+    pub max_consecutive_failures: u32,
+    /// Limit on each RPC call, in milliseconds (0 = no timeout)
+    pub rpc_timeout_ms: u64,
     pub gas_price_gwei: f64,
     pub block_time_seconds: u64,
     pub max_retries: u32,
+    /// Pools fetched per batch in multi-path mode
+    pub batch_size: usize,
+    /// Pause between reserve-fetch batches, to stay under RPC rate limits
+    pub inter_batch_delay_ms: u64,
+    /// Per-pool reserve calls in flight at once (0 = unbounded)
+    pub max_concurrent_requests: usize,
+    /// Share of pools (0-1) a multi-path snapshot needs before the block is analyzed
+    pub min_pool_coverage: f64,
     pub csv_file_path: String,
     pub dex_fee: f64,
     pub ternary_search_iterations: usize,
+    /// Extra search iterations per hop, so longer cycles get more
+    pub ternary_search_iterations_per_hop: usize,
+    /// Trade sizes searched, in WMNT
+    pub min_input_wmnt: f64,
+    pub max_input_wmnt: f64,
+    pub tui_enabled: bool,
+    pub volatility_window: usize,
+    pub volatility_log_interval_blocks: u64,
+    /// Smoothing factor of the per-route net-profit EMA, in (0, 1]
+    pub path_stats_alpha: f64,
+    pub path_stats_log_interval_blocks: u64,
+    pub output_format: OutputFormat,
+    pub parquet_file_path: String,
+    pub parquet_row_group_size: usize,
+    pub sqlite_file_path: String,
+    pub confirmation_blocks: u32,
+    /// Re-price each recorded profitable route at the next block (multi-path mode)
+    pub verify_next_block: bool,
+    pub profit_objective: ProfitObjective,
+    pub target_profit: f64,
+    pub graph_cache_path: Option<String>,
+    /// JSON file remembering each pool's token0/token1 across runs
+    pub pool_token_cache_path: Option<String>,
+    pub approval_gas_units: u64,
+    pub wrap_gas_units: u64,
+    pub include_tokens: Vec<Token>,
+    pub exclude_tokens: Vec<Token>,
+    /// Token multi-path cycles start and end in (WMNT by default); profits, trade sizes
+    /// and gas costs are denominated in it
+    pub base_token: Token,
+    pub min_profit_wmnt: f64,
+    /// Pool value in WMNT (both reserves) counted as arbitrage-ready in multi-path mode
+    pub min_liquidity_wmnt: f64,
+    pub gas_volatility_multiplier: f64,
+    pub gas_history_window: usize,
+    /// Refresh `gas_price_gwei` from the chain (the static value becomes the fallback)
+    pub dynamic_gas_price: bool,
+    pub gas_price_refresh_secs: u64,
+    pub stream_address: Option<String>,
+    pub max_cycles_per_block: usize,
+    /// Longest cycle searched, in hops (multi-path mode)
+    pub max_hops: usize,
+    /// Seed cycle detection from every token in parallel instead of WMNT only
+    pub spfa_all_sources: bool,
+    /// Trade size edge weights are priced at, in base token units (converted into each hop's input token); 0 = marginal rate
+    pub reference_trade_size: f64,
+    pub reserve_log_min_change_pct: f64,
+    pub reserve_log_interval_secs: u64,
+    /// Cached reserves not refreshed for this long are pruned (0 disables)
+    pub reserve_cache_ttl_secs: u64,
+    pub fee_calibration_blocks: u64,
+    pub dev_mode: bool,
+    /// Cross-check SPFA against Bellman-Ford every block (dev only)
+    pub verify_cycle_detection: bool,
+    pub reserve_scale: f64,
+    pub max_price_impact_pct: f64,
+    pub max_capital: f64,
+    pub max_reserve_fraction: f64,
+    /// WMNT held for trading; larger inputs are flash-borrowed (0 = unlimited)
+    pub wallet_balance_wmnt: f64,
+    pub flash_loan_fee_bps: f64,
+    /// Share of the gross spread expected to survive competing bots (0-1)
+    pub capture_ratio: f64,
+    /// Blocks between detection and execution; profits are discounted by reserve drift over them
+    pub execution_delay_blocks: u64,
+    /// Rank routes by expected realized profit instead of net profit
+    pub rank_by_realized_profit: bool,
+    /// USD quotes for reporting (empty = WMNT only)
+    pub usd_prices: StaticPriceSource,
+    pub metrics_snapshot_path: Option<String>,
+    pub metrics_snapshot_interval_secs: u64,
+    /// Prometheus scrape address (requires the `prometheus` feature)
+    pub metrics_address: Option<String>,
+    /// JSON API address for `/opportunities` and `/health` (requires the `http-api` feature)
+    pub api_address: Option<String>,
+    pub token_registry_path: Option<String>,
+    pub monitor_driver: MonitorDriver,
+    pub reserve_source: ReserveSource,
+    pub ws_rpc_url: Option<String>,
+    pub log_format: LogFormat,
+    /// JSON log destination (`-` for stdout)
+    pub log_json_path: String,
+    /// Pools listed in the config file (empty when loaded from the environment only)
+    pub pools: Vec<Address>,
+    /// Per-pool fee overrides from the config file
+    pub pool_fees: HashMap<Address, f64>,
+    /// Pools kept out of the graph and the fetch list
+    pub excluded_pools: Vec<Address>,
+    /// Tokens whose pools are kept out of the graph
+    pub excluded_tokens: Vec<Address>,
+    /// Fail the pool CSV load if any row is skipped
+    pub strict_csv: bool,
+}
+
+/// Looks up a setting by its environment variable name
+pub type VarLookup<'a> = &'a dyn Fn(&str) -> Result<String, VarError>;
+
+/// TOML config file; each setting maps to the environment variable of the same name, uppercased
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    rpc_url: Option<String>,
+    gas_price_gwei: Option<f64>,
+    dex_fee: Option<f64>,
+    block_time_seconds: Option<u64>,
+    max_retries: Option<u32>,
+    ternary_search_iterations: Option<usize>,
+    min_profit_wmnt: Option<f64>,
+    csv_file_path: Option<String>,
+    #[serde(default)]
+    tokens: Vec<ConfigFileToken>,
+    #[serde(default)]
+    pools: Vec<ConfigFilePool>,
+    #[serde(default)]
+    excluded_pools: Vec<Address>,
+    #[serde(default)]
+    excluded_tokens: Vec<Address>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFileToken {
+    symbol: String,
+    address: Address,
+    decimals: u8,
+    /// Fee-on-transfer tax in basis points
+    transfer_tax_bps: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFilePool {
+    address: Address,
+    /// Fee as a fraction (0.003 = 0.3%); DEX_FEE when omitted
+    fee: Option<f64>,
+}
+
+impl ConfigFile {
+    /// Reject values that parse but make no sense
+    fn validate(&self) -> Result<(), String> {
+        if let Some(gas_price) = self.gas_price_gwei
+            && !(gas_price >= 0.0 && gas_price.is_finite())
+        {
+            return Err(format!("gas_price_gwei must be a non-negative number, got {}", gas_price));
+        }
+        for token in &self.tokens {
+            if token.symbol.trim().is_empty() {
+                return Err(format!("token {} has an empty symbol", token.address));
+            }
+        }
+        for pool in &self.pools {
+            if let Some(fee) = pool.fee
+                && !(0.0..1.0).contains(&fee)
+            {
+                return Err(format!("fee of pool {} must be in [0, 1), got {}", pool.address, fee));
+            }
+        }
+        Ok(())
+    }
+
+    /// Scalar settings keyed by environment variable name
+    fn env_values(&self) -> HashMap<String, String> {
+        let address_list = |addresses: &[Address]| {
+            (!addresses.is_empty()).then(|| addresses.iter().map(Address::to_string).collect::<Vec<_>>().join(","))
+        };
+        let entries: [(&str, Option<String>); 10] = [
+            ("RPC_URL", self.rpc_url.clone()),
+            ("GAS_PRICE_GWEI", self.gas_price_gwei.map(|v| v.to_string())),
+            ("DEX_FEE", self.dex_fee.map(|v| v.to_string())),
+            ("BLOCK_TIME_SECONDS", self.block_time_seconds.map(|v| v.to_string())),
+            ("MAX_RETRIES", self.max_retries.map(|v| v.to_string())),
+            ("TERNARY_SEARCH_ITERATIONS", self.ternary_search_iterations.map(|v| v.to_string())),
+            ("MIN_PROFIT_WMNT", self.min_profit_wmnt.map(|v| v.to_string())),
+            ("CSV_FILE_PATH", self.csv_file_path.clone()),
+            ("EXCLUDED_POOLS", address_list(&self.excluded_pools)),
+            ("EXCLUDED_TOKENS", address_list(&self.excluded_tokens)),
+        ];
+        entries
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value?)))
+            .collect()
+    }
 }
 
 impl Config {
-    /// Load configuration from environment variables with fallback to defaults
-    pub fn load() -> Result<Self, Box<dyn Error>> {
+    /// Load configuration from environment variables with fallback to defaults,
+    /// or from the TOML file named by `CONFIG_FILE` when set
+    pub fn load() -> Result<Self, ArbError> {
         // Load .env file if it exists
         let _ = dotenv();
 
-        let rpc_url = env::var("RPC_URL")
-            .or_else(|_| env::var("MANTLE_RPC_URL"))
-            .map_err(|_| "RPC_URL environment variable is required. Please set RPC_URL=your_rpc_endpoint")?;
+        match env::var("CONFIG_FILE") {
+            Ok(path) if !path.is_empty() => Self::from_toml(&path),
+            _ => Self::from_lookup(&|name| env::var(name)),
+        }
+    }
+
+    /// Load configuration from a TOML file; environment variables override file values
+    pub fn from_toml(path: &str) -> Result<Self, ArbError> {
+        let _ = dotenv();
+        Self::from_toml_with(path, &|name| env::var(name))
+    }
+
+    /// `from_toml` with an explicit environment lookup
+    fn from_toml_with(path: &str, env_var: VarLookup) -> Result<Self, ArbError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ArbError::Config(format!("Failed to read config file '{}': {}", path, e)))?;
+        let file: ConfigFile = toml::from_str(&contents)
+            .map_err(|e| ArbError::Config(format!("Invalid config file '{}': {}", path, e)))?;
+        file.validate()
+            .map_err(|e| ArbError::Config(format!("Invalid config file '{}': {}", path, e)))?;
+        if file.rpc_url.is_none() && env_var("RPC_URL").is_err() && env_var("MANTLE_RPC_URL").is_err() {
+            return Err(ArbError::Config(format!("Config file '{}' is missing rpc_url (or set RPC_URL)", path)));
+        }
+
+        // Tokens are registered first so pool and token-filter symbols resolve
+        for token in &file.tokens {
+            crate::token_registry::register(
+                Token::new(token.address, &token.symbol, token.decimals).with_transfer_tax_bps(token.transfer_tax_bps.unwrap_or(0)),
+            );
+        }
 
-        let gas_price_gwei = env::var("GAS_PRICE_GWEI")
+        let file_values = file.env_values();
+        let mut config = Self::from_lookup(&|name| {
+            env_var(name).or_else(|_| file_values.get(name).cloned().ok_or(VarError::NotPresent))
+        })?;
+        config.pools = file.pools.iter().map(|pool| pool.address).collect();
+        config.pool_fees = file.pools.iter().filter_map(|pool| Some((pool.address, pool.fee?))).collect();
+        Ok(config)
+    }
+
+    /// Build the configuration from named settings (environment variable names)
+    pub fn from_lookup(var: VarLookup) -> Result<Self, ArbError> {
+        let rpc_url = var("RPC_URL")
+            .or_else(|_| var("MANTLE_RPC_URL"))
+            .map_err(|_| ArbError::Config("RPC_URL environment variable is required. Please set RPC_URL=your_rpc_endpoint".to_string()))?;
+
+        let gas_price_gwei = var("GAS_PRICE_GWEI")
             .ok()
             .and_then(|s| s.parse().ok())
             .unwrap_or(DEFAULT_GAS_PRICE_GWEI);
 
-        let block_time_seconds = env::var("BLOCK_TIME_SECONDS")
+        let block_time_seconds = var("BLOCK_TIME_SECONDS")
             .ok()
             .and_then(|s| s.parse().ok())
             .unwrap_or(DEFAULT_BLOCK_TIME_SECONDS);
 
-        let max_retries = env::var("MAX_RETRIES")
+        let max_retries = var("MAX_RETRIES")
             .ok()
             .and_then(|s| s.parse().ok())
             .unwrap_or(DEFAULT_MAX_RETRIES);
 
-        let csv_file_path = env::var("CSV_FILE_PATH")
+        let batch_size = var("BATCH_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_BATCH_SIZE);
+
+        let inter_batch_delay_ms = var("INTER_BATCH_DELAY_MS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_INTER_BATCH_DELAY_MS);
+
+        let max_concurrent_requests = var("MAX_CONCURRENT_REQUESTS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS);
+
+        let min_pool_coverage = var("MIN_POOL_COVERAGE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MIN_POOL_COVERAGE);
+
+        let rpc_url_fallback = var("RPC_URL_FALLBACK")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        let max_consecutive_failures = var("MAX_CONSECUTIVE_FAILURES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_CONSECUTIVE_FAILURES);
+
+        let rpc_timeout_ms = var("RPC_TIMEOUT_MS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_RPC_TIMEOUT_MS);
+
+        let csv_file_path = var("CSV_FILE_PATH")
             .unwrap_or_else(|_| DEFAULT_CSV_FILE_PATH.to_string());
 
-        let dex_fee = env::var("DEX_FEE")
+        let dex_fee = var("DEX_FEE")
             .ok()
             .and_then(|s| s.parse().ok())
             .unwrap_or(DEFAULT_DEX_FEE);
 
-        let ternary_search_iterations = env::var("TERNARY_SEARCH_ITERATIONS")
+        let ternary_search_iterations = var("TERNARY_SEARCH_ITERATIONS")
             .ok()
             .and_then(|s| s.parse().ok())
             .unwrap_or(DEFAULT_TERNARY_SEARCH_ITERATIONS);
 
-        Ok(Config {
+        let ternary_search_iterations_per_hop = var("TERNARY_SEARCH_ITERATIONS_PER_HOP")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_TERNARY_SEARCH_ITERATIONS_PER_HOP);
+
+        let min_input_wmnt = var("MIN_INPUT_WMNT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MIN_INPUT_WMNT);
+
+        let max_input_wmnt = var("MAX_INPUT_WMNT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_INPUT_WMNT);
+
+        let tui_enabled = var("TUI")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_TUI_ENABLED);
+
+        let volatility_window = var("VOLATILITY_WINDOW")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_VOLATILITY_WINDOW);
+
+        let volatility_log_interval_blocks = var("VOLATILITY_LOG_INTERVAL_BLOCKS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_VOLATILITY_LOG_INTERVAL_BLOCKS);
+
+        let path_stats_alpha = var("PATH_STATS_ALPHA")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_PATH_STATS_ALPHA);
+
+        let path_stats_log_interval_blocks = var("PATH_STATS_LOG_INTERVAL_BLOCKS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_PATH_STATS_LOG_INTERVAL_BLOCKS);
+
+        let output_format = match var("OUTPUT_FORMAT") {
+            Ok(value) => OutputFormat::parse(&value)
+                .ok_or_else(|| ArbError::Config(format!("Invalid OUTPUT_FORMAT '{}': expected csv, parquet or sqlite", value)))?,
+            Err(_) => OutputFormat::Csv,
+        };
+
+        let parquet_file_path = var("PARQUET_FILE_PATH")
+            .unwrap_or_else(|_| DEFAULT_PARQUET_FILE_PATH.to_string());
+
+        let parquet_row_group_size = var("PARQUET_ROW_GROUP_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_PARQUET_ROW_GROUP_SIZE);
+
+        let sqlite_file_path = var("SQLITE_FILE_PATH")
+            .unwrap_or_else(|_| DEFAULT_SQLITE_FILE_PATH.to_string());
+
+        let verify_next_block = var("VERIFY_NEXT_BLOCK")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_VERIFY_NEXT_BLOCK);
+
+        let confirmation_blocks = var("CONFIRMATION_BLOCKS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_CONFIRMATION_BLOCKS);
+
+        let profit_objective = match var("PROFIT_OBJECTIVE") {
+            Ok(value) => ProfitObjective::parse(&value)
+                .ok_or_else(|| ArbError::Config(format!("Invalid PROFIT_OBJECTIVE '{}': expected max_profit or min_capital", value)))?,
+            Err(_) => ProfitObjective::MaxProfit,
+        };
+
+        let target_profit = var("TARGET_PROFIT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_TARGET_PROFIT);
+
+        let graph_cache_path = var("GRAPH_CACHE_PATH")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        let pool_token_cache_path = Some(var("POOL_TOKEN_CACHE_PATH").unwrap_or_else(|_| DEFAULT_POOL_TOKEN_CACHE_PATH.to_string()))
+            .filter(|s| !s.is_empty());
+
+        let approval_gas_units = var("APPROVAL_GAS_UNITS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_APPROVAL_GAS_UNITS);
+
+        let wrap_gas_units = var("WRAP_GAS_UNITS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_WRAP_GAS_UNITS);
+
+        // Extra tokens must be registered before any symbol is parsed
+        let token_registry_path = var("TOKEN_REGISTRY_PATH")
+            .ok()
+            .filter(|s| !s.is_empty());
+        if let Some(path) = &token_registry_path {
+            crate::token_registry::load_csv(path)
+                .map_err(|e| ArbError::Config(format!("Failed to load TOKEN_REGISTRY_PATH '{}': {}", path, e)))?;
+        }
+
+        let include_tokens = parse_token_list(var, "INCLUDE_TOKENS")?;
+        let exclude_tokens = parse_token_list(var, "EXCLUDE_TOKENS")?;
+        let excluded_pools = parse_address_list(var, "EXCLUDED_POOLS")?;
+        let excluded_tokens = parse_address_list(var, "EXCLUDED_TOKENS")?;
+        let strict_csv = var("STRICT_CSV")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_STRICT_CSV);
+
+        let base_token = match var("BASE_TOKEN") {
+            Ok(value) if !value.trim().is_empty() => Token::parse(&value)
+                .ok_or_else(|| ArbError::TokenUnknown(format!("invalid BASE_TOKEN '{}': expected a known token symbol or address", value.trim())))?,
+            _ => WMNT_ADDRESS.parse().ok().and_then(Token::from_address).ok_or_else(|| ArbError::TokenUnknown("WMNT is missing from the token registry".to_string()))?,
+        };
+
+        // MIN_PROFIT is the older name of the same setting
+        let min_profit_wmnt = var("MIN_PROFIT_WMNT")
+            .or_else(|_| var("MIN_PROFIT"))
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&min: &f64| min >= 0.0)
+            .unwrap_or(DEFAULT_MIN_PROFIT_WMNT);
+
+        let min_liquidity_wmnt = var("MIN_LIQUIDITY_WMNT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&min: &f64| min >= 0.0)
+            .unwrap_or(DEFAULT_MIN_LIQUIDITY_WMNT);
+
+        let gas_volatility_multiplier = var("GAS_VOLATILITY_MULTIPLIER")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_GAS_VOLATILITY_MULTIPLIER);
+
+        let gas_history_window = var("GAS_HISTORY_WINDOW")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_GAS_HISTORY_WINDOW);
+
+        let dynamic_gas_price = var("DYNAMIC_GAS_PRICE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_DYNAMIC_GAS_PRICE);
+
+        let gas_price_refresh_secs = var("GAS_PRICE_REFRESH_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_GAS_PRICE_REFRESH_SECS);
+
+        let stream_address = var("STREAM_ADDRESS")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        let max_cycles_per_block = var("MAX_CYCLES_PER_BLOCK")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_CYCLES_PER_BLOCK);
+
+        let max_hops = var("MAX_HOPS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_HOPS);
+
+        let spfa_all_sources = var("SPFA_ALL_SOURCES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_SPFA_ALL_SOURCES);
+
+        let reference_trade_size = var("REFERENCE_TRADE_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_REFERENCE_TRADE_SIZE);
+
+        let reserve_log_min_change_pct = var("RESERVE_LOG_MIN_CHANGE_PCT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_RESERVE_LOG_MIN_CHANGE_PCT);
+
+        let reserve_log_interval_secs = var("RESERVE_LOG_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_RESERVE_LOG_INTERVAL_SECS);
+
+        let reserve_cache_ttl_secs = var("RESERVE_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_RESERVE_CACHE_TTL_SECS);
+
+        let fee_calibration_blocks = var("FEE_CALIBRATION_BLOCKS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_FEE_CALIBRATION_BLOCKS);
+
+        let dev_mode = var("DEV_MODE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false);
+
+        let verify_cycle_detection = var("VERIFY_CYCLE_DETECTION")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false);
+        if verify_cycle_detection && !dev_mode {
+            return Err(ArbError::Config("VERIFY_CYCLE_DETECTION is a test/dev setting and requires DEV_MODE=true".to_string()));
+        }
+
+        let reserve_scale = var("RESERVE_SCALE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_RESERVE_SCALE);
+        if reserve_scale != DEFAULT_RESERVE_SCALE && !dev_mode {
+            return Err(ArbError::Config("RESERVE_SCALE is a test/dev setting and requires DEV_MODE=true".to_string()));
+        }
+        if reserve_scale <= 0.0 || !reserve_scale.is_finite() {
+            return Err(ArbError::Config(format!("Invalid RESERVE_SCALE '{}': must be a positive number", reserve_scale)));
+        }
+
+        let max_price_impact_pct = var("MAX_PRICE_IMPACT_PCT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_PRICE_IMPACT_PCT);
+
+        let max_capital = var("MAX_CAPITAL")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_CAPITAL);
+
+        let max_reserve_fraction = var("MAX_RESERVE_FRACTION")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_RESERVE_FRACTION);
+
+        let wallet_balance_wmnt = var("WALLET_BALANCE_WMNT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_WALLET_BALANCE_WMNT);
+
+        let flash_loan_fee_bps = var("FLASH_LOAN_FEE_BPS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_FLASH_LOAN_FEE_BPS);
+
+        let capture_ratio = var("CAPTURE_RATIO")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_CAPTURE_RATIO);
+
+        let execution_delay_blocks = var("EXECUTION_DELAY_BLOCKS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_EXECUTION_DELAY_BLOCKS);
+
+        let rank_by_realized_profit = var("RANK_BY_REALIZED_PROFIT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_RANK_BY_REALIZED_PROFIT);
+
+        let usd_prices = match var("USD_PRICES") {
+            Ok(spec) => StaticPriceSource::parse(&spec)?,
+            Err(_) => StaticPriceSource::default(),
+        };
+
+        let metrics_snapshot_path = var("METRICS_SNAPSHOT_PATH")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        let metrics_snapshot_interval_secs = var("METRICS_SNAPSHOT_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&secs| secs > 0)
+            .unwrap_or(DEFAULT_METRICS_SNAPSHOT_INTERVAL_SECS);
+
+        let metrics_address = var("METRICS_ADDRESS")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        let api_address = var("API_ADDRESS")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        let monitor_driver = match var("MONITOR_DRIVER") {
+            Ok(value) => MonitorDriver::parse(&value)
+                .ok_or_else(|| ArbError::Config(format!("Invalid MONITOR_DRIVER '{}': expected polling or websocket", value)))?,
+            Err(_) => MonitorDriver::Polling,
+        };
+
+        let reserve_source = match var("RESERVE_SOURCE") {
+            Ok(value) => ReserveSource::parse(&value)
+                .ok_or_else(|| ArbError::Config(format!("Invalid RESERVE_SOURCE '{}': expected getreserves or sync", value)))?,
+            Err(_) => ReserveSource::GetReserves,
+        };
+
+        let ws_rpc_url = var("WS_RPC_URL")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        let log_format = match var("LOG_FORMAT") {
+            Ok(value) => LogFormat::parse(&value)
+                .ok_or_else(|| ArbError::Config(format!("Invalid LOG_FORMAT '{}': expected console or json", value)))?,
+            Err(_) => LogFormat::Console,
+        };
+
+        let log_json_path = var("LOG_JSON_PATH")
+            .unwrap_or_else(|_| DEFAULT_LOG_JSON_PATH.to_string());
+
+        let config = Config {
             rpc_url,
+            rpc_url_fallback,
+            max_consecutive_failures,
+            rpc_timeout_ms,
             gas_price_gwei,
             block_time_seconds,
             max_retries,
+            batch_size,
+            inter_batch_delay_ms,
+            max_concurrent_requests,
+            min_pool_coverage,
             csv_file_path,
             dex_fee,
             ternary_search_iterations,
-        })
+            ternary_search_iterations_per_hop,
+            min_input_wmnt,
+            max_input_wmnt,
+            tui_enabled,
+            volatility_window,
+            volatility_log_interval_blocks,
+            path_stats_alpha,
+            path_stats_log_interval_blocks,
+            output_format,
+            parquet_file_path,
+            parquet_row_group_size,
+            sqlite_file_path,
+            confirmation_blocks,
+            verify_next_block,
+            profit_objective,
+            target_profit,
+            graph_cache_path,
+            pool_token_cache_path,
+            approval_gas_units,
+            wrap_gas_units,
+            include_tokens,
+            exclude_tokens,
+            base_token,
+            min_profit_wmnt,
+            min_liquidity_wmnt,
+            gas_volatility_multiplier,
+            gas_history_window,
+            dynamic_gas_price,
+            gas_price_refresh_secs,
+            stream_address,
+            max_cycles_per_block,
+            max_hops,
+            spfa_all_sources,
+            reference_trade_size,
+            reserve_log_min_change_pct,
+            reserve_log_interval_secs,
+            reserve_cache_ttl_secs,
+            fee_calibration_blocks,
+            dev_mode,
+            verify_cycle_detection,
+            reserve_scale,
+            max_price_impact_pct,
+            max_capital,
+            max_reserve_fraction,
+            wallet_balance_wmnt,
+            flash_loan_fee_bps,
+            capture_ratio,
+            execution_delay_blocks,
+            rank_by_realized_profit,
+            usd_prices,
+            metrics_snapshot_path,
+            metrics_snapshot_interval_secs,
+            metrics_address,
+            api_address,
+            token_registry_path,
+            monitor_driver,
+            reserve_source,
+            ws_rpc_url,
+            log_format,
+            log_json_path,
+            pools: Vec::new(),
+            pool_fees: HashMap::new(),
+            excluded_pools,
+            excluded_tokens,
+            strict_csv,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject settings that would break the monitor, naming the offending field
+    pub fn validate(&self) -> Result<(), ArbError> {
+        if !(0.0..1.0).contains(&self.dex_fee) {
+            return Err(ArbError::Config(format!("dex_fee (DEX_FEE) must be in [0, 1), got {}", self.dex_fee)));
+        }
+        if !(3..=MAX_SUPPORTED_HOPS).contains(&self.max_hops) {
+            return Err(ArbError::Config(format!("max_hops (MAX_HOPS) must be between 3 and {}, got {}", MAX_SUPPORTED_HOPS, self.max_hops)));
+        }
+        if self.ternary_search_iterations < 1 {
+            return Err(ArbError::Config("ternary_search_iterations (TERNARY_SEARCH_ITERATIONS) must be at least 1".to_string()));
+        }
+        if !(self.reference_trade_size >= 0.0 && self.reference_trade_size.is_finite()) {
+            return Err(ArbError::Config(format!("reference_trade_size (REFERENCE_TRADE_SIZE) must be a non-negative number, got {}", self.reference_trade_size)));
+        }
+        if !(self.wallet_balance_wmnt >= 0.0 && self.wallet_balance_wmnt.is_finite()) {
+            return Err(ArbError::Config(format!("wallet_balance_wmnt (WALLET_BALANCE_WMNT) must be a non-negative number, got {}", self.wallet_balance_wmnt)));
+        }
+        if !(0.0..10_000.0).contains(&self.flash_loan_fee_bps) {
+            return Err(ArbError::Config(format!("flash_loan_fee_bps (FLASH_LOAN_FEE_BPS) must be in [0, 10000), got {}", self.flash_loan_fee_bps)));
+        }
+        if !(0.0..=1.0).contains(&self.capture_ratio) {
+            return Err(ArbError::Config(format!("capture_ratio (CAPTURE_RATIO) must be in [0, 1], got {}", self.capture_ratio)));
+        }
+        if !(self.path_stats_alpha > 0.0 && self.path_stats_alpha <= 1.0) {
+            return Err(ArbError::Config(format!("path_stats_alpha (PATH_STATS_ALPHA) must be in (0, 1], got {}", self.path_stats_alpha)));
+        }
+        validate_input_range(self.input_range())
+            .map_err(|e| ArbError::Config(format!("min_input_wmnt/max_input_wmnt (MIN_INPUT_WMNT/MAX_INPUT_WMNT): {}", e)))?;
+        if self.batch_size < 1 {
+            return Err(ArbError::Config("batch_size (BATCH_SIZE) must be at least 1".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.min_pool_coverage) {
+            return Err(ArbError::Config(format!("min_pool_coverage (MIN_POOL_COVERAGE) must be in [0, 1], got {}", self.min_pool_coverage)));
+        }
+        if self.block_time_seconds < 1 {
+            return Err(ArbError::Config("block_time_seconds (BLOCK_TIME_SECONDS) must be at least 1".to_string()));
+        }
+        if self.monitor_driver == MonitorDriver::WebSocket {
+            return Err(ArbError::Config("monitor_driver (MONITOR_DRIVER) websocket is not supported: this build has no WebSocket transport, use polling".to_string()));
+        }
+        self.rpc_url
+            .parse::<alloy::transports::http::reqwest::Url>()
+            .map_err(|e| ArbError::Config(format!("rpc_url (RPC_URL) '{}' is not a valid URL: {}", self.rpc_url, e)))?;
+        if let Some(fallback) = &self.rpc_url_fallback {
+            fallback
+                .parse::<alloy::transports::http::reqwest::Url>()
+                .map_err(|e| ArbError::Config(format!("rpc_url_fallback (RPC_URL_FALLBACK) '{}' is not a valid URL: {}", fallback, e)))?;
+        }
+        Ok(())
+    }
+
+    /// Trade sizes searched as (MIN_INPUT_WMNT, MAX_INPUT_WMNT)
+    pub fn input_range(&self) -> (f64, f64) {
+        (self.min_input_wmnt, self.max_input_wmnt)
+    }
+
+    /// Ternary search iterations for a path of `hops` pools
+    pub fn search_iterations(&self, hops: usize) -> usize {
+        self.ternary_search_iterations + self.ternary_search_iterations_per_hop * hops
     }
 
     /// Print configuration summary
     pub fn print_summary(&self) {
-        println!("🔍 Algorithm: Ternary search optimization ({} iterations)", self.ternary_search_iterations);
+        if self.reserve_scale != DEFAULT_RESERVE_SCALE {
+            println!("🚨🚨🚨 DEV MODE: ALL RESERVES ARE SCALED BY {} — NOT FOR PRODUCTION 🚨🚨🚨", self.reserve_scale);
+        }
+        if self.ternary_search_iterations_per_hop > 0 {
+            println!(
+                "🔍 Algorithm: Ternary search optimization ({} iterations + {} per hop)",
+                self.ternary_search_iterations, self.ternary_search_iterations_per_hop
+            );
+        } else {
+            println!("🔍 Algorithm: Ternary search optimization ({} iterations)", self.ternary_search_iterations);
+        }
+        println!("📏 Input range: {} - {} WMNT", self.min_input_wmnt, self.max_input_wmnt);
         println!("🌐 RPC URL: {}", self.rpc_url);
-        println!("⛽ Gas Price: {:.3} gwei", self.gas_price_gwei);
-        println!("💸 Gas Cost (3-hops): {:.6} MNT", self.calculate_gas_cost(GAS_UNITS_3_HOPS));
-        println!("💸 Gas Cost (4-hops): {:.6} MNT", self.calculate_gas_cost(GAS_UNITS_4_HOPS));
+        if self.max_consecutive_failures > 0 {
+            let fallback = self.rpc_url_fallback.as_deref().unwrap_or("same endpoint");
+            println!("🔌 Reconnect: after {} consecutive RPC failures ({})", self.max_consecutive_failures, fallback);
+        }
+        if self.rpc_timeout_ms > 0 {
+            println!("⏱️ RPC timeout: {} ms per call", self.rpc_timeout_ms);
+        }
+        if self.dynamic_gas_price {
+            println!("⛽ Gas Price: on-chain, refreshed every {}s ({:.3} gwei fallback)", self.gas_price_refresh_secs, self.gas_price_gwei);
+        } else {
+            println!("⛽ Gas Price: {:.3} gwei", self.gas_price_gwei);
+        }
+        for hops in 3..=self.max_hops {
+            println!("💸 Gas Cost ({}-hops): {:.6} MNT", hops, self.calculate_gas_cost(PathType::from_hops(hops).gas_units()));
+        }
+        if self.max_hops > DEFAULT_MAX_HOPS {
+            println!("⚠️ MAX_HOPS={}: cycle search and analysis time grow quickly with every extra hop", self.max_hops);
+        }
+        if self.approval_gas_units > 0 || self.wrap_gas_units > 0 {
+            println!("⛽ Gas Overhead: {} per approval, {} per wrap", self.approval_gas_units, self.wrap_gas_units);
+        }
+        if self.max_cycles_per_block > 0 {
+            println!("🔢 Cycle budget: {} per block (rest rotated across blocks)", self.max_cycles_per_block);
+        }
+        if self.reference_trade_size > 0.0 {
+            println!("📐 Edge weights: effective rate for a trade worth {} {} on every hop", self.reference_trade_size, self.base_token.symbol());
+        }
+        if self.spfa_all_sources {
+            println!("🧭 Cycle search: SPFA seeded from every token in parallel");
+        }
         println!("💹 DEX fee: {}%", self.dex_fee * 100.0);
+        if self.fee_calibration_blocks > 0 {
+            println!("🧪 Fee calibration: swaps from the last {} blocks (multi-path mode)", self.fee_calibration_blocks);
+        }
         println!("⏰ Block time: {} seconds", self.block_time_seconds);
-        println!("📝 Logging: Only when reserves change (not every block)");
+        if self.reserve_source == ReserveSource::SyncEvents {
+            println!("📡 Reserves: Sync logs on top of a getReserves snapshot (refreshed on reconnect)");
+        }
+        if self.reserve_log_min_change_pct > 0.0 || self.reserve_log_interval_secs > 0 {
+            println!("📝 Logging: Reserve changes of at least {}%, at most every {}s", self.reserve_log_min_change_pct, self.reserve_log_interval_secs);
+        } else {
+            println!("📝 Logging: Only when reserves change (not every block)");
+        }
         println!("📋 Reserves info: Included in each update");
+        if self.confirmation_blocks > 1 {
+            println!("⏳ Confirmation: route must stay profitable for {} consecutive blocks", self.confirmation_blocks);
+        }
+        if self.verify_next_block {
+            println!("🔬 Verification: profitable routes re-priced at the next block before recording");
+        }
+        if self.profit_objective == ProfitObjective::MinCapital {
+            println!("🎯 Objective: minimum input for {:.6} WMNT net profit", self.target_profit);
+        }
+        if self.max_price_impact_pct > 0.0 || self.max_capital > 0.0 || self.max_reserve_fraction > 0.0 {
+            println!(
+                "🧱 Input limits: impact {}%, capital {} WMNT, reserve fraction {} (0 = off, tightest applies)",
+                self.max_price_impact_pct, self.max_capital, self.max_reserve_fraction
+            );
+        }
+        if self.wallet_balance_wmnt > 0.0 {
+            println!("🏦 Wallet: {} WMNT, larger inputs flash-borrowed at {} bps", self.wallet_balance_wmnt, self.flash_loan_fee_bps);
+        }
+        if self.capture_ratio < 1.0 || self.rank_by_realized_profit {
+            println!(
+                "🥊 MEV competition: expect to capture {:.0}% of the gross spread{}",
+                self.capture_ratio * 100.0,
+                if self.rank_by_realized_profit { ", routes ranked by realized profit" } else { "" }
+            );
+        }
+        if self.execution_delay_blocks > 0 {
+            println!("⏳ Execution delay: {} blocks, profits discounted by reserve drift", self.execution_delay_blocks);
+        }
+        if let Some(price) = self.price_source().and_then(|prices| prices.price_usd(&self.base_token)) {
+            println!("💵 USD reporting: 1 {} = ${}", self.base_token.symbol(), price);
+        }
+        if let Some(path) = &self.graph_cache_path {
+            println!("🗂️ Graph cache: {}", path);
+        }
+        if let Some(path) = &self.pool_token_cache_path {
+            println!("🗂️ Pool token cache: {}", path);
+        }
+        if self.min_profit_wmnt > 0.0 {
+            if self.gas_volatility_multiplier > 0.0 {
+                println!("🎚️ Min Profit: {:.6} WMNT, scaled by gas volatility (x{}; smaller profits are logged as not actionable)", self.min_profit_wmnt, self.gas_volatility_multiplier);
+            } else {
+                println!("🎚️ Min Profit: {:.6} WMNT (smaller profits are logged as not actionable)", self.min_profit_wmnt);
+            }
+        }
+        if !self.include_tokens.is_empty() {
+            println!("✅ Required tokens: {}", token_symbols(&self.include_tokens));
+        }
+        if !self.exclude_tokens.is_empty() {
+            println!("🚫 Excluded tokens: {}", token_symbols(&self.exclude_tokens));
+        }
+        if WMNT_ADDRESS.parse::<Address>().ok() != Some(self.base_token.address()) {
+            println!("🏦 Base token: {} (multi-path profits, trade sizes and gas costs in {})", self.base_token.symbol(), self.base_token.symbol());
+        }
+        match self.output_format {
+            OutputFormat::Csv => println!("💾 Output: CSV ({})", self.csv_file_path),
+            OutputFormat::Parquet => println!("💾 Output: Parquet ({}, {} rows per group)", self.parquet_file_path, self.parquet_row_group_size),
+            OutputFormat::Sqlite => println!("💾 Output: SQLite ({})", self.sqlite_file_path),
+        }
+        if self.log_format == LogFormat::Json {
+            println!("🧾 JSON log: {}", if self.log_json_path == "-" { "stdout" } else { &self.log_json_path });
+        }
+        if let Some(address) = &self.stream_address {
+            println!("📡 Opportunity stream: {}", address);
+        }
+        if let Some(path) = &self.token_registry_path {
+            println!("🪙 Token registry: {}", path);
+        }
+        if !self.pools.is_empty() {
+            println!("🏊 Config pools: {} ({} with fee overrides)", self.pools.len(), self.pool_fees.len());
+        }
+        if !self.excluded_pools.is_empty() || !self.excluded_tokens.is_empty() {
+            println!("🚫 Excluded from graph: {} pools, {} tokens", self.excluded_pools.len(), self.excluded_tokens.len());
+        }
+        if self.strict_csv {
+            println!("🔒 Strict pool CSV: any skipped row is an error");
+        }
+        if let Some(path) = &self.metrics_snapshot_path {
+            println!("📊 Metrics snapshot: {} every {}s", path, self.metrics_snapshot_interval_secs);
+        }
+        if let Some(address) = &self.metrics_address {
+            println!("📈 Prometheus metrics: http://{}/metrics", address);
+        }
+        if let Some(address) = &self.api_address {
+            println!("🌐 HTTP API: http://{}/opportunities and /health", address);
+        }
+        if self.tui_enabled {
+            println!("🖥️ TUI dashboard: enabled (multi-path mode)");
+        }
+    }
+
+    /// Scale fetched or loaded reserves in place when a dev reserve scale is configured
+    pub fn apply_reserve_scale(&self, reserves_map: &mut HashMap<Address, PoolReserves>) {
+        if !self.dev_mode || self.reserve_scale == DEFAULT_RESERVE_SCALE {
+            return;
+        }
+        for reserves in reserves_map.values_mut() {
+            *reserves = reserves.scaled(self.reserve_scale);
+        }
+    }
+
+    /// Limits capping a route's input below its unconstrained optimum
+    pub fn input_limits(&self) -> InputLimits {
+        InputLimits {
+            max_price_impact: self.max_price_impact_pct / 100.0,
+            max_capital: self.max_capital,
+            max_reserve_fraction: self.max_reserve_fraction,
+        }
+    }
+
+    /// Strategy used to pick the best route of each block
+    pub fn ranking_strategy(&self) -> OptimizationStrategy {
+        if self.rank_by_realized_profit {
+            OptimizationStrategy::MaxRealizedProfit
+        } else {
+            OptimizationStrategy::MaxProfit
+        }
+    }
+
+    /// Source of USD quotes, `None` when `USD_PRICES` is unset
+    pub fn price_source(&self) -> Option<&dyn PriceSource> {
+        (!self.usd_prices.is_empty()).then_some(&self.usd_prices as &dyn PriceSource)
+    }
+
+    /// Wallet balance and fee used to price flash-borrowed inputs
+    pub fn flash_loan_terms(&self) -> FlashLoanTerms {
+        FlashLoanTerms {
+            wallet_balance: self.wallet_balance_wmnt,
+            fee_bps: self.flash_loan_fee_bps,
+        }
+    }
+
+    /// Approval/wrap gas overhead added to each route
+    pub fn gas_overhead(&self) -> GasOverhead {
+        GasOverhead {
+            approval_gas_units: self.approval_gas_units,
+            wrap_gas_units: self.wrap_gas_units,
+        }
     }
 
     /// Calculate precise gas cost based on gas units (result in MNT)
@@ -82,3 +1092,175 @@ impl Config {
         gas_units as f64 * self.gas_price_gwei * GWEI_TO_MNT_MULTIPLIER
     }
 }
+
+/// Parse a comma-separated list of token symbols or addresses from a setting
+fn parse_token_list(lookup: VarLookup, var: &str) -> Result<Vec<Token>, ArbError> {
+    let value = match lookup(var) {
+        Ok(value) => value,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    value
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            Token::parse(entry)
+                .ok_or_else(|| ArbError::TokenUnknown(format!("invalid {} entry '{}': expected a known token symbol or address", var, entry.trim())))
+        })
+        .collect()
+}
+
+/// Parse a comma-separated list of addresses from a setting
+fn parse_address_list(lookup: VarLookup, var: &str) -> Result<Vec<Address>, ArbError> {
+    let value = match lookup(var) {
+        Ok(value) => value,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry.parse()
+                .map_err(|e| ArbError::Config(format!("invalid {} entry '{}': {}", var, entry, e)))
+        })
+        .collect()
+}
+
+/// Join token symbols for display
+fn token_symbols(tokens: &[Token]) -> String {
+    tokens.iter().map(|t| t.symbol()).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn write_config(contents: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    fn load(file: &NamedTempFile, env: &[(&str, &str)]) -> Result<Config, String> {
+        let env: HashMap<String, String> = env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        Config::from_toml_with(file.path().to_str().unwrap(), &|name| env.get(name).cloned().ok_or(VarError::NotPresent))
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_toml_config_with_env_overrides() {
+        let file = write_config(r#"
+            rpc_url = "https://rpc.example"
+            gas_price_gwei = 0.05
+            dex_fee = 0.0025
+            ternary_search_iterations = 40
+            excluded_pools = ["0x00000000000000000000000000000000000000a3"]
+            excluded_tokens = ["0x00000000000000000000000000000000000000c2"]
+
+            [[tokens]]
+            symbol = "CFGUSD"
+            address = "0x00000000000000000000000000000000000000c1"
+            decimals = 6
+
+            [[pools]]
+            address = "0x00000000000000000000000000000000000000a1"
+            fee = 0.001
+
+            [[pools]]
+            address = "0x00000000000000000000000000000000000000a2"
+        "#);
+
+        let config = load(&file, &[("DEX_FEE", "0.002"), ("INCLUDE_TOKENS", "CFGUSD"), ("TERNARY_SEARCH_ITERATIONS_PER_HOP", "10")]).unwrap();
+        assert_eq!(config.rpc_url, "https://rpc.example");
+        assert_eq!(config.gas_price_gwei, 0.05);
+        assert_eq!(config.ternary_search_iterations, 40);
+        assert_eq!(config.search_iterations(2), 60);
+        assert_eq!(config.search_iterations(5), 90);
+        // Environment wins over the file
+        assert_eq!(config.dex_fee, 0.002);
+        // Unset everywhere: built-in default
+        assert_eq!(config.block_time_seconds, DEFAULT_BLOCK_TIME_SECONDS);
+
+        // File tokens are registered before token filters are parsed
+        assert_eq!(config.include_tokens.len(), 1);
+        assert_eq!(config.include_tokens[0].decimals(), 6);
+
+        let pool_a1: Address = "0x00000000000000000000000000000000000000a1".parse().unwrap();
+        assert_eq!(config.pools.len(), 2);
+        assert_eq!(config.pool_fees, HashMap::from([(pool_a1, 0.001)]));
+        assert_eq!(config.excluded_pools, vec!["0x00000000000000000000000000000000000000a3".parse::<Address>().unwrap()]);
+        assert_eq!(config.excluded_tokens.len(), 1);
+
+        let overridden = load(&file, &[("EXCLUDED_POOLS", " 0x00000000000000000000000000000000000000a4, ")]).unwrap();
+        assert_eq!(overridden.excluded_pools, vec!["0x00000000000000000000000000000000000000a4".parse::<Address>().unwrap()]);
+        assert!(load(&file, &[("EXCLUDED_TOKENS", "0x12")]).unwrap_err().contains("EXCLUDED_TOKENS"));
+    }
+
+    #[test]
+    fn test_toml_config_errors_name_the_field() {
+        let missing_rpc = write_config("dex_fee = 0.003\n");
+        assert!(load(&missing_rpc, &[]).unwrap_err().contains("missing rpc_url"));
+        assert!(matches!(Config::from_lookup(&|_| Err(VarError::NotPresent)), Err(ArbError::Config(_))));
+        assert!(load(&missing_rpc, &[("RPC_URL", "https://rpc.example")]).is_ok());
+
+        let wrong_type = write_config("rpc_url = \"x\"\ndex_fee = \"cheap\"\n");
+        assert!(load(&wrong_type, &[]).unwrap_err().contains("dex_fee"));
+
+        let unknown = write_config("rpc_url = \"x\"\ndex_fees = 0.003\n");
+        assert!(load(&unknown, &[]).unwrap_err().contains("dex_fees"));
+
+        let bad_url = write_config("rpc_url = \"not a url\"\n");
+        assert!(load(&bad_url, &[]).unwrap_err().contains("rpc_url (RPC_URL)"));
+
+        let bad_pool_fee = write_config(r#"
+            rpc_url = "x"
+            [[pools]]
+            address = "0x00000000000000000000000000000000000000a1"
+            fee = 1.5
+        "#);
+        let error = load(&bad_pool_fee, &[]).unwrap_err();
+        assert!(error.contains("fee of pool") && error.contains("1.5"), "{}", error);
+
+        let bad_address = write_config("rpc_url = \"x\"\n[[pools]]\naddress = \"0x1234\"\n");
+        assert!(load(&bad_address, &[]).unwrap_err().contains("address"));
+    }
+
+    #[test]
+    fn test_validate_rejects_nonsensical_settings() {
+        let file = write_config("rpc_url = \"https://rpc.example\"\n");
+        let config = load(&file, &[]).unwrap();
+        assert!(config.validate().is_ok());
+
+        let cases: [(&str, &str, &str); 21] = [
+            ("DEX_FEE", "5.0", "dex_fee"),
+            ("DEX_FEE", "-0.1", "dex_fee"),
+            ("TERNARY_SEARCH_ITERATIONS", "0", "ternary_search_iterations"),
+            ("BLOCK_TIME_SECONDS", "0", "block_time_seconds"),
+            ("BATCH_SIZE", "0", "batch_size"),
+            ("MIN_POOL_COVERAGE", "1.5", "min_pool_coverage"),
+            ("MAX_HOPS", "2", "max_hops"),
+            ("MAX_HOPS", "9", "max_hops"),
+            ("MIN_INPUT_WMNT", "20000", "min_input_wmnt"),
+            ("MAX_INPUT_WMNT", "-1", "max_input_wmnt"),
+            ("REFERENCE_TRADE_SIZE", "-5", "reference_trade_size"),
+            ("WALLET_BALANCE_WMNT", "-100", "wallet_balance_wmnt"),
+            ("FLASH_LOAN_FEE_BPS", "20000", "flash_loan_fee_bps"),
+            ("CAPTURE_RATIO", "1.5", "capture_ratio"),
+            ("CAPTURE_RATIO", "-0.2", "capture_ratio"),
+            ("PATH_STATS_ALPHA", "0", "path_stats_alpha"),
+            ("PATH_STATS_ALPHA", "1.5", "path_stats_alpha"),
+            ("BASE_TOKEN", "DOGE", "BASE_TOKEN"),
+            ("RPC_URL", "rpc.mantle.xyz", "rpc_url"),
+            ("RPC_URL_FALLBACK", "backup.mantle.xyz", "rpc_url_fallback"),
+            ("MONITOR_DRIVER", "websocket", "monitor_driver"),
+        ];
+        for (var, value, field) in cases {
+            let error = load(&file, &[(var, value)]).unwrap_err();
+            assert!(error.contains(field), "{}={}: {}", var, value, error);
+        }
+    }
+}
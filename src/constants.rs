@@ -3,6 +3,21 @@ pub const MOE_WMNT_POOL: &str = "0x763868612858358f62b05691dB82Ad35a9b3E110";
 pub const JOE_MOE_POOL: &str = "0xb670D2B452D0Ecc468cccFD532482d45dDdDe2a1";
 pub const JOE_WMNT_POOL: &str = "0xEFC38C1B0d60725B824EBeE8D431aBFBF12BC953";
 
+/// Pool list loaded by multi-path mode unless `--pools` is given
+pub const POOL_CSV_PATH: &str = "data/selected.csv";
+
+/// Report written by the `diff` mode when no output path is given
+pub const DEFAULT_RESERVE_DIFF_PATH: &str = "reserve_diff.csv";
+
+/// `--record` flushes its snapshot file after this many blocks (and on shutdown)
+pub const RESERVE_RECORDER_FLUSH_BLOCKS: usize = 10;
+
+/// Blocks simulated by the dev-only `synthetic` mode when no count is given
+pub const DEFAULT_SYNTHETIC_BLOCKS: u64 = 15;
+
+/// Multicall3 (same address on every chain it is deployed to, including Mantle)
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
 /// Token addresses on Mantle Network
 pub const WMNT_ADDRESS: &str = "0x78c1b0c915c4faa5fffa6cabf0219da63d7f4cb8";
 pub const MOE_ADDRESS: &str = "0x4515a45337f461a11ff0fe8abf3c606ae5dc00c9";
@@ -11,12 +26,69 @@ pub const JOE_ADDRESS: &str = "0x371c7ec6d8039ff7933a2aa28eb827ffe1f52f07";
 /// Gas cost constants (based on design requirements)
 pub const GAS_UNITS_3_HOPS: u64 = 700_000_000;  // 3-hops: 700M gas
 pub const GAS_UNITS_4_HOPS: u64 = 720_000_000;  // 4-hops: 720M gas
+pub const GAS_UNITS_PER_EXTRA_HOP: u64 = GAS_UNITS_4_HOPS - GAS_UNITS_3_HOPS; // Each hop past 3 adds 20M gas
 pub const DEFAULT_GAS_PRICE_GWEI: f64 = 0.02; // 0.02 gwei as specified in design
 pub const GWEI_TO_MNT_MULTIPLIER: f64 = 1e-9; // Conversion factor from gwei to MNT
 
 /// Default configuration constants
 pub const DEFAULT_BLOCK_TIME_SECONDS: u64 = 2;
+pub const BLOCK_POLL_BUFFER_MS: u64 = 200; // Poll this long before the next expected block
+pub const MIN_BLOCK_POLL_INTERVAL_MS: u64 = 50; // Floor for very short block times
 pub const DEFAULT_MAX_RETRIES: u32 = 3;
+pub const DEFAULT_BATCH_SIZE: usize = 50; // Pools per reserve-fetch batch (multi-path mode)
+pub const DEFAULT_INTER_BATCH_DELAY_MS: u64 = 100;
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 16; // Per-pool reserve calls in flight at once (0 = unbounded)
+pub const DEFAULT_MIN_POOL_COVERAGE: f64 = 0.0; // Share of pools a block needs to be analyzed; 0 = any partial snapshot
+pub const MAX_PLAUSIBLE_RESERVE_UNITS: f64 = 1e15; // Larger reserves (in token units) come from broken or malicious pools
+pub const MAX_SYNC_LOG_BLOCKS: u64 = 500; // Wider gaps take a fresh getReserves snapshot (RESERVE_SOURCE=sync)
+pub const DEFAULT_MAX_CONSECUTIVE_FAILURES: u32 = 5; // Reconnect the RPC provider after this many failures in a row
+pub const DEFAULT_RPC_TIMEOUT_MS: u64 = 10_000; // Per-call RPC timeout; 0 = wait indefinitely
 pub const DEFAULT_CSV_FILE_PATH: &str = "arbitrage_opportunities.csv";
+pub const DEFAULT_POOL_TOKEN_CACHE_PATH: &str = "pool_tokens.json"; // Empty POOL_TOKEN_CACHE_PATH disables it
 pub const DEFAULT_DEX_FEE: f64 = 0.003; // 0.3% fee for most DEXes
+pub const DEFAULT_AGNI_FEE: f64 = 0.0025; // Agni pools without an explicit fee
 pub const DEFAULT_TERNARY_SEARCH_ITERATIONS: usize = 100;
+pub const DEFAULT_TERNARY_SEARCH_ITERATIONS_PER_HOP: usize = 0; // Extra iterations per hop on top of the base
+pub const DEFAULT_MIN_INPUT_WMNT: f64 = 100.0; // Smallest trade size searched
+pub const DEFAULT_MAX_INPUT_WMNT: f64 = 10000.0; // Largest trade size searched
+pub const DEFAULT_TUI_ENABLED: bool = false;
+pub const DEFAULT_VOLATILITY_WINDOW: usize = 20;
+pub const DEFAULT_VOLATILITY_LOG_INTERVAL_BLOCKS: u64 = 50;
+pub const DEFAULT_PATH_STATS_ALPHA: f64 = 0.1; // Weight of the newest scan in each route's profit EMA
+pub const DEFAULT_PATH_STATS_LOG_INTERVAL_BLOCKS: u64 = 0; // 0 = only print route stats on shutdown
+pub const DEFAULT_PARQUET_FILE_PATH: &str = "arbitrage_opportunities.parquet";
+pub const DEFAULT_LOG_JSON_PATH: &str = "arbitrage_log.jsonl"; // JSON lines when LOG_FORMAT=json
+pub const DEFAULT_LOG_FILTER: &str = "warn,triangular_arbitrage_demo=info"; // Console verbosity unless RUST_LOG is set
+pub const DEFAULT_PARQUET_ROW_GROUP_SIZE: usize = 100;
+pub const DEFAULT_SQLITE_FILE_PATH: &str = "arbitrage_opportunities.db";
+pub const DEFAULT_CONFIRMATION_BLOCKS: u32 = 1;
+pub const DEFAULT_VERIFY_NEXT_BLOCK: bool = false; // Re-price profitable routes at block N+1 before recording
+pub const DEFAULT_TARGET_PROFIT: f64 = 0.01; // WMNT net profit for min-capital objective
+pub const DEFAULT_APPROVAL_GAS_UNITS: u64 = 0; // Extra gas per hop for token approvals
+pub const DEFAULT_WRAP_GAS_UNITS: u64 = 0; // Extra gas per WMNT wrap or unwrap
+pub const DEFAULT_MIN_PROFIT_WMNT: f64 = 0.05; // Minimum net profit (WMNT) for an opportunity to be actionable
+pub const DEFAULT_MIN_LIQUIDITY_WMNT: f64 = 1000.0; // Pool value (WMNT) counted as arbitrage-ready
+pub const DEFAULT_GAS_VOLATILITY_MULTIPLIER: f64 = 0.0; // 0 = static threshold
+pub const DEFAULT_GAS_HISTORY_WINDOW: usize = 20;
+pub const DEFAULT_DYNAMIC_GAS_PRICE: bool = false; // Cost routes with the static GAS_PRICE_GWEI
+pub const DEFAULT_GAS_PRICE_REFRESH_SECS: u64 = 10;
+pub const DEFAULT_MAX_CYCLES_PER_BLOCK: usize = 0; // 0 = analyze every cycle
+pub const DEFAULT_MAX_HOPS: usize = 4; // Longest cycle searched (multi-path mode)
+pub const MAX_SUPPORTED_HOPS: usize = 8; // Upper bound accepted for MAX_HOPS
+pub const DEFAULT_SPFA_ALL_SOURCES: bool = false; // Seed cycle search from WMNT only
+pub const DEFAULT_STRICT_CSV: bool = false; // Skip unusable pool CSV rows with a summary instead of failing
+pub const DEFAULT_REFERENCE_TRADE_SIZE: f64 = 0.0; // 0 = edge weights use the marginal rate
+pub const DEFAULT_RESERVE_LOG_MIN_CHANGE_PCT: f64 = 0.0; // 0 = log every reserve change
+pub const DEFAULT_RESERVE_LOG_INTERVAL_SECS: u64 = 0;
+pub const DEFAULT_RESERVE_CACHE_TTL_SECS: u64 = 60; // 0 = keep cached reserves forever
+pub const DEFAULT_FEE_CALIBRATION_BLOCKS: u64 = 0; // 0 = use DEX_FEE for every pool
+pub const DEFAULT_RESERVE_SCALE: f64 = 1.0; // Only honored with DEV_MODE=true
+pub const DEFAULT_MAX_PRICE_IMPACT_PCT: f64 = 0.0; // 0 = no per-hop price impact limit
+pub const DEFAULT_MAX_CAPITAL: f64 = 0.0; // 0 = no WMNT capital limit
+pub const DEFAULT_MAX_RESERVE_FRACTION: f64 = 0.0; // 0 = no cap relative to the entry pool reserve
+pub const DEFAULT_WALLET_BALANCE_WMNT: f64 = 0.0; // 0 = unlimited, never flash-borrow
+pub const DEFAULT_FLASH_LOAN_FEE_BPS: f64 = 5.0; // 0.05% of the borrowed amount
+pub const DEFAULT_CAPTURE_RATIO: f64 = 1.0; // 1 = assume no competing bots take part of the spread
+pub const DEFAULT_EXECUTION_DELAY_BLOCKS: u64 = 0; // Blocks until a trade lands; 0 = no drift discount
+pub const DEFAULT_RANK_BY_REALIZED_PROFIT: bool = false;
+pub const DEFAULT_METRICS_SNAPSHOT_INTERVAL_SECS: u64 = 30;
@@ -1,7 +1,59 @@
 use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
 use alloy::primitives::Address;
+use owo_colors::OwoColorize;
+use crate::metrics::MetricsSnapshot;
 use crate::types::{Token, PoolReserves};
-use crate::math::u256_to_f64;
+
+/// Meaning of a piece of console output, mapped to a terminal color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    Profit,
+    Loss,
+    Warning,
+}
+
+/// Whether console output is colored: stdout is a TTY and `NO_COLOR` is unset or empty
+pub fn color_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    // Tests always see plain text
+    *ENABLED.get_or_init(|| {
+        !cfg!(test) && color_allowed(std::env::var_os("NO_COLOR").as_deref(), std::io::stdout().is_terminal())
+    })
+}
+
+fn color_allowed(no_color: Option<&OsStr>, is_terminal: bool) -> bool {
+    is_terminal && no_color.is_none_or(OsStr::is_empty)
+}
+
+/// Color `text` by tone when the terminal supports it
+pub fn paint(text: &str, tone: Tone) -> String {
+    paint_with(text, tone, color_enabled())
+}
+
+fn paint_with(text: &str, tone: Tone, enabled: bool) -> String {
+    if !enabled {
+        return text.to_string();
+    }
+    match tone {
+        Tone::Profit => text.green().to_string(),
+        Tone::Loss => text.red().to_string(),
+        Tone::Warning => text.yellow().to_string(),
+    }
+}
+
+/// Green for positive values, red for negative ones, plain for zero
+pub fn paint_signed(text: &str, value: f64) -> String {
+    if value > 0.0 {
+        paint(text, Tone::Profit)
+    } else if value < 0.0 {
+        paint(text, Tone::Loss)
+    } else {
+        text.to_string()
+    }
+}
 
 /// Format pool reserves information for logging
 pub fn format_pool_reserves(
@@ -14,9 +66,9 @@ pub fn format_pool_reserves(
     
     // MOE-WMNT Pool
     if let Some(reserves) = reserves_map.get(&moe_wmnt_addr) {
-        let (wmnt_reserve, moe_reserve, pool_name) = match (reserves.token_a, reserves.token_b) {
-            (Token::WMNT(_), Token::MOE(_)) => (u256_to_f64(reserves.reserve_a), u256_to_f64(reserves.reserve_b), "MOE-WMNT"),
-            (Token::MOE(_), Token::WMNT(_)) => (u256_to_f64(reserves.reserve_b), u256_to_f64(reserves.reserve_a), "MOE-WMNT"),
+        let (wmnt_reserve, moe_reserve, pool_name) = match (reserves.token_a.symbol(), reserves.token_b.symbol()) {
+            ("WMNT", "MOE") => (reserves.reserve_a_units(), reserves.reserve_b_units(), "MOE-WMNT"),
+            ("MOE", "WMNT") => (reserves.reserve_b_units(), reserves.reserve_a_units(), "MOE-WMNT"),
             _ => (0.0, 0.0, "MOE-WMNT(?)"),
         };
         output.push_str(&format!("   📊 {}: {:.2} WMNT / {:.2} MOE\n", pool_name, wmnt_reserve, moe_reserve));
@@ -24,9 +76,9 @@ pub fn format_pool_reserves(
     
     // JOE-MOE Pool
     if let Some(reserves) = reserves_map.get(&joe_moe_addr) {
-        let (moe_reserve, joe_reserve, pool_name) = match (reserves.token_a, reserves.token_b) {
-            (Token::MOE(_), Token::JOE(_)) => (u256_to_f64(reserves.reserve_a), u256_to_f64(reserves.reserve_b), "JOE-MOE"),
-            (Token::JOE(_), Token::MOE(_)) => (u256_to_f64(reserves.reserve_b), u256_to_f64(reserves.reserve_a), "JOE-MOE"),
+        let (moe_reserve, joe_reserve, pool_name) = match (reserves.token_a.symbol(), reserves.token_b.symbol()) {
+            ("MOE", "JOE") => (reserves.reserve_a_units(), reserves.reserve_b_units(), "JOE-MOE"),
+            ("JOE", "MOE") => (reserves.reserve_b_units(), reserves.reserve_a_units(), "JOE-MOE"),
             _ => (0.0, 0.0, "JOE-MOE(?)"),
         };
         output.push_str(&format!("   📊 {}: {:.2} MOE / {:.2} JOE\n", pool_name, moe_reserve, joe_reserve));
@@ -34,9 +86,9 @@ pub fn format_pool_reserves(
     
     // JOE-WMNT Pool
     if let Some(reserves) = reserves_map.get(&joe_wmnt_addr) {
-        let (joe_reserve, wmnt_reserve, pool_name) = match (reserves.token_a, reserves.token_b) {
-            (Token::JOE(_), Token::WMNT(_)) => (u256_to_f64(reserves.reserve_a), u256_to_f64(reserves.reserve_b), "JOE-WMNT"),
-            (Token::WMNT(_), Token::JOE(_)) => (u256_to_f64(reserves.reserve_b), u256_to_f64(reserves.reserve_a), "JOE-WMNT"),
+        let (joe_reserve, wmnt_reserve, pool_name) = match (reserves.token_a.symbol(), reserves.token_b.symbol()) {
+            ("JOE", "WMNT") => (reserves.reserve_a_units(), reserves.reserve_b_units(), "JOE-WMNT"),
+            ("WMNT", "JOE") => (reserves.reserve_b_units(), reserves.reserve_a_units(), "JOE-WMNT"),
             _ => (0.0, 0.0, "JOE-WMNT(?)"),
         };
         output.push_str(&format!("   📊 {}: {:.2} JOE / {:.2} WMNT", pool_name, joe_reserve, wmnt_reserve));
@@ -49,12 +101,20 @@ pub fn format_pool_reserves(
 pub fn format_single_pool_reserves(reserves: &PoolReserves) -> String {
     let token_a_symbol = reserves.token_a.symbol();
     let token_b_symbol = reserves.token_b.symbol();
-    let reserve_a = u256_to_f64(reserves.reserve_a);
-    let reserve_b = u256_to_f64(reserves.reserve_b);
+    let reserve_a = reserves.reserve_a_units();
+    let reserve_b = reserves.reserve_b_units();
     
     format!("{:.2} {} / {:.2} {}", reserve_a, token_a_symbol, reserve_b, token_b_symbol)
 }
 
+/// Print what a monitor run did before it stopped
+pub fn print_shutdown_summary(snapshot: &MetricsSnapshot) {
+    println!("\n👋 Monitor stopped");
+    println!("├─ Blocks processed: {}", snapshot.blocks_processed);
+    println!("├─ Opportunities found: {} ({} profitable)", snapshot.opportunities_found, snapshot.profitable_opportunities);
+    println!("└─ Errors: {}", snapshot.errors);
+}
+
 /// Format startup banner with configuration info
 pub fn print_startup_banner() {
     println!("🚀 Starting triangular arbitrage monitor on Mantle Network");
@@ -87,13 +147,14 @@ pub fn format_token_amount(amount: f64, token: &Token) -> String {
     format!("{:.6} {}", amount, token.symbol())
 }
 
-/// Format percentage with color coding (for future terminal color support)
+/// Format percentage, green when positive and red when negative
 pub fn format_percentage(percentage: f64) -> String {
-    if percentage > 0.0 {
+    let text = if percentage > 0.0 {
         format!("+{:.2}%", percentage)
     } else {
         format!("{:.2}%", percentage)
-    }
+    };
+    paint_signed(&text, percentage)
 }
 
 /// Format duration in human-readable format
@@ -113,17 +174,17 @@ pub fn format_block_info(block_number: u64, timestamp: chrono::DateTime<chrono::
 
 /// Format error message with emoji
 pub fn format_error(message: &str) -> String {
-    format!("❌ {}", message)
+    paint(&format!("❌ {}", message), Tone::Loss)
 }
 
 /// Format success message with emoji
 pub fn format_success(message: &str) -> String {
-    format!("✅ {}", message)
+    paint(&format!("✅ {}", message), Tone::Profit)
 }
 
 /// Format warning message with emoji
 pub fn format_warning(message: &str) -> String {
-    format!("⚠️ {}", message)
+    paint(&format!("⚠️ {}", message), Tone::Warning)
 }
 
 /// Format info message with emoji
@@ -206,9 +267,9 @@ mod tests {
 
     fn create_test_reserves() -> PoolReserves {
         PoolReserves {
-            token_a: Token::WMNT(Address::ZERO),
+            token_a: Token::new(Address::ZERO, "WMNT", 18),
             reserve_a: U256::from(1000u128 * 1_000_000_000_000_000_000u128),
-            token_b: Token::MOE(Address::from([1u8; 20])),
+            token_b: Token::new(Address::from([1u8; 20]), "MOE", 18),
             reserve_b: U256::from(2000u128 * 1_000_000_000_000_000_000u128),
             block_number: 1,
             timestamp: Utc::now(),
@@ -227,9 +288,9 @@ mod tests {
     #[test]
     fn test_format_arbitrage_path() {
         let tokens = vec![
-            Token::WMNT(Address::ZERO),
-            Token::MOE(Address::from([1u8; 20])),
-            Token::JOE(Address::from([2u8; 20])),
+            Token::new(Address::ZERO, "WMNT", 18),
+            Token::new(Address::from([1u8; 20]), "MOE", 18),
+            Token::new(Address::from([2u8; 20]), "JOE", 18),
         ];
         let formatted = format_arbitrage_path(&tokens);
         assert_eq!(formatted, "WMNT → MOE → JOE");
@@ -242,6 +303,22 @@ mod tests {
         assert_eq!(format_percentage(0.0), "0.00%");
     }
 
+    #[test]
+    fn test_color_fallback() {
+        assert!(color_allowed(None, true));
+        assert!(color_allowed(Some(OsStr::new("")), true));
+        assert!(!color_allowed(Some(OsStr::new("1")), true));
+        assert!(!color_allowed(None, false));
+
+        assert_eq!(paint_with("+1.00%", Tone::Profit, false), "+1.00%");
+        assert_eq!(paint_with("+1.00%", Tone::Profit, true), "\x1b[32m+1.00%\x1b[39m");
+        assert_eq!(paint_with("-1.00%", Tone::Loss, true), "\x1b[31m-1.00%\x1b[39m");
+        assert_eq!(paint_with("careful", Tone::Warning, true), "\x1b[33mcareful\x1b[39m");
+
+        // Helpers stay plain under test
+        assert_eq!(format_warning("low liquidity"), "⚠️ low liquidity");
+    }
+
     #[test]
     fn test_default_reserves_formatter() {
         let formatter = DefaultReservesFormatter::new();
@@ -0,0 +1,55 @@
+use thiserror::Error;
+
+/// Failure kinds returned by the library, so callers can retry RPC errors and abort on config errors
+#[derive(Debug, Error)]
+pub enum ArbError {
+    /// RPC transport or contract call failure, timeout, or an unusable reserve snapshot
+    #[error("RPC error: {0}")]
+    Rpc(String),
+    /// Missing or invalid setting
+    #[error("{0}")]
+    Config(String),
+    /// Unreadable pool or recording CSV
+    #[error("CSV error: {0}")]
+    CsvParse(#[from] csv::Error),
+    /// Token symbol or address that is not in the registry
+    #[error("unknown token: {0}")]
+    TokenUnknown(String),
+    /// Search input that cannot be optimized over
+    #[error("{0}")]
+    Math(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl ArbError {
+    /// Worth retrying on the next block (RPC failures), as opposed to a setup problem
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ArbError::Rpc(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds() {
+        fn parse_csv() -> Result<(), ArbError> {
+            csv::Reader::from_path("/nonexistent/pools.csv")?;
+            Ok(())
+        }
+        assert!(matches!(parse_csv(), Err(ArbError::CsvParse(_))));
+
+        let rpc = ArbError::Rpc("RPC call timed out after 10 ms".to_string());
+        assert!(rpc.is_transient());
+        assert!(!ArbError::Config("RPC_URL is required".to_string()).is_transient());
+        assert_eq!(rpc.to_string(), "RPC error: RPC call timed out after 10 ms");
+
+        // Still converts into the boxed errors used by the binaries
+        let boxed: Box<dyn std::error::Error> = ArbError::TokenUnknown("FOO".to_string()).into();
+        assert_eq!(boxed.to_string(), "unknown token: FOO");
+    }
+}
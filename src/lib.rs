@@ -0,0 +1,46 @@
+//! Triangular and multi-path arbitrage detection for Mantle AMM pools.
+//!
+//! The `triangular-arbitrage-demo` binary runs the monitor loops; [`analyze_snapshot`]
+//! runs the multi-path analysis on a single reserves snapshot for embedding in other bots.
+
+pub mod error;
+pub mod types;
+pub mod constants;
+pub mod config;
+pub mod cache;
+pub mod math;
+pub mod blockchain;
+pub mod arbitrage;
+pub mod logging;
+pub mod display;
+pub mod pools;
+pub mod graph;
+pub mod multi_path;
+pub mod batch_fetcher;
+pub mod multi_path_main;
+pub mod triangular_main;
+pub mod tui;
+pub mod parquet_export;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_export;
+pub mod stream;
+pub mod reserve_diff;
+pub mod replay;
+pub mod recorder;
+pub mod synthetic;
+pub mod metrics;
+#[cfg(feature = "prometheus")]
+pub mod metrics_server;
+pub mod api;
+#[cfg(feature = "http-api")]
+pub mod api_server;
+pub mod token_registry;
+pub mod price_source;
+pub mod block_driver;
+pub mod rpc_provider;
+pub mod reserve_reader;
+pub mod shutdown;
+pub mod cli;
+
+pub use error::ArbError;
+pub use multi_path::analyze_snapshot;
@@ -1,29 +1,54 @@
 use std::error::Error;
 use std::fs::OpenOptions;
+use std::io::Write;
 use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
 use csv::Writer;
 use chrono::{DateTime, Utc};
+use env_logger::{Env, Target};
+use log::{debug, error, info, warn, Level};
+use serde_json::{json, Value};
 use crate::types::{ArbitrageRecord, PoolReserves, ArbitrageOpportunity};
-use crate::config::Config;
+use crate::config::{Config, LogFormat};
+use crate::constants::DEFAULT_LOG_FILTER;
+
+/// Send `log` output to stdout, filtered by `RUST_LOG` (default `DEFAULT_LOG_FILTER`)
+pub fn init_console_logging() {
+    // A second call (e.g. from tests) keeps the first logger
+    let _ = env_logger::Builder::from_env(Env::default().default_filter_or(DEFAULT_LOG_FILTER))
+        .target(Target::Stdout)
+        .format(|buf, record| writeln!(buf, "{}", console_line(record.level(), &record.args().to_string())))
+        .try_init();
+}
+
+/// Info and above print as plain messages; debug and trace lines carry their level
+fn console_line(level: Level, message: &str) -> String {
+    match level {
+        Level::Error | Level::Warn | Level::Info => message.to_string(),
+        Level::Debug | Level::Trace => format!("[{}] {}", level, message),
+    }
+}
 
 /// Initialize CSV file with headers if it doesn't exist
 pub fn init_csv_file(csv_file_path: &str) -> Result<(), Box<dyn Error>> {
     if !Path::new(csv_file_path).exists() {
         let file = OpenOptions::new()
             .create(true)
-            .write(true)
             .append(true)
             .open(csv_file_path)?;
         
         let mut writer = Writer::from_writer(file);
         
         // Write header if file is new
-        writer.write_record(&[
+        writer.write_record([
             "timestamp", "block_number", "optimal_input_wmnt", "final_output_wmnt", 
             "gross_profit_wmnt", "net_profit_wmnt", "profit_percentage", "gas_cost_mnt", "search_method",
             "moe_wmnt_reserve0", "moe_wmnt_reserve1", 
             "joe_moe_reserve0", "joe_moe_reserve1",
-            "joe_wmnt_reserve0", "joe_wmnt_reserve1", "fetch_time_ms"
+            "joe_wmnt_reserve0", "joe_wmnt_reserve1", "fetch_time_ms", "actionable",
+            "price_impact_percent", "requires_flash_loan", "flash_loan_fee_wmnt",
+            "expected_realized_profit_wmnt", "hop_outputs"
         ])?;
         writer.flush()?;
     }
@@ -31,6 +56,7 @@ pub fn init_csv_file(csv_file_path: &str) -> Result<(), Box<dyn Error>> {
 }
 
 /// Write arbitrage opportunity to CSV
+#[allow(clippy::too_many_arguments)]
 pub fn write_arbitrage_to_csv(
     timestamp: DateTime<Utc>,
     block_number: u64,
@@ -43,7 +69,6 @@ pub fn write_arbitrage_to_csv(
 ) -> Result<(), Box<dyn Error>> {
     let file = OpenOptions::new()
         .create(true)
-        .write(true)
         .append(true)
         .open(&config.csv_file_path)?;
     
@@ -57,7 +82,7 @@ pub fn write_arbitrage_to_csv(
         gross_profit_wmnt: opportunity.gross_profit,
         net_profit_wmnt: opportunity.net_profit,
         profit_percentage: opportunity.profit_percentage,
-        gas_cost_mnt: opportunity.gas_cost(config.gas_price_gwei),
+        gas_cost_mnt: opportunity.gas_cost(config.gas_price_gwei, &config.gas_overhead()),
         search_method: opportunity.search_method.clone(),
         moe_wmnt_reserve0: moe_wmnt_reserves.reserve_a.to_string(),
         moe_wmnt_reserve1: moe_wmnt_reserves.reserve_b.to_string(),
@@ -66,6 +91,12 @@ pub fn write_arbitrage_to_csv(
         joe_wmnt_reserve0: joe_wmnt_reserves.reserve_a.to_string(),
         joe_wmnt_reserve1: joe_wmnt_reserves.reserve_b.to_string(),
         fetch_time_ms,
+        actionable: opportunity.is_profitable_with_threshold(config.min_profit_wmnt),
+        price_impact_percent: opportunity.price_impact_percent,
+        requires_flash_loan: opportunity.requires_flash_loan,
+        flash_loan_fee_wmnt: opportunity.flash_loan_fee,
+        expected_realized_profit_wmnt: opportunity.expected_realized_profit,
+        hop_outputs: opportunity.hop_outputs_json(),
     };
     
     writer.serialize(&record)?;
@@ -79,19 +110,21 @@ pub fn log_profitable_arbitrage(
     fetch_duration: std::time::Duration,
     config: &Config,
 ) {
-    println!("💎 OPTIMAL ARBITRAGE OPPORTUNITY FOUND!");
-    println!("   🎯 Optimal Input: {:.6} WMNT (via {})", opportunity.optimal_input, opportunity.search_method);
-    println!("   📈 Final Output: {:.6} WMNT", opportunity.final_output);
-    println!("   💰 Gross Profit: {:.6} WMNT", opportunity.gross_profit);
-    println!("   🎯 Net Profit: {:.6} WMNT ({:.2}%)", opportunity.net_profit, opportunity.profit_percentage);
-    let gas_cost = if opportunity.hop_count() > 0 {
-        opportunity.gas_cost(config.gas_price_gwei)
-    } else {
-        config.calculate_gas_cost(crate::constants::GAS_UNITS_3_HOPS) // Default to 3-hops for legacy
-    };
-    println!("   ⛽ After {:.6} MNT gas cost", gas_cost);
-    println!("   🔍 Search iterations: {}", config.ternary_search_iterations);
-    println!("   ⚡ Analysis time: {:?}", fetch_duration);
+    info!("💎 OPTIMAL ARBITRAGE OPPORTUNITY FOUND!");
+    info!("   🎯 Optimal Input: {:.6} WMNT (via {})", opportunity.optimal_input, opportunity.search_method);
+    info!("   📈 Final Output: {:.6} WMNT", opportunity.final_output);
+    info!("   🌊 Price Impact: {:.3}% (worst hop)", opportunity.price_impact_percent);
+    info!("   💰 Gross Profit: {:.6} WMNT", opportunity.gross_profit);
+    info!("   🎯 Net Profit: {:.6} WMNT ({:.2}%)", opportunity.net_profit, opportunity.profit_percentage);
+    if opportunity.requires_flash_loan {
+        info!("   🏦 After {:.6} WMNT flash-loan fee", opportunity.flash_loan_fee);
+    }
+    info!("   ⛽ After {:.6} MNT gas cost", opportunity.gas_cost(config.gas_price_gwei, &config.gas_overhead()));
+    if config.capture_ratio < 1.0 {
+        info!("   🥊 Expected realized: {:.6} WMNT at {:.0}% capture", opportunity.expected_realized_profit, config.capture_ratio * 100.0);
+    }
+    info!("   🔍 Search iterations: {}", config.ternary_search_iterations);
+    info!("   ⚡ Analysis time: {:?}", fetch_duration);
 }
 
 /// Log when no profitable opportunity is found
@@ -100,28 +133,67 @@ pub fn log_no_profit(
     net_profit: f64,
     fetch_duration: std::time::Duration,
 ) {
-    println!("   📊 No profitable opportunity after costs. Gross: {:.6}, Net: {:.6} WMNT, Time: {:?}", 
+    debug!("   📊 No profitable opportunity after costs. Gross: {:.6}, Net: {:.6} WMNT, Time: {:?}", 
         gross_profit, net_profit, fetch_duration);
 }
 
+/// Log a profitable opportunity that is below the minimum profit
+pub fn log_below_threshold(
+    net_profit: f64,
+    min_profit: f64,
+    fetch_duration: std::time::Duration,
+) {
+    debug!("   🪙 Net profit {:.6} WMNT is below the {:.6} WMNT minimum (recorded as not actionable). Time: {:?}",
+        net_profit, min_profit, fetch_duration);
+}
+
 /// Log analysis failure
 pub fn log_analysis_failure(fetch_duration: std::time::Duration) {
-    println!("   ❌ Failed to analyze pools. Analysis time: {:?}", fetch_duration);
+    error!("   ❌ Failed to analyze pools. Analysis time: {:?}", fetch_duration);
 }
 
 /// Log successful CSV write
 pub fn log_csv_success(csv_file_path: &str) {
-    println!("   ✅ Logged to CSV: {}", csv_file_path);
+    info!("   ✅ Logged to CSV: {}", csv_file_path);
 }
 
 /// Log CSV write failure
 pub fn log_csv_failure(error: &dyn Error) {
-    println!("   ⚠️ Failed to write to CSV: {}", error);
+    warn!("   ⚠️ Failed to write to CSV: {}", error);
+}
+
+/// Log a record buffered for the Parquet file
+pub fn log_parquet_success(parquet_file_path: &str) {
+    info!("   ✅ Logged to Parquet: {}", parquet_file_path);
+}
+
+/// Log Parquet write failure
+pub fn log_parquet_failure(error: &dyn Error) {
+    warn!("   ⚠️ Failed to write to Parquet: {}", error);
+}
+
+/// Log a record inserted into the SQLite database
+pub fn log_sqlite_success(sqlite_file_path: &str) {
+    info!("   ✅ Logged to SQLite: {}", sqlite_file_path);
+}
+
+/// Log SQLite insert failure
+pub fn log_sqlite_failure(error: &dyn Error) {
+    warn!("   ⚠️ Failed to write to SQLite: {}", error);
 }
 
 /// Generic logger trait for future extensibility
 pub trait ArbitrageLogger {
     fn log_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(), Box<dyn Error>>;
+    /// Log an opportunity with the block it was found in and the analysis time
+    fn log_block_opportunity(
+        &self,
+        _block_number: u64,
+        opportunity: &ArbitrageOpportunity,
+        _analysis_time: Duration,
+    ) -> Result<(), Box<dyn Error>> {
+        self.log_opportunity(opportunity)
+    }
     fn log_reserves_change(&self, block_number: u64, timestamp: DateTime<Utc>);
     fn log_error(&self, error: &str);
     fn log_info(&self, message: &str);
@@ -133,24 +205,24 @@ pub struct ConsoleLogger;
 impl ArbitrageLogger for ConsoleLogger {
     fn log_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(), Box<dyn Error>> {
         if opportunity.is_profitable() {
-            println!("💎 Profitable opportunity: {:.6} WMNT profit ({:.2}%)", 
+            info!("💎 Profitable opportunity: {:.6} WMNT profit ({:.2}%)", 
                 opportunity.net_profit, opportunity.profit_percentage);
         } else {
-            println!("📊 No profit after costs: {:.6} WMNT", opportunity.net_profit);
+            debug!("📊 No profit after costs: {:.6} WMNT", opportunity.net_profit);
         }
         Ok(())
     }
 
     fn log_reserves_change(&self, block_number: u64, timestamp: DateTime<Utc>) {
-        println!("🔄 Reserves changed at block {} ({})", block_number, timestamp.format("%H:%M:%S%.3f"));
+        debug!("🔄 Reserves changed at block {} ({})", block_number, timestamp.format("%H:%M:%S%.3f"));
     }
 
     fn log_error(&self, error: &str) {
-        println!("❌ {}", error);
+        error!("❌ {}", error);
     }
 
     fn log_info(&self, message: &str) {
-        println!("ℹ️ {}", message);
+        info!("ℹ️ {}", message);
     }
 }
 
@@ -167,7 +239,6 @@ impl FileLogger {
 
 impl ArbitrageLogger for FileLogger {
     fn log_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(), Box<dyn Error>> {
-        use std::io::Write;
         let mut file = OpenOptions::new()
             .create(true)
             .append(true)
@@ -183,16 +254,15 @@ impl ArbitrageLogger for FileLogger {
 
     fn log_reserves_change(&self, block_number: u64, timestamp: DateTime<Utc>) {
         // Implementation for file logging
-        let _ = self.log_info(&format!("Reserves changed at block {} ({})", 
+        self.log_info(&format!("Reserves changed at block {} ({})", 
             block_number, timestamp.format("%H:%M:%S%.3f")));
     }
 
     fn log_error(&self, error: &str) {
-        let _ = self.log_info(&format!("ERROR: {}", error));
+        self.log_info(&format!("ERROR: {}", error));
     }
 
     fn log_info(&self, message: &str) {
-        use std::io::Write;
         if let Ok(mut file) = OpenOptions::new()
             .create(true)
             .append(true)
@@ -203,11 +273,158 @@ impl ArbitrageLogger for FileLogger {
     }
 }
 
+/// JSON lines logger: one object per event, for log aggregators
+pub struct JsonLogger {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonLogger {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self { writer: Mutex::new(Box::new(writer)) }
+    }
+
+    /// JSON logger for `LOG_FORMAT=json` (appending to `LOG_JSON_PATH`, `-` for stdout), else `None`
+    pub fn from_config(config: &Config) -> Result<Option<Self>, Box<dyn Error>> {
+        if config.log_format != LogFormat::Json {
+            return Ok(None);
+        }
+        if config.log_json_path == "-" {
+            return Ok(Some(Self::new(std::io::stdout())));
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&config.log_json_path)?;
+        Ok(Some(Self::new(file)))
+    }
+
+    fn write_event(&self, event: &str, mut fields: Value) -> Result<(), Box<dyn Error>> {
+        fields["timestamp"] = json!(Utc::now().to_rfc3339());
+        fields["event"] = json!(event);
+        let mut writer = self.writer.lock().map_err(|_| "JSON log writer poisoned")?;
+        writeln!(writer, "{}", fields)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn opportunity_fields(opportunity: &ArbitrageOpportunity) -> Value {
+        json!({
+            "path": opportunity.path.as_ref().map(|p| p.description()).unwrap_or_else(|| "WMNT -> MOE -> JOE -> WMNT".to_string()),
+            "optimal_input_wmnt": opportunity.optimal_input,
+            "gross_profit_wmnt": opportunity.gross_profit,
+            "net_profit_wmnt": opportunity.net_profit,
+            "gas_cost": opportunity.gross_profit - opportunity.net_profit,
+            "profitable": opportunity.is_profitable(),
+        })
+    }
+}
+
+impl ArbitrageLogger for JsonLogger {
+    fn log_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(), Box<dyn Error>> {
+        self.write_event("opportunity", Self::opportunity_fields(opportunity))
+    }
+
+    fn log_block_opportunity(
+        &self,
+        block_number: u64,
+        opportunity: &ArbitrageOpportunity,
+        analysis_time: Duration,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut fields = Self::opportunity_fields(opportunity);
+        fields["block"] = json!(block_number);
+        fields["analysis_time_ms"] = json!(analysis_time.as_secs_f64() * 1000.0);
+        self.write_event("opportunity", fields)
+    }
+
+    fn log_reserves_change(&self, block_number: u64, timestamp: DateTime<Utc>) {
+        let _ = self.write_event("reserves_change", json!({
+            "block": block_number,
+            "observed_at": timestamp.to_rfc3339(),
+        }));
+    }
+
+    fn log_error(&self, error: &str) {
+        let _ = self.write_event("error", json!({ "message": error }));
+    }
+
+    fn log_info(&self, message: &str) {
+        let _ = self.write_event("info", json!({ "message": message }));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::NamedTempFile;
 
+    /// Writer that can be read back after the logger takes ownership
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::sync::Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_logger_writes_one_object_per_event() {
+        use crate::types::{ArbitragePath, Token};
+        use alloy::primitives::Address;
+
+        let buffer = SharedBuffer::default();
+        let logger = JsonLogger::new(buffer.clone());
+        let wmnt = Token::new(Address::ZERO, "WMNT", 18);
+        let moe = Token::new(Address::with_last_byte(1), "MOE", 18);
+        let opportunity = ArbitrageOpportunity {
+            optimal_input: 100.0,
+            final_output: 102.0,
+            gross_profit: 2.0,
+            net_profit: 1.5,
+            profit_percentage: 1.5,
+            search_method: "test".to_string(),
+            path: Some(ArbitragePath::new(vec![wmnt.clone(), moe, wmnt], vec![Address::ZERO; 2])),
+            robustness: 1.0,
+            unconstrained_input: 100.0,
+            binding_constraint: None,
+            price_impact_percent: 0.0,
+            requires_flash_loan: false,
+            flash_loan_fee: 0.0,
+            min_pool_depth: 0.0,
+            expected_realized_profit: 0.0,
+            hop_outputs: Vec::new(),
+            confidence: 1.0,
+        };
+
+        logger.log_block_opportunity(42, &opportunity, Duration::from_millis(7)).unwrap();
+        logger.log_reserves_change(43, Utc::now());
+        logger.log_error("rpc timeout");
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<Value> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(events.len(), 3);
+
+        let found = &events[0];
+        assert_eq!(found["event"], "opportunity");
+        assert_eq!(found["block"], 42);
+        assert_eq!(found["path"], opportunity.path.as_ref().unwrap().description());
+        assert_eq!(found["net_profit_wmnt"], 1.5);
+        assert_eq!(found["gas_cost"], 0.5);
+        assert_eq!(found["analysis_time_ms"], 7.0);
+        assert!(found["timestamp"].is_string());
+
+        assert_eq!((events[1]["event"].as_str(), events[1]["block"].as_u64()), (Some("reserves_change"), Some(43)));
+        assert_eq!((events[2]["event"].as_str(), events[2]["message"].as_str()), (Some("error"), Some("rpc timeout")));
+    }
+
+    #[test]
+    fn test_console_line_keeps_info_plain() {
+        assert_eq!(console_line(Level::Info, "💎 found"), "💎 found");
+        assert_eq!(console_line(Level::Error, "❌ rpc down"), "❌ rpc down");
+        assert_eq!(console_line(Level::Debug, "🔄 reserves changed"), "[DEBUG] 🔄 reserves changed");
+    }
+
     #[test]
     fn test_init_csv_file() {
         let temp_file = NamedTempFile::new().unwrap();
@@ -232,6 +449,16 @@ mod tests {
             profit_percentage: 3.0,
             search_method: "test".to_string(),
             path: None,
+            robustness: 1.0,
+            unconstrained_input: 100.0,
+            binding_constraint: None,
+            price_impact_percent: 0.0,
+            requires_flash_loan: false,
+            flash_loan_fee: 0.0,
+            min_pool_depth: 0.0,
+            expected_realized_profit: 0.0,
+            hop_outputs: Vec::new(),
+            confidence: 1.0,
         };
 
         let result = logger.log_opportunity(&opportunity);
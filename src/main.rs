@@ -1,180 +1,118 @@
-mod types;
-mod constants;
-mod config;
-mod cache;
-mod math;
-mod blockchain;
-mod arbitrage;
-mod logging;
-mod display;
-mod pools;
-mod graph;
-mod multi_path;
-mod batch_fetcher;
-mod multi_path_main;
-
 use std::error::Error;
-use alloy::providers::ProviderBuilder;
-
+use std::process::ExitCode;
+use std::time::Duration;
+use clap::Parser;
 use tokio::runtime::Runtime;
-use tokio::time::{sleep, Duration, Instant};
-use chrono::Utc;
 use std::env;
 
-use config::Config;
-use cache::ReservesCache;
-use blockchain::{fetch_all_reserves_with_retry, get_current_block};
-use arbitrage::find_optimal_arbitrage;
-use logging::{
-    init_csv_file, write_arbitrage_to_csv, log_profitable_arbitrage, 
-    log_no_profit, log_analysis_failure, log_csv_success, log_csv_failure
-};
-use display::{print_startup_banner, format_pool_reserves, format_block_info};
-use pools::moe::MoeProtocol;
-use multi_path_main::run_multi_path_arbitrage;
+use triangular_arbitrage_demo::blockchain::set_rpc_timeout;
+use triangular_arbitrage_demo::cli::{exit_code, Cli, RunMode};
+use triangular_arbitrage_demo::config::Config;
+use triangular_arbitrage_demo::logging::init_console_logging;
+use triangular_arbitrage_demo::constants::{DEFAULT_RESERVE_DIFF_PATH, DEFAULT_SYNTHETIC_BLOCKS, POOL_CSV_PATH};
+use triangular_arbitrage_demo::multi_path_main::{parse_pool_list, run_list_paths, run_multi_path_arbitrage, PoolSource};
+use triangular_arbitrage_demo::reserve_diff::run_reserve_diff;
+use triangular_arbitrage_demo::replay::run_replay;
+use triangular_arbitrage_demo::synthetic::run_synthetic_market;
+use triangular_arbitrage_demo::triangular_main::run_arbitrage_monitor;
 
 /// Main application entry point
-fn main() -> Result<(), Box<dyn Error>> {
-    // Load configuration from environment variables
-    let config = Config::load().map_err(|e| {
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    let cli = Cli::parse();
+    init_console_logging();
+
+    // Load configuration from environment variables, or the `--config` file
+    let config = match cli.config.as_deref() {
+        Some(path) => Config::from_toml(path),
+        None => Config::load(),
+    };
+    let mut config = config.map_err(|e| {
         eprintln!("Configuration Error: {}", e);
         eprintln!("Please set the RPC_URL environment variable.");
         eprintln!("Example: export RPC_URL=https://rpc.mantle.xyz");
         eprintln!("Or create a .env file with: RPC_URL=your_rpc_endpoint");
         e
     })?;
+    config.strict_csv |= cli.strict_csv;
 
+    set_rpc_timeout(Duration::from_millis(config.rpc_timeout_ms));
     let rt = Runtime::new()?;
 
-    // Check for mode selection via environment variable or command line argument
-    let mode = env::var("ARBITRAGE_MODE")
-        .or_else(|_| env::args().nth(1).ok_or_else(|| ""))
-        .unwrap_or_else(|_| "triangular".to_string());
+    // `--pools addr1,addr2,...` (or a config file pool list) monitors a fixed pool set instead of the CSV
+    let pool_source = match cli.pools.as_deref() {
+        Some(list) => PoolSource::Addresses(parse_pool_list(list)?),
+        None if !config.pools.is_empty() => PoolSource::Addresses(config.pools.clone()),
+        None => PoolSource::Csv(POOL_CSV_PATH.to_string()),
+    };
+    let positional = &cli.args;
+    let run_mode = cli.run_mode();
+
+    // Check for mode selection via --multi-path, environment variable or command line argument
+    let mode = cli.mode(env::var("ARBITRAGE_MODE").ok());
+    let multi_path = matches!(mode.as_str(), "multi" | "multipath" | "multi-path");
+    // A fixed pool set only applies to the multi-path and diff modes
+    if matches!(pool_source, PoolSource::Addresses(_)) && !multi_path && mode != "diff" && !cli.list_paths {
+        return Err(format!(
+            "--pools and the config file pool list only apply to the multi-path and diff modes, not '{}'",
+            mode
+        ).into());
+    }
+    // Recording only applies to multi-path
+    if cli.record.is_some() && !multi_path {
+        return Err(format!("--record only applies to the multi-path mode, not '{}'", mode).into());
+    }
+
+    // --list-paths: show the cycles the pool CSV can form, then exit
+    if cli.list_paths {
+        let PoolSource::Csv(csv_path) = &pool_source else {
+            return Err("--list-paths reads the pool CSV; drop --pools and the config file pool list".into());
+        };
+        run_list_paths(&config, csv_path)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if run_mode == RunMode::Once && matches!(mode.as_str(), "synthetic" | "diff" | "replay") {
+        return Err(format!("--once only applies to the triangular and multi-path monitors, not '{}'", mode).into());
+    }
 
     rt.block_on(async {
-        match mode.to_lowercase().as_str() {
+        match mode.as_str() {
+            "synthetic" => {
+                // synthetic [blocks]: dev-only end-to-end check against injected mispricings
+                if !config.dev_mode {
+                    return Err("Synthetic market mode is a dev tool and requires DEV_MODE=true".into());
+                }
+                let blocks = match positional.get(1) {
+                    Some(arg) => arg.parse().map_err(|e| format!("Invalid block count '{}': {}", arg, e))?,
+                    None => DEFAULT_SYNTHETIC_BLOCKS,
+                };
+                run_synthetic_market(&config, blocks).map(|_| ExitCode::SUCCESS)
+            }
+            "diff" => {
+                // diff <from_block> <to_block> [output.csv|output.json]
+                let block_arg = |i: usize| -> Result<u64, Box<dyn Error>> {
+                    let arg = positional.get(i).ok_or("Usage: diff <from_block> <to_block> [output.csv|output.json]")?;
+                    Ok(arg.parse().map_err(|e| format!("Invalid block number '{}': {}", arg, e))?)
+                };
+                let output = positional.get(3).map(String::as_str).unwrap_or(DEFAULT_RESERVE_DIFF_PATH);
+                run_reserve_diff(config, pool_source, block_arg(1)?, block_arg(2)?, output).await.map(|_| ExitCode::SUCCESS)
+            }
+            "replay" => {
+                // replay <recorded.csv|recorded.jsonl>: dry run over reserves logged by the legacy monitor or `--record`
+                let csv_path = positional.get(1).map(String::as_str).unwrap_or(&config.csv_file_path);
+                run_replay(&config, csv_path).map(|_| ExitCode::SUCCESS)
+            }
             "multi" | "multipath" | "multi-path" => {
                 println!("🚀 Starting Multi-Path Arbitrage Mode");
-                run_multi_path_arbitrage(config).await
+                let found = run_multi_path_arbitrage(config, pool_source, run_mode, cli.record.as_deref()).await?;
+                Ok(ExitCode::from(exit_code(run_mode, found)))
             }
-            "triangular" | "triangle" | "legacy" | _ => {
+            // "triangular" | "triangle" | "legacy" and anything else
+            _ => {
                 println!("🚀 Starting Triangular Arbitrage Mode (Legacy)");
-                run_arbitrage_monitor(config).await
+                let found = run_arbitrage_monitor(config, run_mode).await?;
+                Ok(ExitCode::from(exit_code(run_mode, found)))
             }
         }
     })
 }
-
-/// Main arbitrage monitoring loop
-async fn run_arbitrage_monitor(config: Config) -> Result<(), Box<dyn Error>> {
-    // Set up provider
-    let provider = ProviderBuilder::new().connect_http(config.rpc_url.parse()?);
-
-    // Initialize MOE protocol
-    let moe_protocol = MoeProtocol::new();
-    
-    // Validate triangular arbitrage setup
-    moe_protocol.validate_triangular_setup()
-        .map_err(|e| format!("Triangular arbitrage setup validation failed: {}", e))?;
-
-    // Get pool addresses
-    let (moe_wmnt_addr, joe_moe_addr, joe_wmnt_addr) = moe_protocol.get_main_triangular_pools();
-    let pool_addresses = vec![moe_wmnt_addr, joe_moe_addr, joe_wmnt_addr];
-
-    // Initialize CSV file
-    if let Err(e) = init_csv_file(&config.csv_file_path) {
-        println!("⚠️ Warning: Failed to initialize CSV file: {}", e);
-    } else {
-        println!("📝 CSV logging initialized: {}", config.csv_file_path);
-    }
-
-    // Initialize cache
-    let mut cache = ReservesCache::new();
-    
-    // Print startup information
-    print_startup_banner();
-    config.print_summary();
-    println!();
-
-    // Block-based monitoring loop
-    loop {
-        let start_time = Instant::now();
-        
-        // Get current block number
-        let current_block = match get_current_block(&provider).await {
-            Ok(block) => block,
-            Err(e) => {
-                println!("❌ Error getting block number: {}", e);
-                sleep(Duration::from_secs(config.block_time_seconds)).await;
-                continue;
-            }
-        };
-
-        // Only fetch and process if block has changed
-        if cache.has_changed(current_block) {
-            // Fetch all reserves in parallel
-            match fetch_all_reserves_with_retry(&provider, &pool_addresses, current_block, config.max_retries).await {
-                Ok(reserves_map) => {
-                    // Check if reserves have actually changed
-                    if cache.reserves_changed(&reserves_map) {
-                        let timestamp = Utc::now();
-                        let fetch_duration = start_time.elapsed();
-                        
-                        // Print log with reserves information only when reserves change
-                        println!("🔄 Reserves changed at {}", format_block_info(current_block, timestamp));
-                        println!("{}", format_pool_reserves(moe_wmnt_addr, joe_moe_addr, joe_wmnt_addr, &reserves_map));
-
-                        // Update cache
-                        for (addr, reserves) in &reserves_map {
-                            cache.update(*addr, reserves.clone());
-                        }
-
-                        // Use ternary search to find optimal arbitrage opportunity
-                        let moe_wmnt_reserves = &reserves_map[&moe_wmnt_addr];
-                        let joe_moe_reserves = &reserves_map[&joe_moe_addr];
-                        let joe_wmnt_reserves = &reserves_map[&joe_wmnt_addr];
-
-                        // Find optimal arbitrage using ternary search
-                        match find_optimal_arbitrage(moe_wmnt_reserves, joe_moe_reserves, joe_wmnt_reserves, &config) {
-                            Some(opportunity) => {
-                                if opportunity.is_profitable() {
-                                    log_profitable_arbitrage(&opportunity, fetch_duration, &config);
-
-                                    // Write to CSV
-                                    match write_arbitrage_to_csv(
-                                        timestamp,
-                                        current_block,
-                                        &opportunity,
-                                        moe_wmnt_reserves,
-                                        joe_moe_reserves,
-                                        joe_wmnt_reserves,
-                                        fetch_duration.as_millis() as u64,
-                                        &config,
-                                    ) {
-                                        Ok(_) => log_csv_success(&config.csv_file_path),
-                                        Err(e) => log_csv_failure(e.as_ref()),
-                                    }
-                                } else {
-                                    log_no_profit(opportunity.gross_profit, opportunity.net_profit, fetch_duration);
-                                }
-                            }
-                            None => {
-                                log_analysis_failure(fetch_duration);
-                            }
-                        }
-                        println!(); // Add blank line for readability
-                    } else {
-                        // Update cache block number even if reserves didn't change
-                        cache.update_block_number(current_block);
-                    }
-                }
-                Err(e) => {
-                    println!("❌ Block {}: Failed to fetch reserves: {}", current_block, e);
-                }
-            }
-        }
-
-        // Sleep until next expected block (with a small buffer)
-        sleep(Duration::from_millis((config.block_time_seconds * 1000) - 200)).await;
-    }
-}
\ No newline at end of file
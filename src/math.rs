@@ -1,4 +1,14 @@
-use alloy::primitives::U256;
+use alloy::primitives::{Uint, U256};
+use crate::types::{SwapSample, V3PoolState};
+
+/// Evenly spaced inputs sampled when measuring profit robustness
+const ROBUSTNESS_SAMPLES: usize = 64;
+
+/// Longest cycle solved in closed form; longer paths use ternary search
+const ANALYTIC_MAX_HOPS: usize = 3;
+
+/// Ternary search stops early once its interval is narrower than this fraction of the initial one
+const SEARCH_CONVERGENCE_TOLERANCE: f64 = 1e-12;
 
 /// More accurate swap function using constant product formula (x*y=k)
 pub fn swap(x_reserve: f64, y_reserve: f64, dx: f64, fee: f64) -> f64 {
@@ -12,59 +22,408 @@ pub fn swap(x_reserve: f64, y_reserve: f64, dx: f64, fee: f64) -> f64 {
 /// Calculate arbitrage profit for a given input amount
 pub fn arbitrage_profit(
     dx: f64,
-    pools: &[(f64, f64)], // [(x,y), (x,y), (x,y)] - 3 pools in the arbitrage path
+    pools: &[(f64, f64)], // [(x,y), ...] - pools in path order, any number of hops
     fee: f64,
 ) -> f64 {
-    if pools.len() != 3 {
+    arbitrage_profit_with_fees(dx, pools, &vec![fee; pools.len()])
+}
+
+/// Calculate arbitrage profit with a separate fee for each hop. A fee-on-transfer tax on a
+/// hop's output is passed as a proportionally smaller reserve_out (see `TokenGraph::path_pools`).
+pub fn arbitrage_profit_with_fees(dx: f64, pools: &[(f64, f64)], fees: &[f64]) -> f64 {
+    if pools.is_empty() || fees.len() != pools.len() {
         return -1.0; // Invalid input
     }
-    
-    // pool1: token0 -> token1
-    let dy1 = swap(pools[0].0, pools[0].1, dx, fee);
-    // pool2: token1 -> token2  
-    let dy2 = swap(pools[1].0, pools[1].1, dy1, fee);
-    // pool3: token2 -> token0
-    let dy3 = swap(pools[2].0, pools[2].1, dy2, fee);
 
-    dy3 - dx // profit (can be negative)
+    // Each pool swaps the previous hop's output; the last one returns to the start token
+    let output = pools
+        .iter()
+        .zip(fees)
+        .fold(dx, |amount, (&(x, y), &fee)| swap(x, y, amount, fee));
+
+    output - dx // profit (can be negative)
 }
 
-/// Find optimal input amount using ternary search
+/// Amount held after each hop when routing `dx` through the pools; the last entry is the
+/// final output, so `hop_outputs(..).last() - dx` equals `arbitrage_profit_with_fees`
+pub fn hop_outputs(dx: f64, pools: &[(f64, f64)], fees: &[f64]) -> Vec<f64> {
+    pools
+        .iter()
+        .zip(fees)
+        .scan(dx, |amount, (&(x, y), &fee)| {
+            *amount = swap(x, y, *amount, fee);
+            Some(*amount)
+        })
+        .collect()
+}
+
+/// Find optimal input amount using ternary search.
+/// Returns (best input, best profit, fraction of the search range that is profitable).
 pub fn find_best_input(
-    pools: &[(f64, f64)], // 3 pools
+    pools: &[(f64, f64)], // pools in path order
     fee: f64,
     iterations: usize,
-) -> (f64, f64) {
-    let mut left = 0.0;
-    let mut right = pools[0].0 * 0.999; // Upper limit close to pool's total token0 reserves
-    
-    // Ternary search for maximum profit
-    for _ in 0..iterations {
+) -> (f64, f64, f64) {
+    find_best_input_with_fees(pools, &vec![fee; pools.len()], iterations)
+}
+
+/// Optimal input with a separate fee for each hop: closed form for profitable
+/// 2- and 3-hop cycles, ternary search otherwise
+pub fn find_best_input_with_fees(
+    pools: &[(f64, f64)],
+    fees: &[f64],
+    iterations: usize,
+) -> (f64, f64, f64) {
+    find_best_input_in_range(pools, fees, (0.0, f64::INFINITY), iterations)
+}
+
+/// Optimal input limited to `input_range` (min, max), which is further capped by the
+/// pools' search bound. Profit is concave in the input, so an optimum outside the
+/// range lands on the nearest bound.
+pub fn find_best_input_in_range(
+    pools: &[(f64, f64)],
+    fees: &[f64],
+    input_range: (f64, f64),
+    iterations: usize,
+) -> (f64, f64, f64) {
+    find_best_input_within_impact(pools, fees, input_range, 0.0, iterations)
+}
+
+/// Optimal input within `input_range` whose worst per-hop price impact stays at or under
+/// `max_impact` (fraction, 0 = uncapped). Inputs over the cap score as infeasible in the
+/// search objective, so an optimum past the cap gives way to the best input below it.
+pub fn find_best_input_within_impact(
+    pools: &[(f64, f64)],
+    fees: &[f64],
+    input_range: (f64, f64),
+    max_impact: f64,
+    iterations: usize,
+) -> (f64, f64, f64) {
+    let max_impact = if max_impact > 0.0 { max_impact } else { f64::INFINITY };
+    let upper = search_upper_bound(pools).min(input_range.1);
+    let lower = input_range.0.min(upper);
+    let analytic = if (2..=ANALYTIC_MAX_HOPS).contains(&pools.len()) && fees.len() == pools.len() {
+        analytic_optimal_input(pools, fees)
+            .filter(|&(input, _)| (lower..=upper).contains(&input) && max_price_impact(input, pools, fees) <= max_impact)
+    } else {
+        None
+    };
+
+    let best_input = match analytic {
+        Some((input, _)) => input,
+        None => ternary_search(pools, fees, (lower, upper), iterations, SEARCH_CONVERGENCE_TOLERANCE, max_impact).0,
+    };
+    let best_profit = arbitrage_profit_with_fees(best_input, pools, fees);
+    if best_profit.is_nan() {
+        return (0.0, 0.0, 0.0);
+    }
+    (best_input, best_profit, profitable_fraction(pools, fees))
+}
+
+/// Ternary search running at most `iterations` rounds, fewer once the interval shrinks below
+/// `tolerance` of its initial width. Returns the input and the rounds actually run.
+fn ternary_search(
+    pools: &[(f64, f64)],
+    fees: &[f64],
+    (mut left, mut right): (f64, f64),
+    iterations: usize,
+    tolerance: f64,
+    max_impact: f64,
+) -> (f64, usize) {
+    let min_width = tolerance * (right - left);
+    for round in 0..iterations {
+        if right - left <= min_width {
+            return ((left + right) / 2.0, round);
+        }
         let m1 = left + (right - left) / 3.0;
         let m2 = right - (right - left) / 3.0;
-        let p1 = arbitrage_profit(m1, pools, fee);
-        let p2 = arbitrage_profit(m2, pools, fee);
-        
+        // NaN (degenerate reserves) compares as the worst possible profit
+        let p1 = search_profit(m1, pools, fees, max_impact);
+        let p2 = search_profit(m2, pools, fees, max_impact);
+
         if p1 < p2 {
             left = m1;
         } else {
             right = m2;
         }
     }
-    
-    let best_input = (left + right) / 2.0;
-    let best_profit = arbitrage_profit(best_input, pools, fee);
-    (best_input, best_profit)
+
+    ((left + right) / 2.0, iterations)
+}
+
+/// Search objective: profit, or the worst possible score for inputs over the impact cap.
+/// Profit rises up to the optimum and impact rises with the input, so the feasible
+/// profit curve stays unimodal.
+fn search_profit(dx: f64, pools: &[(f64, f64)], fees: &[f64], max_impact: f64) -> f64 {
+    let profit = arbitrage_profit_with_fees(dx, pools, fees);
+    if profit.is_nan() || (max_impact.is_finite() && max_price_impact(dx, pools, fees) > max_impact) { f64::NEG_INFINITY } else { profit }
+}
+
+/// Upper limit of the input search: the smallest hop reserve converted back into the
+/// input token at spot prices, so a small pool mid-path caps the range
+fn search_upper_bound(pools: &[(f64, f64)]) -> f64 {
+    // Input-token value of one unit of the current hop's input token
+    let mut to_input = 1.0;
+    let mut bound = f64::INFINITY;
+    for &(reserve_in, reserve_out) in pools {
+        bound = bound.min(reserve_in * to_input);
+        if reserve_out > 0.0 {
+            to_input *= reserve_in / reserve_out;
+        }
+    }
+
+    if bound.is_finite() { bound * 0.999 } else { 0.0 }
+}
+
+/// Reproducible random paths of 2..=`max_hops` pools with reserves between 1k and 1M and
+/// output/input ratios in 0.9..1.15, shared by the search benchmarks and accuracy tests
+pub fn seeded_pool_paths(mut seed: u64, count: usize, max_hops: usize) -> Vec<Vec<(f64, f64)>> {
+    // Deterministic LCG so results are reproducible
+    let mut next = || {
+        seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 11) as f64 / (1u64 << 53) as f64
+    };
+    let hop_choices = max_hops.max(2) - 1;
+
+    (0..count)
+        .map(|_| {
+            let hops = 2 + (next() * hop_choices as f64) as usize;
+            (0..hops)
+                .map(|_| {
+                    let reserve = 1_000.0 + next() * 1_000_000.0;
+                    (reserve, reserve * (0.9 + next() * 0.25))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Fraction of evenly sampled inputs across the search range that are profitable.
+/// Near 0 means a knife-edge opportunity where sizing errors turn it into a loss.
+pub fn profitable_fraction(pools: &[(f64, f64)], fees: &[f64]) -> f64 {
+    let upper = search_upper_bound(pools);
+    let profitable = (1..=ROBUSTNESS_SAMPLES)
+        .map(|i| upper * i as f64 / ROBUSTNESS_SAMPLES as f64)
+        .filter(|&dx| arbitrage_profit_with_fees(dx, pools, fees) > 0.0)
+        .count();
+    profitable as f64 / ROBUSTNESS_SAMPLES as f64
+}
+
+/// Find the smallest input whose profit reaches `target_profit` using bisection
+/// on the rising side of the profit curve. Returns `None` if the target is unreachable.
+pub fn find_min_input_for_profit(
+    pools: &[(f64, f64)],
+    fees: &[f64],
+    target_profit: f64,
+    iterations: usize,
+) -> Option<(f64, f64)> {
+    let (best_input, best_profit, _) = find_best_input_with_fees(pools, fees, iterations);
+    if best_profit < target_profit {
+        return None;
+    }
+
+    // Profit is increasing on [0, best_input], so bisect for the crossing point
+    let mut low = 0.0;
+    let mut high = best_input;
+    for _ in 0..iterations {
+        let mid = (low + high) / 2.0;
+        if arbitrage_profit_with_fees(mid, pools, fees) < target_profit {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Some((high, arbitrage_profit_with_fees(high, pools, fees)))
+}
+
+/// Closed-form optimal input and profit for a cycle of constant-product pools.
+/// The hops are folded into one virtual pool, whose optimum is (sqrt(Ea * Eb * g) - Ea) / g.
+/// Returns `None` when the cycle is not profitable.
+pub fn analytic_optimal_input(pools: &[(f64, f64)], fees: &[f64]) -> Option<(f64, f64)> {
+    let (&(first_in, first_out), rest) = pools.split_first()?;
+    let (mut virtual_in, mut virtual_out) = (first_in, first_out);
+    for (&(reserve_in, reserve_out), &fee) in rest.iter().zip(fees.iter().skip(1)) {
+        let g = 1.0 - fee;
+        let denominator = reserve_in + g * virtual_out;
+        virtual_in = virtual_in * reserve_in / denominator;
+        virtual_out = g * virtual_out * reserve_out / denominator;
+    }
+
+    let g = 1.0 - fees.first()?;
+    if virtual_out * g <= virtual_in {
+        return None;
+    }
+    let input = ((virtual_in * virtual_out * g).sqrt() - virtual_in) / g;
+    let profit = virtual_out * g * input / (virtual_in + g * input) - input;
+    Some((input, profit))
+}
+
+/// Closed-form optimal (input, profit) for a 2- or 3-hop cycle with a uniform fee.
+/// `None` for longer paths (left to ternary search) and unprofitable cycles.
+pub fn optimal_input_analytical(pools: &[(f64, f64)], fee: f64) -> Option<(f64, f64)> {
+    if !(2..=ANALYTIC_MAX_HOPS).contains(&pools.len()) {
+        return None;
+    }
+    analytic_optimal_input(pools, &vec![fee; pools.len()])
+}
+
+/// Worst per-hop price impact (fraction of the spot price lost) when routing `dx` through the pools
+pub fn max_price_impact(dx: f64, pools: &[(f64, f64)], fees: &[f64]) -> f64 {
+    let mut amount = dx;
+    let mut worst: f64 = 0.0;
+    for (&(reserve_in, reserve_out), &fee) in pools.iter().zip(fees) {
+        let amount_after_fee = amount * (1.0 - fee);
+        if reserve_in + amount_after_fee > 0.0 {
+            worst = worst.max(amount_after_fee / (reserve_in + amount_after_fee));
+        }
+        amount = swap(reserve_in, reserve_out, amount, fee);
+    }
+    worst
+}
+
+/// Shallowest hop's input-side reserve, valued in the first hop's input token at marginal rates
+pub fn min_path_depth(pools: &[(f64, f64)]) -> f64 {
+    let mut rate = 1.0; // Units of the current hop's input token per start token
+    let mut depth = f64::INFINITY;
+    for &(reserve_in, reserve_out) in pools {
+        if reserve_in <= 0.0 || rate <= 0.0 {
+            return 0.0;
+        }
+        depth = depth.min(reserve_in / rate);
+        rate *= reserve_out / reserve_in;
+    }
+    if depth.is_finite() { depth } else { 0.0 }
+}
+
+/// Net profit when competing bots leave only `capture_ratio` of a positive gross spread;
+/// losses and `costs` (gas, fees) are borne in full
+pub fn realized_profit(gross_profit: f64, costs: f64, capture_ratio: f64) -> f64 {
+    let captured = if gross_profit > 0.0 { gross_profit * capture_ratio } else { gross_profit };
+    captured - costs
+}
+
+/// Chance-like score (0-1) that a relative `edge` survives reserve `drift` of the same units
+/// before execution: 1 without drift, 0.5 when the drift equals the edge, 0 without an edge
+pub fn execution_confidence(edge: f64, drift: f64) -> f64 {
+    if edge <= 0.0 {
+        0.0
+    } else if drift <= 0.0 {
+        1.0
+    } else {
+        edge / (edge + drift)
+    }
+}
+
+/// Largest input whose worst per-hop price impact stays within `max_impact` (bisection)
+pub fn max_input_for_price_impact(
+    pools: &[(f64, f64)],
+    fees: &[f64],
+    max_impact: f64,
+    iterations: usize,
+) -> f64 {
+    let mut low = 0.0;
+    let mut high = search_upper_bound(pools);
+    if max_price_impact(high, pools, fees) <= max_impact {
+        return high;
+    }
+
+    for _ in 0..iterations {
+        let mid = (low + high) / 2.0;
+        if max_price_impact(mid, pools, fees) <= max_impact {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+/// Infer a pool's fee from observed swaps by inverting the constant product formula.
+/// Returns the median estimate, or `None` if no sample is usable.
+pub fn estimate_fee_from_swaps(samples: &[SwapSample]) -> Option<f64> {
+    let mut estimates: Vec<f64> = samples
+        .iter()
+        .filter(|s| s.amount_in > 0.0 && s.amount_out > 0.0 && s.reserve_in > 0.0 && s.reserve_out > s.amount_out)
+        .map(|s| {
+            // amount_out = reserve_out * amount_in * g / (reserve_in + amount_in * g), solved for g = 1 - fee
+            let g = s.amount_out * s.reserve_in / (s.amount_in * (s.reserve_out - s.amount_out));
+            1.0 - g
+        })
+        .filter(|fee| fee.is_finite() && (0.0..0.1).contains(fee))
+        .collect();
+
+    if estimates.is_empty() {
+        return None;
+    }
+    estimates.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    Some(estimates[estimates.len() / 2])
+}
+
+/// sqrt(token1 / token0) in token units from a V3 pool's Q64.96 sqrt price
+pub fn v3_sqrt_price(state: &V3PoolState, decimals0: u8, decimals1: u8) -> f64 {
+    let sqrt_price_raw = u256_to_f64_with_decimals(state.sqrt_price_x96, 0) / 2f64.powi(96);
+    sqrt_price_raw * 10f64.powf((decimals0 as f64 - decimals1 as f64) / 2.0)
+}
+
+/// Virtual (token0, token1) reserves of a V3 pool in token units: L / sqrt(P) and L * sqrt(P).
+/// Within the current tick range the pool trades exactly like x*y=k on these reserves.
+pub fn v3_virtual_reserves(state: &V3PoolState, decimals0: u8, decimals1: u8) -> (f64, f64) {
+    let sqrt_price = v3_sqrt_price(state, decimals0, decimals1);
+    if sqrt_price <= 0.0 || !sqrt_price.is_finite() {
+        return (0.0, 0.0);
+    }
+    let liquidity = state.liquidity as f64 / 10f64.powf((decimals0 as f64 + decimals1 as f64) / 2.0);
+    (liquidity / sqrt_price, liquidity * sqrt_price)
 }
 
-/// Helper function to convert U256 to f64 (in token units, not wei)
+/// Output of a V3 swap (token units) assuming the active liquidity covers the whole trade.
+/// Single-tick approximation: liquidity changes at initialized ticks are ignored, so trades
+/// large enough to cross a tick are overquoted when liquidity thins out and underquoted when it deepens.
+pub fn v3_quote_single_tick(
+    amount_in: f64,
+    state: &V3PoolState,
+    zero_for_one: bool,
+    decimals0: u8,
+    decimals1: u8,
+    fee: f64,
+) -> f64 {
+    let sqrt_price = v3_sqrt_price(state, decimals0, decimals1);
+    let liquidity = state.liquidity as f64 / 10f64.powf((decimals0 as f64 + decimals1 as f64) / 2.0);
+    if amount_in <= 0.0 || liquidity <= 0.0 || sqrt_price <= 0.0 || !sqrt_price.is_finite() {
+        return 0.0;
+    }
+
+    let amount_after_fee = amount_in * (1.0 - fee);
+    if zero_for_one {
+        // Token0 in pushes sqrt(P) down: 1/sqrt(P') = 1/sqrt(P) + dx / L, dy = L * (sqrt(P) - sqrt(P'))
+        let next_sqrt_price = liquidity * sqrt_price / (liquidity + amount_after_fee * sqrt_price);
+        liquidity * (sqrt_price - next_sqrt_price)
+    } else {
+        // Token1 in pushes sqrt(P) up: sqrt(P') = sqrt(P) + dy / L, dx = L * (1/sqrt(P) - 1/sqrt(P'))
+        let next_sqrt_price = sqrt_price + amount_after_fee / liquidity;
+        liquidity * (1.0 / sqrt_price - 1.0 / next_sqrt_price)
+    }
+}
+
+/// Helper function to convert U256 to f64 (in token units, not wei) for an 18-decimal token
 pub fn u256_to_f64(value: U256) -> f64 {
-    value.to_string().parse::<f64>().unwrap_or(0.0) / 1e18
+    u256_to_f64_with_decimals(value, 18)
+}
+
+/// Convert a raw on-chain amount to token units using the token's decimals
+pub fn u256_to_f64_with_decimals(value: U256, decimals: u8) -> f64 {
+    value.to_string().parse::<f64>().unwrap_or(0.0) / 10f64.powi(decimals as i32)
 }
 
-/// Helper function to convert f64 to U256 (from token units to wei)
+/// Helper function to convert f64 to U256 (from token units to wei) for an 18-decimal token
 pub fn f64_to_u256(value: f64) -> U256 {
-    U256::from((value * 1e18) as u128)
+    f64_to_u256_with_decimals(value, 18)
+}
+
+/// Convert token units to a raw on-chain amount using the token's decimals
+pub fn f64_to_u256_with_decimals(value: f64, decimals: u8) -> U256 {
+    U256::from((value * 10f64.powi(decimals as i32)).round() as u128)
 }
 
 /// Legacy function for compatibility (kept for potential future use)
@@ -73,16 +432,43 @@ pub fn get_amount_out(amount_in: U256, reserve_in: U256, reserve_out: U256) -> U
     if amount_in == U256::ZERO || reserve_in == U256::ZERO || reserve_out == U256::ZERO {
         return U256::ZERO;
     }
-    let amount_in_with_fee = amount_in * U256::from(997u64);
-    let numerator = amount_in_with_fee * reserve_out;
-    let denominator = reserve_in * U256::from(1000u64) + amount_in_with_fee;
-    numerator / denominator
+    // amount_in * 997 * reserve_out needs up to 522 bits; the quotient is below reserve_out
+    type U768 = Uint<768, 12>;
+    let amount_in_with_fee = U768::from(amount_in) * U768::from(997u64);
+    let numerator = amount_in_with_fee * U768::from(reserve_out);
+    let denominator = U768::from(reserve_in) * U768::from(1000u64) + amount_in_with_fee;
+    U256::from(numerator / denominator)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_v3_single_tick_quote_matches_virtual_reserves() {
+        // Price 4 token1 per token0 with 6-decimal token1: sqrt(P_raw) = 2 * 10^-6
+        let sqrt_price_raw = 2e-6;
+        let state = V3PoolState {
+            sqrt_price_x96: U256::from((sqrt_price_raw * 2f64.powi(96)) as u128),
+            liquidity: 1_000_000_000_000_000, // 1e15 raw = 1e3 in token units for 18/6 decimals
+            tick: 0,
+        };
+
+        assert!((v3_sqrt_price(&state, 18, 6) - 2.0).abs() < 1e-9);
+        let (virtual0, virtual1) = v3_virtual_reserves(&state, 18, 6);
+        assert!((virtual0 - 500.0).abs() < 1e-6);
+        assert!((virtual1 - 2000.0).abs() < 1e-6);
+
+        // Inside one tick range V3 trades like x*y=k on the virtual reserves, both ways
+        for amount in [0.1, 10.0, 100.0] {
+            let out = v3_quote_single_tick(amount, &state, true, 18, 6, 0.003);
+            assert!((out - swap(virtual0, virtual1, amount, 0.003)).abs() / out < 1e-9);
+            let back = v3_quote_single_tick(amount, &state, false, 18, 6, 0.003);
+            assert!((back - swap(virtual1, virtual0, amount, 0.003)).abs() / back < 1e-9);
+        }
+        assert_eq!(v3_quote_single_tick(10.0, &V3PoolState { liquidity: 0, ..state }, true, 18, 6, 0.003), 0.0);
+    }
+
     #[test]
     fn test_swap_basic() {
         let result = swap(1000.0, 1000.0, 100.0, 0.003);
@@ -97,14 +483,244 @@ mod tests {
         assert!(profit < 0.0); // Should be negative due to fees
     }
 
+    #[test]
+    fn test_arbitrage_profit_any_path_length() {
+        let fee = 0.003;
+        let balanced = |hops: usize| vec![(1_000_000.0, 1_000_000.0); hops];
+        // Last pool pays out 5% more than the balanced price
+        let skewed = |hops: usize| {
+            let mut pools = balanced(hops);
+            pools[hops - 1].1 *= 1.05;
+            pools
+        };
+
+        for hops in 2..=4 {
+            assert!(arbitrage_profit(1_000.0, &balanced(hops), fee) < 0.0, "{} hops", hops);
+            assert!(arbitrage_profit(1_000.0, &skewed(hops), fee) > 0.0, "{} hops", hops);
+
+            let (input, profit, _) = find_best_input(&skewed(hops), fee, 100);
+            assert!(input > 0.0 && profit > 0.0, "{} hops", hops);
+            assert!(profit >= arbitrage_profit(1_000.0, &skewed(hops), fee));
+        }
+
+        // The 3-pool result is the same chain of swaps as before
+        let pools = [(1_000.0, 2_000.0), (2_000.0, 500.0), (500.0, 1_100.0)];
+        let expected = swap(500.0, 1_100.0, swap(2_000.0, 500.0, swap(1_000.0, 2_000.0, 10.0, fee), fee), fee) - 10.0;
+        assert_eq!(arbitrage_profit(10.0, &pools, fee), expected);
+
+        assert_eq!(arbitrage_profit_with_fees(10.0, &pools, &[fee; 2]), -1.0);
+        assert_eq!(arbitrage_profit(10.0, &[], fee), -1.0);
+    }
+
+    #[test]
+    fn test_search_bound_follows_smallest_pool() {
+        // 4 hops (ternary path) through a tiny mispriced middle pool
+        let pools = [(1e12, 1e12), (0.01, 0.011), (1e12, 1e12), (1e12, 1e12)];
+        let fees = [0.003; 4];
+        assert!(search_upper_bound(&pools) < 0.01);
+
+        // Few iterations used to leave the search millions of units wide
+        let (best_input, best_profit, _) = find_best_input_with_fees(&pools, &fees, 30);
+        let (expected_input, _) = analytic_optimal_input(&pools, &fees).unwrap();
+        assert!(best_input < 0.01);
+        assert!(best_profit > 0.0);
+        assert!((best_input - expected_input).abs() / expected_input < 1e-3);
+
+        // Degenerate reserves never produce NaN results
+        let (input, profit, _) = find_best_input_with_fees(&[(0.0, 0.0); 4], &fees, 30);
+        assert!(!input.is_nan() && !profit.is_nan());
+    }
+
     #[test]
     fn test_find_best_input() {
         let pools = vec![(1000.0, 1000.0), (1000.0, 1000.0), (1000.0, 1000.0)];
-        let (best_input, best_profit) = find_best_input(&pools, 0.003, 100);
+        let (best_input, best_profit, _) = find_best_input(&pools, 0.003, 100);
         assert!(best_input >= 0.0);
         assert!(best_profit <= 0.0); // Should be negative or zero for equal pools with fees
     }
 
+    #[test]
+    fn test_analytic_optimum_matches_ternary_search() {
+        let pools = [(1000.0, 900.0), (1000.0, 1100.0), (1000.0, 1200.0)];
+        let fees = [0.003, 0.0025, 0.003];
+        let (input, profit) = analytic_optimal_input(&pools, &fees).unwrap();
+        let best_input = ternary_search(&pools, &fees, (0.0, search_upper_bound(&pools)), 200, SEARCH_CONVERGENCE_TOLERANCE, f64::INFINITY).0;
+        let best_profit = arbitrage_profit_with_fees(best_input, &pools, &fees);
+
+        assert!((input - best_input).abs() / input < 1e-6);
+        assert!((profit - best_profit).abs() / profit < 1e-9);
+        assert!((arbitrage_profit_with_fees(input, &pools, &fees) - profit).abs() < 1e-9);
+
+        // Balanced pools have no arbitrage
+        assert!(analytic_optimal_input(&[(1000.0, 1000.0); 3], &fees).is_none());
+    }
+
+    #[test]
+    fn test_optimal_input_analytical_on_random_reserves() {
+        let fee = 0.003;
+
+        let mut checked = 0;
+        for pools in seeded_pool_paths(0x2545_f491_4f6c_dd1d, 200, 3) {
+            let fees = vec![fee; pools.len()];
+
+            let Some((input, profit)) = optimal_input_analytical(&pools, fee) else {
+                assert!(arbitrage_profit(search_upper_bound(&pools) * 1e-6, &pools, fee) <= 0.0);
+                continue;
+            };
+            let ternary_input = ternary_search(&pools, &fees, (0.0, search_upper_bound(&pools)), 200, SEARCH_CONVERGENCE_TOLERANCE, f64::INFINITY).0;
+            let ternary_profit = arbitrage_profit_with_fees(ternary_input, &pools, &fees);
+            assert!((input - ternary_input).abs() / input < 1e-6, "{:?}", pools);
+            assert!((profit - ternary_profit).abs() <= 1e-6 * profit.max(1.0), "{:?}", pools);
+            assert_eq!(find_best_input(&pools, fee, 100).0, input);
+            checked += 1;
+        }
+        assert!(checked > 20);
+
+        // Longer paths are left to ternary search
+        assert!(optimal_input_analytical(&[(1_000.0, 1_100.0); 4], fee).is_none());
+        assert!(optimal_input_analytical(&[(1_000.0, 1_100.0)], fee).is_none());
+    }
+
+    #[test]
+    fn test_search_matches_grid_maximum() {
+        // Best profit over an evenly spaced grid of the whole search range
+        fn grid_maximum(pools: &[(f64, f64)], fees: &[f64], points: usize) -> f64 {
+            let upper = search_upper_bound(pools);
+            (0..=points)
+                .map(|i| search_profit(upper * i as f64 / points as f64, pools, fees, f64::INFINITY))
+                .fold(0.0, f64::max)
+        }
+
+        let paths = seeded_pool_paths(0x9e37_79b9_7f4a_7c15, 100, 5);
+        assert_eq!(paths, seeded_pool_paths(0x9e37_79b9_7f4a_7c15, 100, 5));
+        assert!(paths.iter().all(|p| (2..=5).contains(&p.len())));
+        assert!(paths.iter().any(|p| p.len() >= 4));
+
+        for pools in &paths {
+            let fees = vec![0.003; pools.len()];
+            let (_, profit, _) = find_best_input_with_fees(pools, &fees, 100);
+            let grid = grid_maximum(pools, &fees, 20_000);
+            assert!(profit.max(0.0) >= grid - 1e-6 * grid.max(1.0), "{:?}: search {} < grid {}", pools, profit, grid);
+        }
+    }
+
+    #[test]
+    fn test_ternary_search_converges_early() {
+        for pools in seeded_pool_paths(0x5851_f42d_4c95_7f2d, 50, 5) {
+            let fees = vec![0.003; pools.len()];
+            let range = (0.0, search_upper_bound(&pools));
+            let (full, full_rounds) = ternary_search(&pools, &fees, range, 200, 0.0, f64::INFINITY);
+            let (early, early_rounds) = ternary_search(&pools, &fees, range, 200, SEARCH_CONVERGENCE_TOLERANCE, f64::INFINITY);
+
+            assert_eq!(full_rounds, 200);
+            assert!(early_rounds < 100, "{} rounds for {:?}", early_rounds, pools);
+            assert!((early - full).abs() <= 1e-9 * range.1, "{:?}", pools);
+            let (full_profit, early_profit) =
+                (arbitrage_profit_with_fees(full, &pools, &fees), arbitrage_profit_with_fees(early, &pools, &fees));
+            // Profit moves less than one unit per unit of input near the optimum
+            assert!((early_profit - full_profit).abs() <= 1e-9 * range.1, "{:?}", pools);
+        }
+    }
+
+    #[test]
+    fn test_search_respects_price_impact_cap() {
+        let pools = [(1000.0, 900.0), (1000.0, 1100.0), (1000.0, 1200.0)];
+        let fees = [0.003; 3];
+        let (unconstrained, unconstrained_profit, _) = find_best_input_in_range(&pools, &fees, (0.0, f64::INFINITY), 100);
+        let cap = 0.02;
+        assert!(max_price_impact(unconstrained, &pools, &fees) > cap);
+
+        // The optimum breaks the cap, so the search settles on the largest input under it
+        let (capped, capped_profit, _) = find_best_input_within_impact(&pools, &fees, (0.0, f64::INFINITY), cap, 100);
+        assert!(capped < unconstrained);
+        assert!(max_price_impact(capped, &pools, &fees) <= cap);
+        assert!(capped_profit > 0.0 && capped_profit < unconstrained_profit);
+        let limit = max_input_for_price_impact(&pools, &fees, cap, 100);
+        assert!((capped - limit).abs() < 1e-6 * limit, "{} vs {}", capped, limit);
+
+        // A loose cap keeps the unconstrained optimum; 0 disables the cap
+        assert_eq!(find_best_input_within_impact(&pools, &fees, (0.0, f64::INFINITY), 0.5, 100).0, unconstrained);
+        assert_eq!(find_best_input_within_impact(&pools, &fees, (0.0, f64::INFINITY), 0.0, 100).0, unconstrained);
+    }
+
+    #[test]
+    fn test_execution_confidence() {
+        assert_eq!(execution_confidence(0.01, 0.0), 1.0);
+        assert_eq!(execution_confidence(0.01, 0.01), 0.5);
+        assert_eq!(execution_confidence(0.0, 0.01), 0.0);
+        assert!(execution_confidence(0.01, 0.05) < execution_confidence(0.01, 0.001));
+    }
+
+    #[test]
+    fn test_realized_profit() {
+        assert_eq!(realized_profit(10.0, 2.0, 1.0), 8.0);
+        assert_eq!(realized_profit(10.0, 2.0, 0.3), 1.0);
+        assert_eq!(realized_profit(10.0, 2.0, 0.0), -2.0);
+        // Competitors never take a share of a loss
+        assert_eq!(realized_profit(-1.0, 2.0, 0.5), -3.0);
+    }
+
+    #[test]
+    fn test_find_min_input_for_profit() {
+        let pools = vec![(1000.0, 900.0), (1000.0, 1100.0), (1000.0, 1200.0)];
+        let (best_input, best_profit, _) = find_best_input(&pools, 0.003, 100);
+        let target = best_profit / 2.0;
+
+        let (min_input, profit) = find_min_input_for_profit(&pools, &[0.003; 3], target, 100).unwrap();
+        assert!((profit - target).abs() < 1e-9);
+        assert!(min_input < best_input);
+
+        assert!(find_min_input_for_profit(&pools, &[0.003; 3], best_profit * 2.0, 100).is_none());
+    }
+
+    #[test]
+    fn test_robustness_ratio() {
+        // Large mispricing: profitable over a wide band of input sizes
+        let robust = vec![(1000.0, 1000.0), (1000.0, 1000.0), (1000.0, 3000.0)];
+        // Barely above break-even after fees: only tiny inputs profit
+        let knife_edge = vec![(1000.0, 1000.0), (1000.0, 1000.0), (1000.0, 1012.0)];
+
+        let (_, robust_profit, robust_ratio) = find_best_input(&robust, 0.003, 100);
+        let (_, knife_profit, knife_ratio) = find_best_input(&knife_edge, 0.003, 100);
+        assert!(robust_profit > 0.0 && knife_profit > 0.0);
+        assert!(robust_ratio > 0.2);
+        assert!(knife_ratio < 0.05);
+
+        let (_, _, flat_ratio) = find_best_input(&[(1000.0, 1000.0); 3], 0.003, 100);
+        assert_eq!(flat_ratio, 0.0);
+    }
+
+    #[test]
+    fn test_estimate_fee_from_swaps() {
+        let fee = 0.0025;
+        let samples: Vec<SwapSample> = [(1000.0, 2000.0, 5.0), (5000.0, 4000.0, 120.0), (800.0, 900.0, 0.5)]
+            .iter()
+            .map(|&(reserve_in, reserve_out, amount_in)| SwapSample {
+                reserve_in,
+                reserve_out,
+                amount_in,
+                amount_out: swap(reserve_in, reserve_out, amount_in, fee),
+            })
+            .collect();
+
+        let estimate = estimate_fee_from_swaps(&samples).unwrap();
+        assert!((estimate - fee).abs() < 1e-9);
+        assert!(estimate_fee_from_swaps(&[]).is_none());
+    }
+
+    #[test]
+    fn test_get_amount_out_near_max_reserves() {
+        // 1000 in, 1000/1000 reserves: 997000 / 1997 = 499
+        assert_eq!(get_amount_out(U256::from(1000u64), U256::from(1000u64), U256::from(1000u64)), U256::from(499u64));
+
+        // amount_in * 997 * reserve_out is far beyond U256 here
+        let reserve = U256::MAX / U256::from(2u64);
+        let amount_in = U256::MAX / U256::from(4u64);
+        let expected: U256 = "19260045540474515655205250592869843865483846298238845903793662204853084793295".parse().unwrap();
+        assert_eq!(get_amount_out(amount_in, reserve, reserve), expected);
+        assert!(get_amount_out(U256::MAX, U256::MAX, U256::MAX) < U256::MAX);
+    }
+
     #[test] 
     fn test_u256_conversion() {
         let value = U256::from(1000000000000000000u128); // 1 token in wei
@@ -114,4 +730,23 @@ mod tests {
         let back = f64_to_u256(converted);
         assert_eq!(back, value);
     }
+
+    #[test]
+    fn test_u256_conversion_with_decimals() {
+        // 1,234.56 USDC, 0.5 WBTC and 42.25 WMNT in raw on-chain units
+        let cases = [
+            (U256::from(1_234_560_000u64), 6u8, 1234.56),
+            (U256::from(50_000_000u64), 8, 0.5),
+            (U256::from(42_250_000_000_000_000_000u128), 18, 42.25),
+        ];
+        for (raw, decimals, units) in cases {
+            let converted = u256_to_f64_with_decimals(raw, decimals);
+            assert!((converted - units).abs() < 1e-9, "{} decimals", decimals);
+            assert_eq!(f64_to_u256_with_decimals(converted, decimals), raw);
+        }
+
+        // The 18-decimal helpers are unchanged
+        assert_eq!(u256_to_f64(cases[2].0), u256_to_f64_with_decimals(cases[2].0, 18));
+        assert_ne!(u256_to_f64(cases[0].0), u256_to_f64_with_decimals(cases[0].0, 6));
+    }
 }
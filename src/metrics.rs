@@ -0,0 +1,215 @@
+use std::error::Error;
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// Upper bounds (seconds) of the reserve fetch duration histogram buckets
+pub const FETCH_DURATION_BUCKETS_SECS: [f64; 8] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Monitor counters and gauges, safe to update and read from any task
+#[derive(Debug, Default)]
+pub struct Metrics {
+    blocks_processed: AtomicU64,
+    opportunities_found: AtomicU64,
+    profitable_opportunities: AtomicU64,
+    errors: AtomicU64,
+    last_block: AtomicU64,
+    last_fetch_ms: AtomicU64,
+    last_analysis_ms: AtomicU64,
+    total_fetch_ms: AtomicU64,
+    total_analysis_ms: AtomicU64,
+    total_fetch_us: AtomicU64,
+    /// Fetches per bucket (not cumulative); the last slot counts fetches above every bound
+    fetch_duration_buckets: [AtomicU64; FETCH_DURATION_BUCKETS_SECS.len() + 1],
+}
+
+/// Cumulative reserve fetch duration histogram, as Prometheus exposes it
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchHistogram {
+    /// (upper bound in seconds, fetches at or below it)
+    pub buckets: Vec<(f64, u64)>,
+    pub count: u64,
+    pub sum_secs: f64,
+}
+
+/// Point-in-time copy of the metrics, as written to the snapshot file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub timestamp: String,
+    pub blocks_processed: u64,
+    pub opportunities_found: u64,
+    pub profitable_opportunities: u64,
+    pub errors: u64,
+    pub last_block: u64,
+    pub last_fetch_ms: u64,
+    pub last_analysis_ms: u64,
+    pub avg_fetch_ms: f64,
+    pub avg_analysis_ms: f64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one analyzed block
+    pub fn record_block(&self, block_number: u64, fetch: Duration, analysis: Duration, opportunities: usize, profitable: usize) {
+        let fetch_ms = fetch.as_millis() as u64;
+        let analysis_ms = analysis.as_millis() as u64;
+
+        self.blocks_processed.fetch_add(1, Ordering::Relaxed);
+        self.opportunities_found.fetch_add(opportunities as u64, Ordering::Relaxed);
+        self.profitable_opportunities.fetch_add(profitable as u64, Ordering::Relaxed);
+        self.last_block.store(block_number, Ordering::Relaxed);
+        self.last_fetch_ms.store(fetch_ms, Ordering::Relaxed);
+        self.last_analysis_ms.store(analysis_ms, Ordering::Relaxed);
+        self.total_fetch_ms.fetch_add(fetch_ms, Ordering::Relaxed);
+        self.total_analysis_ms.fetch_add(analysis_ms, Ordering::Relaxed);
+        self.total_fetch_us.fetch_add(fetch.as_micros() as u64, Ordering::Relaxed);
+
+        let fetch_secs = fetch.as_secs_f64();
+        let bucket = FETCH_DURATION_BUCKETS_SECS
+            .iter()
+            .position(|&bound| fetch_secs <= bound)
+            .unwrap_or(FETCH_DURATION_BUCKETS_SECS.len());
+        self.fetch_duration_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record the latest block seen, even when it is not analyzed
+    pub fn set_current_block(&self, block_number: u64) {
+        self.last_block.store(block_number, Ordering::Relaxed);
+    }
+
+    /// Record a failed RPC call or analysis pass
+    pub fn record_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Cumulative fetch duration buckets, count and sum
+    pub fn fetch_histogram(&self) -> FetchHistogram {
+        let mut cumulative = 0;
+        let buckets = FETCH_DURATION_BUCKETS_SECS
+            .iter()
+            .zip(&self.fetch_duration_buckets)
+            .map(|(&bound, count)| {
+                cumulative += count.load(Ordering::Relaxed);
+                (bound, cumulative)
+            })
+            .collect();
+
+        FetchHistogram {
+            buckets,
+            count: self.fetch_duration_buckets.iter().map(|c| c.load(Ordering::Relaxed)).sum(),
+            sum_secs: self.total_fetch_us.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+        }
+    }
+
+    /// Copy the current values
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let blocks = self.blocks_processed.load(Ordering::Relaxed);
+        let average = |total: &AtomicU64| {
+            if blocks == 0 { 0.0 } else { total.load(Ordering::Relaxed) as f64 / blocks as f64 }
+        };
+
+        MetricsSnapshot {
+            timestamp: Utc::now().to_rfc3339(),
+            blocks_processed: blocks,
+            opportunities_found: self.opportunities_found.load(Ordering::Relaxed),
+            profitable_opportunities: self.profitable_opportunities.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            last_block: self.last_block.load(Ordering::Relaxed),
+            last_fetch_ms: self.last_fetch_ms.load(Ordering::Relaxed),
+            last_analysis_ms: self.last_analysis_ms.load(Ordering::Relaxed),
+            avg_fetch_ms: average(&self.total_fetch_ms),
+            avg_analysis_ms: average(&self.total_analysis_ms),
+        }
+    }
+}
+
+/// Write a snapshot as JSON via a temp file and rename, so readers never see a partial file
+pub fn write_snapshot(path: &str, snapshot: &MetricsSnapshot) -> Result<(), Box<dyn Error>> {
+    let temp_path = format!("{}.tmp", path);
+    fs::write(&temp_path, serde_json::to_vec_pretty(snapshot)?)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// Serve the metrics for Prometheus scraping on `address` (requires the `prometheus` feature)
+pub async fn start_prometheus_exporter(metrics: Arc<Metrics>, address: &str) -> Result<(), Box<dyn Error>> {
+    #[cfg(feature = "prometheus")]
+    {
+        let local_addr = crate::metrics_server::serve(metrics, address).await?;
+        println!("📈 Prometheus metrics on http://{}/metrics", local_addr);
+        Ok(())
+    }
+    #[cfg(not(feature = "prometheus"))]
+    {
+        drop(metrics);
+        Err(format!("METRICS_ADDRESS={} requires building with --features prometheus", address).into())
+    }
+}
+
+/// Dump the metrics to `path` every `interval` in a background task
+pub fn spawn_snapshot_writer(metrics: Arc<Metrics>, path: String, interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = write_snapshot(&path, &metrics.snapshot()) {
+                eprintln!("⚠️ Failed to write metrics snapshot {}: {}", path, e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_snapshot_file_contains_metrics() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("metrics.json");
+        let path = path.to_str().unwrap();
+
+        let metrics = Arc::new(Metrics::new());
+        let workers: Vec<_> = (0..4)
+            .map(|i| {
+                let metrics = Arc::clone(&metrics);
+                std::thread::spawn(move || {
+                    metrics.record_block(100 + i, Duration::from_millis(20), Duration::from_millis(4), 3, 1);
+                })
+            })
+            .collect();
+        workers.into_iter().for_each(|w| w.join().unwrap());
+        metrics.record_error();
+
+        write_snapshot(path, &metrics.snapshot()).unwrap();
+        assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        for field in ["timestamp", "blocks_processed", "opportunities_found", "profitable_opportunities",
+                      "errors", "last_block", "last_fetch_ms", "last_analysis_ms", "avg_fetch_ms", "avg_analysis_ms"] {
+            assert!(json.get(field).is_some(), "missing {}", field);
+        }
+
+        let snapshot: MetricsSnapshot = serde_json::from_value(json).unwrap();
+        assert_eq!(snapshot.blocks_processed, 4);
+        assert_eq!(snapshot.opportunities_found, 12);
+        assert_eq!(snapshot.profitable_opportunities, 4);
+        assert_eq!(snapshot.errors, 1);
+        assert!((100..104).contains(&snapshot.last_block));
+        assert_eq!(snapshot.avg_fetch_ms, 20.0);
+        assert_eq!(snapshot.avg_analysis_ms, 4.0);
+
+        // A later snapshot replaces the earlier one
+        metrics.record_block(200, Duration::from_millis(10), Duration::from_millis(2), 0, 0);
+        write_snapshot(path, &metrics.snapshot()).unwrap();
+        let snapshot: MetricsSnapshot = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!((snapshot.blocks_processed, snapshot.last_block), (5, 200));
+    }
+}
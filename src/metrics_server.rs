@@ -0,0 +1,130 @@
+use std::error::Error;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use log::warn;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use crate::metrics::Metrics;
+
+/// Largest request head read before answering
+const MAX_REQUEST_BYTES: usize = 8192;
+
+/// Render the metrics in the Prometheus text exposition format
+pub fn render(metrics: &Metrics) -> String {
+    let snapshot = metrics.snapshot();
+    let histogram = metrics.fetch_histogram();
+    let mut out = String::new();
+
+    out.push_str("# HELP reserves_fetch_duration_seconds Time spent fetching pool reserves for a block\n");
+    out.push_str("# TYPE reserves_fetch_duration_seconds histogram\n");
+    for (bound, count) in &histogram.buckets {
+        let _ = writeln!(out, "reserves_fetch_duration_seconds_bucket{{le=\"{}\"}} {}", bound, count);
+    }
+    let _ = writeln!(out, "reserves_fetch_duration_seconds_bucket{{le=\"+Inf\"}} {}", histogram.count);
+    let _ = writeln!(out, "reserves_fetch_duration_seconds_sum {}", histogram.sum_secs);
+    let _ = writeln!(out, "reserves_fetch_duration_seconds_count {}", histogram.count);
+
+    let series = [
+        ("opportunities_found_total", "counter", "Arbitrage routes evaluated", snapshot.opportunities_found),
+        ("profitable_opportunities_total", "counter", "Routes above the minimum profit", snapshot.profitable_opportunities),
+        ("rpc_errors_total", "counter", "Failed block number or reserve fetches", snapshot.errors),
+        ("current_block", "gauge", "Latest block seen by the monitor", snapshot.last_block),
+    ];
+    for (name, kind, help, value) in series {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+    out
+}
+
+/// Serve `GET /metrics` on `address` in a background task; returns the bound address
+pub async fn serve(metrics: Arc<Metrics>, address: &str) -> Result<SocketAddr, Box<dyn Error>> {
+    let listener = TcpListener::bind(address).await?;
+    let local_addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((socket, _)) => {
+                    tokio::spawn(answer_scrape(socket, Arc::clone(&metrics)));
+                }
+                Err(e) => warn!("⚠️ Metrics accept failed: {}", e),
+            }
+        }
+    });
+    Ok(local_addr)
+}
+
+/// Answer one HTTP request and close the connection
+async fn answer_scrape(mut socket: TcpStream, metrics: Arc<Metrics>) {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        match socket.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => request.extend_from_slice(&buffer[..n]),
+        }
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+    let (status, content_type, body) = if path == "/metrics" || path.starts_with("/metrics?") {
+        ("200 OK", "text/plain; version=0.0.4", render(&metrics))
+    } else {
+        ("404 Not Found", "text/plain", "not found\n".to_string())
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, content_type, body.len(), body
+    );
+    if let Err(e) = socket.write_all(response.as_bytes()).await {
+        warn!("⚠️ Failed to answer metrics scrape: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{timeout, Duration};
+
+    async fn scrape(addr: SocketAddr, path: &str) -> String {
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).await.unwrap();
+        let mut response = String::new();
+        timeout(Duration::from_secs(5), client.read_to_string(&mut response)).await.unwrap().unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_scrape_exposes_monitor_metrics() {
+        let metrics = Arc::new(Metrics::new());
+        metrics.record_block(100, Duration::from_millis(80), Duration::from_millis(4), 6, 2);
+        metrics.record_block(101, Duration::from_millis(700), Duration::from_millis(4), 6, 0);
+        metrics.record_error();
+        metrics.set_current_block(102);
+
+        let addr = serve(Arc::clone(&metrics), "127.0.0.1:0").await.unwrap();
+        let response = scrape(addr, "/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        let lines: Vec<&str> = response.lines().collect();
+        for expected in [
+            "reserves_fetch_duration_seconds_bucket{le=\"0.05\"} 0",
+            "reserves_fetch_duration_seconds_bucket{le=\"0.1\"} 1",
+            "reserves_fetch_duration_seconds_bucket{le=\"1\"} 2",
+            "reserves_fetch_duration_seconds_bucket{le=\"+Inf\"} 2",
+            "reserves_fetch_duration_seconds_sum 0.78",
+            "reserves_fetch_duration_seconds_count 2",
+            "opportunities_found_total 12",
+            "profitable_opportunities_total 2",
+            "rpc_errors_total 1",
+            "current_block 102",
+        ] {
+            assert!(lines.contains(&expected), "missing '{}' in:\n{}", expected, response);
+        }
+
+        assert!(scrape(addr, "/").await.starts_with("HTTP/1.1 404"));
+    }
+}
@@ -1,75 +1,245 @@
-use std::collections::HashMap;
-use alloy::primitives::{Address, U256};
+use std::collections::{HashMap, HashSet};
+use alloy::primitives::Address;
+use log::warn;
 use rayon::prelude::*;
-use hex;
+use std::time::{Duration, Instant};
 use crate::types::{
-    Token, PoolReserves, ArbitrageOpportunity, ArbitragePath, MultiPathOpportunity
+    Token, PoolReserves, ArbitrageOpportunity, ArbitragePath, MultiPathOpportunity, AnalysisTimings, FlashLoanTerms, GasOverhead, InputConstraint, InputLimits
 };
-use crate::graph::TokenGraph;
-use crate::math::find_best_input;
-use crate::config::Config;
+use crate::graph::{CyclePools, EdgeExclusions, TokenGraph};
+use crate::math::{arbitrage_profit_with_fees, execution_confidence, f64_to_u256_with_decimals, find_best_input_in_range, find_best_input_within_impact, find_min_input_for_profit, hop_outputs, max_price_impact, min_path_depth, profitable_fraction, realized_profit};
+use crate::config::{Config, ProfitObjective};
+use crate::constants::WMNT_ADDRESS;
+use crate::error::ArbError;
+use crate::cache::{GasPriceHistory, ReserveHistory};
+use crate::pools::{owning_protocol, registered_protocols, DexProtocol};
 
 /// Multi-path arbitrage analyzer
 pub struct MultiPathAnalyzer {
     graph: TokenGraph,
-    wmnt_token: Token,
+    /// Token cycles start and end in; profits, trade sizes and gas costs are in its units
+    base_token: Token,
     dex_fee: f64,
+    protocols: Vec<Box<dyn DexProtocol>>,
     gas_price_gwei: f64,
+    profit_objective: ProfitObjective,
+    target_profit: f64,
+    gas_overhead: GasOverhead,
+    input_limits: InputLimits,
+    flash_loan: FlashLoanTerms,
+    capture_ratio: f64,
+    /// Blocks until execution, and the reserve history whose drift discounts profits over them
+    execution_delay_blocks: u64,
+    reserve_history: ReserveHistory,
+    max_cycles_per_block: usize,
+    max_hops: usize,
+    /// Search iterations added per hop on top of the caller's base count
+    iterations_per_hop: usize,
+    spfa_all_sources: bool,
+    rotation_offset: usize,
+    reserve_scale: f64,
+    verify_cycle_detection: bool,
+    /// Fail the CSV load instead of skipping unusable rows
+    strict_csv: bool,
+    /// Cycles found at a graph version, reused until an edge changes
+    cycle_cache: Option<(u64, Vec<ArbitragePath>)>,
+}
+
+/// Rows of a pool CSV that made it into the graph, and the ones skipped, by reason
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CsvLoadReport {
+    pub loaded: usize,
+    /// Fewer than the five required columns
+    pub missing_columns: usize,
+    /// Pair address that does not parse
+    pub bad_address: usize,
+    /// Pair name that is not two registered symbols
+    pub unknown_symbol: usize,
+    /// Pair name whose two symbols resolve to the same token
+    pub same_token: usize,
+    /// Reserve that is not a non-negative number
+    pub bad_reserves: usize,
+    /// Pool address already loaded from an earlier row
+    pub duplicate: usize,
+}
+
+impl CsvLoadReport {
+    /// Rows left out of the graph
+    pub fn skipped(&self) -> usize {
+        self.missing_columns + self.bad_address + self.unknown_symbol + self.same_token + self.bad_reserves + self.duplicate
+    }
+
+    /// Non-zero skip counts, e.g. `2 unknown symbol, 1 bad reserves`
+    pub fn skip_reasons(&self) -> String {
+        [
+            (self.missing_columns, "missing columns"),
+            (self.bad_address, "bad address"),
+            (self.unknown_symbol, "unknown symbol"),
+            (self.same_token, "same token"),
+            (self.bad_reserves, "bad reserves"),
+            (self.duplicate, "duplicate pool"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, reason)| format!("{} {}", count, reason))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
 }
 
 impl MultiPathAnalyzer {
-    /// Create a new multi-path analyzer
-    pub fn new(wmnt_token: Token, config: &Config) -> Self {
+    /// Create a new multi-path analyzer seeking `base_token` -> ... -> `base_token` cycles
+    pub fn new(base_token: Token, config: &Config) -> Self {
+        let mut graph = TokenGraph::new(base_token.clone());
+        graph.set_reference_trade_size(config.reference_trade_size);
+        graph.set_exclusions(EdgeExclusions::new(&config.excluded_pools, &config.excluded_tokens));
         Self {
-            graph: TokenGraph::new(wmnt_token),
-            wmnt_token,
+            graph,
+            base_token,
             dex_fee: config.dex_fee,
+            protocols: registered_protocols(config.dex_fee),
             gas_price_gwei: config.gas_price_gwei,
+            profit_objective: config.profit_objective,
+            target_profit: config.target_profit,
+            gas_overhead: config.gas_overhead(),
+            input_limits: config.input_limits(),
+            flash_loan: config.flash_loan_terms(),
+            capture_ratio: config.capture_ratio,
+            execution_delay_blocks: config.execution_delay_blocks,
+            reserve_history: ReserveHistory::new(config.volatility_window),
+            max_cycles_per_block: config.max_cycles_per_block,
+            max_hops: config.max_hops,
+            iterations_per_hop: config.ternary_search_iterations_per_hop,
+            spfa_all_sources: config.spfa_all_sources,
+            rotation_offset: 0,
+            reserve_scale: if config.dev_mode { config.reserve_scale } else { 1.0 },
+            verify_cycle_detection: config.dev_mode && config.verify_cycle_detection,
+            strict_csv: config.strict_csv,
+            cycle_cache: None,
         }
     }
 
-    /// Load pools from CSV data file
-    pub fn load_pools_from_csv(&mut self, csv_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut reader = csv::Reader::from_path(csv_path)?;
-        
+    /// Load pools from CSV data file. Reserves are whole token units, converted to raw amounts with
+    /// each token's decimals, so they land on the same scale as live `getReserves` updates.
+    /// Unusable rows are skipped and summarized; with `strict_csv` any skipped row is an error.
+    pub fn load_pools_from_csv(&mut self, csv_path: &str) -> Result<CsvLoadReport, ArbError> {
+        // Flexible so rows may omit the optional fee column
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(csv_path)?;
+        let mut seen_pools = HashSet::new();
+        let mut loaded_pools = Vec::new();
+        let mut report = CsvLoadReport::default();
+
         for result in reader.records() {
             let record = result?;
-            
-            // Parse CSV record: Protocol,Pair Name,Pair Address,TokenA Reserves,TokenB Reserves
-            if record.len() >= 5 {
-                let pair_address = record[2].parse::<Address>();
-                if let Ok(pool_addr) = pair_address {
-                    // Parse token symbols from pair name
-                    let pair_name = &record[1];
-                    if let Some((token_a, token_b)) = self.parse_token_pair(pair_name) {
-                        let reserve_a_str = &record[3];
-                        let reserve_b_str = &record[4];
-                        
-                        if let (Ok(reserve_a), Ok(reserve_b)) = (
-                            reserve_a_str.parse::<f64>(),
-                            reserve_b_str.parse::<f64>()
-                        ) {
-                            // Convert to wei (assuming reserves are in token units)
-                            let reserve_a_wei = U256::from((reserve_a * 1e18) as u128);
-                            let reserve_b_wei = U256::from((reserve_b * 1e18) as u128);
-                            
-                            let pool_reserves = PoolReserves::new(
-                                token_a,
-                                reserve_a_wei,
-                                token_b,
-                                reserve_b_wei,
-                                0, // block number will be updated later
-                                pool_addr,
-                            );
-                            
-                            self.graph.add_pool(&pool_reserves, self.dex_fee);
-                        }
-                    }
-                }
+
+            // Parse CSV record: Protocol,Pair Name,Pair Address,TokenA Reserves,TokenB Reserves[,Fee]
+            if record.len() < 5 {
+                report.missing_columns += 1;
+                continue;
+            }
+            let Ok(pool_addr) = record[2].trim().parse::<Address>() else {
+                report.bad_address += 1;
+                continue;
+            };
+            // Parse token symbols from pair name
+            let Some((token_a, token_b)) = self.parse_token_pair(&record[1]) else {
+                report.unknown_symbol += 1;
+                continue;
+            };
+            if token_a == token_b {
+                report.same_token += 1;
+                continue;
+            }
+            let parse_reserve = |value: &str| value.trim().parse::<f64>().ok().filter(|r| r.is_finite() && *r >= 0.0);
+            let (Some(reserve_a), Some(reserve_b)) = (parse_reserve(&record[3]), parse_reserve(&record[4])) else {
+                report.bad_reserves += 1;
+                continue;
+            };
+
+            // Convert token units to raw amounts using each token's decimals
+            let reserve_a_wei = f64_to_u256_with_decimals(reserve_a, token_a.decimals());
+            let reserve_b_wei = f64_to_u256_with_decimals(reserve_b, token_b.decimals());
+
+            let pool_reserves = PoolReserves::new(
+                token_a,
+                reserve_a_wei,
+                token_b,
+                reserve_b_wei,
+                0, // block number will be updated later
+                pool_addr,
+            ).scaled(self.reserve_scale);
+
+            // Optional fee column as a fraction (0.0025 = 0.25%), else the owning protocol's default
+            let fee = record.get(5)
+                .and_then(|fee| fee.trim().parse::<f64>().ok())
+                .filter(|fee| (0.0..1.0).contains(fee))
+                .unwrap_or_else(|| self.pool_fee(pool_addr, Some(&record[0])));
+
+            // Other pools of the same pair become parallel edges; a repeated address would only shadow the first row
+            if !seen_pools.insert(pool_addr) {
+                warn!("⚠️ Pool {} is listed more than once in {}; keeping the first row", pool_addr, csv_path);
+                report.duplicate += 1;
+                continue;
             }
+            loaded_pools.push((pool_reserves, fee));
+            report.loaded += 1;
         }
-        
-        Ok(())
+        self.graph.add_pools(loaded_pools.iter().map(|(pool, fee)| (pool, *fee)));
+
+        if report.skipped() > 0 {
+            if self.strict_csv {
+                return Err(ArbError::Config(format!(
+                    "{}: {} rows skipped ({}) with STRICT_CSV / --strict-csv set",
+                    csv_path, report.skipped(), report.skip_reasons()
+                )));
+            }
+            warn!("⚠️ Skipped {} of {} rows in {}: {}", report.skipped(), report.loaded + report.skipped(), csv_path, report.skip_reasons());
+        }
+        Ok(report)
+    }
+
+    /// Add pools fetched from chain (token pairs from on-chain metadata) to the graph
+    pub fn add_pools<'a>(&mut self, pools: impl IntoIterator<Item = &'a PoolReserves>) {
+        let pools: Vec<(PoolReserves, f64)> = pools
+            .into_iter()
+            .map(|pool| (pool.scaled(self.reserve_scale), self.pool_fee(pool.pool_address, None)))
+            .collect();
+        self.graph.add_pools(pools.iter().map(|(pool, fee)| (pool, *fee)));
+    }
+
+    /// Register another DEX; its pools get its default fee
+    pub fn register_protocol(&mut self, protocol: Box<dyn DexProtocol>) {
+        self.protocols.push(protocol);
+    }
+
+    /// Fee of the protocol owning a pool, or the configured `DEX_FEE` if none claims it
+    fn pool_fee(&self, pool_address: Address, protocol_name: Option<&str>) -> f64 {
+        owning_protocol(&self.protocols, pool_address, protocol_name)
+            .map(|protocol| protocol.default_fee())
+            .unwrap_or(self.dex_fee)
+    }
+
+    /// Load the pool graph from a topology cache, falling back to the CSV and
+    /// refreshing the cache. Returns true if the cache was used.
+    pub fn load_pools_cached(&mut self, csv_path: &str, cache_path: &str) -> Result<bool, ArbError> {
+        match TokenGraph::load_topology(cache_path, csv_path) {
+            // A cache written for another base token would root cycles at the wrong token
+            Ok(Some(mut graph)) if graph.base_token() == &self.base_token => {
+                graph.set_reference_trade_size(self.graph.reference_trade_size());
+                graph.set_exclusions(self.graph.exclusions().clone());
+                self.graph = graph;
+                self.cycle_cache = None;
+                return Ok(true);
+            }
+            Ok(_) => {}
+            Err(e) => warn!("⚠️ Ignoring unreadable graph cache {}: {}", cache_path, e),
+        }
+
+        self.load_pools_from_csv(csv_path)?;
+        if let Err(e) = self.graph.save_topology(cache_path) {
+            warn!("⚠️ Failed to write graph cache {}: {}", cache_path, e);
+        }
+        Ok(false)
     }
 
     /// Parse token pair from pair name (e.g., "MOE-WMNT" -> (MOE, WMNT))
@@ -85,130 +255,346 @@ impl MultiPathAnalyzer {
         Some((token_a, token_b))
     }
 
-    /// Convert symbol string to Token enum
+    /// Look up a registered token by symbol
     fn symbol_to_token(&self, symbol: &str) -> Option<Token> {
-        match symbol {
-            "WMNT" => Some(Token::WMNT(Address::from_slice(&hex::decode(crate::constants::WMNT_ADDRESS.trim_start_matches("0x")).ok()?))),
-            "MOE" => Some(Token::MOE(Address::from_slice(&hex::decode(crate::constants::MOE_ADDRESS.trim_start_matches("0x")).ok()?))),
-            "JOE" => Some(Token::JOE(Address::from_slice(&hex::decode(crate::constants::JOE_ADDRESS.trim_start_matches("0x")).ok()?))),
-            // Add other tokens as needed based on CSV data
-            "mETH" | "PUFF" | "MINU" | "LEND" => {
-                // For now, skip unknown tokens
-                None
-            }
-            _ => None,
-        }
+        crate::token_registry::by_symbol(symbol)
     }
 
-    /// Update pool reserves with new data
-    pub fn update_pool_reserves(&mut self, reserves_map: &HashMap<Address, PoolReserves>) {
-        for pool_reserves in reserves_map.values() {
-            self.graph.update_pool(pool_reserves);
-        }
+    /// Update pool reserves with new data (raw on-chain amounts; the graph keeps token units).
+    /// Returns the number of edge weights recomputed; pools at unchanged reserves are skipped.
+    pub fn update_pool_reserves(&mut self, reserves_map: &HashMap<Address, PoolReserves>) -> usize {
+        self.graph.update_pools(reserves_map.values())
+    }
+
+    /// Add a block's reserves to the per-pool history used for volatility and drift estimates
+    pub fn record_reserve_history(&mut self, reserves_map: &HashMap<Address, PoolReserves>) {
+        self.reserve_history.record(reserves_map);
+    }
+
+    /// Recent reserve ratios per pool
+    pub fn reserve_history(&self) -> &ReserveHistory {
+        &self.reserve_history
+    }
+
+    /// Update only the listed pools, leaving the other edges untouched.
+    /// Returns the number of edge weights recomputed.
+    pub fn update_changed_pools(&mut self, reserves_map: &HashMap<Address, PoolReserves>, changed: &[Address]) -> usize {
+        self.graph.update_pools(changed.iter().filter_map(|addr| reserves_map.get(addr)))
+    }
+
+    /// Edge weights recomputed by reserve updates so far
+    pub fn weight_recomputations(&self) -> u64 {
+        self.graph.weight_recomputations()
     }
 
     /// Find all arbitrage opportunities across multiple paths
-    pub fn find_all_opportunities(&self, input_range: (f64, f64), iterations: usize) -> MultiPathOpportunity {
-        let start_time = std::time::Instant::now();
+    pub fn find_all_opportunities(
+        &mut self,
+        input_range: (f64, f64),
+        iterations: usize,
+    ) -> Result<MultiPathOpportunity, ArbError> {
+        validate_input_range(input_range)?;
+        let start_time = Instant::now();
         
-        // Find all arbitrage cycles (3-hops and 4-hops)
-        let cycles = self.graph.find_arbitrage_cycles(4);
+        // Find all arbitrage cycles (2 to max_hops hops), capped to the per-block budget;
+        // an unchanged graph has the same cycles as the last search
+        let version = self.graph.version();
+        let cached_cycles = self.cycle_cache
+            .as_ref()
+            .filter(|(cached_version, _)| *cached_version == version)
+            .map(|(_, cycles)| cycles.clone());
+        let cycles_reused = cached_cycles.is_some();
+        let cycles = cached_cycles.unwrap_or_else(|| if self.spfa_all_sources {
+            self.graph.find_arbitrage_cycles_all_sources(self.max_hops)
+        } else {
+            self.graph.find_arbitrage_cycles(self.max_hops)
+        });
+        if !cycles_reused {
+            self.cycle_cache = Some((version, cycles.clone()));
+        }
+        if self.verify_cycle_detection && !cycles_reused {
+            // Bellman-Ford is WMNT-seeded, so it is checked against the WMNT-seeded SPFA run
+            let rooted_cycles;
+            let spfa_cycles = if self.spfa_all_sources {
+                rooted_cycles = self.graph.find_arbitrage_cycles(self.max_hops);
+                &rooted_cycles
+            } else {
+                &cycles
+            };
+            if let Err(mismatch) = self.graph.verify_cycle_detection(spfa_cycles, self.max_hops) {
+                warn!("⚠️ Cycle detection mismatch: {}", mismatch);
+            }
+        }
+        let (cycles, cycles_deferred) = self.select_cycles(cycles);
+        let cycle_detection = start_time.elapsed();
         
         // Analyze each cycle in parallel for maximum performance
-        let opportunities: Vec<ArbitrageOpportunity> = cycles
+        let analysis_start = Instant::now();
+        let results: Vec<(Option<ArbitrageOpportunity>, CycleTiming)> = cycles
             .par_iter()
-            .filter_map(|cycle| self.analyze_cycle(cycle, input_range, iterations))
+            .map(|cycle| self.analyze_cycle_timed(cycle, input_range, iterations))
             .collect();
+        let cycle_analysis = analysis_start.elapsed();
+
+        let mut timings = AnalysisTimings {
+            cycle_detection,
+            cycle_analysis,
+            cycles_analyzed: results.len(),
+            cycles_deferred,
+            cycles_reused,
+            ..AnalysisTimings::default()
+        };
+        let mut opportunities = Vec::with_capacity(results.len());
+        for (opportunity, timing) in results {
+            timings.cycle_analysis_total += timing.total;
+            timings.cycle_analysis_max = timings.cycle_analysis_max.max(timing.total);
+            timings.conversion_total += timing.conversion;
+            opportunities.extend(opportunity);
+        }
         
-        let analysis_time_ms = start_time.elapsed().as_millis() as u64;
+        timings.total = start_time.elapsed();
+        let analysis_time_ms = timings.total.as_millis() as u64;
         
-        MultiPathOpportunity::new(opportunities, analysis_time_ms)
+        Ok(MultiPathOpportunity::new(opportunities, analysis_time_ms).with_timings(timings))
+    }
+
+    /// Apply the per-block cycle cap, returning the cycles to analyze and how many were deferred
+    fn select_cycles(&mut self, cycles: Vec<ArbitragePath>) -> (Vec<ArbitragePath>, usize) {
+        if self.max_cycles_per_block == 0 || cycles.len() <= self.max_cycles_per_block {
+            return (cycles, 0);
+        }
+
+        let scored = cycles
+            .into_iter()
+            .map(|cycle| {
+                let score = self.graph.cycle_log_edge(&cycle).unwrap_or(f64::NEG_INFINITY);
+                (cycle, score)
+            })
+            .collect();
+        let (selected, deferred, rotated) = prioritize_cycles(scored, self.max_cycles_per_block, self.rotation_offset);
+        self.rotation_offset = self.rotation_offset.wrapping_add(rotated);
+        (selected, deferred)
+    }
+
+    /// Analyze a cycle while measuring conversion and total time
+    fn analyze_cycle_timed(
+        &self,
+        cycle: &ArbitragePath,
+        input_range: (f64, f64),
+        iterations: usize,
+    ) -> (Option<ArbitrageOpportunity>, CycleTiming) {
+        let start = Instant::now();
+        let pools = self.validated_pools(cycle);
+        let conversion = start.elapsed();
+
+        let opportunity = pools.and_then(|(pools, fees)| self.analyze_pools(cycle, &pools, &fees, input_range, iterations));
+        (opportunity, CycleTiming { conversion, total: start.elapsed() })
     }
 
-    /// Analyze a specific arbitrage cycle for profitability
-    fn analyze_cycle(
+    /// Run the ternary search and cost model over a cycle's pools
+    fn analyze_pools(
         &self,
         cycle: &ArbitragePath,
-        _input_range: (f64, f64),
+        pools: &[(f64, f64)],
+        fees: &[f64],
+        input_range: (f64, f64),
         iterations: usize,
     ) -> Option<ArbitrageOpportunity> {
-        // Convert cycle to pool format for ternary search
-        let pools = self.cycle_to_pools(cycle)?;
-        
-        // Use ternary search to find optimal input amount
-        let (optimal_input, gross_profit) = find_best_input(&pools, self.dex_fee, iterations);
-        
-        // Calculate final output
-        let final_output = optimal_input + gross_profit;
-        
+        // Longer cycles get more iterations to converge
+        let iterations = iterations + self.iterations_per_hop * pools.len();
+
         // Calculate gas cost based on path type
         let gas_cost = self.calculate_gas_cost(cycle);
+
+        // Pick the input amount according to the configured objective, within the input range
+        let (min_input, max_input) = input_range;
+        let min_capital = match self.profit_objective {
+            ProfitObjective::MinCapital => {
+                find_min_input_for_profit(pools, fees, self.target_profit + gas_cost, iterations)
+                    .filter(|&(input, _)| input <= max_input)
+                    .map(|(input, profit)| {
+                        if input < min_input {
+                            (min_input, arbitrage_profit_with_fees(min_input, pools, fees))
+                        } else {
+                            (input, profit)
+                        }
+                    })
+            }
+            ProfitObjective::MaxProfit => None,
+        };
+        // MAX_PRICE_IMPACT_PCT limits the search itself, so the result is the best input under the cap
+        let max_impact = self.input_limits.max_price_impact;
+        let (optimal_input, gross_profit, robustness, search_method, unconstrained_input) = match min_capital {
+            Some((input, profit)) => (input, profit, profitable_fraction(pools, fees), "multi_path_min_capital", input),
+            None => {
+                let (input, profit, robustness) = find_best_input_within_impact(pools, fees, input_range, max_impact, iterations);
+                let unconstrained_input = if max_impact > 0.0 {
+                    find_best_input_in_range(pools, fees, input_range, iterations).0
+                } else {
+                    input
+                };
+                (input, profit, robustness, "multi_path_ternary", unconstrained_input)
+            }
+        };
+        let impact_bound = (optimal_input < unconstrained_input).then_some(InputConstraint::PriceImpact);
+
+        // Clamp to the tightest remaining input limit, re-pricing the route at the capped size
+        let (optimal_input, clamped_by) = self.input_limits.apply(optimal_input, pools, fees, iterations);
+        let gross_profit = if clamped_by.is_some() {
+            arbitrage_profit_with_fees(optimal_input, pools, fees)
+        } else {
+            gross_profit
+        };
+        let binding_constraint = clamped_by.or(impact_bound);
+
+        Some(ArbitrageOpportunity {
+            robustness,
+            unconstrained_input,
+            binding_constraint,
+            ..self.price_at_input(cycle, pools, fees, optimal_input, gross_profit, search_method)
+        })
+    }
+
+    /// Price a cycle at exactly `input` WMNT instead of searching for the optimum
+    /// (`search_method = "fixed_input"`); input limits are not applied.
+    /// `None` if one of the cycle's pools is not in the graph.
+    pub fn evaluate_fixed_input(&self, cycle: &ArbitragePath, input: f64) -> Option<ArbitrageOpportunity> {
+        let (pools, fees) = self.validated_pools(cycle)?;
+        let gross_profit = arbitrage_profit_with_fees(input, &pools, &fees);
+        Some(ArbitrageOpportunity {
+            robustness: profitable_fraction(&pools, &fees),
+            ..self.price_at_input(cycle, &pools, &fees, input, gross_profit, "fixed_input")
+        })
+    }
+
+    /// Apply gas, flash-loan and competition costs to a cycle traded at `input`
+    fn price_at_input(
+        &self,
+        cycle: &ArbitragePath,
+        pools: &[(f64, f64)],
+        fees: &[f64],
+        input: f64,
+        gross_profit: f64,
+        search_method: &str,
+    ) -> ArbitrageOpportunity {
+        let gas_cost = self.calculate_gas_cost(cycle);
+
+        // Calculate final output
+        let final_output = input + gross_profit;
         
-        // Calculate net profit after gas costs
-        let net_profit = gross_profit - gas_cost;
+        // Calculate net profit after gas costs and any flash-loan fee
+        let flash_loan_fee = self.flash_loan.fee_for(input);
+        let net_profit = gross_profit - gas_cost - flash_loan_fee.unwrap_or(0.0);
         
         // Calculate profit percentage
-        let profit_percentage = if optimal_input > 0.0 {
-            (net_profit / optimal_input) * 100.0
+        let profit_percentage = if input > 0.0 {
+            (net_profit / input) * 100.0
         } else {
             0.0
         };
-        
-        Some(ArbitrageOpportunity {
-            optimal_input,
+
+        // Discount a positive spread by the chance it survives reserve drift until execution
+        let edge = if input > 0.0 { gross_profit / input } else { 0.0 };
+        let drift = self.reserve_history.path_drift(&cycle.pools, self.execution_delay_blocks);
+        let confidence = execution_confidence(edge, drift);
+        let expected_gross = if gross_profit > 0.0 { gross_profit * confidence } else { gross_profit };
+
+        ArbitrageOpportunity {
+            optimal_input: input,
             final_output,
             gross_profit,
             net_profit,
             profit_percentage,
-            search_method: "multi_path_ternary".to_string(),
+            search_method: search_method.to_string(),
             path: Some(cycle.clone()),
-        })
+            robustness: 0.0,
+            unconstrained_input: input,
+            binding_constraint: None,
+            price_impact_percent: max_price_impact(input, pools, fees) * 100.0,
+            requires_flash_loan: flash_loan_fee.is_some(),
+            flash_loan_fee: flash_loan_fee.unwrap_or(0.0),
+            min_pool_depth: min_path_depth(pools),
+            expected_realized_profit: realized_profit(expected_gross, gas_cost + flash_loan_fee.unwrap_or(0.0), self.capture_ratio),
+            hop_outputs: hop_outputs(input, pools, fees),
+            confidence,
+        }
     }
 
-    /// Convert arbitrage cycle to pools format for mathematical analysis
-    fn cycle_to_pools(&self, cycle: &ArbitragePath) -> Option<Vec<(f64, f64)>> {
-        let mut pools = Vec::new();
-        
-        for i in 0..cycle.tokens.len() - 1 {
-            let token_in = cycle.tokens[i];
-            let token_out = cycle.tokens[i + 1];
-            
-            let pool_info = self.graph.get_pool_info(token_in, token_out)?;
-            
-            let (reserve_in, reserve_out) = if pool_info.token_a == token_in {
-                (pool_info.reserves_a, pool_info.reserves_b)
+    /// Net profit of a found opportunity at its original input, re-priced against
+    /// other reserves of the same pools (e.g. the next block's); `None` if a pool is missing.
+    /// Hops are built like `cycle_to_pools`, so fees and transfer tax apply the same way.
+    pub fn reprice(&self, opportunity: &ArbitrageOpportunity, reserves_map: &HashMap<Address, PoolReserves>) -> Option<f64> {
+        let cycle = opportunity.path.as_ref()?;
+        let (pools, fees) = self.graph.path_pools_with_reserves(cycle, |pool| {
+            let reserves = reserves_map.get(&pool.pool_address)?.scaled(self.reserve_scale);
+            if reserves.token_a == pool.token_a && reserves.token_b == pool.token_b {
+                Some((reserves.reserve_a_units(), reserves.reserve_b_units()))
+            } else if reserves.token_a == pool.token_b && reserves.token_b == pool.token_a {
+                Some((reserves.reserve_b_units(), reserves.reserve_a_units()))
             } else {
-                (pool_info.reserves_b, pool_info.reserves_a)
-            };
-            
-            pools.push((reserve_in, reserve_out));
-        }
-        
-        // Add the closing pool (back to WMNT)
-        if let Some(last_token) = cycle.tokens.last() {
-            if *last_token != self.wmnt_token {
-                let pool_info = self.graph.get_pool_info(*last_token, self.wmnt_token)?;
-                
-                let (reserve_in, reserve_out) = if pool_info.token_a == *last_token {
-                    (pool_info.reserves_a, pool_info.reserves_b)
-                } else {
-                    (pool_info.reserves_b, pool_info.reserves_a)
-                };
-                
-                pools.push((reserve_in, reserve_out));
+                None
             }
+        })?;
+
+        let gross_profit = arbitrage_profit_with_fees(opportunity.optimal_input, &pools, &fees);
+        Some(gross_profit - self.calculate_gas_cost(cycle) - opportunity.flash_loan_fee)
+    }
+
+    /// Gas price (gwei) used to cost routes from now on
+    pub fn set_gas_price_gwei(&mut self, gas_price_gwei: f64) {
+        self.gas_price_gwei = gas_price_gwei;
+    }
+
+    /// Override a pool's fee with a calibrated estimate
+    pub fn set_pool_fee(&mut self, pool_address: Address, fee: f64) -> bool {
+        self.graph.set_pool_fee(pool_address, fee)
+    }
+
+    /// `cycle_to_pools` for a cycle that passes `ArbitragePath::validate`; invalid cycles are
+    /// reported and skipped
+    fn validated_pools(&self, cycle: &ArbitragePath) -> Option<CyclePools> {
+        if let Err(e) = cycle.validate(&self.graph) {
+            warn!("⚠️ Skipping invalid path {}: {}", cycle.description(), e);
+            return None;
         }
-        
-        Some(pools)
+        self.cycle_to_pools(cycle)
+    }
+
+    /// Convert arbitrage cycle to pools format for mathematical analysis: each hop's
+    /// (reserve_in, reserve_out) and that pool's own fee (from the CSV or calibration,
+    /// configured default otherwise), as priced by `TokenGraph::calculate_path_profit`
+    fn cycle_to_pools(&self, cycle: &ArbitragePath) -> Option<CyclePools> {
+        self.graph.path_pools(cycle)
     }
 
-    /// Calculate gas cost for a specific cycle (result in MNT)
+    /// Calculate gas cost for a specific cycle (result in base-token units; MNT for WMNT)
     fn calculate_gas_cost(&self, cycle: &ArbitragePath) -> f64 {
-        let gas_units = cycle.expected_gas_units();
+        let gas_units = cycle.total_gas_units(&self.gas_overhead);
         
         // Direct calculation: gas_units * gas_price_gwei * gwei_to_mnt_multiplier
         use crate::constants::GWEI_TO_MNT_MULTIPLIER;
-        gas_units as f64 * self.gas_price_gwei * GWEI_TO_MNT_MULTIPLIER
+        gas_units as f64 * self.gas_price_gwei * GWEI_TO_MNT_MULTIPLIER * self.base_per_mnt()
+    }
+
+    /// Base tokens per MNT: 1 for WMNT, otherwise the spot rate of the best-priced
+    /// WMNT/base pool in the graph (1 when there is none)
+    fn base_per_mnt(&self) -> f64 {
+        let Some(wmnt) = WMNT_ADDRESS.parse().ok().and_then(Token::from_address) else {
+            return 1.0;
+        };
+        if wmnt == self.base_token {
+            return 1.0;
+        }
+        self.graph
+            .get_pool_info(&wmnt, &self.base_token)
+            .map(|pool| if pool.token_a == wmnt { pool.get_rate_a_to_b() } else { pool.get_rate_b_to_a() })
+            .filter(|&rate| rate > 0.0)
+            .unwrap_or(1.0)
+    }
+
+    /// Current market efficiency score (0-100, lower means more arbitrage)
+    pub fn market_efficiency_score(&self) -> f64 {
+        self.graph.market_efficiency_score(self.max_hops)
     }
 
     /// Get graph statistics
@@ -216,61 +602,436 @@ impl MultiPathAnalyzer {
         (self.graph.node_count(), self.graph.edge_count())
     }
 
+    /// Directed edges kept out of the graph by `EXCLUDED_POOLS` / `EXCLUDED_TOKENS`
+    pub fn excluded_edge_count(&self) -> usize {
+        self.graph.excluded_edge_count()
+    }
+
     /// Get all available arbitrage paths
     pub fn get_all_paths(&self) -> Vec<ArbitragePath> {
-        self.graph.find_arbitrage_cycles(4)
+        self.graph.find_arbitrage_cycles(self.max_hops)
+    }
+
+    /// Every cycle the loaded pools can form up to `max_hops`, profitable or not
+    pub fn get_structural_paths(&self) -> Vec<ArbitragePath> {
+        self.graph.enumerate_structural_cycles(self.max_hops)
     }
 }
 
-/// Batch reserves fetcher for all pools
-pub struct BatchReservesFetcher {
-    pool_addresses: Vec<Address>,
+/// Find arbitrage opportunities in one reserves snapshot, with no I/O or printing
+/// (diagnostics such as invalid reserves go through `log`).
+/// Cycles start and end at `config.base_token`; `config.pool_fees` overrides `DEX_FEE` per pool,
+/// and inputs are searched within `config.input_range()`, which must pass `validate_input_range`.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use std::env::VarError;
+/// use alloy::primitives::Address;
+/// use triangular_arbitrage_demo::analyze_snapshot;
+/// use triangular_arbitrage_demo::config::Config;
+/// use triangular_arbitrage_demo::constants::{JOE_ADDRESS, MOE_ADDRESS, WMNT_ADDRESS};
+/// use triangular_arbitrage_demo::math::f64_to_u256_with_decimals;
+/// use triangular_arbitrage_demo::types::{PoolReserves, Token};
+///
+/// let config = Config::from_lookup(&|name| match name {
+///     "RPC_URL" => Ok("http://localhost:8545".to_string()),
+///     _ => Err(VarError::NotPresent),
+/// })?;
+///
+/// // WMNT = 2 MOE = 0.5 JOE, except JOE-WMNT pays 10% more WMNT per JOE
+/// let pools = [
+///     (WMNT_ADDRESS, 100_000.0, MOE_ADDRESS, 200_000.0),
+///     (MOE_ADDRESS, 200_000.0, JOE_ADDRESS, 50_000.0),
+///     (JOE_ADDRESS, 50_000.0, WMNT_ADDRESS, 110_000.0),
+/// ];
+/// let mut reserves = HashMap::new();
+/// for (i, (token_a, reserve_a, token_b, reserve_b)) in pools.into_iter().enumerate() {
+///     let token_a = Token::from_address(token_a.parse()?).unwrap();
+///     let token_b = Token::from_address(token_b.parse()?).unwrap();
+///     let pool = Address::with_last_byte(i as u8 + 1);
+///     let reserve_a = f64_to_u256_with_decimals(reserve_a, token_a.decimals());
+///     let reserve_b = f64_to_u256_with_decimals(reserve_b, token_b.decimals());
+///     reserves.insert(pool, PoolReserves::new(token_a, reserve_a, token_b, reserve_b, 1, pool));
+/// }
+///
+/// let result = analyze_snapshot(&reserves, &config)?;
+/// assert!(result.has_profitable_opportunities());
+/// println!("best route: {:?}", result.best_opportunity.map(|o| o.net_profit));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn analyze_snapshot(reserves: &HashMap<Address, PoolReserves>, config: &Config) -> Result<MultiPathOpportunity, ArbError> {
+    validate_input_range(config.input_range())?;
+    let mut analyzer = MultiPathAnalyzer::new(config.base_token.clone(), config);
+    analyzer.add_pools(reserves.values());
+    for (&pool, &fee) in &config.pool_fees {
+        analyzer.set_pool_fee(pool, fee);
+    }
+
+    analyzer.find_all_opportunities(config.input_range(), config.ternary_search_iterations)
 }
 
-impl BatchReservesFetcher {
-    /// Create new batch fetcher
-    pub fn new() -> Self {
+/// Check that a search input range is positive, finite and ordered (min < max)
+pub fn validate_input_range(input_range: (f64, f64)) -> Result<(), ArbError> {
+    let (min, max) = input_range;
+    if !min.is_finite() || !max.is_finite() {
+        return Err(ArbError::Math(format!("Invalid input range ({}, {}): bounds must be finite", min, max)));
+    }
+    if min <= 0.0 || max <= 0.0 {
+        return Err(ArbError::Math(format!("Invalid input range ({}, {}): bounds must be positive", min, max)));
+    }
+    if min >= max {
+        return Err(ArbError::Math(format!("Invalid input range ({}, {}): min must be less than max", min, max)));
+    }
+    Ok(())
+}
+
+/// Pick at most `cap` cycles: most slots go to the highest pre-scores, and a quarter
+/// (at least one when `cap > 1`) rotates through the lower-ranked remainder starting
+/// at `rotation_offset` so every cycle is eventually analyzed.
+/// Returns (selected, deferred count, rotation slots used).
+pub fn prioritize_cycles(
+    mut scored: Vec<(ArbitragePath, f64)>,
+    cap: usize,
+    rotation_offset: usize,
+) -> (Vec<ArbitragePath>, usize, usize) {
+    if scored.len() <= cap {
+        return (scored.into_iter().map(|(cycle, _)| cycle).collect(), 0, 0);
+    }
+
+    let deferred = scored.len() - cap;
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let rotation_slots = if cap > 1 { (cap / 4).max(1) } else { 0 };
+    let priority_slots = cap - rotation_slots;
+    let lower_ranked = scored.split_off(priority_slots);
+
+    let mut selected: Vec<ArbitragePath> = scored.into_iter().map(|(cycle, _)| cycle).collect();
+    let start = rotation_offset % lower_ranked.len();
+    selected.extend(
+        lower_ranked
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(rotation_slots)
+            .map(|(cycle, _)| cycle.clone()),
+    );
+
+    (selected, deferred, rotation_slots)
+}
+
+/// Per-cycle timing collected during parallel analysis
+struct CycleTiming {
+    conversion: Duration,
+    total: Duration,
+}
+
+/// Result of running a scan through the confirmation filter
+#[derive(Debug, Clone)]
+pub struct ConfirmationReport {
+    /// Opportunities safe to act on (confirmed profitable routes plus all non-profitable ones)
+    pub actionable: MultiPathOpportunity,
+    /// Profitable routes that have not yet persisted long enough, with their current streak
+    pub unconfirmed: Vec<(ArbitrageOpportunity, u32)>,
+}
+
+/// Requires a route to stay profitable for N consecutive scans before it is actionable
+pub struct ConfirmationFilter {
+    required_blocks: u32,
+    streaks: HashMap<Vec<Address>, u32>,
+}
+
+impl ConfirmationFilter {
+    /// Create a filter requiring `required_blocks` consecutive profitable scans (1 = report immediately)
+    pub fn new(required_blocks: u32) -> Self {
         Self {
-            pool_addresses: Vec::new(),
+            required_blocks: required_blocks.max(1),
+            streaks: HashMap::new(),
         }
     }
 
-    /// Load pool addresses from CSV
-    pub fn load_pool_addresses_from_csv(&mut self, csv_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut reader = csv::Reader::from_path(csv_path)?;
-        
-        for result in reader.records() {
-            let record = result?;
-            if record.len() >= 3 {
-                if let Ok(pool_addr) = record[2].parse::<Address>() {
-                    self.pool_addresses.push(pool_addr);
+    /// Update per-route streaks with a new scan and split confirmed from unconfirmed opportunities
+    pub fn observe(&mut self, multi_opportunity: &MultiPathOpportunity) -> ConfirmationReport {
+        let mut streaks = HashMap::new();
+        let mut kept = Vec::new();
+        let mut unconfirmed = Vec::new();
+
+        for opportunity in &multi_opportunity.opportunities {
+            let route = match (&opportunity.path, opportunity.is_profitable()) {
+                (Some(path), true) => path.pools.clone(),
+                _ => {
+                    kept.push(opportunity.clone());
+                    continue;
                 }
+            };
+
+            // Routes missing from the previous scan start a fresh streak
+            let streak = self.streaks.get(&route).copied().unwrap_or(0) + 1;
+            streaks.insert(route, streak);
+
+            if streak >= self.required_blocks {
+                kept.push(opportunity.clone());
+            } else {
+                unconfirmed.push((opportunity.clone(), streak));
             }
         }
-        
-        println!("📊 Loaded {} pool addresses from CSV", self.pool_addresses.len());
-        Ok(())
+
+        self.streaks = streaks;
+
+        ConfirmationReport {
+            actionable: MultiPathOpportunity::new(kept, multi_opportunity.analysis_time_ms)
+                .with_timings(multi_opportunity.timings.clone()),
+            unconfirmed,
+        }
+    }
+
+    /// Number of consecutive scans required for confirmation
+    pub fn required_blocks(&self) -> u32 {
+        self.required_blocks
+    }
+}
+
+/// Profitable route that was seen over consecutive scans and has now disappeared
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndedOpportunity {
+    pub opportunity_id: String,
+    pub path_description: String,
+    pub first_seen_block: u64,
+    pub last_seen_block: u64,
+}
+
+impl EndedOpportunity {
+    /// Number of blocks the route stayed profitable
+    pub fn persisted_blocks(&self) -> u64 {
+        self.last_seen_block - self.first_seen_block + 1
+    }
+}
+
+/// Remembers when each profitable route (by `opportunity_id`) was first and last seen
+#[derive(Debug, Default)]
+pub struct OpportunityTracker {
+    active: HashMap<String, EndedOpportunity>,
+}
+
+impl OpportunityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the profitable routes of a scan and return the ones that disappeared since the last scan
+    pub fn observe(&mut self, block_number: u64, multi_opportunity: &MultiPathOpportunity) -> Vec<EndedOpportunity> {
+        let mut active = HashMap::new();
+        for path in multi_opportunity.profitable_opportunities().into_iter().filter_map(|o| o.path.as_ref()) {
+            let opportunity_id = path.opportunity_id();
+            let mut sighting = self.active.remove(&opportunity_id).unwrap_or_else(|| EndedOpportunity {
+                opportunity_id: opportunity_id.clone(),
+                path_description: path.description(),
+                first_seen_block: block_number,
+                last_seen_block: block_number,
+            });
+            sighting.last_seen_block = block_number;
+            active.insert(opportunity_id, sighting);
+        }
+
+        let mut ended: Vec<_> = std::mem::replace(&mut self.active, active).into_values().collect();
+        ended.sort_by_key(|e| e.first_seen_block);
+        ended
+    }
+
+    /// Block at which a still-active route was first seen
+    pub fn first_seen(&self, opportunity_id: &str) -> Option<u64> {
+        self.active.get(opportunity_id).map(|s| s.first_seen_block)
+    }
+}
+
+/// Running profitability of one route across scans
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathStat {
+    pub path_description: String,
+    /// Exponential moving average of the route's net profit (base token)
+    pub ema_net_profit: f64,
+    /// Scans in which the route was analyzed
+    pub samples: u64,
+    /// Scans in which the route was profitable
+    pub hits: u64,
+}
+
+/// In-memory net-profit EMA and hit count per route (by `opportunity_id`), to tell
+/// consistently near-profitable cycles from one-off flukes
+#[derive(Debug)]
+pub struct PathStats {
+    alpha: f64,
+    paths: HashMap<String, PathStat>,
+}
+
+impl PathStats {
+    /// Track routes with smoothing factor `alpha` (weight of the newest scan, in (0, 1])
+    pub fn new(alpha: f64) -> Self {
+        Self { alpha, paths: HashMap::new() }
+    }
+
+    /// Fold every analyzed route of a scan into its EMA; a route's first scan seeds the average
+    pub fn observe(&mut self, multi_opportunity: &MultiPathOpportunity) {
+        for opportunity in &multi_opportunity.opportunities {
+            let Some(path) = opportunity.path.as_ref() else {
+                continue;
+            };
+            let stat = self.paths.entry(path.opportunity_id()).or_insert_with(|| PathStat {
+                path_description: path.description(),
+                ema_net_profit: opportunity.net_profit,
+                samples: 0,
+                hits: 0,
+            });
+            if stat.samples > 0 {
+                stat.ema_net_profit += self.alpha * (opportunity.net_profit - stat.ema_net_profit);
+            }
+            stat.samples += 1;
+            if opportunity.is_profitable() {
+                stat.hits += 1;
+            }
+        }
+    }
+
+    /// Stats of one route
+    pub fn get(&self, opportunity_id: &str) -> Option<&PathStat> {
+        self.paths.get(opportunity_id)
+    }
+
+    /// Up to `limit` routes with the highest EMA, best first
+    pub fn top_by_ema(&self, limit: usize) -> Vec<(&str, &PathStat)> {
+        let mut ranked: Vec<_> = self.paths.iter().map(|(id, stat)| (id.as_str(), stat)).collect();
+        ranked.sort_by(|a, b| b.1.ema_net_profit.total_cmp(&a.1.ema_net_profit).then(a.0.cmp(b.0)));
+        ranked.truncate(limit);
+        ranked
     }
 
-    /// Get all pool addresses
-    pub fn get_pool_addresses(&self) -> &[Address] {
-        &self.pool_addresses
+    /// Print the `limit` routes with the highest EMA, in `unit` (the base token symbol)
+    pub fn print_top(&self, limit: usize, unit: &str) {
+        let ranked = self.top_by_ema(limit);
+        if ranked.is_empty() {
+            return;
+        }
+
+        println!("📈 Top Routes by EMA Net Profit:");
+        for (i, (opportunity_id, stat)) in ranked.iter().enumerate() {
+            let prefix = if i == ranked.len() - 1 { "└─" } else { "├─" };
+            println!("{} {} | EMA: {:.6} {} | Hits: {}/{} | Path: {}",
+                    prefix,
+                    opportunity_id,
+                    stat.ema_net_profit,
+                    unit,
+                    stat.hits,
+                    stat.samples,
+                    stat.path_description);
+        }
+    }
+}
+
+/// Drops opportunities by token membership before reporting
+pub struct TokenFilter {
+    include: Vec<Token>,
+    exclude: Vec<Token>,
+}
+
+impl TokenFilter {
+    /// Create a filter; an empty include list allows any token
+    pub fn new(include: Vec<Token>, exclude: Vec<Token>) -> Self {
+        Self { include, exclude }
     }
 
-    /// Add individual pool address
-    pub fn add_pool_address(&mut self, address: Address) {
-        if !self.pool_addresses.contains(&address) {
-            self.pool_addresses.push(address);
+    /// Whether the filter has any effect
+    pub fn is_active(&self) -> bool {
+        !self.include.is_empty() || !self.exclude.is_empty()
+    }
+
+    /// Check an opportunity against the include/exclude sets
+    pub fn allows(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        if self.exclude.iter().any(|t| opportunity.touches_token(t)) {
+            return false;
+        }
+        self.include.is_empty() || self.include.iter().any(|t| opportunity.touches_token(t))
+    }
+
+    /// Keep only allowed opportunities, preserving analysis timings
+    pub fn apply(&self, multi_opportunity: MultiPathOpportunity) -> MultiPathOpportunity {
+        if !self.is_active() {
+            return multi_opportunity;
         }
+
+        let kept = multi_opportunity.opportunities
+            .into_iter()
+            .filter(|opportunity| self.allows(opportunity))
+            .collect();
+        MultiPathOpportunity::new(kept, multi_opportunity.analysis_time_ms)
+            .with_timings(multi_opportunity.timings)
+    }
+}
+
+/// Minimum net profit that widens with recent gas price volatility
+pub struct AdaptiveProfitThreshold {
+    min_profit: f64,
+    volatility_multiplier: f64,
+}
+
+impl AdaptiveProfitThreshold {
+    /// Create a threshold; a zero multiplier keeps it static at `min_profit`
+    pub fn new(min_profit: f64, volatility_multiplier: f64) -> Self {
+        Self {
+            min_profit,
+            volatility_multiplier,
+        }
+    }
+
+    /// Whether gas history is needed to compute the threshold
+    pub fn is_adaptive(&self) -> bool {
+        self.volatility_multiplier > 0.0
+    }
+
+    /// Effective minimum profit given recent gas prices (static fallback without history)
+    pub fn effective(&self, gas_history: &GasPriceHistory) -> f64 {
+        match gas_history.volatility() {
+            Some(volatility) if self.is_adaptive() => {
+                self.min_profit * (1.0 + self.volatility_multiplier * volatility)
+            }
+            _ => self.min_profit,
+        }
+    }
+
+    /// Drop profitable opportunities whose net profit is below the threshold
+    pub fn apply(&self, multi_opportunity: MultiPathOpportunity, threshold: f64) -> MultiPathOpportunity {
+        if threshold <= 0.0 {
+            return multi_opportunity;
+        }
+
+        let kept = multi_opportunity.opportunities
+            .into_iter()
+            .filter(|opportunity| !opportunity.is_profitable() || opportunity.is_profitable_with_threshold(threshold))
+            .collect();
+        MultiPathOpportunity::new(kept, multi_opportunity.analysis_time_ms)
+            .with_timings(multi_opportunity.timings)
+    }
+
+    /// Most profitable route that is profitable but below the threshold (dropped by `apply`)
+    pub fn best_below(&self, multi_opportunity: &MultiPathOpportunity, threshold: f64) -> Option<ArbitrageOpportunity> {
+        multi_opportunity.opportunities
+            .iter()
+            .filter(|opportunity| opportunity.is_profitable() && !opportunity.is_profitable_with_threshold(threshold))
+            .max_by(|a, b| a.net_profit.partial_cmp(&b.net_profit).unwrap_or(std::cmp::Ordering::Equal))
+            .cloned()
     }
 }
 
 /// Strategy for selecting optimal arbitrage opportunity
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OptimizationStrategy {
     MaxProfit,
+    /// Highest net profit after the expected share lost to competing bots
+    MaxRealizedProfit,
     MaxProfitPercent,
     MinRisk,
     BalancedRiskReturn,
+    /// Most profitable route whose price impact stays under the cap (%);
+    /// falls back to the lowest-impact route when none does
+    MinSlippage { max_impact_percent: f64 },
 }
 
 /// Multi-path strategy selector
@@ -293,6 +1054,14 @@ impl StrategySelector {
                     .filter(|opp| opp.is_profitable())
                     .max_by(|a, b| a.net_profit.partial_cmp(&b.net_profit).unwrap_or(std::cmp::Ordering::Equal))
             }
+            OptimizationStrategy::MaxRealizedProfit => {
+                opportunities
+                    .iter()
+                    .filter(|opp| opp.is_profitable())
+                    .max_by(|a, b| {
+                        a.expected_realized_profit.partial_cmp(&b.expected_realized_profit).unwrap_or(std::cmp::Ordering::Equal)
+                    })
+            }
             OptimizationStrategy::MaxProfitPercent => {
                 opportunities
                     .iter()
@@ -300,11 +1069,14 @@ impl StrategySelector {
                     .max_by(|a, b| a.profit_percentage.partial_cmp(&b.profit_percentage).unwrap_or(std::cmp::Ordering::Equal))
             }
             OptimizationStrategy::MinRisk => {
-                // Lower hop count = lower risk
+                // Fewer hops first, then deeper pools and lower impact
                 opportunities
                     .iter()
                     .filter(|opp| opp.is_profitable())
-                    .min_by(|a, b| a.hop_count().cmp(&b.hop_count()))
+                    .min_by(|a, b| {
+                        a.hop_count().cmp(&b.hop_count())
+                            .then_with(|| a.risk_score().partial_cmp(&b.risk_score()).unwrap_or(std::cmp::Ordering::Equal))
+                    })
             }
             OptimizationStrategy::BalancedRiskReturn => {
                 // Weighted score: profit / (hop_count^2)
@@ -317,31 +1089,120 @@ impl StrategySelector {
                         score_a.partial_cmp(&score_b).unwrap_or(std::cmp::Ordering::Equal)
                     })
             }
+            OptimizationStrategy::MinSlippage { max_impact_percent } => {
+                let profitable = || opportunities.iter().filter(|opp| opp.is_profitable());
+                profitable()
+                    .filter(|opp| opp.price_impact_percent <= max_impact_percent)
+                    .max_by(|a, b| a.net_profit.partial_cmp(&b.net_profit).unwrap_or(std::cmp::Ordering::Equal))
+                    .or_else(|| {
+                        profitable().min_by(|a, b| {
+                            a.price_impact_percent.partial_cmp(&b.price_impact_percent).unwrap_or(std::cmp::Ordering::Equal)
+                        })
+                    })
+            }
         }
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    use crate::types::Token;
-    use alloy::primitives::Address;
+    use crate::math::{f64_to_u256, find_best_input_with_fees, swap};
+    use crate::constants::{GAS_UNITS_4_HOPS, GAS_UNITS_PER_EXTRA_HOP};
+    use crate::types::{PathType, Token};
+    use alloy::primitives::{Address, U256};
+
+    /// Input range wide enough that only the pools bound the search
+    const UNBOUNDED_INPUT: (f64, f64) = (1e-9, 1e12);
 
-    fn create_test_config() -> Config {
+    /// Config with every optional feature disabled (shared with other modules' tests)
+    pub(crate) fn create_test_config() -> Config {
         Config {
             rpc_url: "test".to_string(),
+            rpc_url_fallback: None,
+            max_consecutive_failures: 5,
+            rpc_timeout_ms: 10_000,
             csv_file_path: "test.csv".to_string(),
             dex_fee: 0.003,
             ternary_search_iterations: 100,
+            ternary_search_iterations_per_hop: 0,
+            min_input_wmnt: 100.0,
+            max_input_wmnt: 10000.0,
             gas_price_gwei: 0.02,
             block_time_seconds: 2,
             max_retries: 3,
+            batch_size: 50,
+            inter_batch_delay_ms: 100,
+            max_concurrent_requests: 16,
+            min_pool_coverage: 0.0,
+            tui_enabled: false,
+            volatility_window: 20,
+            volatility_log_interval_blocks: 50,
+            path_stats_alpha: 0.1,
+            path_stats_log_interval_blocks: 0,
+            output_format: crate::config::OutputFormat::Csv,
+            parquet_file_path: "test.parquet".to_string(),
+            parquet_row_group_size: 100,
+            sqlite_file_path: "test.db".to_string(),
+            confirmation_blocks: 1,
+            verify_next_block: false,
+            profit_objective: crate::config::ProfitObjective::MaxProfit,
+            target_profit: 0.01,
+            graph_cache_path: None,
+            pool_token_cache_path: None,
+            approval_gas_units: 0,
+            wrap_gas_units: 0,
+            include_tokens: Vec::new(),
+            exclude_tokens: Vec::new(),
+            base_token: Token::from_address(WMNT_ADDRESS.parse().unwrap()).unwrap(),
+            min_liquidity_wmnt: 1000.0,
+            min_profit_wmnt: 0.0,
+            gas_volatility_multiplier: 0.0,
+            gas_history_window: 20,
+            dynamic_gas_price: false,
+            gas_price_refresh_secs: 10,
+            stream_address: None,
+            max_cycles_per_block: 0,
+            max_hops: 4,
+            spfa_all_sources: false,
+            reference_trade_size: 0.0,
+            reserve_log_min_change_pct: 0.0,
+            reserve_log_interval_secs: 0,
+            reserve_cache_ttl_secs: 0,
+            fee_calibration_blocks: 0,
+            dev_mode: false,
+            verify_cycle_detection: false,
+            reserve_scale: 1.0,
+            max_price_impact_pct: 0.0,
+            max_capital: 0.0,
+            max_reserve_fraction: 0.0,
+            wallet_balance_wmnt: 0.0,
+            flash_loan_fee_bps: 5.0,
+            capture_ratio: 1.0,
+            execution_delay_blocks: 0,
+            rank_by_realized_profit: false,
+            usd_prices: Default::default(),
+            metrics_snapshot_path: None,
+            metrics_snapshot_interval_secs: 30,
+            metrics_address: None,
+            api_address: None,
+            token_registry_path: None,
+            monitor_driver: crate::config::MonitorDriver::Polling,
+            reserve_source: crate::config::ReserveSource::GetReserves,
+            ws_rpc_url: None,
+            log_format: crate::config::LogFormat::Console,
+            log_json_path: "-".to_string(),
+            pools: Vec::new(),
+            pool_fees: HashMap::new(),
+            excluded_pools: Vec::new(),
+            excluded_tokens: Vec::new(),
+            strict_csv: false,
         }
     }
 
     #[test]
     fn test_multi_path_analyzer_creation() {
-        let wmnt = Token::WMNT(Address::ZERO);
+        let wmnt = Token::new(Address::ZERO, "WMNT", 18);
         let config = create_test_config();
         let analyzer = MultiPathAnalyzer::new(wmnt, &config);
         
@@ -351,17 +1212,711 @@ mod tests {
     }
 
     #[test]
-    fn test_batch_reserves_fetcher() {
-        let mut fetcher = BatchReservesFetcher::new();
-        
-        let addr1 = Address::from([1u8; 20]);
-        let addr2 = Address::from([2u8; 20]);
-        
-        fetcher.add_pool_address(addr1);
-        fetcher.add_pool_address(addr2);
-        fetcher.add_pool_address(addr1); // Duplicate should be ignored
-        
-        assert_eq!(fetcher.get_pool_addresses().len(), 2);
+    fn test_registered_tokens_load_from_csv() {
+        use std::io::Write;
+
+        let meth = Token::new(Address::with_last_byte(0xa1), "mETH", 18);
+        crate::token_registry::register(meth.clone());
+        let wmnt = Token::parse("WMNT").unwrap();
+        let mut analyzer = MultiPathAnalyzer::new(wmnt.clone(), &create_test_config());
+
+        let (token_a, token_b) = analyzer.parse_token_pair("mETH-WMNT").unwrap();
+        assert_eq!((token_a.symbol(), token_b.symbol()), ("mETH", "WMNT"));
+        assert!(analyzer.parse_token_pair("NOTATOKEN-WMNT").is_none());
+
+        let mut csv = tempfile::NamedTempFile::new().unwrap();
+        writeln!(csv, "Protocol,Pair Name,Pair Address,TokenA Reserves,TokenB Reserves").unwrap();
+        writeln!(csv, "MOE,mETH-WMNT,0x00000000000000000000000000000000000000b1,10,30000").unwrap();
+        writeln!(csv, "MOE,MOE-WMNT,0x00000000000000000000000000000000000000b2,500,1000").unwrap();
+        analyzer.load_pools_from_csv(csv.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(analyzer.get_graph_stats(), (3, 4));
+        let pool = analyzer.graph.get_pool_info(&meth, &wmnt).unwrap();
+        assert_eq!(pool.token_a.symbol(), "mETH");
+        assert_eq!(pool.reserves_a, 10.0);
+    }
+
+    #[test]
+    fn test_excluded_pools_and_tokens_skipped_from_csv() {
+        use std::io::Write;
+
+        let mut csv = tempfile::NamedTempFile::new().unwrap();
+        writeln!(csv, "Protocol,Pair Name,Pair Address,TokenA Reserves,TokenB Reserves").unwrap();
+        writeln!(csv, "MOE,MOE-WMNT,0x00000000000000000000000000000000000000d1,500,1000").unwrap();
+        writeln!(csv, "MOE,JOE-MOE,0x00000000000000000000000000000000000000d2,500,1000").unwrap();
+        writeln!(csv, "MOE,JOE-WMNT,0x00000000000000000000000000000000000000d3,500,1000").unwrap();
+        let csv_path = csv.path().to_str().unwrap();
+        let wmnt = Token::parse("WMNT").unwrap();
+
+        let mut config = create_test_config();
+        config.excluded_pools = vec!["0x00000000000000000000000000000000000000d2".parse().unwrap()];
+        let mut analyzer = MultiPathAnalyzer::new(wmnt.clone(), &config);
+        analyzer.load_pools_from_csv(csv_path).unwrap();
+        assert_eq!(analyzer.get_graph_stats(), (3, 4));
+        assert_eq!(analyzer.excluded_edge_count(), 2);
+        assert!(analyzer.graph.pool("0x00000000000000000000000000000000000000d2".parse().unwrap()).is_none());
+
+        // Excluding a token drops every pool trading it
+        config.excluded_pools.clear();
+        config.excluded_tokens = vec![Token::parse("JOE").unwrap().address()];
+        let mut analyzer = MultiPathAnalyzer::new(wmnt, &config);
+        analyzer.load_pools_from_csv(csv_path).unwrap();
+        assert_eq!(analyzer.get_graph_stats(), (2, 2));
+        assert_eq!(analyzer.excluded_edge_count(), 4);
+    }
+
+    #[test]
+    fn test_mixed_validity_csv_reports_skipped_rows() {
+        use std::io::Write;
+
+        let mut csv = tempfile::NamedTempFile::new().unwrap();
+        writeln!(csv, "Protocol,Pair Name,Pair Address,TokenA Reserves,TokenB Reserves").unwrap();
+        writeln!(csv, "MOE,MOE-WMNT,0x00000000000000000000000000000000000000f1,500,1000").unwrap();
+        writeln!(csv, "MOE,JOE-MOE,0x00000000000000000000000000000000000000f2,500,1000").unwrap();
+        writeln!(csv, "MOE,JOE-WMNT,0x00000000000000000000000000000000000000f3,500,1000").unwrap();
+        writeln!(csv, "MOE,FOO-WMNT,0x00000000000000000000000000000000000000f4,500,1000").unwrap();
+        writeln!(csv, "MOE,MOE-BAR,0x00000000000000000000000000000000000000f5,500,1000").unwrap();
+        writeln!(csv, "MOE,MOE-WMNT,0xnot-an-address,500,1000").unwrap();
+        writeln!(csv, "MOE,JOE-WMNT,0x00000000000000000000000000000000000000f6,lots,1000").unwrap();
+        writeln!(csv, "MOE,JOE-WMNT,0x00000000000000000000000000000000000000f7,500,-1").unwrap();
+        writeln!(csv, "MOE,WMNT-WMNT,0x00000000000000000000000000000000000000f8,500,1000").unwrap();
+        writeln!(csv, "MOE,MOE-WMNT").unwrap();
+        writeln!(csv, "MOE,MOE-WMNT,0x00000000000000000000000000000000000000f1,600,1000").unwrap();
+        let csv_path = csv.path().to_str().unwrap();
+        let wmnt = Token::parse("WMNT").unwrap();
+
+        let mut config = create_test_config();
+        let mut analyzer = MultiPathAnalyzer::new(wmnt.clone(), &config);
+        let report = analyzer.load_pools_from_csv(csv_path).unwrap();
+        assert_eq!(report, CsvLoadReport {
+            loaded: 3,
+            missing_columns: 1,
+            bad_address: 1,
+            unknown_symbol: 2,
+            same_token: 1,
+            bad_reserves: 2,
+            duplicate: 1,
+        });
+        assert_eq!(report.skipped(), 8);
+        assert_eq!(
+            report.skip_reasons(),
+            "1 missing columns, 1 bad address, 2 unknown symbol, 1 same token, 2 bad reserves, 1 duplicate pool"
+        );
+        assert_eq!(analyzer.get_graph_stats(), (3, 6));
+
+        // Strict mode refuses the file instead of loading a partial graph
+        config.strict_csv = true;
+        let mut analyzer = MultiPathAnalyzer::new(wmnt, &config);
+        let err = analyzer.load_pools_from_csv(csv_path).unwrap_err();
+        assert!(matches!(err, ArbError::Config(_)));
+        assert!(err.to_string().contains("8 rows skipped"));
+    }
+
+    #[test]
+    fn test_csv_reserves_match_raw_chain_updates() {
+        use std::io::Write;
+
+        let usdc = Token::new("0x09Bc4E0D864854c6aFB6eB9A9cdF58aC190D0dF9".parse().unwrap(), "USDC", 6);
+        crate::token_registry::register(usdc.clone());
+        let wmnt = Token::parse("WMNT").unwrap();
+        let pool: Address = "0x00000000000000000000000000000000000000e1".parse().unwrap();
+        let mut analyzer = MultiPathAnalyzer::new(wmnt.clone(), &create_test_config());
+
+        let mut csv = tempfile::NamedTempFile::new().unwrap();
+        writeln!(csv, "Protocol,Pair Name,Pair Address,TokenA Reserves,TokenB Reserves").unwrap();
+        writeln!(csv, "MOE,USDC-WMNT,{},2000,1000", pool).unwrap();
+        analyzer.load_pools_from_csv(csv.path().to_str().unwrap()).unwrap();
+        let loaded = analyzer.graph.pool(pool).unwrap();
+        assert_eq!((loaded.reserves_a, loaded.reserves_b), (2000.0, 1000.0));
+        let rate = loaded.get_rate_a_to_b();
+
+        // getReserves reports raw amounts: 6 decimals for USDC, 18 for WMNT, in either order
+        let raw_usdc = U256::from(2_000_000_000u64);
+        let raw_wmnt = U256::from(1_000u64) * U256::from(10u64).pow(U256::from(18u64));
+        for reserves in [
+            PoolReserves::new(usdc.clone(), raw_usdc, wmnt.clone(), raw_wmnt, 2, pool),
+            PoolReserves::new(wmnt.clone(), raw_wmnt, usdc.clone(), raw_usdc, 3, pool),
+        ] {
+            analyzer.update_pool_reserves(&HashMap::from([(pool, reserves)]));
+            let updated = analyzer.graph.pool(pool).unwrap();
+            assert!((updated.reserves_a - 2000.0).abs() < 1e-9);
+            assert!((updated.reserves_b - 1000.0).abs() < 1e-9);
+            assert!((updated.get_rate_a_to_b() - rate).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_csv_fee_column_sets_per_hop_fees() {
+        use std::io::Write;
+
+        let wmnt = Token::parse("WMNT").unwrap();
+        let load = |rows: &[&str]| {
+            let mut csv = tempfile::NamedTempFile::new().unwrap();
+            writeln!(csv, "Protocol,Pair Name,Pair Address,TokenA Reserves,TokenB Reserves,Fee").unwrap();
+            for row in rows {
+                writeln!(csv, "{}", row).unwrap();
+            }
+            let mut analyzer = MultiPathAnalyzer::new(wmnt.clone(), &create_test_config());
+            analyzer.load_pools_from_csv(csv.path().to_str().unwrap()).unwrap();
+            analyzer
+        };
+
+        // Same reserves; the mixed-tier file sets two hops to 0.05% and 1% and leaves one on the default
+        let uniform = load(&[
+            "MOE,WMNT-MOE,0x00000000000000000000000000000000000000c1,1000,2100",
+            "MOE,MOE-JOE,0x00000000000000000000000000000000000000c2,2000,500",
+            "MOE,JOE-WMNT,0x00000000000000000000000000000000000000c3,500,1000",
+        ]);
+        let mixed = load(&[
+            "MOE,WMNT-MOE,0x00000000000000000000000000000000000000c1,1000,2100,0.0005",
+            "MOE,MOE-JOE,0x00000000000000000000000000000000000000c2,2000,500,0.01",
+            "MOE,JOE-WMNT,0x00000000000000000000000000000000000000c3,500,1000,",
+        ]);
+
+        let cycle = ArbitragePath::new(
+            vec![wmnt.clone(), Token::parse("MOE").unwrap(), Token::parse("JOE").unwrap(), wmnt.clone()],
+            vec![Address::ZERO; 3],
+        );
+        let (uniform_pools, uniform_fees) = uniform.cycle_to_pools(&cycle).unwrap();
+        let (mixed_pools, mixed_fees) = mixed.cycle_to_pools(&cycle).unwrap();
+        assert_eq!(uniform_pools, mixed_pools);
+        assert_eq!(uniform_fees, vec![0.003; 3]);
+        assert_eq!(mixed_fees, vec![0.0005, 0.01, 0.003]);
+
+        let uniform_profit = arbitrage_profit_with_fees(10.0, &uniform_pools, &uniform_fees);
+        let mixed_profit = arbitrage_profit_with_fees(10.0, &mixed_pools, &mixed_fees);
+        assert!(mixed_profit < uniform_profit);
+
+        let (_, uniform_best, _) = find_best_input_with_fees(&uniform_pools, &uniform_fees, 100);
+        let (_, mixed_best, _) = find_best_input_with_fees(&mixed_pools, &mixed_fees, 100);
+        assert!((uniform_best - mixed_best).abs() > 1e-6);
+    }
+
+    #[test]
+    fn test_pools_from_all_protocols_share_one_graph() {
+        use std::io::Write;
+        use crate::constants::DEFAULT_AGNI_FEE;
+
+        let wmnt = Token::parse("WMNT").unwrap();
+        let joe = Token::parse("JOE").unwrap();
+        let moe_pool: Address = "0x00000000000000000000000000000000000000c3".parse().unwrap();
+        let agni_pool: Address = "0x00000000000000000000000000000000000000a3".parse().unwrap();
+
+        // MOE and Agni both list JOE-WMNT; Agni pays slightly more WMNT per JOE, too little for a two-pool round trip
+        let mut csv = tempfile::NamedTempFile::new().unwrap();
+        writeln!(csv, "Protocol,Pair Name,Pair Address,TokenA Reserves,TokenB Reserves").unwrap();
+        writeln!(csv, "MOE,WMNT-MOE,0x00000000000000000000000000000000000000c1,1000,2100").unwrap();
+        writeln!(csv, "MOE,MOE-JOE,0x00000000000000000000000000000000000000c2,2000,500").unwrap();
+        writeln!(csv, "MOE,JOE-WMNT,{},500,1000", moe_pool).unwrap();
+        writeln!(csv, "Agni Finance,JOE-WMNT,{},500,1004", agni_pool).unwrap();
+
+        let mut config = create_test_config();
+        config.dex_fee = 0.002;
+        let mut analyzer = MultiPathAnalyzer::new(wmnt.clone(), &config);
+        analyzer.load_pools_from_csv(csv.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(analyzer.graph.hop_pool(&joe, &wmnt, Some(moe_pool)).unwrap().fee, 0.002);
+        assert_eq!(analyzer.graph.hop_pool(&joe, &wmnt, Some(agni_pool)).unwrap().fee, DEFAULT_AGNI_FEE);
+
+        // The cycle exits through the better-priced Agni pool
+        let result = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap();
+        let best = result.opportunities
+            .iter()
+            .max_by(|a, b| a.gross_profit.partial_cmp(&b.gross_profit).unwrap())
+            .unwrap();
+        assert!(best.gross_profit > 0.0);
+        assert!(best.path.as_ref().unwrap().pools.contains(&agni_pool));
+
+        // Reserve updates land on the matching pool only
+        let mut reserves = HashMap::new();
+        reserves.insert(agni_pool, PoolReserves::new(
+            joe.clone(), f64_to_u256_with_decimals(500.0, 18), wmnt.clone(), f64_to_u256_with_decimals(990.0, 18), 1, agni_pool,
+        ));
+        analyzer.update_pool_reserves(&reserves);
+        let agni = analyzer.graph.hop_pool(&joe, &wmnt, Some(agni_pool)).unwrap();
+        let moe = analyzer.graph.hop_pool(&joe, &wmnt, Some(moe_pool)).unwrap();
+        assert!((agni.reserves_b - 990.0).abs() < 1e-9);
+        assert!((moe.reserves_b - 1000.0).abs() < 1e-9);
+        assert_eq!(analyzer.graph.hop_pool(&joe, &wmnt, None).unwrap().pool_address, moe_pool);
+    }
+
+    #[test]
+    fn test_duplicate_pair_rows_quote_deepest_pool() {
+        use std::io::Write;
+
+        let wmnt = Token::parse("WMNT").unwrap();
+        let moe = Token::parse("MOE").unwrap();
+        let deep_pool: Address = "0x00000000000000000000000000000000000000d1".parse().unwrap();
+        let shallow_pool: Address = "0x00000000000000000000000000000000000000d2".parse().unwrap();
+
+        // Two MOE-WMNT pools at the same price, plus a stale repeat of the deep pool's row
+        let mut csv = tempfile::NamedTempFile::new().unwrap();
+        writeln!(csv, "Protocol,Pair Name,Pair Address,TokenA Reserves,TokenB Reserves").unwrap();
+        writeln!(csv, "MOE,MOE-WMNT,{},2000,1000", deep_pool).unwrap();
+        writeln!(csv, "MOE,MOE-WMNT,{},20,10", shallow_pool).unwrap();
+        writeln!(csv, "MOE,MOE-WMNT,{},4,2", deep_pool).unwrap();
+
+        let mut analyzer = MultiPathAnalyzer::new(wmnt.clone(), &create_test_config());
+        analyzer.load_pools_from_csv(csv.path().to_str().unwrap()).unwrap();
+
+        // Both pools are parallel edges; the repeated row added nothing and did not overwrite the first
+        assert_eq!(analyzer.graph.edge_count(), 4);
+        let deep = analyzer.graph.hop_pool(&moe, &wmnt, Some(deep_pool)).unwrap();
+        assert!((deep.reserves_a - 2000.0).abs() < 1e-9);
+
+        // Equal prices quote through the higher-liquidity pool in both directions
+        assert_eq!(analyzer.graph.get_pool_info(&wmnt, &moe).unwrap().pool_address, deep_pool);
+        assert_eq!(analyzer.graph.get_pool_info(&moe, &wmnt).unwrap().pool_address, deep_pool);
+    }
+
+    /// Analyzer over a mispriced WMNT/MOE/JOE triangle
+    fn create_triangle_analyzer(config: &Config) -> MultiPathAnalyzer {
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let mut analyzer = MultiPathAnalyzer::new(wmnt.clone(), config);
+
+        let unit = U256::from(10u64).pow(U256::from(18u64));
+        let pools = [
+            (wmnt.clone(), 1_000u64, moe.clone(), 900u64, [1u8; 20]),
+            (moe, 1_000, joe.clone(), 1_100, [2u8; 20]),
+            (joe, 1_000, wmnt, 1_200, [3u8; 20]),
+        ];
+        for (token_a, reserve_a, token_b, reserve_b, addr) in pools {
+            let reserves = PoolReserves::new(
+                token_a, U256::from(reserve_a) * unit,
+                token_b, U256::from(reserve_b) * unit,
+                0, Address::from(addr),
+            );
+            analyzer.graph.add_pool(&reserves, config.dex_fee);
+        }
+        analyzer
+    }
+
+    #[test]
+    fn test_search_objective_is_graph_path_profit() {
+        let config = create_test_config();
+        let mut analyzer = create_triangle_analyzer(&config);
+        let result = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap();
+        assert!(!result.opportunities.is_empty());
+
+        // Reported profit is the graph's path profit at the chosen input, to the bit
+        for opportunity in &result.opportunities {
+            let cycle = opportunity.path.as_ref().unwrap();
+            assert_eq!(analyzer.graph.calculate_path_profit(cycle, opportunity.optimal_input), Some(opportunity.gross_profit));
+            let (pools, fees) = analyzer.cycle_to_pools(cycle).unwrap();
+            for input in [0.0, 1.0, 25.0, 400.0] {
+                assert_eq!(analyzer.graph.calculate_path_profit(cycle, input), Some(arbitrage_profit_with_fees(input, &pools, &fees)));
+            }
+        }
+
+        // A path that stops short of WMNT is closed the same way by both
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let open = ArbitragePath::new(vec![wmnt.clone(), moe.clone(), joe.clone()], vec![Address::from([1u8; 20]), Address::from([2u8; 20])]);
+        let closed = ArbitragePath::new(vec![wmnt.clone(), moe, joe, wmnt], vec![Address::from([1u8; 20]), Address::from([2u8; 20]), Address::from([3u8; 20])]);
+        assert_eq!(analyzer.cycle_to_pools(&open), analyzer.cycle_to_pools(&closed));
+        assert_eq!(analyzer.graph.calculate_path_profit(&open, 10.0), analyzer.graph.calculate_path_profit(&closed, 10.0));
+    }
+
+    #[test]
+    fn test_add_pools_builds_graph_from_pool_set() {
+        let config = create_test_config();
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let unit = U256::from(10u64).pow(U256::from(18u64));
+        let pools = [
+            PoolReserves::new(wmnt.clone(), unit * U256::from(1_000u64), moe.clone(), unit * U256::from(900u64), 0, Address::from([1u8; 20])),
+            PoolReserves::new(moe.clone(), unit * U256::from(1_000u64), joe.clone(), unit * U256::from(1_100u64), 0, Address::from([2u8; 20])),
+        ];
+
+        let mut analyzer = MultiPathAnalyzer::new(wmnt.clone(), &config);
+        analyzer.add_pools(&pools);
+
+        // One edge per swap direction
+        assert_eq!(analyzer.get_graph_stats(), (3, 4));
+        let edge = analyzer.graph.get_pool_info(&wmnt, &moe).unwrap();
+        assert_eq!(edge.pool_address, Address::from([1u8; 20]));
+        assert!(analyzer.graph.get_pool_info(&moe, &joe).is_some());
+        assert!(analyzer.graph.get_pool_info(&wmnt, &joe).is_none());
+    }
+
+    #[test]
+    fn test_optimum_clamped_to_input_range() {
+        let config = create_test_config();
+        let unconstrained = create_triangle_analyzer(&config)
+            .find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap()
+            .opportunities.remove(0);
+        assert!(unconstrained.gross_profit > 0.0);
+
+        // A max below the optimum caps the input at the max, at lower profit
+        let max_input = unconstrained.optimal_input / 2.0;
+        let clamped = create_triangle_analyzer(&config)
+            .find_all_opportunities((max_input / 10.0, max_input), 100).unwrap()
+            .opportunities.remove(0);
+        assert!((clamped.optimal_input - max_input).abs() / max_input < 1e-6);
+        assert!(clamped.gross_profit > 0.0 && clamped.gross_profit < unconstrained.gross_profit);
+
+        // A min above the optimum forces the input up to the min
+        let min_input = unconstrained.optimal_input * 2.0;
+        let raised = create_triangle_analyzer(&config)
+            .find_all_opportunities((min_input, min_input * 10.0), 100).unwrap()
+            .opportunities.remove(0);
+        assert!((raised.optimal_input - min_input).abs() / min_input < 1e-6);
+    }
+
+    #[test]
+    fn test_usdc_base_token_cycles() {
+        let config = create_test_config();
+        let usdc = Token::new(Address::from([9u8; 20]), "USDC", 6);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let pools: Vec<PoolReserves> = [
+            (usdc.clone(), 1_000.0, moe.clone(), 900.0),
+            (moe.clone(), 1_000.0, joe.clone(), 1_100.0),
+            (joe.clone(), 1_000.0, usdc.clone(), 1_200.0),
+            (wmnt.clone(), 1_000.0, moe.clone(), 1_000.0),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (token_a, reserve_a, token_b, reserve_b))| {
+            let (reserve_a, reserve_b) = (
+                f64_to_u256_with_decimals(reserve_a, token_a.decimals()),
+                f64_to_u256_with_decimals(reserve_b, token_b.decimals()),
+            );
+            PoolReserves::new(token_a, reserve_a, token_b, reserve_b, 1, Address::with_last_byte(i as u8 + 1))
+        })
+        .collect();
+
+        let mut analyzer = MultiPathAnalyzer::new(usdc.clone(), &config);
+        analyzer.add_pools(&pools);
+        let result = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap();
+
+        // The mispriced USDC triangle is found and every route starts and ends in USDC
+        assert!(result.has_profitable_opportunities());
+        for opportunity in &result.opportunities {
+            let path = opportunity.path.as_ref().unwrap();
+            assert_eq!((path.tokens.first(), path.tokens.last()), (Some(&usdc), Some(&usdc)));
+            assert!(!path.tokens.contains(&wmnt));
+        }
+        let best = result.best_opportunity.unwrap();
+        assert_eq!(best.path.unwrap().description(), "USDC -> MOE -> JOE -> USDC");
+    }
+
+    #[test]
+    fn test_fixed_input_below_optimum_profit() {
+        let config = create_test_config();
+        let mut analyzer = create_triangle_analyzer(&config);
+        let optimum = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap().opportunities.remove(0);
+        let cycle = optimum.path.clone().unwrap();
+
+        // At the optimum's own size the fixed quote matches the search
+        let at_optimum = analyzer.evaluate_fixed_input(&cycle, optimum.optimal_input).unwrap();
+        assert_eq!(at_optimum.search_method, "fixed_input");
+        assert!((at_optimum.net_profit - optimum.net_profit).abs() < 1e-9);
+
+        // Any other held amount nets less, and is priced at exactly that amount
+        for input in [optimum.optimal_input / 2.0, optimum.optimal_input * 2.0] {
+            let fixed = analyzer.evaluate_fixed_input(&cycle, input).unwrap();
+            assert_eq!(fixed.optimal_input, input);
+            assert_eq!(fixed.hop_outputs.last().copied(), Some(fixed.final_output));
+            assert!(fixed.net_profit < optimum.net_profit);
+            assert!(fixed.binding_constraint.is_none());
+        }
+    }
+
+    #[test]
+    fn test_tightest_input_limit_governs() {
+        let unconstrained = create_triangle_analyzer(&create_test_config())
+            .find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap()
+            .opportunities.remove(0);
+        assert!(unconstrained.binding_constraint.is_none());
+        assert_eq!(unconstrained.optimal_input, unconstrained.unconstrained_input);
+
+        // (impact %, capital, reserve fraction) -> expected binding constraint
+        let cases = [
+            (0.1, 20.0, 0.02, InputConstraint::PriceImpact),
+            (5.0, 5.0, 0.02, InputConstraint::Capital),
+            (5.0, 20.0, 0.002, InputConstraint::ReserveCap),
+        ];
+        for (impact_pct, capital, reserve_fraction, expected) in cases {
+            let mut config = create_test_config();
+            config.max_price_impact_pct = impact_pct;
+            config.max_capital = capital;
+            config.max_reserve_fraction = reserve_fraction;
+            let mut analyzer = create_triangle_analyzer(&config);
+            let opportunity = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap()
+                .opportunities.remove(0);
+
+            assert_eq!(opportunity.binding_constraint, Some(expected));
+            assert_eq!(opportunity.unconstrained_input, unconstrained.optimal_input);
+            assert!(opportunity.optimal_input < unconstrained.optimal_input);
+            assert!(opportunity.gross_profit < unconstrained.gross_profit);
+
+            // The capped input satisfies every active limit
+            let (pools, fees) = analyzer.cycle_to_pools(opportunity.path.as_ref().unwrap()).unwrap();
+            assert!(max_price_impact(opportunity.optimal_input, &pools, &fees) <= impact_pct / 100.0 + 1e-9);
+            assert!(opportunity.optimal_input <= capital + 1e-9);
+            assert!(opportunity.optimal_input <= pools[0].0 * reserve_fraction + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_price_impact_cap_is_searched() {
+        let mut config = create_test_config();
+        config.max_price_impact_pct = 0.5;
+        let mut analyzer = create_triangle_analyzer(&config);
+        let opportunity = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap()
+            .opportunities.remove(0);
+        assert_eq!(opportunity.binding_constraint, Some(InputConstraint::PriceImpact));
+
+        // The chosen input is the search's optimum under the cap, not a clamp of the free optimum
+        let (pools, fees) = analyzer.cycle_to_pools(opportunity.path.as_ref().unwrap()).unwrap();
+        let cap = config.max_price_impact_pct / 100.0;
+        let (capped_input, _, _) = find_best_input_within_impact(&pools, &fees, UNBOUNDED_INPUT, cap, 200);
+        assert!((opportunity.optimal_input - capped_input).abs() < 1e-3 * capped_input);
+        assert!(max_price_impact(opportunity.optimal_input, &pools, &fees) <= cap + 1e-9);
+        assert!(opportunity.optimal_input < opportunity.unconstrained_input);
+
+        // No input within the cap does better
+        for step in 1..=100 {
+            let input = opportunity.optimal_input * step as f64 / 100.0;
+            assert!(arbitrage_profit_with_fees(input, &pools, &fees) <= opportunity.gross_profit + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_analysis_timings_sum_to_total() {
+        let mut analyzer = create_triangle_analyzer(&create_test_config());
+        let result = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap();
+        let timings = &result.timings;
+
+        let phases = timings.cycle_detection + timings.cycle_analysis;
+        assert!(phases <= timings.total);
+        assert!(timings.total - phases < Duration::from_millis(5));
+        assert!(timings.cycle_analysis_max <= timings.cycle_analysis_total);
+        assert!(timings.conversion_total <= timings.cycle_analysis_total);
+        assert_eq!(timings.total.as_millis() as u64, result.analysis_time_ms);
+    }
+
+    #[test]
+    fn test_gas_overhead_reduces_net_profit() {
+        let mut config = create_test_config();
+        let baseline = create_triangle_analyzer(&config)
+            .find_all_opportunities(UNBOUNDED_INPUT, 100)
+            .unwrap()
+            .best_opportunity
+            .unwrap();
+
+        config.approval_gas_units = 50_000_000;
+        config.wrap_gas_units = 30_000_000;
+        let with_overhead = create_triangle_analyzer(&config)
+            .find_all_opportunities(UNBOUNDED_INPUT, 100)
+            .unwrap()
+            .best_opportunity
+            .unwrap();
+
+        // The test triangle's base token is not the real WMNT, so only approvals are charged
+        let path = baseline.path.as_ref().unwrap();
+        let overhead_units = config.gas_overhead().units_for(path);
+        assert_eq!(overhead_units, 50_000_000 * path.pools.len() as u64);
+
+        let overhead_cost = config.calculate_gas_cost(overhead_units);
+        assert_eq!(with_overhead.gross_profit, baseline.gross_profit);
+        assert!((baseline.net_profit - with_overhead.net_profit - overhead_cost).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_flash_loan_fee_charged_above_wallet_balance() {
+        let mut config = create_test_config();
+        let unlimited = create_triangle_analyzer(&config)
+            .find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap()
+            .opportunities.remove(0);
+        assert!(!unlimited.requires_flash_loan);
+        assert_eq!(unlimited.flash_loan_fee, 0.0);
+
+        // A wallet holding a quarter of the optimal input borrows the rest
+        config.wallet_balance_wmnt = unlimited.optimal_input / 4.0;
+        config.flash_loan_fee_bps = 9.0;
+        let borrowed = create_triangle_analyzer(&config)
+            .find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap()
+            .opportunities.remove(0);
+        let expected_fee = (unlimited.optimal_input - config.wallet_balance_wmnt) * 0.0009;
+        assert!(borrowed.requires_flash_loan);
+        assert!((borrowed.flash_loan_fee - expected_fee).abs() < 1e-9);
+        assert_eq!(borrowed.gross_profit, unlimited.gross_profit);
+        assert!((unlimited.net_profit - borrowed.net_profit - expected_fee).abs() < 1e-9);
+        assert!(borrowed.profit_percentage < unlimited.profit_percentage);
+
+        // A wallet covering the whole input needs no loan
+        config.wallet_balance_wmnt = unlimited.optimal_input * 2.0;
+        let funded = create_triangle_analyzer(&config)
+            .find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap()
+            .opportunities.remove(0);
+        assert!(!funded.requires_flash_loan);
+        assert_eq!(funded.net_profit, unlimited.net_profit);
+    }
+
+    #[test]
+    fn test_token_filter_excludes_cycles() {
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let opportunity = |tokens: Vec<Token>| ArbitrageOpportunity {
+            optimal_input: 100.0,
+            final_output: 101.0,
+            gross_profit: 1.0,
+            net_profit: 0.5,
+            profit_percentage: 0.5,
+            search_method: "test".to_string(),
+            path: Some(ArbitragePath::new(tokens, vec![Address::ZERO; 3])),
+            robustness: 1.0,
+            unconstrained_input: 100.0,
+            binding_constraint: None,
+            price_impact_percent: 0.0,
+            requires_flash_loan: false,
+            flash_loan_fee: 0.0,
+            min_pool_depth: 0.0,
+            expected_realized_profit: 0.0,
+            hop_outputs: Vec::new(),
+            confidence: 1.0,
+        };
+        let through_joe = opportunity(vec![wmnt.clone(), moe.clone(), joe.clone(), wmnt.clone()]);
+        let without_joe = opportunity(vec![wmnt.clone(), moe, wmnt]);
+        assert!(through_joe.touches_token(&joe));
+        assert!(!without_joe.touches_token(&joe));
+
+        let filter = TokenFilter::new(Vec::new(), vec![joe.clone()]);
+        let filtered = filter.apply(MultiPathOpportunity::new(vec![through_joe.clone(), without_joe.clone()], 1));
+        assert_eq!(filtered.opportunities.len(), 1);
+        assert!(!filtered.opportunities[0].touches_token(&joe));
+
+        let required = TokenFilter::new(vec![joe], Vec::new());
+        assert!(required.allows(&through_joe));
+        assert!(!required.allows(&without_joe));
+    }
+
+    #[test]
+    fn test_min_profit_threshold_separates_dust() {
+        let opportunity = |net_profit: f64| ArbitrageOpportunity {
+            optimal_input: 100.0,
+            final_output: 100.0 + net_profit,
+            gross_profit: net_profit,
+            net_profit,
+            profit_percentage: net_profit,
+            search_method: "test".to_string(),
+            path: None,
+            robustness: 1.0,
+            unconstrained_input: 100.0,
+            binding_constraint: None,
+            price_impact_percent: 0.0,
+            requires_flash_loan: false,
+            flash_loan_fee: 0.0,
+            min_pool_depth: 0.0,
+            expected_realized_profit: 0.0,
+            hop_outputs: Vec::new(),
+            confidence: 1.0,
+        };
+        assert!(opportunity(0.0001).is_profitable());
+        assert!(!opportunity(0.0001).is_profitable_with_threshold(0.05));
+        assert!(opportunity(0.05).is_profitable_with_threshold(0.05));
+        assert!(!opportunity(-1.0).is_profitable_with_threshold(0.0));
+
+        let threshold = AdaptiveProfitThreshold::new(0.05, 0.0);
+        let scan = MultiPathOpportunity::new(vec![opportunity(0.0001), opportunity(0.02), opportunity(-0.3)], 1);
+        let dust = threshold.best_below(&scan, 0.05).unwrap();
+        assert_eq!(dust.net_profit, 0.02);
+
+        let actionable = threshold.apply(scan, 0.05);
+        assert!(!actionable.has_profitable_opportunities());
+        assert_eq!(actionable.opportunities.len(), 1);
+    }
+
+    #[test]
+    fn test_adaptive_threshold_rises_with_gas_volatility() {
+        let threshold = AdaptiveProfitThreshold::new(0.5, 2.0);
+        let mut gas_history = GasPriceHistory::new(5);
+
+        // No history yet: static fallback
+        assert_eq!(threshold.effective(&gas_history), 0.5);
+
+        for _ in 0..5 {
+            gas_history.record(0.02);
+        }
+        let calm = threshold.effective(&gas_history);
+        assert_eq!(calm, 0.5);
+
+        for gas in [0.03, 0.05] {
+            gas_history.record(gas);
+        }
+        let choppy = threshold.effective(&gas_history);
+        for gas in [0.01, 0.08, 0.02] {
+            gas_history.record(gas);
+        }
+        let volatile = threshold.effective(&gas_history);
+        assert!(choppy > calm);
+        assert!(volatile > choppy);
+
+        // Static mode ignores volatility
+        assert_eq!(AdaptiveProfitThreshold::new(0.5, 0.0).effective(&gas_history), 0.5);
+    }
+
+    #[test]
+    fn test_input_range_validation() {
+        assert!(validate_input_range((100.0, 10000.0)).is_ok());
+
+        let inverted = validate_input_range((10000.0, 100.0)).unwrap_err().to_string();
+        assert!(inverted.contains("min must be less than max"));
+
+        let negative = validate_input_range((-5.0, 100.0)).unwrap_err().to_string();
+        assert!(negative.contains("must be positive"));
+
+        let zero_width = validate_input_range((100.0, 100.0)).unwrap_err().to_string();
+        assert!(zero_width.contains("min must be less than max"));
+
+        let mut analyzer = create_triangle_analyzer(&create_test_config());
+        assert!(matches!(analyzer.find_all_opportunities((10000.0, 100.0), 100), Err(ArbError::Math(_))));
+
+        let mut config = create_test_config();
+        config.min_input_wmnt = config.max_input_wmnt;
+        assert!(matches!(analyze_snapshot(&HashMap::new(), &config), Err(ArbError::Math(_))));
+    }
+
+    #[test]
+    fn test_cycle_cap_prioritizes_deviation() {
+        let wmnt = Token::new(Address::ZERO, "WMNT", 18);
+        let scored: Vec<(ArbitragePath, f64)> = (0..10u8)
+            .map(|i| {
+                let pool = Address::from([i; 20]);
+                let path = ArbitragePath::new(vec![wmnt.clone(), Token::new(pool, "MOE", 18), wmnt.clone()], vec![pool, pool]);
+                (path, i as f64 * 0.001)
+            })
+            .collect();
+        let pool_id = |path: &ArbitragePath| path.pools[0].0[0];
+
+        let (selected, deferred, rotated) = prioritize_cycles(scored.clone(), 4, 0);
+        assert_eq!(selected.len(), 4);
+        assert_eq!(deferred, 6);
+        assert_eq!(rotated, 1);
+        // Highest-deviation cycles take the priority slots
+        let priority: Vec<u8> = selected[..3].iter().map(pool_id).collect();
+        assert_eq!(priority, vec![9, 8, 7]);
+
+        // Lower-ranked cycles rotate through the remaining slot across blocks
+        let mut covered = std::collections::HashSet::new();
+        for block in 0..7 {
+            let (selected, _, _) = prioritize_cycles(scored.clone(), 4, block);
+            covered.insert(pool_id(&selected[3]));
+        }
+        assert_eq!(covered.len(), 7);
+
+        // Under the cap nothing is deferred
+        let (selected, deferred, _) = prioritize_cycles(scored, 20, 0);
+        assert_eq!(selected.len(), 10);
+        assert_eq!(deferred, 0);
     }
 
     #[test]
@@ -375,6 +1930,16 @@ mod tests {
                 profit_percentage: 4.0,
                 search_method: "test".to_string(),
                 path: None,
+                robustness: 1.0,
+                unconstrained_input: 100.0,
+                binding_constraint: None,
+                price_impact_percent: 0.5,
+                requires_flash_loan: false,
+                flash_loan_fee: 0.0,
+                min_pool_depth: 0.0,
+                expected_realized_profit: 0.0,
+                hop_outputs: Vec::new(),
+                confidence: 1.0,
             },
             ArbitrageOpportunity {
                 optimal_input: 200.0,
@@ -384,11 +1949,448 @@ mod tests {
                 profit_percentage: 4.0,
                 search_method: "test".to_string(),
                 path: None,
+                robustness: 1.0,
+                unconstrained_input: 200.0,
+                binding_constraint: None,
+                price_impact_percent: 3.0,
+                requires_flash_loan: false,
+                flash_loan_fee: 0.0,
+                min_pool_depth: 0.0,
+                expected_realized_profit: 0.0,
+                hop_outputs: Vec::new(),
+                confidence: 1.0,
             },
         ];
 
         let best = StrategySelector::select_best(&opportunities, OptimizationStrategy::MaxProfit);
         assert!(best.is_some());
         assert_eq!(best.unwrap().net_profit, 8.0);
+
+        // The larger trade moves prices too much under a 1% cap
+        let min_slippage = |cap| StrategySelector::select_best(&opportunities, OptimizationStrategy::MinSlippage { max_impact_percent: cap })
+            .unwrap()
+            .net_profit;
+        assert_eq!(min_slippage(1.0), 4.0);
+        assert_eq!(min_slippage(5.0), 8.0);
+        assert_eq!(min_slippage(0.1), 4.0); // Nothing under the cap: lowest impact wins
+    }
+
+    #[test]
+    fn test_min_risk_prefers_deeper_route() {
+        let config = create_test_config();
+        let analyzer = create_triangle_analyzer(&config);
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let cycle = ArbitragePath::new(vec![wmnt.clone(), moe, joe, wmnt], vec![Address::ZERO; 3]);
+
+        // Same prices, one route with 10x the reserves in every pool
+        let shallow_pools = [(1_000.0, 900.0), (1_000.0, 1_100.0), (1_000.0, 1_200.0)];
+        let deep_pools = shallow_pools.map(|(a, b)| (a * 10.0, b * 10.0));
+        let fees = [config.dex_fee; 3];
+        let analyze = |pools: &[(f64, f64)]| analyzer.analyze_pools(&cycle, pools, &fees, (0.1, 20.0), 100).unwrap();
+        let (shallow, deep) = (analyze(&shallow_pools), analyze(&deep_pools));
+        assert!(shallow.is_profitable() && deep.is_profitable());
+        assert!((deep.min_pool_depth - 10_000.0).abs() < 1e-6);
+        assert!(deep.risk_score() < shallow.risk_score());
+
+        let opportunities = vec![shallow, deep];
+        let safest = StrategySelector::select_best(&opportunities, OptimizationStrategy::MinRisk).unwrap();
+        assert_eq!(safest.min_pool_depth, opportunities[1].min_pool_depth);
+
+        // Hop count still dominates depth
+        let mut two_hop = opportunities[0].clone();
+        two_hop.path = Some(ArbitragePath::new(vec![Token::new(Address::ZERO, "WMNT", 18); 3], vec![Address::ZERO; 2]));
+        let with_two_hop = vec![opportunities[1].clone(), two_hop];
+        assert_eq!(StrategySelector::select_best(&with_two_hop, OptimizationStrategy::MinRisk).unwrap().hop_count(), 2);
+    }
+
+    #[test]
+    fn test_capture_ratio_scales_realized_profit() {
+        let mut config = create_test_config();
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let cycle = ArbitragePath::new(vec![wmnt.clone(), moe, joe, wmnt], vec![Address::ZERO; 3]);
+        let pools = [(1_000.0, 900.0), (1_000.0, 1_100.0), (1_000.0, 1_200.0)];
+        let fees = [config.dex_fee; 3];
+
+        // Full capture leaves net profit unchanged
+        let full = create_triangle_analyzer(&config).analyze_pools(&cycle, &pools, &fees, (0.1, 20.0), 100).unwrap();
+        assert!((full.expected_realized_profit - full.net_profit).abs() < 1e-12);
+
+        // Half capture halves the gross spread but keeps the full cost
+        config.capture_ratio = 0.5;
+        let half = create_triangle_analyzer(&config).analyze_pools(&cycle, &pools, &fees, (0.1, 20.0), 100).unwrap();
+        let costs = half.gross_profit - half.net_profit;
+        assert_eq!(half.net_profit, full.net_profit);
+        assert!((half.expected_realized_profit - (0.5 * half.gross_profit - costs)).abs() < 1e-12);
+
+        // A wide but costly route loses to a cheap one once competition is priced in
+        let cheap = ArbitrageOpportunity { gross_profit: 5.0, net_profit: 4.0, expected_realized_profit: 1.5, ..half.clone() };
+        let costly = ArbitrageOpportunity { gross_profit: 30.0, net_profit: 8.0, expected_realized_profit: -7.0, ..half };
+        let opportunities = vec![cheap, costly];
+        let pick = |strategy| StrategySelector::select_best(&opportunities, strategy).unwrap().net_profit;
+        assert_eq!(pick(OptimizationStrategy::MaxProfit), 8.0);
+        assert_eq!(pick(OptimizationStrategy::MaxRealizedProfit), 4.0);
+    }
+
+    #[test]
+    fn test_confidence_falls_with_reserve_volatility() {
+        let mut config = create_test_config();
+        config.execution_delay_blocks = 2;
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let addresses = [Address::from([1u8; 20]), Address::from([2u8; 20]), Address::from([3u8; 20])];
+        let cycle = ArbitragePath::new(vec![wmnt.clone(), moe.clone(), joe.clone(), wmnt.clone()], addresses.to_vec());
+        let pools = [(1_000.0, 900.0), (1_000.0, 1_100.0), (1_000.0, 1_200.0)];
+        let fees = [config.dex_fee; 3];
+
+        // Each block moves every pool's reserve_b by the given fraction, alternating direction
+        let analyze_with_moves = |config: &Config, swing: f64| {
+            let mut analyzer = create_triangle_analyzer(config);
+            for block in 0..10u64 {
+                let factor = if block % 2 == 0 { 1.0 + swing } else { 1.0 - swing };
+                let snapshot = addresses
+                    .iter()
+                    .zip([(&wmnt, &moe), (&moe, &joe), (&joe, &wmnt)])
+                    .map(|(&address, (token_a, token_b))| {
+                        let reserves = PoolReserves::new(
+                            token_a.clone(), f64_to_u256(1_000.0),
+                            token_b.clone(), f64_to_u256(1_000.0 * factor),
+                            block, address,
+                        );
+                        (address, reserves)
+                    })
+                    .collect();
+                analyzer.record_reserve_history(&snapshot);
+            }
+            analyzer.analyze_pools(&cycle, &pools, &fees, (0.1, 20.0), 100).unwrap()
+        };
+
+        let calm = analyze_with_moves(&config, 0.0001);
+        let volatile = analyze_with_moves(&config, 0.05);
+        assert!(calm.confidence > 0.9, "{}", calm.confidence);
+        assert!(volatile.confidence < 0.5, "{}", volatile.confidence);
+        assert_eq!(calm.net_profit, volatile.net_profit);
+        assert!(volatile.expected_realized_profit < calm.expected_realized_profit);
+
+        // Without a delay, or without history, nothing is discounted
+        let fresh = create_triangle_analyzer(&config).analyze_pools(&cycle, &pools, &fees, (0.1, 20.0), 100).unwrap();
+        assert_eq!(fresh.confidence, 1.0);
+        assert!((fresh.expected_realized_profit - fresh.net_profit).abs() < 1e-12);
+        config.execution_delay_blocks = 0;
+        assert_eq!(analyze_with_moves(&config, 0.05).confidence, 1.0);
+    }
+
+    #[test]
+    fn test_hop_outputs_follow_swap_chain() {
+        let config = create_test_config();
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let cycle = ArbitragePath::new(vec![wmnt.clone(), moe, joe, wmnt], vec![Address::ZERO; 3]);
+        let pools = [(1_000.0, 900.0), (1_000.0, 1_100.0), (1_000.0, 1_200.0)];
+        let fees = [config.dex_fee; 3];
+        let opportunity = create_triangle_analyzer(&config).analyze_pools(&cycle, &pools, &fees, (0.1, 20.0), 100).unwrap();
+
+        // One entry per hop, each the previous amount swapped through the next pool
+        let after_first = swap(pools[0].0, pools[0].1, opportunity.optimal_input, fees[0]);
+        let after_second = swap(pools[1].0, pools[1].1, after_first, fees[1]);
+        let after_third = swap(pools[2].0, pools[2].1, after_second, fees[2]);
+        assert_eq!(opportunity.hop_outputs, vec![after_first, after_second, after_third]);
+        assert_eq!(opportunity.hop_outputs.last().copied(), Some(opportunity.final_output));
+
+        // serde_json's float parsing may be off by the last bit
+        let recorded: Vec<f64> = serde_json::from_str(&opportunity.hop_outputs_json()).unwrap();
+        assert_eq!(recorded.len(), opportunity.hop_outputs.len());
+        assert!(recorded.iter().zip(&opportunity.hop_outputs).all(|(r, h)| (r - h).abs() <= 1e-12 * h));
+    }
+
+    #[test]
+    fn test_unchanged_pools_keep_weights_and_cycles() {
+        let config = create_test_config();
+        let mut analyzer = create_triangle_analyzer(&config);
+        let first = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap();
+        assert!(!first.timings.cycles_reused);
+
+        let unit = U256::from(10u64).pow(U256::from(18u64));
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let snapshot = |moe_wmnt: u64| HashMap::from([
+            (Address::from([1u8; 20]), PoolReserves::new(wmnt.clone(), U256::from(1_000u64) * unit, moe.clone(), U256::from(moe_wmnt) * unit, 1, Address::from([1u8; 20]))),
+            (Address::from([2u8; 20]), PoolReserves::new(moe.clone(), U256::from(1_000u64) * unit, joe.clone(), U256::from(1_100u64) * unit, 1, Address::from([2u8; 20]))),
+            // Reported in reverse token order; still the same reserves
+            (Address::from([3u8; 20]), PoolReserves::new(wmnt.clone(), U256::from(1_200u64) * unit, joe.clone(), U256::from(1_000u64) * unit, 1, Address::from([3u8; 20]))),
+        ]);
+
+        // Same reserves everywhere: no weight is recomputed and SPFA is not re-run
+        assert_eq!(analyzer.update_pool_reserves(&snapshot(900)), 0);
+        assert_eq!(analyzer.weight_recomputations(), 0);
+        let second = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap();
+        assert!(second.timings.cycles_reused);
+        assert_eq!(second.opportunities.len(), first.opportunities.len());
+        assert_eq!(second.best_opportunity.unwrap().net_profit, first.best_opportunity.unwrap().net_profit);
+
+        // One pool moves: only its two edges are recomputed, and cycles are searched again
+        assert_eq!(analyzer.update_pool_reserves(&snapshot(880)), 2);
+        assert_eq!(analyzer.weight_recomputations(), 2);
+        let third = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap();
+        assert!(!third.timings.cycles_reused);
+        let changed = [Address::from([1u8; 20])];
+        assert_eq!(analyzer.update_changed_pools(&snapshot(880), &changed), 0);
+    }
+
+    #[test]
+    fn test_opportunity_id_stable_across_snapshots() {
+        let config = create_test_config();
+        let mut analyzer = create_triangle_analyzer(&config);
+        let first = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap();
+
+        // Next block: same pools, different reserves
+        let unit = U256::from(10u64).pow(U256::from(18u64));
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let moved = PoolReserves::new(wmnt, U256::from(1_050u64) * unit, moe, U256::from(880u64) * unit, 1, Address::from([1u8; 20]));
+        analyzer.update_pool_reserves(&HashMap::from([(moved.pool_address, moved)]));
+        let second = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap();
+
+        let id = |multi: &MultiPathOpportunity| multi.best_opportunity.as_ref().unwrap().path.as_ref().unwrap().opportunity_id();
+        assert_ne!(first.best_opportunity.as_ref().unwrap().net_profit, second.best_opportunity.as_ref().unwrap().net_profit);
+        assert_eq!(id(&first), id(&second));
+        assert_eq!(id(&first).len(), 16);
+        let reversed = ArbitragePath::new(Vec::new(), vec![Address::from([3u8; 20]), Address::from([2u8; 20]), Address::from([1u8; 20])]);
+        assert_ne!(reversed.opportunity_id(), id(&first));
+
+        // The tracker spans both snapshots and reports the route once it disappears
+        let mut tracker = OpportunityTracker::new();
+        assert!(tracker.observe(100, &first).is_empty());
+        assert!(tracker.observe(101, &second).is_empty());
+        assert_eq!(tracker.first_seen(&id(&first)), Some(100));
+        let ended = tracker.observe(102, &MultiPathOpportunity::new(Vec::new(), 0));
+        assert_eq!(ended.len(), 1);
+        assert_eq!(ended[0].opportunity_id, id(&first));
+        assert_eq!(ended[0].persisted_blocks(), 2);
+        assert_eq!(tracker.first_seen(&id(&first)), None);
+    }
+
+    #[test]
+    fn test_reprice_at_next_block_reserves() {
+        let config = create_test_config();
+        let mut analyzer = create_triangle_analyzer(&config);
+        let best = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap().best_opportunity.unwrap();
+        assert!(best.is_profitable());
+
+        let unit = U256::from(10u64).pow(U256::from(18u64));
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let snapshot = |joe_wmnt_out: u64| -> HashMap<Address, PoolReserves> {
+            [
+                (wmnt.clone(), 1_000u64, moe.clone(), 900u64, [1u8; 20]),
+                (moe.clone(), 1_000, joe.clone(), 1_100, [2u8; 20]),
+                (joe.clone(), 1_000, wmnt.clone(), joe_wmnt_out, [3u8; 20]),
+            ]
+            .into_iter()
+            .map(|(token_a, reserve_a, token_b, reserve_b, addr)| {
+                let reserves = PoolReserves::new(token_a, U256::from(reserve_a) * unit, token_b, U256::from(reserve_b) * unit, 1, Address::from(addr));
+                (reserves.pool_address, reserves)
+            })
+            .collect()
+        };
+
+        // Unchanged reserves reproduce the net profit; a closed mispricing loses money
+        let unchanged = analyzer.reprice(&best, &snapshot(1_200)).unwrap();
+        assert!((unchanged - best.net_profit).abs() < 1e-9);
+        assert!(analyzer.reprice(&best, &snapshot(1_010)).unwrap() < 0.0);
+
+        let mut missing = snapshot(1_200);
+        missing.remove(&Address::from([2u8; 20]));
+        assert_eq!(analyzer.reprice(&best, &missing), None);
+    }
+
+    #[test]
+    fn test_reprice_applies_transfer_tax() {
+        let config = create_test_config();
+        let wmnt = Token::new(Address::from([0u8; 20]), "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18).with_transfer_tax_bps(300);
+        let unit = U256::from(10u64).pow(U256::from(18u64));
+        let reserves: HashMap<Address, PoolReserves> = [
+            (wmnt.clone(), 1_000u64, moe.clone(), 900u64, [1u8; 20]),
+            (moe, 1_000, joe.clone(), 1_100, [2u8; 20]),
+            (joe.clone(), 1_000, wmnt.clone(), 1_200, [3u8; 20]),
+        ]
+        .into_iter()
+        .map(|(token_a, reserve_a, token_b, reserve_b, addr)| {
+            let reserves = PoolReserves::new(token_a, U256::from(reserve_a) * unit, token_b, U256::from(reserve_b) * unit, 1, Address::from(addr));
+            (reserves.pool_address, reserves)
+        })
+        .collect();
+        let mut analyzer = MultiPathAnalyzer::new(wmnt, &config);
+        analyzer.add_pools(reserves.values());
+
+        let best = analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap().best_opportunity.unwrap();
+        assert!(best.touches_token(&joe));
+
+        // Re-pricing the unchanged reserves charges JOE's tax exactly as the search did
+        let repriced = analyzer.reprice(&best, &reserves).unwrap();
+        assert!((repriced - best.net_profit).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_five_hop_cycle_needs_max_hops() {
+        let tokens: Vec<Token> = ["WMNT", "AAA", "BBB", "CCC", "DDD"]
+            .iter()
+            .enumerate()
+            .map(|(i, symbol)| Token::new(Address::from([i as u8; 20]), *symbol, 18))
+            .collect();
+        let unit = U256::from(10u64).pow(U256::from(18u64));
+        // A single loop WMNT -> AAA -> BBB -> CCC -> DDD -> WMNT, mispriced only as a whole
+        let pools: Vec<PoolReserves> = (0..5)
+            .map(|i| {
+                let reserve_out = if i == 4 { 1_300u64 } else { 1_000 };
+                PoolReserves::new(
+                    tokens[i].clone(), U256::from(1_000u64) * unit,
+                    tokens[(i + 1) % 5].clone(), U256::from(reserve_out) * unit,
+                    0, Address::from([0x50 + i as u8; 20]),
+                )
+            })
+            .collect();
+        let analyze = |max_hops: usize| {
+            let mut config = create_test_config();
+            config.max_hops = max_hops;
+            let mut analyzer = MultiPathAnalyzer::new(tokens[0].clone(), &config);
+            for reserves in &pools {
+                analyzer.graph.add_pool(reserves, config.dex_fee);
+            }
+            analyzer.find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap()
+        };
+
+        // The default 4-hop limit cannot see it
+        assert!(analyze(4).opportunities.is_empty());
+
+        let found = analyze(5);
+        let best = found.best_opportunity.as_ref().unwrap();
+        let path = best.path.as_ref().unwrap();
+        assert!(best.is_profitable());
+        assert_eq!(best.hop_count(), 5);
+        assert_eq!(path.path_type, PathType::Custom(5));
+        assert_eq!(path.description(), "WMNT -> AAA -> BBB -> CCC -> DDD -> WMNT");
+        assert_eq!(path.expected_gas_units(), GAS_UNITS_4_HOPS + GAS_UNITS_PER_EXTRA_HOP);
+    }
+
+    #[test]
+    fn test_price_impact_rises_with_input() {
+        let pools = [(1_000.0, 900.0), (1_000.0, 1_100.0), (1_000.0, 1_200.0)];
+        let fees = [0.003; 3];
+        let impacts: Vec<f64> = [1.0, 10.0, 50.0, 200.0].iter().map(|&dx| max_price_impact(dx, &pools, &fees)).collect();
+        assert!(impacts.windows(2).all(|w| w[0] < w[1]), "{:?}", impacts);
+
+        // Capping the input lowers the reported impact
+        let unconstrained = create_triangle_analyzer(&create_test_config())
+            .find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap()
+            .opportunities.remove(0);
+        let mut config = create_test_config();
+        config.max_capital = unconstrained.optimal_input / 4.0;
+        let capped = create_triangle_analyzer(&config)
+            .find_all_opportunities(UNBOUNDED_INPUT, 100).unwrap()
+            .opportunities.remove(0);
+
+        assert!(unconstrained.price_impact_percent > 0.0);
+        assert!(capped.price_impact_percent < unconstrained.price_impact_percent);
+        let (pools, fees) = create_triangle_analyzer(&config).cycle_to_pools(capped.path.as_ref().unwrap()).unwrap();
+        assert!((capped.price_impact_percent - max_price_impact(capped.optimal_input, &pools, &fees) * 100.0).abs() < 1e-9);
+    }
+
+    fn create_route_opportunity(pools: Vec<Address>, net_profit: f64) -> ArbitrageOpportunity {
+        let wmnt = Token::new(Address::ZERO, "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        ArbitrageOpportunity {
+            optimal_input: 100.0,
+            final_output: 100.0 + net_profit,
+            gross_profit: net_profit,
+            net_profit,
+            profit_percentage: net_profit,
+            search_method: "test".to_string(),
+            path: Some(ArbitragePath::new(vec![wmnt.clone(), moe, joe, wmnt], pools)),
+            robustness: 1.0,
+            unconstrained_input: 100.0,
+            binding_constraint: None,
+            price_impact_percent: 0.0,
+            requires_flash_loan: false,
+            flash_loan_fee: 0.0,
+            min_pool_depth: 0.0,
+            expected_realized_profit: 0.0,
+            hop_outputs: Vec::new(),
+            confidence: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_confirmation_filter() {
+        let persistent_route = vec![Address::from([3u8; 20]), Address::from([4u8; 20]), Address::from([5u8; 20])];
+        let flickering_route = vec![Address::from([6u8; 20]), Address::from([7u8; 20]), Address::from([8u8; 20])];
+        let mut filter = ConfirmationFilter::new(3);
+
+        // Block 1: both routes profitable
+        let report = filter.observe(&MultiPathOpportunity::new(vec![
+            create_route_opportunity(persistent_route.clone(), 1.0),
+            create_route_opportunity(flickering_route.clone(), 1.0),
+        ], 0));
+        assert_eq!(report.unconfirmed.len(), 2);
+        assert!(!report.actionable.has_profitable_opportunities());
+
+        // Block 2: flickering route turns unprofitable, resetting its streak
+        let report = filter.observe(&MultiPathOpportunity::new(vec![
+            create_route_opportunity(persistent_route.clone(), 1.0),
+            create_route_opportunity(flickering_route.clone(), -1.0),
+        ], 0));
+        assert_eq!(report.unconfirmed.len(), 1);
+        assert_eq!(report.unconfirmed[0].1, 2);
+
+        // Block 3: persistent route reaches 3 blocks, flickering one only 1
+        let report = filter.observe(&MultiPathOpportunity::new(vec![
+            create_route_opportunity(persistent_route.clone(), 1.0),
+            create_route_opportunity(flickering_route.clone(), 1.0),
+        ], 0));
+        assert_eq!(report.actionable.profitable_count(), 1);
+        let confirmed = report.actionable.best_opportunity.unwrap();
+        assert_eq!(confirmed.path.unwrap().pools, persistent_route);
+        assert_eq!(report.unconfirmed.len(), 1);
+        assert_eq!(report.unconfirmed[0].0.path.as_ref().unwrap().pools, flickering_route);
+        assert_eq!(report.unconfirmed[0].1, 1);
+    }
+
+    #[test]
+    fn test_path_stats_ema() {
+        let steady_route = vec![Address::from([3u8; 20]), Address::from([4u8; 20]), Address::from([5u8; 20])];
+        let fluke_route = vec![Address::from([6u8; 20]), Address::from([7u8; 20]), Address::from([8u8; 20])];
+        let steady_id = ArbitragePath::new(Vec::new(), steady_route.clone()).opportunity_id();
+        let fluke_id = ArbitragePath::new(Vec::new(), fluke_route.clone()).opportunity_id();
+        let mut stats = PathStats::new(0.25);
+
+        // First scan seeds the EMA; later ones move it a quarter of the way
+        for (steady, fluke) in [(1.0, -2.0), (-1.0, 6.0), (2.0, -2.0), (0.5, -2.0)] {
+            stats.observe(&MultiPathOpportunity::new(vec![
+                create_route_opportunity(steady_route.clone(), steady),
+                create_route_opportunity(fluke_route.clone(), fluke),
+            ], 0));
+        }
+
+        // steady: 1.0 -> 0.5 -> 0.875 -> 0.78125; fluke: -2.0 -> 0.0 -> -0.5 -> -0.875
+        let steady = stats.get(&steady_id).unwrap();
+        assert_eq!(steady.ema_net_profit, 0.78125);
+        assert_eq!((steady.hits, steady.samples), (3, 4));
+        let fluke = stats.get(&fluke_id).unwrap();
+        assert_eq!(fluke.ema_net_profit, -0.875);
+        assert_eq!((fluke.hits, fluke.samples), (1, 4));
+
+        let top = stats.top_by_ema(5);
+        assert_eq!(top.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![steady_id.as_str(), fluke_id.as_str()]);
+        assert_eq!(stats.top_by_ema(1).len(), 1);
     }
 }
@@ -1,151 +1,685 @@
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
-use alloy::providers::ProviderBuilder;
-use hex;
+use std::sync::Arc;
 use alloy::primitives::Address;
-use tokio::time::{sleep, Duration, Instant};
+use alloy::providers::Provider;
+use tokio::time::{Duration, Instant};
 use chrono::Utc;
+use log::{debug, error, info, log, log_enabled, warn, Level};
 
-use crate::config::Config;
-use crate::types::{Token, MultiPathOpportunity};
-use crate::multi_path::{MultiPathAnalyzer, OptimizationStrategy, StrategySelector};
-use crate::batch_fetcher::{BatchReservesFetcher, LiquidityAnalyzer};
-use crate::blockchain::get_current_block;
-use crate::constants::WMNT_ADDRESS;
-use crate::logging::{init_csv_file, log_csv_success, log_csv_failure};
-use crate::display::print_startup_banner;
-
-/// Multi-path arbitrage monitoring system
-pub async fn run_multi_path_arbitrage(config: Config) -> Result<(), Box<dyn Error>> {
-    // Set up provider
-    let provider = ProviderBuilder::new().connect_http(config.rpc_url.parse()?);
-
-    // Create WMNT token for graph root
-    let wmnt_token = Token::WMNT(Address::from_slice(
-        &hex::decode(WMNT_ADDRESS.trim_start_matches("0x"))?
-    ));
+use crate::config::{Config, OutputFormat, ReserveSource};
+use crate::cache::{GasPriceHistory, GasPriceOracle, ReserveHistory, ReservesCache};
+use crate::types::{Token, ArbitrageOpportunity, GasOverhead, MultiPathOpportunity, MultiPathArbitrageRecord, AnalysisTimings, PoolReserves};
+use crate::multi_path::{AdaptiveProfitThreshold, ConfirmationFilter, EndedOpportunity, MultiPathAnalyzer, OpportunityTracker, PathStats, StrategySelector, TokenFilter};
+use crate::batch_fetcher::{BatchReservesFetcher, LiquidityAnalyzer, SyncedReserves};
+use crate::blockchain::{fetch_gas_price, fetch_pools_at_block, fetch_swap_samples, get_current_block, load_pool_tokens, refresh_gas_price, save_pool_tokens};
+use crate::reserve_reader::ReserveReader;
+use crate::math::estimate_fee_from_swaps;
+use crate::constants::{RESERVE_RECORDER_FLUSH_BLOCKS, WMNT_ADDRESS};
+use crate::logging::{ArbitrageLogger, JsonLogger, init_csv_file, log_csv_success, log_csv_failure, log_parquet_success, log_parquet_failure, log_sqlite_success, log_sqlite_failure};
+use crate::parquet_export::ParquetSink;
+use crate::price_source::PriceSource;
+use crate::recorder::ReservesRecorder;
+#[cfg(feature = "sqlite")]
+use crate::sqlite_export::SqliteLogger;
+use crate::stream::OpportunityStream;
+use crate::metrics::{spawn_snapshot_writer, start_prometheus_exporter, Metrics};
+use crate::display::{paint_signed, print_shutdown_summary, print_startup_banner};
+use crate::block_driver::BlockTicker;
+use crate::rpc_provider::ReconnectingProvider;
+use crate::shutdown::Shutdown;
+use crate::api::{start_api_server, ApiState};
+use crate::cli::RunMode;
+use crate::tui::{Dashboard, DashboardState, SessionStats};
+
+/// Routes listed in the periodic and shutdown route-stats dumps
+const PATH_STATS_TOP_ROUTES: usize = 10;
+
+/// Where the multi-path monitor gets its pool set from
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoolSource {
+    /// Pool list CSV with pair names and reserves
+    Csv(String),
+    /// Fixed set of pool addresses; token pairs are fetched on chain
+    Addresses(Vec<Address>),
+}
+
+/// Parse a comma-separated `--pools` list, rejecting invalid or duplicate addresses
+pub fn parse_pool_list(list: &str) -> Result<Vec<Address>, String> {
+    let mut addresses = Vec::new();
+    for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let address = entry
+            .parse::<Address>()
+            .map_err(|e| format!("Invalid pool address '{}': {}", entry, e))?;
+        if addresses.contains(&address) {
+            return Err(format!("Duplicate pool address '{}'", entry));
+        }
+        addresses.push(address);
+    }
+
+    if addresses.is_empty() {
+        return Err("--pools requires at least one pool address".to_string());
+    }
+    Ok(addresses)
+}
+
+/// Multi-path arbitrage monitoring system; returns whether an actionable opportunity was found.
+/// With `record_path`, every block's reserves are appended there for `replay`.
+pub async fn run_multi_path_arbitrage(
+    config: Config,
+    pool_source: PoolSource,
+    run_mode: RunMode,
+    record_path: Option<&str>,
+) -> Result<bool, Box<dyn Error>> {
+    // Set up provider (reconnects after repeated failures)
+    let mut rpc = ReconnectingProvider::connect(&config)?;
+
+    // Reuse pool tokens from earlier runs so only getReserves hits the chain
+    if let Some(path) = config.pool_token_cache_path.as_deref() {
+        match load_pool_tokens(path) {
+            Ok(0) => {}
+            Ok(count) => info!("🗂️ Loaded tokens of {} pools from {}", count, path),
+            Err(e) => warn!("⚠️ Ignoring unreadable pool token cache {}: {}", path, e),
+        }
+    }
+
+    // WMNT prices pool liquidity; cycles are rooted at the configured base token
+    let wmnt_token = Token::from_address(WMNT_ADDRESS.parse()?)
+        .ok_or("WMNT is missing from the token registry")?;
 
     // Initialize multi-path analyzer
-    let mut analyzer = MultiPathAnalyzer::new(wmnt_token, &config);
+    let mut analyzer = MultiPathAnalyzer::new(config.base_token.clone(), &config);
 
     // Initialize batch fetcher
-    let mut batch_fetcher = BatchReservesFetcher::new(config.max_retries as usize);
+    let mut batch_fetcher = BatchReservesFetcher::new(
+        config.max_retries as usize,
+        config.batch_size,
+        Duration::from_millis(config.inter_batch_delay_ms),
+    );
+    batch_fetcher.set_min_pool_coverage(config.min_pool_coverage);
+    batch_fetcher.set_max_concurrent_requests(config.max_concurrent_requests);
+    batch_fetcher.set_excluded_pools(&config.excluded_pools);
 
-    // Load pools from CSV data
-    let csv_path = "data/selected.csv";
-    println!("🔄 Loading pools from: {}", csv_path);
-    
-    match batch_fetcher.load_pool_addresses_from_csv(csv_path) {
-        Ok(()) => {
-            println!("✅ Successfully loaded pool addresses");
+    match &pool_source {
+        PoolSource::Csv(csv_path) => {
+            info!("🔄 Loading pools from: {}", csv_path);
+
+            match batch_fetcher.load_pool_addresses_from_csv(csv_path) {
+                Ok(()) => {
+                    info!("✅ Successfully loaded pool addresses");
+                }
+                Err(e) => {
+                    error!("❌ Failed to load pool addresses: {}", e);
+                    return Err(e.into());
+                }
+            }
+
+            // Load pools into analyzer (from the topology cache when configured)
+            let graph_load = match config.graph_cache_path.as_deref() {
+                Some(cache_path) => analyzer.load_pools_cached(csv_path, cache_path),
+                None => analyzer.load_pools_from_csv(csv_path).map(|_| false),
+            };
+            match graph_load {
+                Ok(from_cache) => {
+                    let (nodes, edges) = analyzer.get_graph_stats();
+                    let source = if from_cache { "cache" } else { "CSV" };
+                    info!("✅ Graph initialized from {}: {} tokens, {} pools", source, nodes, edges);
+                }
+                Err(e) => {
+                    error!("❌ Failed to initialize graph: {}", e);
+                    return Err(e.into());
+                }
+            }
         }
-        Err(e) => {
-            println!("❌ Failed to load pool addresses: {}", e);
-            return Err(e);
+        PoolSource::Addresses(addresses) => {
+            info!("🔄 Loading {} pools from the command line", addresses.len());
+            for address in addresses {
+                batch_fetcher.add_pool_address(*address);
+            }
+
+            // Token pairs come from the pools' on-chain token0/token1
+            let current_block = rpc.provider().block_number().await?;
+            let reserves_map = batch_fetcher.fetch_all_reserves(rpc.provider(), current_block).await?;
+            if reserves_map.is_empty() {
+                return Err("None of the given pools could be loaded from chain".into());
+            }
+            analyzer.add_pools(reserves_map.values());
+
+            let (nodes, edges) = analyzer.get_graph_stats();
+            info!("✅ Graph initialized from chain: {} tokens, {} pools", nodes, edges);
         }
     }
 
-    // Load pools into analyzer
-    match analyzer.load_pools_from_csv(csv_path) {
-        Ok(()) => {
-            let (nodes, edges) = analyzer.get_graph_stats();
-            println!("✅ Graph initialized: {} tokens, {} pools", nodes, edges);
+    let excluded_edges = analyzer.excluded_edge_count();
+    if excluded_edges > 0 {
+        info!("🚫 Excluded {} edges touching EXCLUDED_POOLS / EXCLUDED_TOKENS", excluded_edges);
+    }
+
+    // Replace the default fee with on-chain estimates where swaps are available
+    if config.fee_calibration_blocks > 0 {
+        calibrate_pool_fees(rpc.provider(), &mut analyzer, batch_fetcher.get_pool_addresses(), &config).await;
+    }
+
+    // Fees set explicitly in the config file win over defaults and estimates
+    for (&pool, &fee) in &config.pool_fees {
+        if !analyzer.set_pool_fee(pool, fee) {
+            warn!("⚠️ Config fee for pool {} ignored: pool is not in the graph", pool);
         }
-        Err(e) => {
-            println!("❌ Failed to initialize graph: {}", e);
-            return Err(e);
+    }
+
+    // Initialize opportunity output (CSV, Parquet or SQLite)
+    let mut record_sink = match config.output_format {
+        OutputFormat::Parquet => {
+            let sink = ParquetSink::create(&config.parquet_file_path, config.parquet_row_group_size)?;
+            info!("📝 Parquet logging initialized: {}", config.parquet_file_path);
+            RecordSink::Parquet(Box::new(sink))
+        }
+        #[cfg(feature = "sqlite")]
+        OutputFormat::Sqlite => {
+            let logger = SqliteLogger::open(&config.sqlite_file_path)?;
+            info!("📝 SQLite logging initialized: {}", config.sqlite_file_path);
+            RecordSink::Sqlite(logger)
+        }
+        #[cfg(not(feature = "sqlite"))]
+        OutputFormat::Sqlite => return Err("OUTPUT_FORMAT=sqlite requires building with --features sqlite".into()),
+        OutputFormat::Csv => {
+            if let Err(e) = init_csv_file(&config.csv_file_path) {
+                warn!("⚠️ Warning: Failed to initialize CSV file: {}", e);
+            } else {
+                info!("📝 CSV logging initialized: {}", config.csv_file_path);
+            }
+            RecordSink::Csv
         }
+    };
+
+    // Optional push stream of profitable opportunities for external executors
+    let opportunity_stream = match config.stream_address.as_deref() {
+        Some(address) => {
+            let stream = OpportunityStream::bind(address).await?;
+            info!("📡 Streaming opportunities on {}", address);
+            Some(stream)
+        }
+        None => None,
+    };
+
+    // Optional JSON lines log next to the console output
+    let json_logger = JsonLogger::from_config(&config)?;
+
+    // Optional raw reserves recording (all pools, every block) for later replay
+    let mut recorder = match record_path {
+        Some(path) => {
+            let recorder = ReservesRecorder::create(path, RESERVE_RECORDER_FLUSH_BLOCKS)?;
+            info!("📼 Recording reserves of every block to {}", path);
+            Some(recorder)
+        }
+        None => None,
+    };
+
+    // Counters for lightweight monitoring, optionally dumped to a JSON file or scraped by Prometheus
+    let metrics = Arc::new(Metrics::new());
+    if let Some(path) = &config.metrics_snapshot_path {
+        spawn_snapshot_writer(Arc::clone(&metrics), path.clone(), Duration::from_secs(config.metrics_snapshot_interval_secs));
+    }
+    if let Some(address) = config.metrics_address.as_deref() {
+        start_prometheus_exporter(Arc::clone(&metrics), address).await?;
     }
 
-    // Initialize CSV logging
-    if let Err(e) = init_csv_file(&config.csv_file_path) {
-        println!("⚠️ Warning: Failed to initialize CSV file: {}", e);
-    } else {
-        println!("📝 CSV logging initialized: {}", config.csv_file_path);
+    // Ctrl-C stops the loop between blocks instead of killing it mid-write
+    let shutdown = Shutdown::on_ctrl_c();
+
+    // Latest analysis for the optional HTTP API; the server stops with the monitor
+    let api_state = ApiState::shared();
+    if let Some(address) = config.api_address.as_deref() {
+        start_api_server(Arc::clone(&api_state), address, shutdown.clone()).await?;
     }
 
     // Print startup information
     print_startup_banner();
     config.print_summary();
-    println!("\n🚀 Multi-Path Arbitrage Monitor Started");
-    println!("├─ Pool Count: {}", batch_fetcher.pool_count());
+    info!("\n🚀 Multi-Path Arbitrage Monitor Started");
+    info!("├─ Pool Count: {}", batch_fetcher.pool_count());
     let all_paths = analyzer.get_all_paths();
-    println!("├─ Available Paths: {}", all_paths.len());
-    println!("│  ├─ 3-hop paths: {}", all_paths.iter().filter(|p| p.tokens.len() == 4).count());
-    println!("│  └─ 4-hop paths: {}", all_paths.iter().filter(|p| p.tokens.len() == 5).count());
-    println!("├─ Optimization Strategy: MaxProfit");
-    println!("└─ Update Interval: {}s", config.block_time_seconds);
-    println!();
+    info!("├─ Available Paths: {}", all_paths.len());
+    info!("│  ├─ 2-hop (cross-pool) paths: {}", all_paths.iter().filter(|p| p.tokens.len() == 3).count());
+    info!("│  ├─ 3-hop paths: {}", all_paths.iter().filter(|p| p.tokens.len() == 4).count());
+    info!("│  └─ 4-hop paths: {}", all_paths.iter().filter(|p| p.tokens.len() == 5).count());
+    info!("├─ Optimization Strategy: {:?}", config.ranking_strategy());
+    info!("└─ Update Interval: {}s", config.block_time_seconds);
+    info!("");
+
+    // Optional TUI dashboard replaces the scrolling block summaries
+    let mut dashboard = if config.tui_enabled {
+        Some(Dashboard::stdout()?)
+    } else {
+        None
+    };
+    let mut session_stats = SessionStats::new();
+
+    // Last reserves applied to the graph, so only moved pools are updated
+    let mut reserves_cache = ReservesCache::new();
+
+    let mut processed_blocks = 0u64;
+    // Edge weights a full refresh would have recomputed, and blocks that reused the last cycle search
+    let mut full_refresh_edges = 0u64;
+    let mut cycle_searches_skipped = 0u64;
+
+    // Drop routes through excluded tokens (or missing required ones) before reporting
+    let token_filter = TokenFilter::new(config.include_tokens.clone(), config.exclude_tokens.clone());
+
+    // Minimum profit, optionally widened by recent gas volatility
+    let profit_threshold = AdaptiveProfitThreshold::new(config.min_profit_wmnt, config.gas_volatility_multiplier);
+    let mut gas_history = GasPriceHistory::new(config.gas_history_window);
+
+    // On-chain gas price for route costs, when enabled
+    let mut gas_oracle = config.dynamic_gas_price
+        .then(|| GasPriceOracle::new(config.gas_price_gwei, Duration::from_secs(config.gas_price_refresh_secs)));
+
+    // Routes must stay profitable for CONFIRMATION_BLOCKS scans before being reported
+    let mut confirmation_filter = ConfirmationFilter::new(config.confirmation_blocks);
+
+    // First/last-seen blocks of each profitable route, to report how long it lasted
+    let mut opportunity_tracker = OpportunityTracker::new();
+
+    // Net-profit EMA and hit count per route, to spot consistently near-profitable cycles
+    let mut path_stats = PathStats::new(config.path_stats_alpha);
+
+    // RESERVE_SOURCE=sync: reserves follow Sync logs, re-snapshotted after each reconnect
+    let mut synced_reserves = SyncedReserves::new();
+    let mut synced_reconnects = rpc.reconnects();
+
+    // Profitable records waiting to be re-priced at their next block (VERIFY_NEXT_BLOCK)
+    let mut pending_verifications: Vec<PendingVerification> = Vec::new();
 
     // Main monitoring loop
+    let mut ticker = BlockTicker::start(
+        config.monitor_driver,
+        config.ws_rpc_url.as_deref(),
+        Duration::from_secs(config.block_time_seconds),
+    );
     let mut last_block = 0u64;
     let mut iteration_count = 0u64;
+    let mut found_actionable = false;
 
     loop {
-        let start_time = Instant::now();
-        iteration_count += 1;
-
-        // Get current block number
-        let current_block = match get_current_block(&provider).await {
+        // Get current block number, unless shutdown was requested while waiting
+        let block_result = tokio::select! {
+            biased;
+            _ = shutdown.wait() => break,
+            result = ticker.next_block(rpc.provider()) => result,
+        };
+        rpc.observe(&block_result);
+        let current_block = match block_result {
             Ok(block) => block,
             Err(e) => {
-                println!("❌ Error getting block number: {}", e);
-                sleep(Duration::from_secs(config.block_time_seconds)).await;
+                error!("❌ Error getting block number: {}", e);
+                metrics.record_error();
+                if let Some(logger) = &json_logger {
+                    logger.log_error(&format!("Error getting block number: {}", e));
+                }
+                if run_mode == RunMode::Once {
+                    return Err(format!("Error getting block number: {}", e).into());
+                }
                 continue;
             }
         };
+        let start_time = Instant::now();
+        iteration_count += 1;
+        metrics.set_current_block(current_block);
+
+        // Forget reserves of pools that have not been fetched recently
+        if config.reserve_cache_ttl_secs > 0 {
+            reserves_cache.prune_older_than(Duration::from_secs(config.reserve_cache_ttl_secs));
+        }
 
         // Only process if block has changed or it's the first iteration
         if current_block != last_block || iteration_count == 1 {
             last_block = current_block;
 
-            println!("🔄 Block {} - Fetching reserves for {} pools...", current_block, batch_fetcher.pool_count());
+            debug!("🔄 Block {} - Fetching reserves for {} pools...", current_block, batch_fetcher.pool_count());
 
-            // Fetch all reserves in parallel
-            match batch_fetcher.fetch_all_reserves(&provider, current_block).await {
-                Ok(reserves_map) => {
+            // Fetch all reserves in parallel, or follow Sync logs from the last snapshot
+            let fetched = match config.reserve_source {
+                ReserveSource::GetReserves => batch_fetcher.fetch_all_reserves(rpc.provider(), current_block).await,
+                ReserveSource::SyncEvents => {
+                    if rpc.reconnects() != synced_reconnects {
+                        synced_reconnects = rpc.reconnects();
+                        synced_reserves.invalidate();
+                    }
+                    batch_fetcher.fetch_reserves_via_sync(rpc.provider(), &mut synced_reserves, current_block).await
+                }
+            };
+            rpc.observe(&fetched);
+            if let Some(path) = config.pool_token_cache_path.as_deref()
+                && let Err(e) = save_pool_tokens(path)
+            {
+                warn!("⚠️ Failed to write pool token cache {}: {}", path, e);
+            }
+            match fetched {
+                Ok(mut reserves_map) => {
+                    config.apply_reserve_scale(&mut reserves_map);
                     let fetch_duration = start_time.elapsed();
-                    println!("✅ Fetched {} pools in {:?}", reserves_map.len(), fetch_duration);
+                    api_state.write().unwrap_or_else(|e| e.into_inner()).record_fetch(current_block);
+                    debug!("✅ Fetched {} pools in {:?}", reserves_map.len(), fetch_duration);
+                    if let Some(recorder) = recorder.as_mut()
+                        && let Err(e) = recorder.record(current_block, &reserves_map)
+                    {
+                        warn!("⚠️ Failed to record reserves to {}: {}", recorder.file_path(), e);
+                    }
 
-                    // Analyze liquidity
-                    let _liquidity_stats = LiquidityAnalyzer::analyze_liquidity_distribution(&reserves_map);
+                    // Analyze liquidity in WMNT, pricing tokens off the fetched pools
+                    let prices = LiquidityAnalyzer::wmnt_prices(&reserves_map, &wmnt_token);
+                    let _liquidity_stats = LiquidityAnalyzer::analyze_liquidity_distribution(&reserves_map, &prices);
                     
                     // Filter pools with sufficient liquidity
-                    let min_liquidity = 1000.0; // Minimum liquidity threshold
-                    let liquid_pools = LiquidityAnalyzer::get_arbitrage_ready_pools(&reserves_map, min_liquidity);
+                    let liquid_pools = LiquidityAnalyzer::get_arbitrage_ready_pools(&reserves_map, &prices, config.min_liquidity_wmnt);
                     
-                    println!("📊 Liquidity Analysis: {}/{} pools above ${} threshold", 
-                            liquid_pools.len(), reserves_map.len(), min_liquidity);
+                    debug!("📊 Liquidity Analysis: {}/{} pools above {} WMNT threshold", 
+                            liquid_pools.len(), reserves_map.len(), config.min_liquidity_wmnt);
+
+                    // Update analyzer edges only for pools whose reserves moved
+                    let changed_pools = reserves_cache.changed_pools(&reserves_map);
+                    let recomputed_edges = analyzer.update_changed_pools(&reserves_map, &changed_pools);
+                    full_refresh_edges += analyzer.get_graph_stats().1 as u64;
+                    for (address, reserves) in &reserves_map {
+                        reserves_cache.update(*address, reserves.clone());
+                    }
+                    reserves_cache.update_block_number(current_block);
+                    if !config.tui_enabled {
+                        let total_edges = analyzer.get_graph_stats().1;
+                        debug!("🔁 Reserves changed in {}/{} pools; recomputed {}/{} edge weights ({} skipped)",
+                            changed_pools.len(), reserves_map.len(), recomputed_edges, total_edges,
+                            total_edges.saturating_sub(recomputed_edges));
+                    }
+                    if let Some(logger) = &json_logger {
+                        logger.log_reserves_change(current_block, Utc::now());
+                    }
+                    if !config.tui_enabled {
+                        debug!("📐 Market Efficiency: {:.2}/100", analyzer.market_efficiency_score());
+                    }
 
-                    // Update analyzer with new reserves
-                    analyzer.update_pool_reserves(&reserves_map);
+                    // Track reserve volatility and periodically report the most active pools
+                    analyzer.record_reserve_history(&reserves_map);
+                    processed_blocks += 1;
+                    if !config.tui_enabled
+                        && config.volatility_log_interval_blocks > 0
+                        && processed_blocks.is_multiple_of(config.volatility_log_interval_blocks)
+                    {
+                        print_most_volatile_pools(analyzer.reserve_history());
+                    }
+
+                    if let Some(oracle) = gas_oracle.as_mut() {
+                        analyzer.set_gas_price_gwei(refresh_gas_price(oracle, rpc.provider()).await);
+                    }
+
+                    // Re-price routes recorded at earlier blocks now that their next block is here
+                    if !pending_verifications.is_empty() {
+                        verify_pending_records(
+                            &mut pending_verifications,
+                            &analyzer,
+                            rpc.provider(),
+                            current_block,
+                            &reserves_map,
+                            &mut record_sink,
+                            &config,
+                        ).await;
+                    }
 
                     // Find all arbitrage opportunities
                     let analysis_start = Instant::now();
                     let multi_opportunity = analyzer.find_all_opportunities(
-                        (100.0, 10000.0), // Input range in WMNT
+                        config.input_range(),
                         config.ternary_search_iterations
-                    );
+                    )?;
                     let analysis_duration = analysis_start.elapsed();
+                    cycle_searches_skipped += multi_opportunity.timings.cycles_reused as u64;
+                    let multi_opportunity = token_filter.apply(multi_opportunity);
 
-                    // Process results
-                    process_multi_path_results(
-                        &multi_opportunity,
+                    if profit_threshold.is_adaptive() {
+                        match fetch_gas_price(rpc.provider()).await {
+                            Ok(gas_price) => gas_history.record(gas_price),
+                            Err(e) => warn!("⚠️ Failed to fetch gas price: {}", e),
+                        }
+                    }
+                    let effective_min_profit = profit_threshold.effective(&gas_history);
+                    if !config.tui_enabled && effective_min_profit > 0.0 {
+                        debug!("🎚️ Effective Min Profit: {:.6} {}", effective_min_profit, config.base_token.symbol());
+                    }
+                    let below_threshold = profit_threshold.best_below(&multi_opportunity, effective_min_profit);
+                    let multi_opportunity = profit_threshold.apply(multi_opportunity, effective_min_profit);
+
+                    // Keep a trace of dust routes: recorded, but flagged as not actionable
+                    if !multi_opportunity.has_profitable_opportunities()
+                        && let Some(dust) = below_threshold.as_ref()
+                    {
+                        if !config.tui_enabled {
+                            debug!("🪙 Best route nets {:.6} {unit}, below the {:.6} {unit} minimum (recorded as not actionable)",
+                                dust.net_profit, effective_min_profit, unit = config.base_token.symbol());
+                        }
+                        let record = build_multi_path_record(
+                            Utc::now(),
+                            current_block,
+                            dust,
+                            fetch_duration.as_millis() as u64,
+                            analysis_duration.as_millis() as u64,
+                            false,
+                            &config.gas_overhead(),
+                            config.price_source(),
+                        );
+                        if let Err(e) = record_multi_path_opportunity(record, &mut record_sink, &config) {
+                            warn!("⚠️ Failed to record opportunity: {}", e);
+                        }
+                    }
+
+                    let ended = opportunity_tracker.observe(current_block, &multi_opportunity);
+                    if !config.tui_enabled && !ended.is_empty() {
+                        print_ended_opportunities(&ended);
+                    }
+
+                    path_stats.observe(&multi_opportunity);
+                    if !config.tui_enabled
+                        && config.path_stats_log_interval_blocks > 0
+                        && processed_blocks.is_multiple_of(config.path_stats_log_interval_blocks)
+                    {
+                        path_stats.print_top(PATH_STATS_TOP_ROUTES, config.base_token.symbol());
+                    }
+
+                    // Hold back profitable routes that have not persisted long enough
+                    let confirmation = confirmation_filter.observe(&multi_opportunity);
+                    let multi_opportunity = confirmation.actionable;
+                    if !config.tui_enabled && !confirmation.unconfirmed.is_empty() {
+                        print_unconfirmed_opportunities(&confirmation.unconfirmed, confirmation_filter.required_blocks(), config.base_token.symbol());
+                    }
+                    api_state.write().unwrap_or_else(|e| e.into_inner()).publish(multi_opportunity.clone());
+                    found_actionable |= multi_opportunity.has_profitable_opportunities();
+
+                    metrics.record_block(
                         current_block,
                         fetch_duration,
                         analysis_duration,
-                        &config,
-                    ).await;
+                        multi_opportunity.opportunities.len(),
+                        multi_opportunity.profitable_opportunities().len(),
+                    );
+
+                    // Process results
+                    if let Some(dashboard) = dashboard.as_mut() {
+                        session_stats.record_scan(&multi_opportunity);
+                        let state = DashboardState::new(current_block, &reserves_map, &multi_opportunity, &session_stats, config.base_token.symbol());
+                        if let Err(e) = dashboard.draw(&state) {
+                            warn!("⚠️ Failed to draw dashboard: {}", e);
+                        }
+
+                        // Keep opportunity logging active while the dashboard owns the screen
+                        if let Some(best) = StrategySelector::select_best(&multi_opportunity.opportunities, config.ranking_strategy())
+                        {
+                            let record = build_multi_path_record(
+                                Utc::now(),
+                                current_block,
+                                best,
+                                fetch_duration.as_millis() as u64,
+                                analysis_duration.as_millis() as u64,
+                                true,
+                                &config.gas_overhead(),
+                                config.price_source(),
+                            );
+                            if config.verify_next_block {
+                                pending_verifications.push(PendingVerification { opportunity: best.clone(), record });
+                            } else if let Err(e) = record_multi_path_opportunity(record, &mut record_sink, &config) {
+                                warn!("⚠️ Failed to record opportunity: {}", e);
+                            }
+                        }
+                    } else {
+                        process_multi_path_results(
+                            &multi_opportunity,
+                            current_block,
+                            fetch_duration,
+                            analysis_duration,
+                            &mut record_sink,
+                            &mut pending_verifications,
+                            &config,
+                        ).await;
+                    }
+
+                    if let Some(logger) = &json_logger {
+                        for opportunity in multi_opportunity.profitable_opportunities() {
+                            if let Err(e) = logger.log_block_opportunity(current_block, opportunity, analysis_duration) {
+                                warn!("⚠️ Failed to write JSON log: {}", e);
+                            }
+                        }
+                    }
+
+                    if let Some(stream) = opportunity_stream.as_ref() {
+                        for opportunity in multi_opportunity.profitable_opportunities() {
+                            let record = build_multi_path_record(
+                                Utc::now(),
+                                current_block,
+                                opportunity,
+                                fetch_duration.as_millis() as u64,
+                                analysis_duration.as_millis() as u64,
+                                true,
+                                &config.gas_overhead(),
+                                config.price_source(),
+                            );
+                            if let Err(e) = stream.publish(&record) {
+                                warn!("⚠️ Failed to stream opportunity: {}", e);
+                            }
+                        }
+                    }
 
                 }
                 Err(e) => {
-                    println!("❌ Block {}: Failed to fetch reserves: {}", current_block, e);
+                    error!("❌ Block {}: Failed to fetch reserves: {}", current_block, e);
+                    metrics.record_error();
+                    if let Some(logger) = &json_logger {
+                        logger.log_error(&format!("Block {}: Failed to fetch reserves: {}", current_block, e));
+                    }
+                    if run_mode == RunMode::Once {
+                        return Err(format!("Block {}: failed to fetch reserves: {}", current_block, e).into());
+                    }
+                }
+            }
+
+            // --once: a single fetch and analysis cycle
+            if run_mode == RunMode::Once {
+                break;
+            }
+        }
+    }
+
+    // Stop the API server, give the terminal back and write out buffered records before the summary
+    shutdown.trigger();
+    drop(dashboard);
+    for pending in pending_verifications {
+        if let Err(e) = record_multi_path_opportunity(pending.record, &mut record_sink, &config) {
+            warn!("⚠️ Failed to record unverified opportunity: {}", e);
+        }
+    }
+    if let Err(e) = record_sink.close() {
+        warn!("⚠️ Failed to flush opportunity records: {}", e);
+    }
+    if let Some(recorder) = recorder.as_mut()
+        && let Err(e) = recorder.flush()
+    {
+        warn!("⚠️ Failed to flush reserves recording {}: {}", recorder.file_path(), e);
+    }
+    path_stats.print_top(PATH_STATS_TOP_ROUTES, config.base_token.symbol());
+    if full_refresh_edges > 0 {
+        let recomputed = analyzer.weight_recomputations();
+        info!("♻️ Incremental updates: recomputed {}/{} edge weights ({:.1}% skipped), cycle search skipped on {}/{} blocks",
+            recomputed, full_refresh_edges,
+            100.0 * (1.0 - recomputed as f64 / full_refresh_edges as f64),
+            cycle_searches_skipped, processed_blocks);
+    }
+    print_shutdown_summary(&metrics.snapshot());
+    Ok(found_actionable)
+}
+
+/// Print every cycle the pool CSV can form (up to `MAX_HOPS`) with its swap gas, without
+/// fetching reserves. Cycles SPFA finds profitable at the CSV reserves are marked.
+pub fn run_list_paths(config: &Config, csv_path: &str) -> Result<usize, Box<dyn Error>> {
+    let mut analyzer = MultiPathAnalyzer::new(config.base_token.clone(), config);
+    analyzer.load_pools_from_csv(csv_path)?;
+    let mut paths = analyzer.get_structural_paths();
+    paths.sort_by_key(|path| (path.pools.len(), path.description()));
+    let profitable: HashSet<Vec<Address>> = analyzer.get_all_paths().into_iter().map(|path| path.pools).collect();
+
+    let (tokens, edges) = analyzer.get_graph_stats();
+    println!("🧭 {} cycles through {} (2-{} hops) over {} tokens and {} pools from {}",
+        paths.len(), config.base_token.symbol(), config.max_hops, tokens, edges / 2, csv_path);
+    for (i, path) in paths.iter().enumerate() {
+        let marker = if profitable.contains(&path.pools) { " | 💰 profitable at CSV reserves" } else { "" };
+        println!("{:>4}. {} | {} gas | id {}{}", i + 1, path.description(), path.expected_gas_units(), path.opportunity_id(), marker);
+    }
+    let profitable_count = paths.iter().filter(|path| profitable.contains(&path.pools)).count();
+    println!("✅ {} of {} cycles are profitable at the CSV reserves", profitable_count, paths.len());
+    Ok(paths.len())
+}
+
+/// Estimate each pool's fee from recent swaps, keeping the configured default when none are found
+async fn calibrate_pool_fees<P: alloy::providers::Provider>(
+    provider: &P,
+    analyzer: &mut MultiPathAnalyzer,
+    pool_addresses: &[Address],
+    config: &Config,
+) {
+    let to_block = match get_current_block(provider).await {
+        Ok(block) => block,
+        Err(e) => {
+            warn!("⚠️ Fee calibration skipped: {}", e);
+            return;
+        }
+    };
+    let from_block = to_block.saturating_sub(config.fee_calibration_blocks);
+
+    let mut calibrated = 0;
+    for &pool in pool_addresses {
+        match fetch_swap_samples(provider, pool, from_block, to_block).await {
+            Ok(samples) => {
+                if let Some(fee) = estimate_fee_from_swaps(&samples)
+                    && analyzer.set_pool_fee(pool, fee)
+                {
+                    calibrated += 1;
                 }
             }
+            Err(e) => warn!("⚠️ Failed to fetch swaps for {}: {}", pool, e),
         }
+    }
 
-        // Sleep until next iteration
-        sleep(Duration::from_secs(config.block_time_seconds)).await;
+    info!("🧪 Calibrated fees for {}/{} pools (others use {}%)",
+        calibrated, pool_addresses.len(), config.dex_fee * 100.0);
+}
+
+/// Print the per-phase breakdown of an analysis pass
+fn print_analysis_timings(timings: &AnalysisTimings) {
+    if timings.cycles_reused {
+        debug!("│  ├─ Cycle Detection: skipped, graph unchanged ({:?})", timings.cycle_detection);
+    } else {
+        debug!("│  ├─ Cycle Detection: {:?}", timings.cycle_detection);
     }
+    debug!("│  ├─ Cycle Analysis: {:?} ({} cycles, sum {:?}, max {:?})",
+        timings.cycle_analysis, timings.cycles_analyzed,
+        timings.cycle_analysis_total, timings.cycle_analysis_max);
+    debug!("│  ├─ Reserve Conversion: {:?}", timings.conversion_total);
+    debug!("│  └─ Deferred Cycles: {}", timings.cycles_deferred);
 }
 
 /// Process and display multi-path arbitrage results
@@ -154,40 +688,58 @@ async fn process_multi_path_results(
     block_number: u64,
     fetch_duration: Duration,
     analysis_duration: Duration,
+    record_sink: &mut RecordSink,
+    pending_verifications: &mut Vec<PendingVerification>,
     config: &Config,
 ) {
     let timestamp = Utc::now();
-    
-    println!("\n📈 Multi-Path Analysis Results (Block {}):", block_number);
-    println!("├─ Fetch Time: {:?}", fetch_duration);
-    println!("├─ Analysis Time: {:?}", analysis_duration);
-    println!("├─ Total Opportunities: {}", multi_opportunity.opportunities.len());
-    println!("├─ Profitable Opportunities: {}", multi_opportunity.profitable_count());
+    // Blocks without a profitable route are only summarized at debug level
+    let level = if multi_opportunity.has_profitable_opportunities() { Level::Info } else { Level::Debug };
+
+    log!(level, "\n📈 Multi-Path Analysis Results (Block {}):", block_number);
+    log!(level, "├─ Fetch Time: {:?}", fetch_duration);
+    log!(level, "├─ Analysis Time: {:?}", analysis_duration);
+    print_analysis_timings(&multi_opportunity.timings);
+    log!(level, "├─ Total Opportunities: {}", multi_opportunity.opportunities.len());
+    log!(level, "├─ Profitable Opportunities: {}", multi_opportunity.profitable_count());
 
     if multi_opportunity.has_profitable_opportunities() {
-        println!("└─ 💰 PROFITABLE OPPORTUNITIES FOUND!");
+        info!("└─ 💰 PROFITABLE OPPORTUNITIES FOUND!");
         
         // Select best opportunity using strategy
         let profitable_ops = multi_opportunity.profitable_opportunities();
         
         if let Some(best_opportunity) = StrategySelector::select_best(
             &profitable_ops.iter().cloned().cloned().collect::<Vec<_>>(),
-            OptimizationStrategy::MaxProfit
+            config.ranking_strategy()
         ) {
-            println!("\n🎯 BEST OPPORTUNITY:");
-            print_opportunity_details(best_opportunity);
+            info!("\n🎯 BEST OPPORTUNITY:");
+            print_opportunity_details(best_opportunity, config.base_token.symbol(), config.price_source());
             
-            // Log to CSV if configured
-            match write_multi_path_opportunity_to_csv(
+            // Log to the configured output format
+            let record = build_multi_path_record(
                 timestamp,
                 block_number,
                 best_opportunity,
                 fetch_duration.as_millis() as u64,
                 analysis_duration.as_millis() as u64,
-                config,
-            ) {
-                Ok(_) => log_csv_success(&config.csv_file_path),
-                Err(e) => log_csv_failure(e.as_ref()),
+                true,
+                &config.gas_overhead(),
+                config.price_source(),
+            );
+            if config.verify_next_block {
+                info!("🔬 Recording once re-priced at block {}", block_number + 1);
+                pending_verifications.push(PendingVerification { opportunity: best_opportunity.clone(), record });
+            } else {
+                let result = record_multi_path_opportunity(record, record_sink, config);
+                match (&config.output_format, result) {
+                    (OutputFormat::Csv, Ok(())) => log_csv_success(&config.csv_file_path),
+                    (OutputFormat::Csv, Err(e)) => log_csv_failure(e.as_ref()),
+                    (OutputFormat::Parquet, Ok(())) => log_parquet_success(&config.parquet_file_path),
+                    (OutputFormat::Parquet, Err(e)) => log_parquet_failure(e.as_ref()),
+                    (OutputFormat::Sqlite, Ok(())) => log_sqlite_success(&config.sqlite_file_path),
+                    (OutputFormat::Sqlite, Err(e)) => log_sqlite_failure(e.as_ref()),
+                }
             }
         }
 
@@ -195,14 +747,15 @@ async fn process_multi_path_results(
         let mut sorted_ops = profitable_ops;
         sorted_ops.sort_by(|a, b| b.net_profit.partial_cmp(&a.net_profit).unwrap_or(std::cmp::Ordering::Equal));
         
-        println!("\n📊 TOP 5 OPPORTUNITIES:");
+        info!("\n📊 TOP 5 OPPORTUNITIES:");
         for (i, opportunity) in sorted_ops.iter().take(5).enumerate() {
-            println!("{}. {} | Profit: {:.4} WMNT ({:.2}%) | Path: {}", 
+            info!("{}. {} | Profit: {:.4} {} ({:.2}%) | Path: {}", 
                     i + 1,
                     opportunity.path.as_ref()
                         .map(|p| format!("{}-hop", p.tokens.len() - 1))
                         .unwrap_or_else(|| "Unknown".to_string()),
                     opportunity.net_profit,
+                    config.base_token.symbol(),
                     opportunity.profit_percentage,
                     opportunity.path.as_ref()
                         .map(|p| p.description())
@@ -211,47 +764,193 @@ async fn process_multi_path_results(
         }
         
     } else {
-        println!("└─ ❌ No profitable opportunities found");
+        debug!("└─ ❌ No profitable opportunities found");
         
-        if !multi_opportunity.opportunities.is_empty() {
+        if !multi_opportunity.opportunities.is_empty() && log_enabled!(Level::Debug) {
             // Show best non-profitable opportunity for analysis
             if let Some(best_attempt) = multi_opportunity.opportunities
                 .iter()
                 .max_by(|a, b| a.net_profit.partial_cmp(&b.net_profit).unwrap_or(std::cmp::Ordering::Equal))
             {
-                println!("\n📊 Best Attempt (Non-profitable):");
-                print_opportunity_details(best_attempt);
+                debug!("\n📊 Best Attempt (Non-profitable):");
+                print_opportunity_details(best_attempt, config.base_token.symbol(), config.price_source());
             }
         }
     }
     
-    println!(); // Add spacing for readability
+    log!(level, ""); // Add spacing for readability
+}
+
+/// Print profitable routes that disappeared, with how long they lasted
+fn print_ended_opportunities(ended: &[EndedOpportunity]) {
+    for opportunity in ended {
+        info!("⌛ Opportunity {} ({}) gone, persisted {} blocks ({} → {})",
+                opportunity.opportunity_id,
+                opportunity.path_description,
+                opportunity.persisted_blocks(),
+                opportunity.first_seen_block,
+                opportunity.last_seen_block);
+    }
+}
+
+/// Print profitable routes still waiting for confirmation
+fn print_unconfirmed_opportunities(unconfirmed: &[(ArbitrageOpportunity, u32)], required_blocks: u32, unit: &str) {
+    debug!("⏳ Unconfirmed Opportunities ({}):", unconfirmed.len());
+    for (i, (opportunity, streak)) in unconfirmed.iter().enumerate() {
+        let prefix = if i == unconfirmed.len() - 1 { "└─" } else { "├─" };
+        debug!("{} {}/{} blocks | Profit: {:.4} {} | Path: {}",
+                prefix,
+                streak,
+                required_blocks,
+                opportunity.net_profit,
+                unit,
+                opportunity.path.as_ref()
+                    .map(|p| p.description())
+                    .unwrap_or_else(|| "Unknown path".to_string())
+        );
+    }
+}
+
+/// Print the pools whose reserve ratio moved the most over the volatility window
+fn print_most_volatile_pools(reserve_history: &ReserveHistory) {
+    let ranked = reserve_history.most_volatile(5);
+    if ranked.is_empty() {
+        return;
+    }
+
+    info!("🌊 Most Volatile Pools:");
+    for (i, (address, volatility)) in ranked.iter().enumerate() {
+        let prefix = if i == ranked.len() - 1 { "└─" } else { "├─" };
+        info!("{} {} | Volatility: {:.4}%", prefix, address, volatility * 100.0);
+    }
 }
 
 /// Print detailed opportunity information
-fn print_opportunity_details(opportunity: &crate::types::ArbitrageOpportunity) {
-    println!("├─ Input Amount: {:.4} WMNT", opportunity.optimal_input);
-    println!("├─ Output Amount: {:.4} WMNT", opportunity.final_output);
-    println!("├─ Gross Profit: {:.4} WMNT", opportunity.gross_profit);
-    println!("├─ Net Profit: {:.4} WMNT", opportunity.net_profit);
-    println!("├─ Profit %: {:.2}%", opportunity.profit_percentage);
+pub(crate) fn print_opportunity_details(opportunity: &crate::types::ArbitrageOpportunity, unit: &str, prices: Option<&dyn PriceSource>) {
+    info!("├─ Input Amount: {:.4} {}", opportunity.optimal_input, unit);
+    if let Some(constraint) = opportunity.binding_constraint {
+        info!("├─ Input capped by {} (unconstrained optimum {:.4} {})", constraint.label(), opportunity.unconstrained_input, unit);
+    }
+    info!("├─ Output Amount: {:.4} {}", opportunity.final_output, unit);
+    info!("├─ Gross Profit: {}", paint_signed(&format!("{:.4} {}", opportunity.gross_profit, unit), opportunity.gross_profit));
+    info!("├─ Net Profit: {}", paint_signed(&format!("{:.4} {}", opportunity.net_profit, unit), opportunity.net_profit));
+    if let Some(usd) = prices.and_then(|prices| opportunity.net_profit_usd(prices)) {
+        info!("├─ Net Profit (USD): {}", paint_signed(&format!("${:.2}", usd), usd));
+    }
+    info!(
+        "├─ Expected Realized: {} (after MEV competition)",
+        paint_signed(&format!("{:.4} {}", opportunity.expected_realized_profit, unit), opportunity.expected_realized_profit)
+    );
+    info!("├─ Profit %: {}", paint_signed(&format!("{:.2}%", opportunity.profit_percentage), opportunity.profit_percentage));
+    info!("├─ Robustness: {:.0}% of input range profitable", opportunity.robustness * 100.0);
+    if opportunity.confidence < 1.0 {
+        info!("├─ Confidence: {:.0}% the spread survives until execution", opportunity.confidence * 100.0);
+    }
+    info!("├─ Price Impact: {:.3}% (worst hop)", opportunity.price_impact_percent);
+    info!("├─ Path Depth: {:.2} {} (shallowest pool)", opportunity.min_pool_depth, unit);
+    if !opportunity.hop_outputs.is_empty() {
+        let amounts: Vec<String> = opportunity.hop_outputs.iter().map(|amount| format!("{:.4}", amount)).collect();
+        info!("├─ Hop Outputs: {}", amounts.join(" → "));
+    }
+    if opportunity.requires_flash_loan {
+        info!("├─ Flash Loan: required, {:.4} {} fee (included in net profit)", opportunity.flash_loan_fee, unit);
+    }
     
     if let Some(path) = &opportunity.path {
-        println!("├─ Path Type: {}-hop", path.tokens.len() - 1);
-        println!("├─ Gas Units: {}", path.expected_gas_units());
-        println!("└─ Route: {}", path.description());
+        info!("├─ Path Type: {}-hop", path.tokens.len() - 1);
+        info!("├─ Gas Units: {}", path.expected_gas_units());
+        info!("└─ Route: {}", path.description());
     } else {
-        println!("└─ Route: Legacy triangular");
+        info!("└─ Route: Legacy triangular");
+    }
+}
+
+/// Profitable record held back until its route is re-priced at the next block
+struct PendingVerification {
+    opportunity: ArbitrageOpportunity,
+    record: MultiPathArbitrageRecord,
+}
+
+/// Re-price records found before `current_block` against their next block and write them out.
+/// The current block's reserves are reused when they are that block; otherwise the route's
+/// pools are fetched at N+1.
+async fn verify_pending_records<P: Provider>(
+    pending_verifications: &mut Vec<PendingVerification>,
+    analyzer: &MultiPathAnalyzer,
+    provider: &P,
+    current_block: u64,
+    reserves_map: &HashMap<Address, PoolReserves>,
+    record_sink: &mut RecordSink,
+    config: &Config,
+) {
+    let due: Vec<_> = pending_verifications
+        .extract_if(.., |pending| pending.record.block_number < current_block)
+        .collect();
+    for PendingVerification { opportunity, mut record } in due {
+        let verify_block = record.block_number + 1;
+        let repriced = if verify_block == current_block {
+            Ok(analyzer.reprice(&opportunity, reserves_map))
+        } else {
+            let pools = opportunity.path.as_ref().map(|p| p.pools.clone()).unwrap_or_default();
+            fetch_pools_at_block(provider, &pools, verify_block).await.map(|mut reserves| {
+                config.apply_reserve_scale(&mut reserves);
+                analyzer.reprice(&opportunity, &reserves)
+            })
+        };
+
+        match repriced {
+            Ok(Some(net_profit)) => {
+                record.verified_net_profit = Some(net_profit);
+                if !config.tui_enabled {
+                    let verdict = if net_profit > 0.0 { "survived" } else { "faded" };
+                    info!("🔬 Block {} route {} {} at block {}: {:.6} → {:.6} {} net",
+                            record.block_number, record.opportunity_id, verdict, verify_block, record.net_profit_wmnt, net_profit, config.base_token.symbol());
+                }
+            }
+            Ok(None) => warn!("⚠️ Could not re-price route {}: pool reserves missing at block {}", record.opportunity_id, verify_block),
+            Err(e) => warn!("⚠️ Failed to fetch route {} at block {}: {}", record.opportunity_id, verify_block, e),
+        }
+        if let Err(e) = record_multi_path_opportunity(record, record_sink, config) {
+            warn!("⚠️ Failed to record opportunity: {}", e);
+        }
+    }
+}
+
+/// Destination for opportunity records, chosen by `OUTPUT_FORMAT`
+enum RecordSink {
+    Csv,
+    Parquet(Box<ParquetSink>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteLogger),
+}
+
+impl RecordSink {
+    /// Write out anything still buffered (Parquet row groups and footer)
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        match self {
+            RecordSink::Parquet(sink) => sink.close(),
+            _ => Ok(()),
+        }
     }
 }
 
-/// Write multi-path opportunity to CSV
+/// Route an opportunity record to the configured sink
+fn record_multi_path_opportunity(
+    record: MultiPathArbitrageRecord,
+    record_sink: &mut RecordSink,
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    match record_sink {
+        RecordSink::Csv => write_multi_path_opportunity_to_csv(&record, config),
+        RecordSink::Parquet(sink) => sink.write(record),
+        #[cfg(feature = "sqlite")]
+        RecordSink::Sqlite(logger) => logger.insert(&record),
+    }
+}
+
+/// Write multi-path opportunity record to CSV
 fn write_multi_path_opportunity_to_csv(
-    timestamp: chrono::DateTime<Utc>,
-    block_number: u64,
-    opportunity: &crate::types::ArbitrageOpportunity,
-    fetch_time_ms: u64,
-    analysis_time_ms: u64,
+    record: &MultiPathArbitrageRecord,
     config: &Config,
 ) -> Result<(), Box<dyn Error>> {
     use std::fs::OpenOptions;
@@ -262,9 +961,25 @@ fn write_multi_path_opportunity_to_csv(
         .open(&config.csv_file_path)?;
 
     let mut writer = csv::Writer::from_writer(file);
-    
-    // Create enhanced record for multi-path arbitrage
-    let record = crate::types::MultiPathArbitrageRecord {
+    writer.serialize(record)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Build the enhanced record for a multi-path opportunity
+#[allow(clippy::too_many_arguments)]
+fn build_multi_path_record(
+    timestamp: chrono::DateTime<Utc>,
+    block_number: u64,
+    opportunity: &crate::types::ArbitrageOpportunity,
+    fetch_time_ms: u64,
+    analysis_time_ms: u64,
+    actionable: bool,
+    gas_overhead: &GasOverhead,
+    prices: Option<&dyn PriceSource>,
+) -> MultiPathArbitrageRecord {
+    MultiPathArbitrageRecord {
         timestamp: timestamp.to_rfc3339(),
         block_number,
         optimal_input_wmnt: opportunity.optimal_input,
@@ -280,16 +995,22 @@ fn write_multi_path_opportunity_to_csv(
             .map(|p| p.description())
             .unwrap_or_else(|| "WMNT -> MOE -> JOE -> WMNT".to_string()),
         gas_units: opportunity.path.as_ref()
-            .map(|p| p.expected_gas_units())
+            .map(|p| p.total_gas_units(gas_overhead))
             .unwrap_or(700_000),
         fetch_time_ms,
         analysis_time_ms,
-    };
-
-    writer.serialize(record)?;
-    writer.flush()?;
-    
-    Ok(())
+        actionable,
+        price_impact_percent: opportunity.price_impact_percent,
+        requires_flash_loan: opportunity.requires_flash_loan,
+        flash_loan_fee_wmnt: opportunity.flash_loan_fee,
+        opportunity_id: opportunity.path.as_ref()
+            .map(|p| p.opportunity_id())
+            .unwrap_or_default(),
+        verified_net_profit: None,
+        expected_realized_profit_wmnt: opportunity.expected_realized_profit,
+        hop_outputs: opportunity.hop_outputs_json(),
+        net_profit_usd: prices.and_then(|prices| opportunity.net_profit_usd(prices)),
+    }
 }
 
 #[cfg(test)]
@@ -300,6 +1021,146 @@ mod tests {
     fn test_multi_path_main_functions_exist() {
         // Test that the main functions are properly defined
         // This is a compilation test
-        assert!(true);
+        let _ = run_multi_path_arbitrage;
+    }
+
+    #[test]
+    fn test_parse_pool_list() {
+        let addresses = parse_pool_list(
+            "0x0000000000000000000000000000000000000001, 0x0000000000000000000000000000000000000002,",
+        ).unwrap();
+        assert_eq!(addresses, vec![Address::with_last_byte(1), Address::with_last_byte(2)]);
+
+        assert!(parse_pool_list("0x01,not-an-address").is_err());
+        assert!(parse_pool_list(
+            "0x0000000000000000000000000000000000000001,0x0000000000000000000000000000000000000001"
+        ).is_err());
+        assert!(parse_pool_list(" , ").is_err());
+    }
+
+    #[test]
+    fn test_list_paths_includes_unprofitable_cycles() {
+        use std::io::Write;
+        use crate::multi_path::tests::create_test_config;
+
+        // Balanced reserves: SPFA finds nothing, but the pools still form cycles
+        let mut csv = tempfile::NamedTempFile::new().unwrap();
+        writeln!(csv, "Protocol,Pair Name,Pair Address,TokenA Reserves,TokenB Reserves").unwrap();
+        writeln!(csv, "MOE,MOE-WMNT,0x00000000000000000000000000000000000000b1,1000,1000").unwrap();
+        writeln!(csv, "MOE,JOE-MOE,0x00000000000000000000000000000000000000b2,1000,1000").unwrap();
+        writeln!(csv, "MOE,JOE-WMNT,0x00000000000000000000000000000000000000b3,1000,1000").unwrap();
+        writeln!(csv, "Agni,JOE-WMNT,0x00000000000000000000000000000000000000b4,1000,1000").unwrap();
+        let csv_path = csv.path().to_str().unwrap();
+        let config = create_test_config();
+
+        let mut analyzer = MultiPathAnalyzer::new(config.base_token.clone(), &config);
+        analyzer.load_pools_from_csv(csv_path).unwrap();
+        assert!(analyzer.get_all_paths().is_empty());
+
+        // Two 2-hop cycles across the JOE-WMNT pools, and each triangle direction through either of them
+        let paths = analyzer.get_structural_paths();
+        let count = |description: &str| paths.iter().filter(|path| path.description() == description).count();
+        assert_eq!(count("WMNT -> JOE -> WMNT"), 2);
+        assert_eq!(count("WMNT -> MOE -> JOE -> WMNT"), 2);
+        assert_eq!(count("WMNT -> JOE -> MOE -> WMNT"), 2);
+        assert_eq!(paths.len(), 6);
+        assert!(paths.iter().all(|path| path.pools.windows(2).all(|hop| hop[0] != hop[1])));
+
+        assert_eq!(run_list_paths(&config, csv_path).unwrap(), 6);
+    }
+
+    struct StubPrices;
+
+    impl PriceSource for StubPrices {
+        fn price_usd(&self, token: &Token) -> Option<f64> {
+            (token.symbol() == "WMNT").then_some(0.5)
+        }
+    }
+
+    #[test]
+    fn test_wrap_gas_in_csv_gas_units_for_wmnt_endpoints() {
+        use crate::constants::{GAS_UNITS_3_HOPS, GWEI_TO_MNT_MULTIPLIER};
+        use crate::types::ArbitragePath;
+
+        let [wmnt, moe, joe] = ["WMNT", "MOE", "JOE"].map(|symbol| Token::parse(symbol).unwrap());
+        let pools = vec![Address::from([1u8; 20]), Address::from([2u8; 20]), Address::from([3u8; 20])];
+        let mut opportunity = ArbitrageOpportunity {
+            optimal_input: 100.0,
+            final_output: 101.0,
+            gross_profit: 1.0,
+            net_profit: 1.0,
+            profit_percentage: 1.0,
+            search_method: "test".to_string(),
+            path: Some(ArbitragePath::new(vec![wmnt.clone(), moe.clone(), joe.clone(), wmnt], pools.clone())),
+            robustness: 1.0,
+            unconstrained_input: 100.0,
+            binding_constraint: None,
+            price_impact_percent: 0.0,
+            requires_flash_loan: false,
+            flash_loan_fee: 0.0,
+            min_pool_depth: 0.0,
+            expected_realized_profit: 1.0,
+            hop_outputs: Vec::new(),
+            confidence: 1.0,
+        };
+        let wrap = GasOverhead { approval_gas_units: 0, wrap_gas_units: 30_000_000 };
+        let gas_units = |opportunity: &ArbitrageOpportunity, overhead: &GasOverhead| {
+            build_multi_path_record(Utc::now(), 1, opportunity, 0, 0, true, overhead, None).gas_units
+        };
+
+        // Zero wrap gas (the default) keeps the swap-only figure
+        assert_eq!(gas_units(&opportunity, &GasOverhead::default()), GAS_UNITS_3_HOPS);
+        assert_eq!(gas_units(&opportunity, &wrap), GAS_UNITS_3_HOPS + 60_000_000);
+        let gas_cost = opportunity.gas_cost(1.0, &wrap);
+        assert!((gas_cost - (GAS_UNITS_3_HOPS + 60_000_000) as f64 * GWEI_TO_MNT_MULTIPLIER).abs() < 1e-12);
+
+        // A route that never touches MNT needs no wrap
+        opportunity.path = Some(ArbitragePath::new(vec![moe.clone(), joe, Token::parse("WMNT").unwrap(), moe], pools));
+        assert_eq!(gas_units(&opportunity, &wrap), GAS_UNITS_3_HOPS);
+    }
+
+    #[test]
+    fn test_net_profit_usd_in_csv_record() {
+        let wmnt = Token::new(Address::ZERO, "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let mut opportunity = ArbitrageOpportunity {
+            optimal_input: 100.0,
+            final_output: 101.5,
+            gross_profit: 1.5,
+            net_profit: 1.5,
+            profit_percentage: 1.5,
+            search_method: "test".to_string(),
+            path: Some(crate::types::ArbitragePath::new(vec![wmnt.clone(), moe.clone(), joe.clone(), wmnt], Vec::new())),
+            robustness: 1.0,
+            unconstrained_input: 100.0,
+            binding_constraint: None,
+            price_impact_percent: 0.0,
+            requires_flash_loan: false,
+            flash_loan_fee: 0.0,
+            min_pool_depth: 0.0,
+            expected_realized_profit: 1.5,
+            hop_outputs: Vec::new(),
+            confidence: 1.0,
+        };
+        let csv_text = |record: &MultiPathArbitrageRecord| {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.serialize(record).unwrap();
+            String::from_utf8(writer.into_inner().unwrap()).unwrap()
+        };
+
+        // WMNT profit converts at the quoted price and gets its own column
+        let record = build_multi_path_record(Utc::now(), 1, &opportunity, 0, 0, true, &GasOverhead::default(), Some(&StubPrices));
+        assert!((record.net_profit_usd.unwrap() - 0.75).abs() < 1e-12);
+        let text = csv_text(&record);
+        assert!(text.lines().next().unwrap().ends_with(",net_profit_usd"));
+        assert!(text.lines().nth(1).unwrap().ends_with(",0.75"));
+
+        // Without a source, or without a quote for the start token, the column is left out
+        let record = build_multi_path_record(Utc::now(), 1, &opportunity, 0, 0, true, &GasOverhead::default(), None);
+        assert_eq!(record.net_profit_usd, None);
+        assert!(!csv_text(&record).contains("net_profit_usd"));
+        opportunity.path = Some(crate::types::ArbitragePath::new(vec![moe.clone(), joe, moe], Vec::new()));
+        assert_eq!(opportunity.net_profit_usd(&StubPrices), None);
     }
 }
@@ -0,0 +1,231 @@
+use std::error::Error;
+use std::fs::File;
+use std::sync::Arc;
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, TimestampMillisecondArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::DateTime;
+use log::warn;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+use crate::types::MultiPathArbitrageRecord;
+
+/// Arrow schema mirroring `MultiPathArbitrageRecord` with typed columns
+pub fn opportunity_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("timestamp", DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())), false),
+        Field::new("block_number", DataType::UInt64, false),
+        Field::new("optimal_input_wmnt", DataType::Float64, false),
+        Field::new("final_output_wmnt", DataType::Float64, false),
+        Field::new("gross_profit_wmnt", DataType::Float64, false),
+        Field::new("net_profit_wmnt", DataType::Float64, false),
+        Field::new("profit_percentage", DataType::Float64, false),
+        Field::new("search_method", DataType::Utf8, false),
+        Field::new("path_type", DataType::Utf8, false),
+        Field::new("path_description", DataType::Utf8, false),
+        Field::new("gas_units", DataType::UInt64, false),
+        Field::new("fetch_time_ms", DataType::UInt64, false),
+        Field::new("analysis_time_ms", DataType::UInt64, false),
+        Field::new("actionable", DataType::Boolean, false),
+        Field::new("price_impact_percent", DataType::Float64, false),
+        Field::new("requires_flash_loan", DataType::Boolean, false),
+        Field::new("flash_loan_fee_wmnt", DataType::Float64, false),
+        Field::new("opportunity_id", DataType::Utf8, false),
+        Field::new("verified_net_profit", DataType::Float64, true),
+        Field::new("expected_realized_profit_wmnt", DataType::Float64, false),
+        Field::new("hop_outputs", DataType::Utf8, false),
+    ]))
+}
+
+/// Convert buffered records into a single Arrow record batch
+fn records_to_batch(schema: SchemaRef, records: &[MultiPathArbitrageRecord]) -> Result<RecordBatch, Box<dyn Error>> {
+    let timestamps = records
+        .iter()
+        .map(|r| DateTime::parse_from_rfc3339(&r.timestamp).map(|t| t.timestamp_millis()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(TimestampMillisecondArray::from(timestamps).with_timezone("UTC")),
+        Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.block_number))),
+        Arc::new(Float64Array::from_iter_values(records.iter().map(|r| r.optimal_input_wmnt))),
+        Arc::new(Float64Array::from_iter_values(records.iter().map(|r| r.final_output_wmnt))),
+        Arc::new(Float64Array::from_iter_values(records.iter().map(|r| r.gross_profit_wmnt))),
+        Arc::new(Float64Array::from_iter_values(records.iter().map(|r| r.net_profit_wmnt))),
+        Arc::new(Float64Array::from_iter_values(records.iter().map(|r| r.profit_percentage))),
+        Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.search_method.as_str()))),
+        Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.path_type.as_str()))),
+        Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.path_description.as_str()))),
+        Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.gas_units))),
+        Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.fetch_time_ms))),
+        Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.analysis_time_ms))),
+        Arc::new(BooleanArray::from_iter(records.iter().map(|r| Some(r.actionable)))),
+        Arc::new(Float64Array::from_iter_values(records.iter().map(|r| r.price_impact_percent))),
+        Arc::new(BooleanArray::from_iter(records.iter().map(|r| Some(r.requires_flash_loan)))),
+        Arc::new(Float64Array::from_iter_values(records.iter().map(|r| r.flash_loan_fee_wmnt))),
+        Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.opportunity_id.as_str()))),
+        Arc::new(Float64Array::from_iter(records.iter().map(|r| r.verified_net_profit))),
+        Arc::new(Float64Array::from_iter_values(records.iter().map(|r| r.expected_realized_profit_wmnt))),
+        Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.hop_outputs.as_str()))),
+    ];
+
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
+
+/// Parquet sink that buffers opportunity records and writes them as row groups
+pub struct ParquetSink {
+    writer: Option<ArrowWriter<File>>,
+    schema: SchemaRef,
+    buffer: Vec<MultiPathArbitrageRecord>,
+    row_group_size: usize,
+    file_path: String,
+}
+
+impl ParquetSink {
+    /// Create (or truncate) a Parquet file for opportunity records
+    pub fn create(file_path: &str, row_group_size: usize) -> Result<Self, Box<dyn Error>> {
+        let row_group_size = row_group_size.max(1);
+        let schema = opportunity_schema();
+        let file = File::create(file_path)?;
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(row_group_size)
+            .build();
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
+
+        Ok(Self {
+            writer: Some(writer),
+            schema,
+            buffer: Vec::with_capacity(row_group_size),
+            row_group_size,
+            file_path: file_path.to_string(),
+        })
+    }
+
+    /// Buffer a record, writing a row group once the buffer is full
+    pub fn write(&mut self, record: MultiPathArbitrageRecord) -> Result<(), Box<dyn Error>> {
+        self.buffer.push(record);
+        if self.buffer.len() >= self.row_group_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write all buffered records out as a row group
+    pub fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let writer = self.writer.as_mut().ok_or("Parquet sink already closed")?;
+        let batch = records_to_batch(self.schema.clone(), &self.buffer)?;
+        writer.write(&batch)?;
+        writer.flush()?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Flush remaining records and write the Parquet footer
+    pub fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        self.flush()?;
+        if let Some(writer) = self.writer.take() {
+            writer.close()?;
+        }
+        Ok(())
+    }
+
+    /// Number of records waiting for the next row group
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Path of the Parquet file being written
+    pub fn file_path(&self) -> &str {
+        &self.file_path
+    }
+}
+
+impl Drop for ParquetSink {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            warn!("⚠️ Failed to finalize Parquet file {}: {}", self.file_path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use tempfile::NamedTempFile;
+
+    fn create_record(block_number: u64, net_profit: f64) -> MultiPathArbitrageRecord {
+        MultiPathArbitrageRecord {
+            timestamp: "2025-01-01T00:00:00.250+00:00".to_string(),
+            block_number,
+            optimal_input_wmnt: 100.0,
+            final_output_wmnt: 100.0 + net_profit,
+            gross_profit_wmnt: net_profit + 0.5,
+            net_profit_wmnt: net_profit,
+            profit_percentage: net_profit,
+            search_method: "multi_path_ternary".to_string(),
+            path_type: "3-hop".to_string(),
+            path_description: "WMNT -> MOE -> JOE -> WMNT".to_string(),
+            gas_units: 700_000_000,
+            fetch_time_ms: 12,
+            analysis_time_ms: 3,
+            actionable: net_profit >= 2.0,
+            price_impact_percent: 0.4,
+            requires_flash_loan: false,
+            flash_loan_fee_wmnt: 0.0,
+            opportunity_id: "3f1c2a9b8e7d6c5a".to_string(),
+            verified_net_profit: block_number.is_multiple_of(2).then_some(net_profit - 0.5),
+            expected_realized_profit_wmnt: net_profit * 0.6,
+            hop_outputs: "[100.0,92.1,101.4,104.2]".to_string(),
+            net_profit_usd: None,
+        }
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let mut sink = ParquetSink::create(path, 2).unwrap();
+        sink.write(create_record(10, 1.5)).unwrap();
+        sink.write(create_record(11, 2.5)).unwrap(); // Fills the first row group
+        sink.write(create_record(12, 3.5)).unwrap();
+        assert_eq!(sink.buffered(), 1);
+        sink.close().unwrap();
+
+        let file = File::open(path).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        assert_eq!(builder.metadata().num_row_groups(), 2);
+        assert_eq!(builder.schema().fields(), opportunity_schema().fields());
+
+        let batches: Vec<RecordBatch> = builder.build().unwrap().map(|b| b.unwrap()).collect();
+        let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(rows, 3);
+
+        let first = &batches[0];
+        let blocks = first.column_by_name("block_number").unwrap()
+            .as_any().downcast_ref::<UInt64Array>().unwrap();
+        let profits = first.column_by_name("net_profit_wmnt").unwrap()
+            .as_any().downcast_ref::<Float64Array>().unwrap();
+        let timestamps = first.column_by_name("timestamp").unwrap()
+            .as_any().downcast_ref::<TimestampMillisecondArray>().unwrap();
+        let paths = first.column_by_name("path_description").unwrap()
+            .as_any().downcast_ref::<StringArray>().unwrap();
+
+        assert_eq!(blocks.value(0), 10);
+        assert_eq!(profits.value(1), 2.5);
+        assert_eq!(timestamps.value(0), 1_735_689_600_250);
+        assert_eq!(paths.value(0), "WMNT -> MOE -> JOE -> WMNT");
+        assert_eq!(paths.null_count(), 0);
+        let actionable = first.column_by_name("actionable").unwrap()
+            .as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert!(!actionable.value(0) && actionable.value(1));
+        let verified = first.column_by_name("verified_net_profit").unwrap()
+            .as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(verified.value(0), 1.0);
+        assert!(verified.is_null(1));
+    }
+}
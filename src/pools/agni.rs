@@ -0,0 +1,54 @@
+//! Agni Finance DEX protocol implementation
+
+use alloy::primitives::Address;
+use crate::types::Token;
+use crate::constants::DEFAULT_AGNI_FEE;
+use super::DexProtocol;
+
+/// Agni Finance DEX protocol implementation.
+/// No Agni pools are built in: list them in the pool CSV with an `Agni` protocol, or use `with_pools`.
+pub struct AgniProtocol {
+    name: String,
+    default_fee: f64,
+    known_pools: Vec<(Address, String, Token, Token)>,
+}
+
+impl AgniProtocol {
+    /// Create an Agni protocol instance without known pools
+    pub fn new() -> Self {
+        Self::with_pools(Vec::new())
+    }
+
+    /// Agni protocol that owns the given (address, name, token_a, token_b) pools
+    pub fn with_pools(known_pools: Vec<(Address, String, Token, Token)>) -> Self {
+        Self {
+            name: "Agni".to_string(),
+            default_fee: DEFAULT_AGNI_FEE,
+            known_pools,
+        }
+    }
+}
+
+impl DexProtocol for AgniProtocol {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_known_pools(&self) -> Vec<(Address, String, Token, Token)> {
+        self.known_pools.clone()
+    }
+
+    fn default_fee(&self) -> f64 {
+        self.default_fee
+    }
+
+    fn is_valid_pool(&self, pool_address: Address) -> bool {
+        self.known_pools.iter().any(|(addr, _, _, _)| *addr == pool_address)
+    }
+}
+
+impl Default for AgniProtocol {
+    fn default() -> Self {
+        Self::new()
+    }
+}
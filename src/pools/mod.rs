@@ -4,9 +4,51 @@
 //! DEX protocols that can be used for arbitrage opportunities.
 
 pub mod moe;
+pub mod agni;
 
 use alloy::primitives::Address;
 use crate::types::Token;
+use agni::AgniProtocol;
+use moe::MoeProtocol;
+
+/// A DEX whose pools can be added to the arbitrage graph
+pub trait DexProtocol: Send + Sync {
+    /// Protocol name; pool CSV `Protocol` values containing it (any case) belong to this DEX
+    fn name(&self) -> &str;
+
+    /// Built-in pools as (address, name, token_a, token_b)
+    fn get_known_pools(&self) -> Vec<(Address, String, Token, Token)>;
+
+    /// Swap fee (fraction) for this DEX's pools that have no explicit fee
+    fn default_fee(&self) -> f64;
+
+    /// Whether an address is one of this DEX's known pools
+    fn is_valid_pool(&self, pool_address: Address) -> bool;
+}
+
+/// Every supported DEX; MOE keeps `moe_fee` (the configured `DEX_FEE`)
+pub fn registered_protocols(moe_fee: f64) -> Vec<Box<dyn DexProtocol>> {
+    vec![
+        Box::new(MoeProtocol::with_fee(moe_fee)),
+        Box::new(AgniProtocol::new()),
+    ]
+}
+
+/// Protocol owning a pool: a known pool address wins, then the pool CSV protocol name
+pub fn owning_protocol<'a>(
+    protocols: &'a [Box<dyn DexProtocol>],
+    pool_address: Address,
+    protocol_name: Option<&str>,
+) -> Option<&'a dyn DexProtocol> {
+    protocols
+        .iter()
+        .find(|protocol| protocol.is_valid_pool(pool_address))
+        .or_else(|| {
+            let protocol_name = protocol_name?.to_lowercase();
+            protocols.iter().find(|protocol| protocol_name.contains(&protocol.name().to_lowercase()))
+        })
+        .map(|protocol| protocol.as_ref())
+}
 
 /// Pool information structure
 #[derive(Debug, Clone)]
@@ -66,15 +108,33 @@ mod tests {
         let pool = PoolInfo::new(
             Address::from([2u8; 20]),
             "Test Pool".to_string(),
-            Token::WMNT(wmnt_addr),
-            Token::MOE(moe_addr),
+            Token::new(wmnt_addr, "WMNT", 18),
+            Token::new(moe_addr, "MOE", 18),
             "TestDEX".to_string(),
             0.003,
         );
 
         assert_eq!(pool.get_display_name(), "WMNT-MOE (TestDEX)");
-        assert!(pool.contains_tokens(Token::WMNT(wmnt_addr), Token::MOE(moe_addr)));
-        assert!(pool.contains_tokens(Token::MOE(moe_addr), Token::WMNT(wmnt_addr)));
+        assert!(pool.contains_tokens(Token::new(wmnt_addr, "WMNT", 18), Token::new(moe_addr, "MOE", 18)));
+        assert!(pool.contains_tokens(Token::new(moe_addr, "MOE", 18), Token::new(wmnt_addr, "WMNT", 18)));
+    }
+
+    #[test]
+    fn test_owning_protocol() {
+        let protocols = registered_protocols(0.002);
+        let moe_pool: Address = crate::constants::MOE_WMNT_POOL.parse().unwrap();
+        let other_pool = Address::from([9u8; 20]);
+
+        // Known addresses win over the CSV name
+        let owner = owning_protocol(&protocols, moe_pool, Some("Agni")).unwrap();
+        assert_eq!((owner.name(), owner.default_fee()), ("MOE", 0.002));
+
+        let owner = owning_protocol(&protocols, other_pool, Some("Agni Finance")).unwrap();
+        assert_eq!((owner.name(), owner.default_fee()), ("Agni", crate::constants::DEFAULT_AGNI_FEE));
+        assert_eq!(owning_protocol(&protocols, other_pool, Some("Merchant Moe")).unwrap().name(), "MOE");
+
+        assert!(owning_protocol(&protocols, other_pool, Some("FusionX")).is_none());
+        assert!(owning_protocol(&protocols, other_pool, None).is_none());
     }
 
 
@@ -3,6 +3,8 @@
 use alloy::primitives::Address;
 use crate::types::Token;
 use crate::constants::*;
+use crate::error::ArbError;
+use super::DexProtocol;
 
 /// MOE DEX protocol implementation
 pub struct MoeProtocol {
@@ -14,6 +16,11 @@ pub struct MoeProtocol {
 impl MoeProtocol {
     /// Create a new MOE protocol instance
     pub fn new() -> Self {
+        Self::with_fee(DEFAULT_DEX_FEE)
+    }
+
+    /// MOE protocol whose pools default to `default_fee`
+    pub fn with_fee(default_fee: f64) -> Self {
         let wmnt_addr: Address = WMNT_ADDRESS.parse().expect("Invalid WMNT address");
         let moe_addr: Address = MOE_ADDRESS.parse().expect("Invalid MOE address");
         let joe_addr: Address = JOE_ADDRESS.parse().expect("Invalid JOE address");
@@ -22,26 +29,26 @@ impl MoeProtocol {
             (
                 MOE_WMNT_POOL.parse().expect("Invalid MOE-WMNT pool address"),
                 "MOE-WMNT".to_string(),
-                Token::MOE(moe_addr),
-                Token::WMNT(wmnt_addr),
+                Token::new(moe_addr, "MOE", 18),
+                Token::new(wmnt_addr, "WMNT", 18),
             ),
             (
                 JOE_MOE_POOL.parse().expect("Invalid JOE-MOE pool address"),
                 "JOE-MOE".to_string(),
-                Token::JOE(joe_addr),
-                Token::MOE(moe_addr),
+                Token::new(joe_addr, "JOE", 18),
+                Token::new(moe_addr, "MOE", 18),
             ),
             (
                 JOE_WMNT_POOL.parse().expect("Invalid JOE-WMNT pool address"),
                 "JOE-WMNT".to_string(),
-                Token::JOE(joe_addr),
-                Token::WMNT(wmnt_addr),
+                Token::new(joe_addr, "JOE", 18),
+                Token::new(wmnt_addr, "WMNT", 18),
             ),
         ];
 
         Self {
             name: "MOE".to_string(),
-            default_fee: DEFAULT_DEX_FEE,
+            default_fee,
             known_pools,
         }
     }
@@ -64,10 +71,10 @@ impl MoeProtocol {
         let joe_addr: Address = JOE_ADDRESS.parse().expect("Invalid JOE address");
 
         vec![vec![
-            Token::WMNT(wmnt_addr),
-            Token::MOE(moe_addr),
-            Token::JOE(joe_addr),
-            Token::WMNT(wmnt_addr), // Complete the cycle
+            Token::new(wmnt_addr, "WMNT", 18),
+            Token::new(moe_addr, "MOE", 18),
+            Token::new(joe_addr, "JOE", 18),
+            Token::new(wmnt_addr, "WMNT", 18), // Complete the cycle
         ]]
     }
 
@@ -75,30 +82,27 @@ impl MoeProtocol {
     pub fn is_moe_pool(&self, address: Address) -> bool {
         self.known_pools.iter().any(|(addr, _, _, _)| *addr == address)
     }
+}
 
-    /// Get the protocol name
-    pub fn name(&self) -> &str {
+impl DexProtocol for MoeProtocol {
+    fn name(&self) -> &str {
         &self.name
     }
 
-    /// Get the default fee for this DEX
-    pub fn default_fee(&self) -> f64 {
-        self.default_fee
+    fn get_known_pools(&self) -> Vec<(Address, String, Token, Token)> {
+        self.known_pools.clone()
     }
 
-    /// Get all known pool addresses for this DEX
-    pub fn get_known_pools(&self) -> Vec<(Address, String, Token, Token)> {
-        self.known_pools.clone()
+    fn default_fee(&self) -> f64 {
+        self.default_fee
     }
 
-    /// Validate if a pool address belongs to this DEX
-    pub fn is_valid_pool(&self, pool_address: Address) -> bool {
+    fn is_valid_pool(&self, pool_address: Address) -> bool {
         self.is_moe_pool(pool_address)
     }
 }
 
 
-
 impl Default for MoeProtocol {
     fn default() -> Self {
         Self::new()
@@ -132,17 +136,17 @@ impl MoeProtocol {
     }
 
     /// Validate that we have all required pools for triangular arbitrage
-    pub fn validate_triangular_setup(&self) -> Result<(), String> {
+    pub fn validate_triangular_setup(&self) -> Result<(), ArbError> {
         let (moe_wmnt, joe_moe, joe_wmnt) = self.get_main_triangular_pools();
         
         if !self.is_valid_pool(moe_wmnt) {
-            return Err("MOE-WMNT pool not found".to_string());
+            return Err(ArbError::Config("MOE-WMNT pool not found".to_string()));
         }
         if !self.is_valid_pool(joe_moe) {
-            return Err("JOE-MOE pool not found".to_string());
+            return Err(ArbError::Config("JOE-MOE pool not found".to_string()));
         }
         if !self.is_valid_pool(joe_wmnt) {
-            return Err("JOE-WMNT pool not found".to_string());
+            return Err(ArbError::Config("JOE-WMNT pool not found".to_string()));
         }
 
         Ok(())
@@ -0,0 +1,73 @@
+//! USD prices for reporting profits outside the WMNT base unit
+
+use std::collections::HashMap;
+use alloy::primitives::Address;
+use crate::error::ArbError;
+use crate::types::Token;
+
+/// Quotes tokens in USD
+pub trait PriceSource: Send + Sync {
+    /// USD price of one whole `token`, `None` if the source has no quote
+    fn price_usd(&self, token: &Token) -> Option<f64>;
+
+    /// USD value of `amount` whole units of `token`
+    fn value_usd(&self, token: &Token, amount: f64) -> Option<f64> {
+        self.price_usd(token).map(|price| price * amount)
+    }
+}
+
+/// Fixed prices from `USD_PRICES` (`SYMBOL=price` entries, comma-separated)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StaticPriceSource {
+    prices: HashMap<Address, f64>,
+}
+
+impl StaticPriceSource {
+    /// Parse `WMNT=0.85,MOE=0.02`; tokens are symbols or addresses
+    pub fn parse(spec: &str) -> Result<Self, ArbError> {
+        let mut prices = HashMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (token, price) = entry
+                .split_once('=')
+                .ok_or_else(|| ArbError::Config(format!("invalid USD_PRICES entry '{}': expected TOKEN=price", entry)))?;
+            let token = Token::parse(token)
+                .ok_or_else(|| ArbError::TokenUnknown(format!("invalid USD_PRICES entry '{}': unknown token '{}'", entry, token.trim())))?;
+            let price: f64 = price.trim().parse()
+                .ok()
+                .filter(|price: &f64| price.is_finite() && *price > 0.0)
+                .ok_or_else(|| ArbError::Config(format!("invalid USD_PRICES entry '{}': price must be a positive number", entry)))?;
+            prices.insert(token.address(), price);
+        }
+        Ok(Self { prices })
+    }
+
+    /// No prices configured
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+}
+
+impl PriceSource for StaticPriceSource {
+    fn price_usd(&self, token: &Token) -> Option<f64> {
+        self.prices.get(&token.address()).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_price_source() {
+        let prices = StaticPriceSource::parse("WMNT=0.85, moe = 0.02,").unwrap();
+        let wmnt = Token::parse("WMNT").unwrap();
+        assert_eq!(prices.price_usd(&wmnt), Some(0.85));
+        assert_eq!(prices.value_usd(&Token::parse("MOE").unwrap(), 50.0), Some(1.0));
+        assert_eq!(prices.price_usd(&Token::parse("JOE").unwrap()), None);
+        assert!(StaticPriceSource::parse("").unwrap().is_empty());
+
+        assert!(matches!(StaticPriceSource::parse("WMNT"), Err(ArbError::Config(_))));
+        assert!(matches!(StaticPriceSource::parse("WMNT=-1"), Err(ArbError::Config(_))));
+        assert!(matches!(StaticPriceSource::parse("FOO=1"), Err(ArbError::TokenUnknown(_))));
+    }
+}
@@ -0,0 +1,202 @@
+//! `--record`: every block's reserves of all monitored pools as newline-delimited JSON
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use alloy::primitives::{Address, U256};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use crate::error::ArbError;
+use crate::types::{PoolReserves, Token};
+
+/// One pool's reserves in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolSnapshot {
+    pub address: Address,
+    pub token_a: Token,
+    pub reserve_a: U256,
+    pub token_b: Token,
+    pub reserve_b: U256,
+}
+
+/// Reserves of every monitored pool at one block (one line of a recording)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReservesSnapshot {
+    pub block_number: u64,
+    pub timestamp: DateTime<Utc>,
+    pub pools: Vec<PoolSnapshot>,
+}
+
+impl ReservesSnapshot {
+    /// Snapshot of a fetched reserves map, pools sorted by address so recordings diff cleanly
+    pub fn new(block_number: u64, timestamp: DateTime<Utc>, reserves_map: &HashMap<Address, PoolReserves>) -> Self {
+        let mut pools: Vec<PoolSnapshot> = reserves_map
+            .iter()
+            .map(|(address, reserves)| PoolSnapshot {
+                address: *address,
+                token_a: reserves.token_a.clone(),
+                reserve_a: reserves.reserve_a,
+                token_b: reserves.token_b.clone(),
+                reserve_b: reserves.reserve_b,
+            })
+            .collect();
+        pools.sort_by_key(|pool| pool.address);
+        Self { block_number, timestamp, pools }
+    }
+
+    /// The reserves map the snapshot was taken from
+    pub fn reserves(&self) -> HashMap<Address, PoolReserves> {
+        self.pools
+            .iter()
+            .map(|pool| {
+                let mut reserves = PoolReserves::new(
+                    pool.token_a.clone(),
+                    pool.reserve_a,
+                    pool.token_b.clone(),
+                    pool.reserve_b,
+                    self.block_number,
+                    pool.address,
+                );
+                reserves.timestamp = self.timestamp;
+                (pool.address, reserves)
+            })
+            .collect()
+    }
+}
+
+/// Appends one snapshot line per block, flushing every `flush_every` blocks and when dropped
+pub struct ReservesRecorder {
+    writer: BufWriter<File>,
+    file_path: String,
+    flush_every: usize,
+    unflushed: usize,
+}
+
+impl ReservesRecorder {
+    /// Open (or create) `file_path` for appending
+    pub fn create(file_path: &str, flush_every: usize) -> Result<Self, ArbError> {
+        let file = OpenOptions::new().create(true).append(true).open(file_path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            file_path: file_path.to_string(),
+            flush_every: flush_every.max(1),
+            unflushed: 0,
+        })
+    }
+
+    /// Append the block's reserves
+    pub fn record(&mut self, block_number: u64, reserves_map: &HashMap<Address, PoolReserves>) -> Result<(), ArbError> {
+        let snapshot = ReservesSnapshot::new(block_number, Utc::now(), reserves_map);
+        serde_json::to_writer(&mut self.writer, &snapshot)?;
+        self.writer.write_all(b"\n")?;
+        self.unflushed += 1;
+        if self.unflushed >= self.flush_every {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered snapshots to disk
+    pub fn flush(&mut self) -> Result<(), ArbError> {
+        self.writer.flush()?;
+        self.unflushed = 0;
+        Ok(())
+    }
+
+    /// Snapshots recorded since the last flush
+    pub fn unflushed(&self) -> usize {
+        self.unflushed
+    }
+
+    /// Path of the recording
+    pub fn file_path(&self) -> &str {
+        &self.file_path
+    }
+}
+
+impl Drop for ReservesRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("⚠️ Failed to flush reserves recording {}: {}", self.file_path, e);
+        }
+    }
+}
+
+/// Read every snapshot of a recording, skipping blank lines
+pub fn load_snapshots(file_path: &str) -> Result<Vec<ReservesSnapshot>, ArbError> {
+    let reader = BufReader::new(File::open(file_path)?);
+    let mut snapshots = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            snapshots.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn create_reserves_map(block_number: u64, pools: u8) -> HashMap<Address, PoolReserves> {
+        (1..=pools)
+            .map(|i| {
+                let address = Address::with_last_byte(i);
+                let token_a = Token::new(Address::with_last_byte(100 + i), format!("A{}", i), 18);
+                let token_b = Token::new(Address::with_last_byte(200 + i), format!("B{}", i), 6);
+                let reserves = PoolReserves::new(
+                    token_a,
+                    U256::from(1_000u64 * i as u64 + block_number),
+                    token_b,
+                    U256::from(u128::MAX - i as u128),
+                    block_number,
+                    address,
+                );
+                (address, reserves)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_recorder_round_trip_and_flushing() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("reserves.jsonl");
+        let path = path.to_str().unwrap();
+
+        let mut recorder = ReservesRecorder::create(path, 2).unwrap();
+        recorder.record(100, &create_reserves_map(100, 5)).unwrap();
+        assert_eq!(recorder.unflushed(), 1);
+        assert!(load_snapshots(path).unwrap().is_empty());
+
+        // The second block reaches the flush interval
+        recorder.record(101, &create_reserves_map(101, 5)).unwrap();
+        assert_eq!(recorder.unflushed(), 0);
+        assert_eq!(load_snapshots(path).unwrap().len(), 2);
+
+        // Dropping the recorder (shutdown) flushes the rest
+        recorder.record(102, &create_reserves_map(102, 3)).unwrap();
+        drop(recorder);
+        let snapshots = load_snapshots(path).unwrap();
+        assert_eq!(snapshots.iter().map(|s| s.block_number).collect::<Vec<_>>(), vec![100, 101, 102]);
+
+        // Every pool comes back with its tokens, raw reserves and block
+        let original = create_reserves_map(101, 5);
+        let restored = snapshots[1].reserves();
+        assert_eq!(restored.len(), 5);
+        for (address, reserves) in &original {
+            let copy = &restored[address];
+            assert_eq!((copy.reserve_a, copy.reserve_b), (reserves.reserve_a, reserves.reserve_b));
+            assert_eq!((copy.token_a.symbol(), copy.token_b.decimals()), (reserves.token_a.symbol(), 6));
+            assert_eq!(copy.block_number, 101);
+        }
+
+        // Reopening appends instead of truncating
+        let mut recorder = ReservesRecorder::create(path, 10).unwrap();
+        recorder.record(103, &create_reserves_map(103, 1)).unwrap();
+        drop(recorder);
+        assert_eq!(load_snapshots(path).unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::error::Error;
+use alloy::primitives::{Address, U256};
+use crate::config::{Config, ProfitObjective};
+use crate::multi_path::MultiPathAnalyzer;
+use crate::multi_path_main::print_opportunity_details;
+use crate::pools::DexProtocol;
+use crate::pools::moe::MoeProtocol;
+use crate::recorder::load_snapshots;
+use crate::types::{ArbitrageRecord, PoolReserves};
+
+/// Reserves of the monitored pools at one recorded block
+#[derive(Debug, Clone)]
+pub struct ReplayBlock {
+    pub block_number: u64,
+    pub reserves: HashMap<Address, PoolReserves>,
+}
+
+/// What a replay found across all recorded blocks
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReplaySummary {
+    pub blocks: usize,
+    pub opportunities: usize,
+    pub profitable_blocks: usize,
+    pub best_net_profit: f64,
+}
+
+/// Read reserve snapshots from a `--record` JSON lines file (`.jsonl`/`.ndjson`, every pool), or
+/// from a CSV written by the legacy monitor (`ArbitrageRecord` rows). CSV rows for the same block
+/// are merged; raw reserves map onto the MOE-WMNT, JOE-MOE and JOE-WMNT pools.
+pub fn load_replay_blocks(csv_path: &str) -> Result<Vec<ReplayBlock>, Box<dyn Error>> {
+    if csv_path.ends_with(".jsonl") || csv_path.ends_with(".ndjson") {
+        return Ok(load_snapshots(csv_path)?
+            .iter()
+            .map(|snapshot| ReplayBlock { block_number: snapshot.block_number, reserves: snapshot.reserves() })
+            .collect());
+    }
+
+    let pools = MoeProtocol::new().get_known_pools();
+    let mut reader = csv::Reader::from_path(csv_path)?;
+    let headers = reader.headers()?.clone();
+    let mut blocks: Vec<ReplayBlock> = Vec::new();
+
+    for (row, result) in reader.records().enumerate() {
+        let row_record = result?;
+        // Each live write re-emits the header line; skip the repeats
+        if row_record == headers {
+            continue;
+        }
+        let record: ArbitrageRecord = row_record
+            .deserialize(Some(&headers))
+            .map_err(|e| format!("{} row {}: {}", csv_path, row + 1, e))?;
+        if blocks.last().is_some_and(|b| b.block_number == record.block_number) {
+            continue;
+        }
+
+        let raw_reserves = [
+            (&record.moe_wmnt_reserve0, &record.moe_wmnt_reserve1),
+            (&record.joe_moe_reserve0, &record.joe_moe_reserve1),
+            (&record.joe_wmnt_reserve0, &record.joe_wmnt_reserve1),
+        ];
+        let mut reserves = HashMap::new();
+        for ((address, name, token_a, token_b), (reserve_a, reserve_b)) in pools.iter().zip(raw_reserves) {
+            let parse = |value: &str| {
+                value.parse::<U256>()
+                    .map_err(|e| format!("{} row {}: invalid {} reserve '{}': {}", csv_path, row + 1, name, value, e))
+            };
+            reserves.insert(*address, PoolReserves::new(
+                token_a.clone(),
+                parse(reserve_a)?,
+                token_b.clone(),
+                parse(reserve_b)?,
+                record.block_number,
+                *address,
+            ));
+        }
+        blocks.push(ReplayBlock { block_number: record.block_number, reserves });
+    }
+    Ok(blocks)
+}
+
+/// Feed recorded reserves through the multi-path analyzer and print what it would have found
+pub fn run_replay(config: &Config, csv_path: &str) -> Result<ReplaySummary, Box<dyn Error>> {
+    let blocks = load_replay_blocks(csv_path)?;
+    let first = blocks.first().ok_or_else(|| format!("{} has no recorded blocks to replay", csv_path))?;
+
+    let mut analyzer = MultiPathAnalyzer::new(config.base_token.clone(), config);
+    analyzer.add_pools(first.reserves.values());
+
+    println!("⏪ Replaying {} blocks from {} (no RPC)", blocks.len(), csv_path);
+    let mut summary = ReplaySummary { blocks: blocks.len(), ..Default::default() };
+    for block in &blocks {
+        analyzer.update_pool_reserves(&block.reserves);
+        analyzer.record_reserve_history(&block.reserves);
+        let result = analyzer.find_all_opportunities(config.input_range(), config.ternary_search_iterations)?;
+        summary.opportunities += result.opportunities.len();
+
+        let actionable: Vec<_> = result
+            .profitable_opportunities()
+            .into_iter()
+            .filter(|o| config.profit_objective == ProfitObjective::MinCapital || o.is_profitable_with_threshold(config.min_profit_wmnt))
+            .collect();
+        let Some(best) = actionable
+            .iter()
+            .max_by(|a, b| a.net_profit.partial_cmp(&b.net_profit).unwrap_or(std::cmp::Ordering::Equal))
+        else {
+            continue;
+        };
+
+        summary.profitable_blocks += 1;
+        summary.best_net_profit = summary.best_net_profit.max(best.net_profit);
+        println!("\n💰 Block {}: {} profitable route(s), best:", block.block_number, actionable.len());
+        print_opportunity_details(best, config.base_token.symbol(), config.price_source());
+    }
+
+    println!(
+        "\n✅ Replay done: {} blocks, {} routes analyzed, {} blocks with an actionable opportunity (best {:.6} {} net)",
+        summary.blocks, summary.opportunities, summary.profitable_blocks, summary.best_net_profit, config.base_token.symbol()
+    );
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tempfile::tempdir;
+    use crate::logging::{init_csv_file, write_arbitrage_to_csv};
+    use crate::math::f64_to_u256;
+    use crate::multi_path::tests::create_test_config;
+    use crate::recorder::ReservesRecorder;
+    use crate::types::{ArbitrageOpportunity, Token};
+
+    fn pool_reserves(pools: &[(Address, String, Token, Token)], i: usize, reserve_a: f64, reserve_b: f64) -> PoolReserves {
+        let (address, _, token_a, token_b) = &pools[i];
+        PoolReserves::new(token_a.clone(), f64_to_u256(reserve_a), token_b.clone(), f64_to_u256(reserve_b), 1, *address)
+    }
+
+    #[test]
+    fn test_replay_reads_live_csv() {
+        let dir = tempdir().unwrap();
+        let csv_path = dir.path().join("recorded.csv");
+        let mut config = create_test_config();
+        config.csv_file_path = csv_path.to_str().unwrap().to_string();
+        config.min_profit_wmnt = 0.0;
+
+        // Record two blocks the way the live monitor does: balanced, then JOE-WMNT mispriced
+        let pools = MoeProtocol::new().get_known_pools();
+        let opportunity = ArbitrageOpportunity {
+            optimal_input: 0.0,
+            final_output: 0.0,
+            gross_profit: 0.0,
+            net_profit: 0.0,
+            profit_percentage: 0.0,
+            search_method: "ternary_search".to_string(),
+            path: None,
+            robustness: 0.0,
+            unconstrained_input: 0.0,
+            binding_constraint: None,
+            price_impact_percent: 0.0,
+            requires_flash_loan: false,
+            flash_loan_fee: 0.0,
+            min_pool_depth: 0.0,
+            expected_realized_profit: 0.0,
+            hop_outputs: Vec::new(),
+            confidence: 1.0,
+        };
+        init_csv_file(&config.csv_file_path).unwrap();
+        for (block, joe_wmnt) in [(100u64, 100_000.0), (101, 115_000.0)] {
+            write_arbitrage_to_csv(
+                Utc::now(),
+                block,
+                &opportunity,
+                &pool_reserves(&pools, 0, 100_000.0, 100_000.0),
+                &pool_reserves(&pools, 1, 100_000.0, 100_000.0),
+                &pool_reserves(&pools, 2, 100_000.0, joe_wmnt),
+                5,
+                &config,
+            ).unwrap();
+        }
+
+        let blocks = load_replay_blocks(&config.csv_file_path).unwrap();
+        assert_eq!(blocks.iter().map(|b| b.block_number).collect::<Vec<_>>(), vec![100, 101]);
+        let joe_wmnt = &blocks[1].reserves[&pools[2].0];
+        assert!((joe_wmnt.reserve_b_units() - 115_000.0).abs() < 1e-6);
+
+        let summary = run_replay(&config, &config.csv_file_path).unwrap();
+        assert_eq!(summary.blocks, 2);
+        assert_eq!(summary.profitable_blocks, 1);
+        assert!(summary.best_net_profit > 0.0);
+
+        // A `--record` file of the same blocks replays to the same result
+        let record_path = dir.path().join("recorded.jsonl");
+        let record_path = record_path.to_str().unwrap();
+        let mut recorder = ReservesRecorder::create(record_path, 10).unwrap();
+        for block in &blocks {
+            recorder.record(block.block_number, &block.reserves).unwrap();
+        }
+        drop(recorder);
+        assert_eq!(run_replay(&config, record_path).unwrap(), summary);
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+use alloy::primitives::Address;
+use alloy::providers::{Provider, ProviderBuilder};
+use log::warn;
+use serde::Serialize;
+use crate::batch_fetcher::BatchReservesFetcher;
+use crate::blockchain::fetch_pool_reserves_at_block;
+use crate::config::Config;
+use crate::multi_path_main::PoolSource;
+use crate::types::PoolReserves;
+
+/// How one pool's reserves moved between two snapshots
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolReserveDiff {
+    pub pool_address: Address,
+    pub token_a: String,
+    pub token_b: String,
+    pub from_block: u64,
+    pub to_block: u64,
+    pub reserve_a_before: f64,
+    pub reserve_a_after: f64,
+    pub reserve_a_change: f64,
+    pub reserve_a_change_pct: f64,
+    pub reserve_b_before: f64,
+    pub reserve_b_after: f64,
+    pub reserve_b_change: f64,
+    pub reserve_b_change_pct: f64,
+    /// Price of token A in token B (reserve_b / reserve_a)
+    pub price_before: f64,
+    pub price_after: f64,
+    pub price_change_pct: f64,
+}
+
+/// Percentage change from `before` to `after` (0 when there is no baseline)
+fn pct_change(before: f64, after: f64) -> f64 {
+    if before == 0.0 { 0.0 } else { (after - before) / before * 100.0 }
+}
+
+fn price(reserve_a: f64, reserve_b: f64) -> f64 {
+    if reserve_a > 0.0 { reserve_b / reserve_a } else { 0.0 }
+}
+
+/// Per-pool reserve diff between two snapshots, largest price move first.
+/// Pools missing from either snapshot are skipped.
+pub fn diff_reserves(
+    before: &HashMap<Address, PoolReserves>,
+    after: &HashMap<Address, PoolReserves>,
+) -> Vec<PoolReserveDiff> {
+    let mut diffs: Vec<PoolReserveDiff> = before
+        .iter()
+        .filter_map(|(addr, old)| {
+            let new = after.get(addr)?;
+            let (a_before, b_before) = (old.reserve_a_units(), old.reserve_b_units());
+            let (a_after, b_after) = (new.reserve_a_units(), new.reserve_b_units());
+            let (price_before, price_after) = (price(a_before, b_before), price(a_after, b_after));
+
+            Some(PoolReserveDiff {
+                pool_address: *addr,
+                token_a: old.token_a.symbol().to_string(),
+                token_b: old.token_b.symbol().to_string(),
+                from_block: old.block_number,
+                to_block: new.block_number,
+                reserve_a_before: a_before,
+                reserve_a_after: a_after,
+                reserve_a_change: a_after - a_before,
+                reserve_a_change_pct: pct_change(a_before, a_after),
+                reserve_b_before: b_before,
+                reserve_b_after: b_after,
+                reserve_b_change: b_after - b_before,
+                reserve_b_change_pct: pct_change(b_before, b_after),
+                price_before,
+                price_after,
+                price_change_pct: pct_change(price_before, price_after),
+            })
+        })
+        .collect();
+
+    diffs.sort_by(|a, b| {
+        b.price_change_pct.abs()
+            .partial_cmp(&a.price_change_pct.abs())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    diffs
+}
+
+/// Write a diff report as JSON (`.json` extension) or CSV (anything else)
+pub fn export_reserve_diff(path: &str, diffs: &[PoolReserveDiff]) -> Result<(), Box<dyn Error>> {
+    let is_json = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        serde_json::to_writer_pretty(File::create(path)?, diffs)?;
+    } else {
+        let mut writer = csv::Writer::from_path(path)?;
+        for diff in diffs {
+            writer.serialize(diff)?;
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Snapshot every pool at `block_number`, skipping pools that fail to load
+async fn fetch_snapshot<P: Provider>(provider: &P, pools: &[Address], block_number: u64) -> HashMap<Address, PoolReserves> {
+    let mut snapshot = HashMap::new();
+    for &pool in pools {
+        match fetch_pool_reserves_at_block(provider, pool, block_number).await {
+            Ok(reserves) => {
+                snapshot.insert(pool, reserves);
+            }
+            Err(e) => warn!("⚠️ Skipping pool {} at block {}: {}", pool, block_number, e),
+        }
+    }
+    snapshot
+}
+
+/// Fetch reserves at two blocks and export the per-pool diff report
+pub async fn run_reserve_diff(
+    config: Config,
+    pool_source: PoolSource,
+    from_block: u64,
+    to_block: u64,
+    output_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let provider = ProviderBuilder::new().connect_http(config.rpc_url.parse()?);
+
+    let pools = match pool_source {
+        PoolSource::Addresses(addresses) => addresses,
+        PoolSource::Csv(csv_path) => {
+            let mut fetcher = BatchReservesFetcher::new(config.max_retries as usize, config.batch_size, Duration::from_millis(config.inter_batch_delay_ms));
+            fetcher.load_pool_addresses_from_csv(&csv_path)?;
+            fetcher.get_pool_addresses().to_vec()
+        }
+    };
+
+    println!("🔄 Diffing reserves of {} pools between blocks {} and {}", pools.len(), from_block, to_block);
+    let before = fetch_snapshot(&provider, &pools, from_block).await;
+    let after = fetch_snapshot(&provider, &pools, to_block).await;
+
+    let diffs = diff_reserves(&before, &after);
+    export_reserve_diff(output_path, &diffs)?;
+    println!("✅ Wrote {} pool diffs to {}", diffs.len(), output_path);
+
+    for diff in diffs.iter().take(5) {
+        println!("   {} {}/{}: price {:+.4}%", diff.pool_address, diff.token_a, diff.token_b, diff.price_change_pct);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U256;
+    use tempfile::Builder;
+    use crate::types::Token;
+
+    fn create_reserves(pool: Address, block: u64, reserve_a: u64, reserve_b: u64) -> PoolReserves {
+        let unit = U256::from(10u64).pow(U256::from(18u64));
+        PoolReserves::new(
+            Token::new(Address::from([0u8; 20]), "WMNT", 18), U256::from(reserve_a) * unit,
+            Token::new(Address::from([1u8; 20]), "MOE", 18), U256::from(reserve_b) * unit,
+            block, pool,
+        )
+    }
+
+    #[test]
+    fn test_diff_reserves_reports_deltas() {
+        let moved = Address::from([1u8; 20]);
+        let unchanged = Address::from([2u8; 20]);
+        let vanished = Address::from([3u8; 20]);
+
+        let before = HashMap::from([
+            (moved, create_reserves(moved, 100, 1_000, 2_000)),
+            (unchanged, create_reserves(unchanged, 100, 500, 500)),
+            (vanished, create_reserves(vanished, 100, 10, 10)),
+        ]);
+        let after = HashMap::from([
+            (moved, create_reserves(moved, 105, 1_250, 1_600)),
+            (unchanged, create_reserves(unchanged, 105, 500, 500)),
+        ]);
+
+        let diffs = diff_reserves(&before, &after);
+        assert_eq!(diffs.len(), 2);
+
+        let diff = &diffs[0];
+        assert_eq!(diff.pool_address, moved);
+        assert_eq!((diff.from_block, diff.to_block), (100, 105));
+        assert_eq!((diff.token_a.as_str(), diff.token_b.as_str()), ("WMNT", "MOE"));
+        assert!((diff.reserve_a_change - 250.0).abs() < 1e-9);
+        assert!((diff.reserve_a_change_pct - 25.0).abs() < 1e-9);
+        assert!((diff.reserve_b_change + 400.0).abs() < 1e-9);
+        assert!((diff.reserve_b_change_pct + 20.0).abs() < 1e-9);
+        assert!((diff.price_before - 2.0).abs() < 1e-9);
+        assert!((diff.price_after - 1.28).abs() < 1e-9);
+        assert!((diff.price_change_pct + 36.0).abs() < 1e-9);
+
+        assert_eq!(diffs[1].pool_address, unchanged);
+        assert_eq!(diffs[1].price_change_pct, 0.0);
+
+        // Both export formats round-trip the same rows
+        let json_file = Builder::new().suffix(".json").tempfile().unwrap();
+        export_reserve_diff(json_file.path().to_str().unwrap(), &diffs).unwrap();
+        let json: serde_json::Value = serde_json::from_reader(File::open(json_file.path()).unwrap()).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[0]["reserve_a_change_pct"], 25.0);
+
+        let csv_file = Builder::new().suffix(".csv").tempfile().unwrap();
+        export_reserve_diff(csv_file.path().to_str().unwrap(), &diffs).unwrap();
+        let mut reader = csv::Reader::from_path(csv_file.path()).unwrap();
+        assert!(reader.headers().unwrap().iter().any(|h| h == "price_change_pct"));
+        assert_eq!(reader.records().count(), 2);
+    }
+}
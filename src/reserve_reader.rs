@@ -0,0 +1,147 @@
+//! Block and reserve reads behind a trait object, so monitor loops can run against a mock chain
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use alloy::primitives::Address;
+use alloy::providers::DynProvider;
+use async_trait::async_trait;
+use crate::blockchain::{fetch_all_reserves, fetch_pool_reserves_at_block, get_current_block, ReserveFetchOutcome};
+use crate::error::ArbError;
+use crate::types::PoolReserves;
+
+/// Chain reads the monitor loops need: the head block and pool reserves at a block
+#[async_trait]
+pub trait ReserveReader: Send + Sync {
+    /// Latest block number
+    async fn block_number(&self) -> Result<u64, ArbError>;
+
+    /// Reserves of one pool as of `block_number`
+    async fn get_reserves(&self, pool_address: Address, block_number: u64) -> Result<PoolReserves, ArbError>;
+
+    /// Reserves of many pools as of `block_number`, with at most `max_concurrent` calls in flight
+    /// (0 = unbounded); pools that failed every attempt are listed as failed
+    async fn fetch_reserves(&self, pool_addresses: &[Address], block_number: u64, max_retries: u32, max_concurrent: usize) -> ReserveFetchOutcome;
+}
+
+/// Alloy RPC: Multicall3 `getReserves` with per-pool fallback and retries
+#[async_trait]
+impl ReserveReader for DynProvider {
+    async fn block_number(&self) -> Result<u64, ArbError> {
+        get_current_block(self).await
+    }
+
+    async fn get_reserves(&self, pool_address: Address, block_number: u64) -> Result<PoolReserves, ArbError> {
+        fetch_pool_reserves_at_block(self, pool_address, block_number).await
+    }
+
+    async fn fetch_reserves(&self, pool_addresses: &[Address], block_number: u64, max_retries: u32, max_concurrent: usize) -> ReserveFetchOutcome {
+        fetch_all_reserves(self.clone(), pool_addresses, block_number, max_retries, max_concurrent).await
+    }
+}
+
+/// In-memory chain: a settable head block and per-pool reserves; pools without reserves fail
+#[derive(Debug, Default)]
+pub struct MockReserveReader {
+    state: Mutex<MockChain>,
+}
+
+#[derive(Debug, Default)]
+struct MockChain {
+    block_number: u64,
+    reserves: HashMap<Address, PoolReserves>,
+}
+
+impl MockReserveReader {
+    pub fn new(block_number: u64) -> Self {
+        let reader = Self::default();
+        reader.set_block_number(block_number);
+        reader
+    }
+
+    /// Move the chain head
+    pub fn set_block_number(&self, block_number: u64) {
+        self.state().block_number = block_number;
+    }
+
+    /// Serve `reserves` for its pool from now on
+    pub fn set_reserves(&self, reserves: PoolReserves) {
+        self.state().reserves.insert(reserves.pool_address, reserves);
+    }
+
+    /// Make a pool fail until its reserves are set again
+    pub fn remove_pool(&self, pool_address: Address) {
+        self.state().reserves.remove(&pool_address);
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockChain> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl ReserveReader for MockReserveReader {
+    async fn block_number(&self) -> Result<u64, ArbError> {
+        Ok(self.state().block_number)
+    }
+
+    async fn get_reserves(&self, pool_address: Address, block_number: u64) -> Result<PoolReserves, ArbError> {
+        self.state()
+            .reserves
+            .get(&pool_address)
+            .map(|reserves| PoolReserves { block_number, ..reserves.clone() })
+            .ok_or_else(|| ArbError::Rpc(format!("mock chain has no reserves for pool {}", pool_address)))
+    }
+
+    /// A mock pool answers every time or never, so there is nothing to retry
+    async fn fetch_reserves(&self, pool_addresses: &[Address], block_number: u64, _max_retries: u32, _max_concurrent: usize) -> ReserveFetchOutcome {
+        let mut outcome = ReserveFetchOutcome::default();
+        for &pool_address in pool_addresses {
+            match self.get_reserves(pool_address, block_number).await {
+                Ok(reserves) => {
+                    outcome.reserves.insert(pool_address, reserves);
+                }
+                Err(_) => outcome.failed.push(pool_address),
+            }
+        }
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Duration;
+    use crate::batch_fetcher::BatchReservesFetcher;
+    use crate::block_driver::BlockTicker;
+    use crate::config::MonitorDriver;
+    use crate::math::f64_to_u256;
+    use crate::types::Token;
+
+    #[tokio::test]
+    async fn test_monitor_reads_run_against_mock_chain() {
+        let [wmnt, moe, joe] = ["WMNT", "MOE", "JOE"].map(|symbol| Token::parse(symbol).unwrap());
+        let (moe_wmnt, joe_wmnt) = (Address::with_last_byte(0xf1), Address::with_last_byte(0xf2));
+        let chain = MockReserveReader::new(100);
+        chain.set_reserves(PoolReserves::new(moe, f64_to_u256(500.0), wmnt.clone(), f64_to_u256(1000.0), 0, moe_wmnt));
+        chain.set_reserves(PoolReserves::new(joe, f64_to_u256(800.0), wmnt, f64_to_u256(1000.0), 0, joe_wmnt));
+
+        let mut ticker = BlockTicker::start(MonitorDriver::Polling, None, Duration::from_millis(1));
+        let mut fetcher = BatchReservesFetcher::new(3, 1, Duration::ZERO);
+        fetcher.add_pool_address(moe_wmnt);
+        fetcher.add_pool_address(joe_wmnt);
+
+        let block = ticker.next_block(&chain).await.unwrap();
+        let reserves = fetcher.fetch_all_reserves(&chain, block).await.unwrap();
+        assert_eq!(block, 100);
+        assert!(reserves.values().all(|r| r.block_number == 100));
+        assert_eq!(reserves[&moe_wmnt].reserve_a_units(), 500.0);
+
+        // A pool that stops answering degrades the snapshot, or fails it under full coverage
+        chain.set_block_number(101);
+        chain.remove_pool(joe_wmnt);
+        let block = ticker.next_block(&chain as &dyn ReserveReader).await.unwrap();
+        assert_eq!(fetcher.fetch_all_reserves(&chain, block).await.unwrap().len(), 1);
+        fetcher.set_min_pool_coverage(1.0);
+        assert!(fetcher.fetch_all_reserves(&chain, block).await.is_err());
+    }
+}
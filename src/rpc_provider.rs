@@ -0,0 +1,159 @@
+use std::error::Error;
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use log::{error, warn};
+use crate::config::Config;
+
+/// Counts consecutive RPC failures and picks the endpoint to reconnect to
+#[derive(Debug, Clone)]
+pub struct FailureTracker {
+    urls: Vec<String>,
+    active: usize,
+    consecutive_failures: u32,
+    max_consecutive_failures: u32,
+}
+
+impl FailureTracker {
+    /// Track `primary` (and `fallback`, if any); 0 failures disables reconnecting
+    pub fn new(primary: &str, fallback: Option<&str>, max_consecutive_failures: u32) -> Self {
+        let mut urls = vec![primary.to_string()];
+        urls.extend(fallback.map(str::to_string));
+        Self {
+            urls,
+            active: 0,
+            consecutive_failures: 0,
+            max_consecutive_failures,
+        }
+    }
+
+    /// Endpoint currently in use
+    pub fn active_url(&self) -> &str {
+        &self.urls[self.active]
+    }
+
+    /// Failures since the last success or reconnect
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// A call succeeded
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+    }
+
+    /// A call failed; at the threshold, rotate to the next endpoint and return the URL to reconnect to
+    pub fn record_failure(&mut self) -> Option<&str> {
+        self.consecutive_failures += 1;
+        if self.max_consecutive_failures == 0 || self.consecutive_failures < self.max_consecutive_failures {
+            return None;
+        }
+        self.consecutive_failures = 0;
+        self.active = (self.active + 1) % self.urls.len();
+        Some(self.active_url())
+    }
+}
+
+/// HTTP provider that rebuilds its connection after repeated failures,
+/// alternating between `RPC_URL` and `RPC_URL_FALLBACK` when one is set
+pub struct ReconnectingProvider {
+    provider: DynProvider,
+    tracker: FailureTracker,
+    reconnects: u64,
+}
+
+impl ReconnectingProvider {
+    /// Connect to `RPC_URL` with the configured failure threshold and fallback
+    pub fn connect(config: &Config) -> Result<Self, Box<dyn Error>> {
+        let tracker = FailureTracker::new(
+            &config.rpc_url,
+            config.rpc_url_fallback.as_deref(),
+            config.max_consecutive_failures,
+        );
+        Ok(Self {
+            provider: build_provider(tracker.active_url())?,
+            tracker,
+            reconnects: 0,
+        })
+    }
+
+    /// Provider for the active endpoint
+    pub fn provider(&self) -> &DynProvider {
+        &self.provider
+    }
+
+    /// Endpoint currently in use
+    pub fn active_url(&self) -> &str {
+        self.tracker.active_url()
+    }
+
+    /// Connections rebuilt so far; state tied to the old connection is stale once this moves
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects
+    }
+
+    /// Feed back the outcome of an RPC call, reconnecting once failures reach the threshold
+    pub fn observe<T, E>(&mut self, result: &Result<T, E>) {
+        if result.is_ok() {
+            self.tracker.record_success();
+            return;
+        }
+        let Some(url) = self.tracker.record_failure().map(str::to_string) else {
+            return;
+        };
+        match build_provider(&url) {
+            Ok(provider) => {
+                warn!("🔌 RPC unhealthy, reconnecting to {}", url);
+                self.provider = provider;
+                self.reconnects += 1;
+            }
+            Err(e) => error!("⚠️ Failed to reconnect to {}: {}", url, e),
+        }
+    }
+}
+
+fn build_provider(url: &str) -> Result<DynProvider, Box<dyn Error>> {
+    Ok(ProviderBuilder::new().connect_http(url.parse()?).erased())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi_path::tests::create_test_config;
+
+    #[test]
+    fn test_reconnects_after_consecutive_failures() {
+        let mut config = create_test_config();
+        config.rpc_url = "http://127.0.0.1:1".to_string();
+        config.rpc_url_fallback = Some("http://127.0.0.1:2".to_string());
+        config.max_consecutive_failures = 3;
+        let mut rpc = ReconnectingProvider::connect(&config).unwrap();
+        let failed: Result<u64, &str> = Err("connection refused");
+
+        // Two failures stay on the primary; the third switches to the fallback
+        rpc.observe(&failed);
+        rpc.observe(&failed);
+        assert_eq!(rpc.active_url(), "http://127.0.0.1:1");
+        rpc.observe(&failed);
+        assert_eq!(rpc.active_url(), "http://127.0.0.1:2");
+        assert_eq!(rpc.reconnects(), 1);
+
+        // A success resets the count, so isolated failures never reconnect
+        rpc.observe(&failed);
+        rpc.observe(&failed);
+        rpc.observe(&Ok::<u64, &str>(100));
+        rpc.observe(&failed);
+        rpc.observe(&failed);
+        assert_eq!(rpc.active_url(), "http://127.0.0.1:2");
+        assert_eq!(rpc.tracker.consecutive_failures(), 2);
+
+        // Another run of failures rotates back to the primary
+        rpc.observe(&failed);
+        assert_eq!(rpc.active_url(), "http://127.0.0.1:1");
+
+        // Without a fallback the same endpoint is rebuilt; 0 disables reconnecting
+        let mut tracker = FailureTracker::new("http://a", None, 2);
+        assert_eq!(tracker.record_failure(), None);
+        assert_eq!(tracker.record_failure(), Some("http://a"));
+        let mut disabled = FailureTracker::new("http://a", Some("http://b"), 0);
+        assert!((0..10).all(|_| disabled.record_failure().is_none()));
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use log::{info, warn};
+use tokio::sync::Notify;
+
+/// Cooperative stop signal shared by the monitor loops; clones observe the same request
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    inner: Arc<ShutdownState>,
+}
+
+#[derive(Debug, Default)]
+struct ShutdownState {
+    requested: AtomicBool,
+    notify: Notify,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signal that triggers on the first Ctrl-C; a second Ctrl-C exits immediately
+    pub fn on_ctrl_c() -> Self {
+        let shutdown = Self::new();
+        let handle = shutdown.clone();
+        tokio::spawn(async move {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                warn!("⚠️ Failed to listen for Ctrl-C: {}", e);
+                return;
+            }
+            info!("🛑 Ctrl-C received, finishing the current block (press again to force quit)");
+            handle.trigger();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        });
+        shutdown
+    }
+
+    /// Request shutdown and wake every waiter
+    pub fn trigger(&self) {
+        self.inner.requested.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Whether shutdown has been requested
+    pub fn is_requested(&self) -> bool {
+        self.inner.requested.load(Ordering::SeqCst)
+    }
+
+    /// Resolve once shutdown is requested (immediately if it already was)
+    pub async fn wait(&self) {
+        let notified = self.inner.notify.notified();
+        tokio::pin!(notified);
+        // Register before checking the flag so a trigger in between is not missed
+        notified.as_mut().enable();
+        if self.is_requested() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{timeout, Duration};
+
+    #[tokio::test]
+    async fn test_trigger_wakes_waiters() {
+        let shutdown = Shutdown::new();
+        assert!(!shutdown.is_requested());
+        assert!(timeout(Duration::from_millis(20), shutdown.wait()).await.is_err());
+
+        // A waiter in another task wakes when a clone triggers
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait().await }
+        });
+        tokio::task::yield_now().await;
+        shutdown.clone().trigger();
+        timeout(Duration::from_secs(5), waiter).await.unwrap().unwrap();
+
+        // Later waits return at once
+        assert!(shutdown.is_requested());
+        timeout(Duration::from_millis(20), shutdown.wait()).await.unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+use std::error::Error;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, Row, params};
+use crate::types::MultiPathArbitrageRecord;
+
+/// Columns of the `opportunities` table, in `MultiPathArbitrageRecord` field order
+pub const OPPORTUNITY_COLUMNS: [&str; 21] = [
+    "timestamp",
+    "block_number",
+    "optimal_input_wmnt",
+    "final_output_wmnt",
+    "gross_profit_wmnt",
+    "net_profit_wmnt",
+    "profit_percentage",
+    "search_method",
+    "path_type",
+    "path_description",
+    "gas_units",
+    "fetch_time_ms",
+    "analysis_time_ms",
+    "actionable",
+    "price_impact_percent",
+    "requires_flash_loan",
+    "flash_loan_fee_wmnt",
+    "opportunity_id",
+    "verified_net_profit",
+    "expected_realized_profit_wmnt",
+    "hop_outputs",
+];
+
+const CREATE_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS opportunities (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp TEXT NOT NULL,
+        block_number INTEGER NOT NULL,
+        optimal_input_wmnt REAL NOT NULL,
+        final_output_wmnt REAL NOT NULL,
+        gross_profit_wmnt REAL NOT NULL,
+        net_profit_wmnt REAL NOT NULL,
+        profit_percentage REAL NOT NULL,
+        search_method TEXT NOT NULL,
+        path_type TEXT NOT NULL,
+        path_description TEXT NOT NULL,
+        gas_units INTEGER NOT NULL,
+        fetch_time_ms INTEGER NOT NULL,
+        analysis_time_ms INTEGER NOT NULL,
+        actionable INTEGER NOT NULL,
+        price_impact_percent REAL NOT NULL,
+        requires_flash_loan INTEGER NOT NULL,
+        flash_loan_fee_wmnt REAL NOT NULL,
+        opportunity_id TEXT NOT NULL,
+        verified_net_profit REAL,
+        expected_realized_profit_wmnt REAL NOT NULL,
+        hop_outputs TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_opportunities_block_profit
+        ON opportunities (block_number, net_profit_wmnt);
+";
+
+/// SQLite sink that stores each recorded opportunity as a row of `opportunities`
+pub struct SqliteLogger {
+    conn: Connection,
+    file_path: String,
+}
+
+impl SqliteLogger {
+    /// Open (or create) the database and make sure the table and index exist
+    pub fn open(file_path: &str) -> Result<Self, Box<dyn Error>> {
+        let conn = Connection::open(file_path)?;
+        conn.execute_batch(CREATE_SCHEMA)?;
+        Ok(Self { conn, file_path: file_path.to_string() })
+    }
+
+    /// Insert one opportunity record
+    pub fn insert(&self, record: &MultiPathArbitrageRecord) -> Result<(), Box<dyn Error>> {
+        let placeholders = vec!["?"; OPPORTUNITY_COLUMNS.len()].join(", ");
+        let sql = format!("INSERT INTO opportunities ({}) VALUES ({})", OPPORTUNITY_COLUMNS.join(", "), placeholders);
+        self.conn.prepare_cached(&sql)?.execute(params![
+            record.timestamp,
+            record.block_number,
+            record.optimal_input_wmnt,
+            record.final_output_wmnt,
+            record.gross_profit_wmnt,
+            record.net_profit_wmnt,
+            record.profit_percentage,
+            record.search_method,
+            record.path_type,
+            record.path_description,
+            record.gas_units,
+            record.fetch_time_ms,
+            record.analysis_time_ms,
+            record.actionable,
+            record.price_impact_percent,
+            record.requires_flash_loan,
+            record.flash_loan_fee_wmnt,
+            record.opportunity_id,
+            record.verified_net_profit,
+            record.expected_realized_profit_wmnt,
+            record.hop_outputs,
+        ])?;
+        Ok(())
+    }
+
+    /// The `limit` most profitable opportunities recorded at or after `since`
+    pub fn top_opportunities_since(
+        &self,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<MultiPathArbitrageRecord>, Box<dyn Error>> {
+        // Timestamps are stored as UTC RFC 3339, so text order is time order
+        let sql = format!(
+            "SELECT {} FROM opportunities WHERE timestamp >= ?1 ORDER BY net_profit_wmnt DESC LIMIT ?2",
+            OPPORTUNITY_COLUMNS.join(", ")
+        );
+        let mut statement = self.conn.prepare_cached(&sql)?;
+        let rows = statement.query_map(params![since.to_rfc3339(), limit as i64], record_from_row)?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Path of the database file
+    pub fn file_path(&self) -> &str {
+        &self.file_path
+    }
+}
+
+fn record_from_row(row: &Row) -> rusqlite::Result<MultiPathArbitrageRecord> {
+    Ok(MultiPathArbitrageRecord {
+        timestamp: row.get(0)?,
+        block_number: row.get(1)?,
+        optimal_input_wmnt: row.get(2)?,
+        final_output_wmnt: row.get(3)?,
+        gross_profit_wmnt: row.get(4)?,
+        net_profit_wmnt: row.get(5)?,
+        profit_percentage: row.get(6)?,
+        search_method: row.get(7)?,
+        path_type: row.get(8)?,
+        path_description: row.get(9)?,
+        gas_units: row.get(10)?,
+        fetch_time_ms: row.get(11)?,
+        analysis_time_ms: row.get(12)?,
+        actionable: row.get(13)?,
+        price_impact_percent: row.get(14)?,
+        requires_flash_loan: row.get(15)?,
+        flash_loan_fee_wmnt: row.get(16)?,
+        opportunity_id: row.get(17)?,
+        verified_net_profit: row.get(18)?,
+        expected_realized_profit_wmnt: row.get(19)?,
+        hop_outputs: row.get(20)?,
+        net_profit_usd: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::tempdir;
+
+    fn create_record(timestamp: DateTime<Utc>, block_number: u64, net_profit: f64) -> MultiPathArbitrageRecord {
+        MultiPathArbitrageRecord {
+            timestamp: timestamp.to_rfc3339(),
+            block_number,
+            optimal_input_wmnt: 100.0,
+            final_output_wmnt: 100.0 + net_profit + 0.01,
+            gross_profit_wmnt: net_profit + 0.01,
+            net_profit_wmnt: net_profit,
+            profit_percentage: net_profit,
+            search_method: "analytic".to_string(),
+            path_type: "3-hop".to_string(),
+            path_description: "WMNT -> MOE -> JOE -> WMNT".to_string(),
+            gas_units: 450_000,
+            fetch_time_ms: 12,
+            analysis_time_ms: 3,
+            actionable: net_profit > 0.05,
+            price_impact_percent: 0.4,
+            requires_flash_loan: false,
+            flash_loan_fee_wmnt: 0.0,
+            opportunity_id: "3f1c2a9b8e7d6c5a".to_string(),
+            verified_net_profit: None,
+            expected_realized_profit_wmnt: net_profit * 0.6,
+            hop_outputs: "[100.0,92.1,101.4,100.51]".to_string(),
+            net_profit_usd: None,
+        }
+    }
+
+    #[test]
+    fn test_top_opportunities_since() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("opportunities.db");
+        let logger = SqliteLogger::open(path.to_str().unwrap()).unwrap();
+
+        // Columns stay in step with the record struct
+        let json = serde_json::to_value(create_record(Utc::now(), 1, 0.0)).unwrap();
+        let fields = json.as_object().unwrap();
+        assert_eq!(fields.len(), OPPORTUNITY_COLUMNS.len());
+        assert!(OPPORTUNITY_COLUMNS.iter().all(|c| fields.contains_key(*c)));
+
+        let now = Utc::now();
+        let old = now - Duration::hours(2);
+        logger.insert(&create_record(old, 100, 9.0)).unwrap();
+        logger.insert(&create_record(now, 200, 0.5)).unwrap();
+        logger.insert(&create_record(now, 201, 2.0)).unwrap();
+        logger.insert(&create_record(now, 202, 0.01)).unwrap();
+
+        let top = logger.top_opportunities_since(now - Duration::hours(1), 2).unwrap();
+        assert_eq!(top.iter().map(|r| r.block_number).collect::<Vec<_>>(), vec![201, 200]);
+        assert_eq!(top[0].net_profit_wmnt, 2.0);
+        assert_eq!(top[0].path_description, "WMNT -> MOE -> JOE -> WMNT");
+        assert!(top[0].actionable);
+
+        assert_eq!(logger.top_opportunities_since(old, 10).unwrap().len(), 4);
+
+        // Reopening keeps existing rows and the index
+        drop(logger);
+        let logger = SqliteLogger::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(logger.top_opportunities_since(old, 1).unwrap()[0].block_number, 100);
+        let index_count: i64 = logger.conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'idx_opportunities_block_profit'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(index_count, 1);
+    }
+}
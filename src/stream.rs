@@ -0,0 +1,156 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use serde::Serialize;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Messages buffered per client before a slow reader starts skipping
+const STREAM_BUFFER: usize = 256;
+
+/// Push stream of opportunities as newline-delimited JSON to connected clients
+pub struct OpportunityStream {
+    sender: broadcast::Sender<String>,
+    local_addr: Option<SocketAddr>,
+}
+
+impl OpportunityStream {
+    /// Listen on `host:port` (TCP) or `unix:/path/to.sock` (Unix socket)
+    pub async fn bind(address: &str) -> Result<Self, Box<dyn Error>> {
+        let (sender, _) = broadcast::channel(STREAM_BUFFER);
+
+        if let Some(path) = address.strip_prefix("unix:") {
+            return Self::bind_unix(path, sender);
+        }
+
+        let listener = TcpListener::bind(address).await?;
+        let local_addr = listener.local_addr()?;
+        let accept_sender = sender.clone();
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((socket, _)) => {
+                        tokio::spawn(serve_client(socket, accept_sender.subscribe()));
+                    }
+                    Err(e) => eprintln!("⚠️ Stream accept failed: {}", e),
+                }
+            }
+        });
+
+        Ok(Self {
+            sender,
+            local_addr: Some(local_addr),
+        })
+    }
+
+    #[cfg(unix)]
+    fn bind_unix(path: &str, sender: broadcast::Sender<String>) -> Result<Self, Box<dyn Error>> {
+        // Remove a stale socket left behind by a previous run
+        let _ = std::fs::remove_file(path);
+        let listener = tokio::net::UnixListener::bind(path)?;
+        let accept_sender = sender.clone();
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((socket, _)) => {
+                        tokio::spawn(serve_client(socket, accept_sender.subscribe()));
+                    }
+                    Err(e) => eprintln!("⚠️ Stream accept failed: {}", e),
+                }
+            }
+        });
+
+        Ok(Self {
+            sender,
+            local_addr: None,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn bind_unix(_path: &str, _sender: broadcast::Sender<String>) -> Result<Self, Box<dyn Error>> {
+        Err("Unix socket streams are not supported on this platform".into())
+    }
+
+    /// Serialize an item and send it to every connected client; returns the client count
+    pub fn publish<T: Serialize>(&self, item: &T) -> Result<usize, Box<dyn Error>> {
+        let line = serde_json::to_string(item)?;
+        // Sending only fails when nobody is connected
+        Ok(self.sender.send(line).unwrap_or(0))
+    }
+
+    /// Number of currently connected clients
+    pub fn client_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Bound TCP address (None for Unix sockets)
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+}
+
+/// Forward published lines to one client until it disconnects
+async fn serve_client<W: AsyncWrite + Unpin>(mut socket: W, mut receiver: broadcast::Receiver<String>) {
+    loop {
+        match receiver.recv().await {
+            Ok(mut line) => {
+                line.push('\n');
+                if socket.write_all(line.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                eprintln!("⚠️ Stream client lagging, skipped {} opportunities", skipped);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::net::TcpStream;
+    use tokio::time::{sleep, timeout, Duration};
+
+    #[derive(Serialize)]
+    struct TestOpportunity {
+        block_number: u64,
+        net_profit_wmnt: f64,
+    }
+
+    #[tokio::test]
+    async fn test_client_receives_streamed_opportunity() {
+        let stream = OpportunityStream::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(stream.local_addr().unwrap()).await.unwrap();
+
+        // Wait for the accept loop to register the client
+        timeout(Duration::from_secs(5), async {
+            while stream.client_count() == 0 {
+                sleep(Duration::from_millis(5)).await;
+            }
+        }).await.unwrap();
+
+        let opportunity = TestOpportunity { block_number: 42, net_profit_wmnt: 1.25 };
+        assert_eq!(stream.publish(&opportunity).unwrap(), 1);
+
+        let mut line = String::new();
+        let mut reader = BufReader::new(client);
+        timeout(Duration::from_secs(5), reader.read_line(&mut line)).await.unwrap().unwrap();
+
+        let received: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(received["block_number"], 42);
+        assert_eq!(received["net_profit_wmnt"], 1.25);
+
+        // A dropped client is cleaned up on the next publish
+        drop(reader);
+        stream.publish(&opportunity).unwrap();
+        timeout(Duration::from_secs(5), async {
+            while stream.client_count() > 0 {
+                stream.publish(&opportunity).unwrap();
+                sleep(Duration::from_millis(5)).await;
+            }
+        }).await.unwrap();
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::error::Error;
+use alloy::primitives::Address;
+use crate::config::{Config, ProfitObjective};
+use crate::constants::{JOE_ADDRESS, MOE_ADDRESS, WMNT_ADDRESS};
+use crate::math::{analytic_optimal_input, f64_to_u256_with_decimals};
+use crate::multi_path::MultiPathAnalyzer;
+use crate::multi_path_main::print_opportunity_details;
+use crate::types::{MultiPathOpportunity, PoolReserves, Token};
+
+/// Allowed relative gap between detected and analytic gross profit
+pub const SYNTHETIC_PROFIT_TOLERANCE: f64 = 0.01;
+
+/// Input range wide enough to reach the analytic optimum; MIN/MAX_INPUT_WMNT do not apply
+const SYNTHETIC_INPUT_RANGE: (f64, f64) = (1e-9, 1e12);
+
+/// Balanced reserves (token units): WMNT = 2 MOE = 0.5 JOE
+const BASE_POOLS: [(&str, f64, &str, f64); 3] = [
+    (WMNT_ADDRESS, 100_000.0, MOE_ADDRESS, 200_000.0),
+    (MOE_ADDRESS, 200_000.0, JOE_ADDRESS, 50_000.0),
+    (JOE_ADDRESS, 50_000.0, WMNT_ADDRESS, 100_000.0),
+];
+
+/// One block of the synthetic market with its injected mispricing
+#[derive(Debug, Clone)]
+pub struct SyntheticBlock {
+    pub block_number: u64,
+    pub reserves: HashMap<Address, PoolReserves>,
+    pub mispriced_pool: Address,
+    pub mispricing_pct: f64,
+    /// Analytic (input, gross profit) of the injected arbitrage
+    pub expected: Option<(f64, f64)>,
+}
+
+/// Three-pool market that skews one pool's reserves per block to create a known arbitrage
+pub struct SyntheticMarket {
+    base: Vec<PoolReserves>,
+    fee: f64,
+    block_number: u64,
+}
+
+impl SyntheticMarket {
+    /// Create a balanced WMNT/MOE/JOE triangle with no arbitrage
+    pub fn new(fee: f64) -> Self {
+        let base = BASE_POOLS
+            .iter()
+            .enumerate()
+            .map(|(i, &(token_a, reserve_a, token_b, reserve_b))| {
+                let (token_a, token_b) = (synthetic_token(token_a), synthetic_token(token_b));
+                let reserve_a = f64_to_u256_with_decimals(reserve_a, token_a.decimals());
+                let reserve_b = f64_to_u256_with_decimals(reserve_b, token_b.decimals());
+                PoolReserves::new(token_a, reserve_a, token_b, reserve_b, 0, Address::with_last_byte(i as u8 + 1))
+            })
+            .collect();
+
+        Self { base, fee, block_number: 0 }
+    }
+
+    /// Balanced starting pools
+    pub fn pools(&self) -> &[PoolReserves] {
+        &self.base
+    }
+
+    /// Advance one block: restore balance, then inflate one pool's second reserve by 1-5%
+    pub fn next_block(&mut self) -> SyntheticBlock {
+        self.block_number += 1;
+        let skewed = (self.block_number % self.base.len() as u64) as usize;
+        let mispricing_pct = 1.0 + (self.block_number % 5) as f64;
+
+        let pools: Vec<PoolReserves> = self.base
+            .iter()
+            .enumerate()
+            .map(|(i, pool)| {
+                let mut pool = pool.clone();
+                pool.block_number = self.block_number;
+                if i == skewed {
+                    pool.reserve_b = f64_to_u256_with_decimals(pool.reserve_b_units() * (1.0 + mispricing_pct / 100.0), pool.token_b.decimals());
+                }
+                pool
+            })
+            .collect();
+
+        SyntheticBlock {
+            block_number: self.block_number,
+            mispriced_pool: pools[skewed].pool_address,
+            mispricing_pct,
+            expected: self.expected_arbitrage(&pools),
+            reserves: pools.into_iter().map(|p| (p.pool_address, p)).collect(),
+        }
+    }
+
+    /// Best analytic arbitrage over both directions of the triangle
+    fn expected_arbitrage(&self, pools: &[PoolReserves]) -> Option<(f64, f64)> {
+        let forward: Vec<(f64, f64)> = pools
+            .iter()
+            .map(|p| (p.reserve_a_units(), p.reserve_b_units()))
+            .collect();
+        let backward: Vec<(f64, f64)> = forward.iter().rev().map(|&(a, b)| (b, a)).collect();
+        let fees = vec![self.fee; pools.len()];
+
+        [forward, backward]
+            .iter()
+            .filter_map(|cycle| analytic_optimal_input(cycle, &fees))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+    }
+}
+
+/// Relative gap between the best detected gross profit and the analytic expectation
+pub fn check_detection(block: &SyntheticBlock, result: &MultiPathOpportunity) -> Result<f64, String> {
+    let (_, expected_profit) = block.expected
+        .ok_or_else(|| format!("block {}: injected mispricing has no analytic arbitrage", block.block_number))?;
+    let found = result.opportunities
+        .iter()
+        .map(|o| o.gross_profit)
+        .fold(f64::NEG_INFINITY, f64::max);
+    if !found.is_finite() {
+        return Err(format!("block {}: no arbitrage detected (expected {:.4} WMNT)", block.block_number, expected_profit));
+    }
+
+    let error = (found - expected_profit).abs() / expected_profit;
+    if error > SYNTHETIC_PROFIT_TOLERANCE {
+        return Err(format!(
+            "block {}: detected {:.4} WMNT, expected {:.4} WMNT ({:.2}% off)",
+            block.block_number, found, expected_profit, error * 100.0
+        ));
+    }
+    Ok(error)
+}
+
+/// Analyzer over the synthetic market; input limits and reserve scaling are disabled
+/// so results are comparable with the unconstrained analytic optimum
+pub fn synthetic_analyzer(config: &Config, market: &SyntheticMarket) -> MultiPathAnalyzer {
+    let mut config = config.clone();
+    config.profit_objective = ProfitObjective::MaxProfit;
+    config.max_price_impact_pct = 0.0;
+    config.max_capital = 0.0;
+    config.max_reserve_fraction = 0.0;
+    config.dev_mode = false;
+
+    let mut analyzer = MultiPathAnalyzer::new(synthetic_token(WMNT_ADDRESS), &config);
+    analyzer.add_pools(market.pools());
+    analyzer
+}
+
+/// Run the full pipeline against `blocks` synthetic mispricings and fail if any is missed
+pub fn run_synthetic_market(config: &Config, blocks: u64) -> Result<(), Box<dyn Error>> {
+    let mut market = SyntheticMarket::new(config.dex_fee);
+    let mut analyzer = synthetic_analyzer(config, &market);
+    let mut failures = Vec::new();
+
+    println!("🧪 Synthetic market: {} blocks, profit tolerance {:.1}%", blocks, SYNTHETIC_PROFIT_TOLERANCE * 100.0);
+    for _ in 0..blocks {
+        let block = market.next_block();
+        analyzer.update_pool_reserves(&block.reserves);
+        let result = analyzer.find_all_opportunities(SYNTHETIC_INPUT_RANGE, config.ternary_search_iterations)?;
+
+        println!("\n🧪 Block {}: pool {} skewed by {:.0}%", block.block_number, block.mispriced_pool, block.mispricing_pct);
+        match check_detection(&block, &result) {
+            Ok(error) => println!("✅ Detected within {:.4}% of the analytic profit", error * 100.0),
+            Err(e) => {
+                println!("❌ {}", e);
+                failures.push(e);
+            }
+        }
+        if let Some(best) = &result.best_opportunity {
+            print_opportunity_details(best, config.base_token.symbol(), config.price_source());
+        }
+    }
+
+    if failures.is_empty() {
+        println!("\n✅ Synthetic market: all {} injected mispricings detected", blocks);
+        Ok(())
+    } else {
+        Err(format!("{} of {} synthetic mispricings missed: {}", failures.len(), blocks, failures.join("; ")).into())
+    }
+}
+
+fn synthetic_token(address: &str) -> Token {
+    let address: Address = address.parse().expect("token constants are valid addresses");
+    Token::from_address(address).expect("token constants map to known tokens")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi_path::tests::create_test_config;
+
+    #[test]
+    fn test_injected_mispricing_is_detected() {
+        let config = create_test_config();
+        let mut market = SyntheticMarket::new(config.dex_fee);
+        let mut analyzer = synthetic_analyzer(&config, &market);
+
+        // Balanced market: nothing to find
+        let result = analyzer.find_all_opportunities(SYNTHETIC_INPUT_RANGE, 100).unwrap();
+        assert!(result.opportunities.iter().all(|o| o.gross_profit <= 0.0));
+
+        // Cover every pool and mispricing size
+        for _ in 0..15 {
+            let block = market.next_block();
+            analyzer.update_pool_reserves(&block.reserves);
+            let result = analyzer.find_all_opportunities(SYNTHETIC_INPUT_RANGE, 100).unwrap();
+
+            let (expected_input, expected_profit) = block.expected.unwrap();
+            assert!(expected_profit > 0.0);
+            let error = check_detection(&block, &result).unwrap();
+            assert!(error <= SYNTHETIC_PROFIT_TOLERANCE);
+
+            let best = result.opportunities
+                .iter()
+                .max_by(|a, b| a.gross_profit.partial_cmp(&b.gross_profit).unwrap())
+                .unwrap();
+            assert!((best.optimal_input - expected_input).abs() / expected_input < 0.01);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Registry of known ERC-20 tokens, keyed by address
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::{LazyLock, RwLock};
+use alloy::primitives::Address;
+use crate::constants::{JOE_ADDRESS, MOE_ADDRESS, WMNT_ADDRESS};
+use crate::types::Token;
+
+static REGISTRY: LazyLock<RwLock<HashMap<Address, Token>>> = LazyLock::new(|| RwLock::new(builtin_tokens()));
+
+/// Tokens known without any registry file
+fn builtin_tokens() -> HashMap<Address, Token> {
+    [("WMNT", WMNT_ADDRESS), ("MOE", MOE_ADDRESS), ("JOE", JOE_ADDRESS)]
+        .into_iter()
+        .map(|(symbol, address)| {
+            let address: Address = address.parse().expect("built-in token addresses are valid");
+            (address, Token::new(address, symbol, 18))
+        })
+        .collect()
+}
+
+/// Add or replace a token
+pub fn register(token: Token) {
+    REGISTRY.write().unwrap_or_else(|e| e.into_inner()).insert(token.address(), token);
+}
+
+/// Look up a token by address
+pub fn by_address(address: Address) -> Option<Token> {
+    REGISTRY.read().unwrap_or_else(|e| e.into_inner()).get(&address).cloned()
+}
+
+/// Look up a token by symbol (case-insensitive)
+pub fn by_symbol(symbol: &str) -> Option<Token> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .values()
+        .find(|token| token.symbol().eq_ignore_ascii_case(symbol))
+        .cloned()
+}
+
+/// Register tokens from a CSV with `symbol,address,decimals` columns and an optional
+/// `transfer_tax_bps` column for fee-on-transfer tokens (empty = no tax); returns the count loaded
+pub fn load_csv(path: &str) -> Result<usize, Box<dyn Error>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let mut loaded = 0;
+    for result in reader.records() {
+        let record = result?;
+        if record.len() < 3 {
+            return Err(format!("Token registry row {:?} needs symbol,address,decimals", record).into());
+        }
+        let address: Address = record[1].trim().parse()
+            .map_err(|e| format!("Invalid address for token {}: {}", &record[0], e))?;
+        let decimals: u8 = record[2].trim().parse()
+            .map_err(|e| format!("Invalid decimals for token {}: {}", &record[0], e))?;
+        let transfer_tax_bps: u32 = match record.get(3).map(str::trim) {
+            Some(tax) if !tax.is_empty() => tax.parse()
+                .map_err(|e| format!("Invalid transfer tax for token {}: {}", &record[0], e))?,
+            _ => 0,
+        };
+        register(Token::new(address, record[0].trim(), decimals).with_transfer_tax_bps(transfer_tax_bps));
+        loaded += 1;
+    }
+    Ok(loaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_registry_lookup_and_csv_load() {
+        let wmnt = by_symbol("wmnt").unwrap();
+        assert_eq!(wmnt.address(), WMNT_ADDRESS.parse::<Address>().unwrap());
+        assert_eq!(by_address(wmnt.address()).unwrap().symbol(), "WMNT");
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "symbol,address,decimals").unwrap();
+        writeln!(file, "TESTMETH,0x00000000000000000000000000000000000000e1,18").unwrap();
+        writeln!(file, "TESTUSD,0x00000000000000000000000000000000000000e2,6").unwrap();
+        assert_eq!(load_csv(file.path().to_str().unwrap()).unwrap(), 2);
+
+        let usd = by_symbol("TestUSD").unwrap();
+        assert_eq!(usd.decimals(), 6);
+        assert_eq!(usd.transfer_tax_bps(), 0);
+
+        // Optional fourth column: fee-on-transfer tax
+        let mut taxed = NamedTempFile::new().unwrap();
+        writeln!(taxed, "symbol,address,decimals,transfer_tax_bps").unwrap();
+        writeln!(taxed, "TESTTAX,0x00000000000000000000000000000000000000e3,18,300").unwrap();
+        writeln!(taxed, "TESTFREE,0x00000000000000000000000000000000000000e4,18,").unwrap();
+        assert_eq!(load_csv(taxed.path().to_str().unwrap()).unwrap(), 2);
+        assert_eq!(by_symbol("TESTTAX").unwrap().transfer_tax_bps(), 300);
+        assert!((by_symbol("TESTTAX").unwrap().received_fraction() - 0.97).abs() < 1e-12);
+        assert_eq!(by_symbol("TESTFREE").unwrap().transfer_tax_bps(), 0);
+        assert_eq!(Token::parse("0x00000000000000000000000000000000000000e1").unwrap().symbol(), "TESTMETH");
+
+        let mut bad = NamedTempFile::new().unwrap();
+        writeln!(bad, "symbol,address,decimals\nBAD,not-an-address,18").unwrap();
+        assert!(load_csv(bad.path().to_str().unwrap()).is_err());
+    }
+}
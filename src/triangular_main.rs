@@ -0,0 +1,252 @@
+use std::error::Error;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+use chrono::Utc;
+use log::{debug, error, info, warn};
+
+use crate::config::Config;
+use crate::cache::{GasPriceOracle, ReserveLogGate, ReservesCache};
+use crate::blockchain::{load_pool_tokens, refresh_gas_price, save_pool_tokens};
+use crate::reserve_reader::ReserveReader;
+use crate::block_driver::{BlockTicker, poll_interval_for_block_time};
+use crate::metrics::{start_prometheus_exporter, Metrics};
+use crate::arbitrage::find_optimal_arbitrage;
+use crate::logging::{
+    ArbitrageLogger, JsonLogger, init_csv_file, write_arbitrage_to_csv, log_profitable_arbitrage, 
+    log_no_profit, log_below_threshold, log_analysis_failure, log_csv_success, log_csv_failure
+};
+use crate::display::{print_startup_banner, print_shutdown_summary, format_pool_reserves, format_block_info};
+use crate::pools::moe::MoeProtocol;
+use crate::rpc_provider::ReconnectingProvider;
+use crate::shutdown::Shutdown;
+use crate::cli::RunMode;
+
+/// Main arbitrage monitoring loop over the MOE-WMNT, JOE-MOE and JOE-WMNT triangle;
+/// returns whether an actionable opportunity was found
+pub async fn run_arbitrage_monitor(mut config: Config, run_mode: RunMode) -> Result<bool, Box<dyn Error>> {
+    // Set up provider (reconnects after repeated failures)
+    let mut rpc = ReconnectingProvider::connect(&config)?;
+
+    // Reuse pool tokens from earlier runs so only getReserves hits the chain
+    if let Some(path) = config.pool_token_cache_path.as_deref() {
+        match load_pool_tokens(path) {
+            Ok(0) => {}
+            Ok(count) => info!("🗂️ Loaded tokens of {} pools from {}", count, path),
+            Err(e) => warn!("⚠️ Ignoring unreadable pool token cache {}: {}", path, e),
+        }
+    }
+
+    // Initialize MOE protocol
+    let moe_protocol = MoeProtocol::new();
+    
+    // Validate triangular arbitrage setup
+    moe_protocol.validate_triangular_setup()
+        .map_err(|e| format!("Triangular arbitrage setup validation failed: {}", e))?;
+
+    // Get pool addresses
+    let (moe_wmnt_addr, joe_moe_addr, joe_wmnt_addr) = moe_protocol.get_main_triangular_pools();
+    let pool_addresses = vec![moe_wmnt_addr, joe_moe_addr, joe_wmnt_addr];
+
+    // Initialize CSV file
+    if let Err(e) = init_csv_file(&config.csv_file_path) {
+        warn!("⚠️ Warning: Failed to initialize CSV file: {}", e);
+    } else {
+        info!("📝 CSV logging initialized: {}", config.csv_file_path);
+    }
+
+    // Optional JSON lines log next to the console output
+    let json_logger = JsonLogger::from_config(&config)?;
+
+    // Counters for the optional Prometheus exporter
+    let metrics = Arc::new(Metrics::new());
+    if let Some(address) = config.metrics_address.as_deref() {
+        start_prometheus_exporter(Arc::clone(&metrics), address).await?;
+    }
+
+    // Initialize cache
+    let mut cache = ReservesCache::new();
+
+    // On-chain gas price for route costs, when enabled
+    let mut gas_oracle = config.dynamic_gas_price
+        .then(|| GasPriceOracle::new(config.gas_price_gwei, Duration::from_secs(config.gas_price_refresh_secs)));
+
+    // Reserve-change logging can be throttled without affecting analysis
+    let mut reserve_log_gate = ReserveLogGate::new(
+        config.reserve_log_min_change_pct,
+        Duration::from_secs(config.reserve_log_interval_secs),
+    );
+    
+    // Print startup information
+    print_startup_banner();
+    config.print_summary();
+    info!("");
+
+    // Next expected block (with a small buffer) when polling
+    let mut ticker = BlockTicker::start(
+        config.monitor_driver,
+        config.ws_rpc_url.as_deref(),
+        poll_interval_for_block_time(config.block_time_seconds),
+    );
+
+    // Ctrl-C stops the loop between blocks instead of killing it mid-write
+    let shutdown = Shutdown::on_ctrl_c();
+
+    // Block-based monitoring loop
+    let mut found_actionable = false;
+    loop {
+        // Get current block number, unless shutdown was requested while waiting
+        let block_result = tokio::select! {
+            biased;
+            _ = shutdown.wait() => break,
+            result = ticker.next_block(rpc.provider()) => result,
+        };
+        rpc.observe(&block_result);
+        let current_block = match block_result {
+            Ok(block) => block,
+            Err(e) => {
+                error!("❌ Error getting block number: {}", e);
+                metrics.record_error();
+                if let Some(logger) = &json_logger {
+                    logger.log_error(&format!("Error getting block number: {}", e));
+                }
+                if run_mode == RunMode::Once {
+                    return Err(format!("Error getting block number: {}", e).into());
+                }
+                continue;
+            }
+        };
+        let start_time = Instant::now();
+        metrics.set_current_block(current_block);
+
+        // Forget reserves of pools that have not been fetched recently
+        if config.reserve_cache_ttl_secs > 0 {
+            cache.prune_older_than(Duration::from_secs(config.reserve_cache_ttl_secs));
+        }
+
+        // Only fetch and process if block has changed
+        if cache.has_changed(current_block) {
+            // Fetch all reserves in parallel
+            let fetched = rpc.provider().fetch_reserves(&pool_addresses, current_block, config.max_retries, config.max_concurrent_requests).await.into_complete();
+            rpc.observe(&fetched);
+            if let Some(path) = config.pool_token_cache_path.as_deref()
+                && let Err(e) = save_pool_tokens(path)
+            {
+                warn!("⚠️ Failed to write pool token cache {}: {}", path, e);
+            }
+            match fetched {
+                Ok(mut reserves_map) => {
+                    config.apply_reserve_scale(&mut reserves_map);
+
+                    // Check if reserves have actually changed
+                    if cache.reserves_changed(&reserves_map) {
+                        let timestamp = Utc::now();
+                        let fetch_duration = start_time.elapsed();
+                        
+                        // Print log with reserves information only when the change passes the log gate
+                        if let Some(logger) = &json_logger {
+                            logger.log_reserves_change(current_block, timestamp);
+                        }
+                        let log_reserves = reserve_log_gate.should_log(&reserves_map, std::time::Instant::now());
+                        if log_reserves {
+                            debug!("🔄 Reserves changed at {}", format_block_info(current_block, timestamp));
+                            debug!("{}", format_pool_reserves(moe_wmnt_addr, joe_moe_addr, joe_wmnt_addr, &reserves_map));
+                        }
+
+                        // Update cache
+                        for (addr, reserves) in &reserves_map {
+                            cache.update(*addr, reserves.clone());
+                        }
+
+                        // Use ternary search to find optimal arbitrage opportunity
+                        let moe_wmnt_reserves = &reserves_map[&moe_wmnt_addr];
+                        let joe_moe_reserves = &reserves_map[&joe_moe_addr];
+                        let joe_wmnt_reserves = &reserves_map[&joe_wmnt_addr];
+
+                        if let Some(oracle) = gas_oracle.as_mut() {
+                            config.gas_price_gwei = refresh_gas_price(oracle, rpc.provider()).await;
+                        }
+
+                        // Find optimal arbitrage using ternary search
+                        let analysis_start = Instant::now();
+                        let result = find_optimal_arbitrage(moe_wmnt_reserves, joe_moe_reserves, joe_wmnt_reserves, &config);
+                        let profitable = result.as_ref().is_some_and(|o| o.is_profitable_with_threshold(config.min_profit_wmnt));
+                        found_actionable |= profitable;
+                        metrics.record_block(
+                            current_block,
+                            fetch_duration,
+                            analysis_start.elapsed(),
+                            result.is_some() as usize,
+                            profitable as usize,
+                        );
+                        match result {
+                            Some(opportunity) => {
+                                if let Some(logger) = &json_logger
+                                    && let Err(e) = logger.log_block_opportunity(current_block, &opportunity, fetch_duration)
+                                {
+                                    warn!("⚠️ Failed to write JSON log: {}", e);
+                                }
+
+                                if opportunity.is_profitable() {
+                                    if opportunity.is_profitable_with_threshold(config.min_profit_wmnt) {
+                                        log_profitable_arbitrage(&opportunity, fetch_duration, &config);
+                                    } else {
+                                        log_below_threshold(opportunity.net_profit, config.min_profit_wmnt, fetch_duration);
+                                    }
+
+                                    // Write to CSV (sub-threshold rows are flagged as not actionable)
+                                    match write_arbitrage_to_csv(
+                                        timestamp,
+                                        current_block,
+                                        &opportunity,
+                                        moe_wmnt_reserves,
+                                        joe_moe_reserves,
+                                        joe_wmnt_reserves,
+                                        fetch_duration.as_millis() as u64,
+                                        &config,
+                                    ) {
+                                        Ok(_) => log_csv_success(&config.csv_file_path),
+                                        Err(e) => log_csv_failure(e.as_ref()),
+                                    }
+                                } else {
+                                    log_no_profit(opportunity.gross_profit, opportunity.net_profit, fetch_duration);
+                                }
+                            }
+                            None => {
+                                log_analysis_failure(fetch_duration);
+                            }
+                        }
+                        if log_reserves {
+                            debug!(""); // Add blank line for readability
+                        }
+                    } else {
+                        // Refresh entries so unchanged pools are not pruned as stale
+                        for (addr, reserves) in reserves_map {
+                            cache.update(addr, reserves);
+                        }
+                        // Update cache block number even if reserves didn't change
+                        cache.update_block_number(current_block);
+                    }
+                }
+                Err(e) => {
+                    error!("❌ Block {}: Failed to fetch reserves: {}", current_block, e);
+                    metrics.record_error();
+                    if let Some(logger) = &json_logger {
+                        logger.log_error(&format!("Block {}: Failed to fetch reserves: {}", current_block, e));
+                    }
+                    if run_mode == RunMode::Once {
+                        return Err(format!("Block {}: failed to fetch reserves: {}", current_block, e).into());
+                    }
+                }
+            }
+
+            // --once: a single fetch and analysis cycle
+            if run_mode == RunMode::Once {
+                break;
+            }
+        }
+    }
+
+    // CSV and JSON rows are flushed as they are written, so only the summary remains
+    print_shutdown_summary(&metrics.snapshot());
+    Ok(found_actionable)
+}
\ No newline at end of file
@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, Stdout};
+use std::time::Instant;
+use alloy::primitives::Address;
+use ratatui::Frame;
+use ratatui::Terminal;
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+use crate::types::{MultiPathOpportunity, PoolReserves};
+
+/// Maximum number of opportunities shown in the dashboard table
+const TOP_OPPORTUNITIES: usize = 10;
+
+/// Session-wide statistics accumulated across monitored blocks
+#[derive(Debug, Clone)]
+pub struct SessionStats {
+    pub started_at: Instant,
+    pub blocks_processed: u64,
+    pub opportunities_seen: u64,
+    pub profitable_seen: u64,
+    pub best_net_profit: Option<f64>,
+    pub last_analysis_ms: u64,
+}
+
+impl SessionStats {
+    /// Create empty session statistics starting now
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            blocks_processed: 0,
+            opportunities_seen: 0,
+            profitable_seen: 0,
+            best_net_profit: None,
+            last_analysis_ms: 0,
+        }
+    }
+
+    /// Fold the result of one block's scan into the session statistics
+    pub fn record_scan(&mut self, multi_opportunity: &MultiPathOpportunity) {
+        self.blocks_processed += 1;
+        self.opportunities_seen += multi_opportunity.opportunities.len() as u64;
+        self.profitable_seen += multi_opportunity.profitable_count() as u64;
+        self.last_analysis_ms = multi_opportunity.analysis_time_ms;
+
+        if let Some(best) = &multi_opportunity.best_opportunity {
+            let improved = self.best_net_profit.is_none_or(|current| best.net_profit > current);
+            if improved {
+                self.best_net_profit = Some(best.net_profit);
+            }
+        }
+    }
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One row of the pool reserves panel
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolRow {
+    pub address: Address,
+    pub pair: String,
+    pub reserve_a: f64,
+    pub reserve_b: f64,
+}
+
+/// One row of the top opportunities table
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpportunityRow {
+    pub route: String,
+    pub hops: usize,
+    pub input: f64,
+    pub net_profit: f64,
+    pub profit_percentage: f64,
+    pub profitable: bool,
+}
+
+/// Snapshot of everything the dashboard renders for a single block
+#[derive(Debug, Clone)]
+pub struct DashboardState {
+    pub block_number: u64,
+    pub pools: Vec<PoolRow>,
+    pub opportunities: Vec<OpportunityRow>,
+    pub stats: SessionStats,
+    /// Symbol profits are shown in (the base token)
+    pub unit: String,
+}
+
+impl DashboardState {
+    /// Build dashboard state from one block's reserves and scan result
+    pub fn new(
+        block_number: u64,
+        reserves_map: &HashMap<Address, PoolReserves>,
+        multi_opportunity: &MultiPathOpportunity,
+        stats: &SessionStats,
+        unit: &str,
+    ) -> Self {
+        let mut pools: Vec<PoolRow> = reserves_map
+            .values()
+            .map(|reserves| PoolRow {
+                address: reserves.pool_address,
+                pair: format!("{}-{}", reserves.token_a.symbol(), reserves.token_b.symbol()),
+                reserve_a: reserves.reserve_a_units(),
+                reserve_b: reserves.reserve_b_units(),
+            })
+            .collect();
+        pools.sort_by(|a, b| a.pair.cmp(&b.pair).then(a.address.cmp(&b.address)));
+
+        let mut sorted = multi_opportunity.opportunities.iter().collect::<Vec<_>>();
+        sorted.sort_by(|a, b| b.net_profit.partial_cmp(&a.net_profit).unwrap_or(std::cmp::Ordering::Equal));
+
+        let opportunities = sorted
+            .into_iter()
+            .take(TOP_OPPORTUNITIES)
+            .map(|opp| OpportunityRow {
+                route: opp.path.as_ref()
+                    .map(|p| p.description())
+                    .unwrap_or_else(|| "Legacy triangular".to_string()),
+                hops: opp.hop_count(),
+                input: opp.optimal_input,
+                net_profit: opp.net_profit,
+                profit_percentage: opp.profit_percentage,
+                profitable: opp.is_profitable(),
+            })
+            .collect();
+
+        Self {
+            block_number,
+            pools,
+            opportunities,
+            stats: stats.clone(),
+            unit: unit.to_string(),
+        }
+    }
+}
+
+/// Render the dashboard panels into a frame
+pub fn render(frame: &mut Frame, state: &DashboardState) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Min(6),
+        ])
+        .split(frame.area());
+
+    let header = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(rows[0]);
+
+    render_block_panel(frame, header[0], state);
+    render_stats_panel(frame, header[1], &state.stats, &state.unit);
+    render_pools_panel(frame, rows[1], &state.pools);
+    render_opportunities_panel(frame, rows[2], &state.opportunities);
+}
+
+fn render_block_panel(frame: &mut Frame, area: Rect, state: &DashboardState) {
+    let paragraph = Paragraph::new(format!("#{}", state.block_number))
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL).title(" Block "));
+    frame.render_widget(paragraph, area);
+}
+
+fn render_stats_panel(frame: &mut Frame, area: Rect, stats: &SessionStats, unit: &str) {
+    let best = stats.best_net_profit
+        .map(|p| format!("{:.4} {}", p, unit))
+        .unwrap_or_else(|| "-".to_string());
+    let text = format!(
+        "Uptime: {}s | Blocks: {} | Opportunities: {} | Profitable: {} | Best: {} | Analysis: {}ms",
+        stats.started_at.elapsed().as_secs(),
+        stats.blocks_processed,
+        stats.opportunities_seen,
+        stats.profitable_seen,
+        best,
+        stats.last_analysis_ms,
+    );
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title(" Session "));
+    frame.render_widget(paragraph, area);
+}
+
+fn render_pools_panel(frame: &mut Frame, area: Rect, pools: &[PoolRow]) {
+    let rows = pools.iter().map(|pool| {
+        Row::new(vec![
+            Cell::from(pool.pair.clone()),
+            Cell::from(pool.address.to_string()),
+            Cell::from(format!("{:.2}", pool.reserve_a)),
+            Cell::from(format!("{:.2}", pool.reserve_b)),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(14),
+            Constraint::Length(44),
+            Constraint::Min(12),
+            Constraint::Min(12),
+        ],
+    )
+    .header(Row::new(vec!["Pair", "Pool", "Reserve A", "Reserve B"])
+        .style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(Block::default().borders(Borders::ALL).title(format!(" Pool Reserves ({}) ", pools.len())));
+    frame.render_widget(table, area);
+}
+
+fn render_opportunities_panel(frame: &mut Frame, area: Rect, opportunities: &[OpportunityRow]) {
+    let rows = opportunities.iter().enumerate().map(|(i, opp)| {
+        let style = if opp.profitable {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        Row::new(vec![
+            Cell::from(format!("{}", i + 1)),
+            Cell::from(format!("{}-hop", opp.hops)),
+            Cell::from(format!("{:.4}", opp.input)),
+            Cell::from(format!("{:.4}", opp.net_profit)),
+            Cell::from(format!("{:.2}%", opp.profit_percentage)),
+            Cell::from(opp.route.clone()),
+        ])
+        .style(style)
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(6),
+            Constraint::Length(14),
+            Constraint::Length(14),
+            Constraint::Length(9),
+            Constraint::Min(20),
+        ],
+    )
+    .header(Row::new(vec!["#", "Type", "Input", "Net Profit", "Profit %", "Route"])
+        .style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(Block::default().borders(Borders::ALL).title(" Top Opportunities "));
+    frame.render_widget(table, area);
+}
+
+/// Terminal dashboard that redraws on each processed block
+pub struct Dashboard<B: Backend> {
+    terminal: Terminal<B>,
+}
+
+impl Dashboard<CrosstermBackend<Stdout>> {
+    /// Create a dashboard drawing to stdout
+    pub fn stdout() -> Result<Self, Box<dyn Error>> {
+        Self::with_backend(CrosstermBackend::new(io::stdout()))
+    }
+}
+
+impl<B: Backend> Dashboard<B> {
+    /// Create a dashboard on top of any ratatui backend
+    pub fn with_backend(backend: B) -> Result<Self, Box<dyn Error>> {
+        let mut terminal = Terminal::new(backend)?;
+        terminal.clear()?;
+        Ok(Self { terminal })
+    }
+
+    /// Redraw the whole dashboard (clears first so stray log lines don't linger)
+    pub fn draw(&mut self, state: &DashboardState) -> Result<(), Box<dyn Error>> {
+        self.terminal.clear()?;
+        self.terminal.draw(|frame| render(frame, state))?;
+        Ok(())
+    }
+
+    /// Access the underlying terminal (used by tests to inspect the buffer)
+    pub fn terminal(&self) -> &Terminal<B> {
+        &self.terminal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U256;
+    use chrono::Utc;
+    use ratatui::backend::TestBackend;
+    use crate::types::{ArbitrageOpportunity, ArbitragePath, Token};
+
+    fn create_opportunity(net_profit: f64, path: ArbitragePath) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            optimal_input: 100.0,
+            final_output: 100.0 + net_profit,
+            gross_profit: net_profit,
+            net_profit,
+            profit_percentage: net_profit,
+            search_method: "test".to_string(),
+            path: Some(path),
+            robustness: 1.0,
+            unconstrained_input: 100.0,
+            binding_constraint: None,
+            price_impact_percent: 0.0,
+            requires_flash_loan: false,
+            flash_loan_fee: 0.0,
+            min_pool_depth: 0.0,
+            expected_realized_profit: 0.0,
+            hop_outputs: Vec::new(),
+            confidence: 1.0,
+        }
+    }
+
+    fn create_state() -> DashboardState {
+        let wmnt = Token::new(Address::ZERO, "WMNT", 18);
+        let moe = Token::new(Address::from([1u8; 20]), "MOE", 18);
+        let joe = Token::new(Address::from([2u8; 20]), "JOE", 18);
+        let path = ArbitragePath::new(
+            vec![wmnt.clone(), moe.clone(), joe, wmnt.clone()],
+            vec![Address::from([3u8; 20]), Address::from([4u8; 20]), Address::from([5u8; 20])],
+        );
+
+        let multi_opportunity = MultiPathOpportunity::new(
+            vec![create_opportunity(-1.0, path.clone()), create_opportunity(2.5, path)],
+            7,
+        );
+
+        let mut reserves_map = HashMap::new();
+        reserves_map.insert(Address::from([3u8; 20]), PoolReserves {
+            token_a: wmnt,
+            reserve_a: U256::from(1000u128 * 1_000_000_000_000_000_000u128),
+            token_b: moe,
+            reserve_b: U256::from(2000u128 * 1_000_000_000_000_000_000u128),
+            block_number: 42,
+            timestamp: Utc::now(),
+            pool_address: Address::from([3u8; 20]),
+        });
+
+        let mut stats = SessionStats::new();
+        stats.record_scan(&multi_opportunity);
+
+        DashboardState::new(42, &reserves_map, &multi_opportunity, &stats, "WMNT")
+    }
+
+    #[test]
+    fn test_dashboard_state_from_multi_path_opportunity() {
+        let state = create_state();
+
+        assert_eq!(state.block_number, 42);
+        assert_eq!(state.pools.len(), 1);
+        assert_eq!(state.pools[0].pair, "WMNT-MOE");
+        assert_eq!(state.pools[0].reserve_b, 2000.0);
+
+        // Opportunities are ordered by net profit, best first
+        assert_eq!(state.opportunities.len(), 2);
+        assert_eq!(state.opportunities[0].net_profit, 2.5);
+        assert!(state.opportunities[0].profitable);
+        assert!(!state.opportunities[1].profitable);
+        assert_eq!(state.opportunities[0].hops, 3);
+
+        assert_eq!(state.stats.blocks_processed, 1);
+        assert_eq!(state.stats.profitable_seen, 1);
+        assert_eq!(state.stats.best_net_profit, Some(2.5));
+    }
+
+    #[test]
+    fn test_dashboard_renders_without_terminal() {
+        let state = create_state();
+        let mut dashboard = Dashboard::with_backend(TestBackend::new(140, 30)).unwrap();
+        dashboard.draw(&state).unwrap();
+
+        let buffer = dashboard.terminal().backend().buffer();
+        let content: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(content.contains("#42"));
+        assert!(content.contains("WMNT -> MOE -> JOE -> WMNT"));
+    }
+}
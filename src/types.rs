@@ -1,44 +1,123 @@
-use alloy::primitives::{Address, U256};
+use std::time::Duration;
+use alloy::primitives::{Address, U256, keccak256};
 use chrono::{DateTime, Utc};
-use serde::Serialize;
-
-/// Token enum for identification across different DEX protocols
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum Token {
-    WMNT(Address),
-    MOE(Address), 
-    JOE(Address),
+use serde::{Deserialize, Serialize};
+use crate::graph::TokenGraph;
+
+/// ERC-20 token identified by its address, with display metadata from the token registry
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Token {
+    address: Address,
+    symbol: String,
+    decimals: u8,
+    /// Fee-on-transfer tax in basis points, taken from every amount the token is swapped into
+    #[serde(default, skip_serializing_if = "is_zero")]
+    transfer_tax_bps: u32,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
 }
 
 impl Token {
+    /// Create a token from its address and metadata
+    pub fn new(address: Address, symbol: impl Into<String>, decimals: u8) -> Self {
+        Self {
+            address,
+            symbol: symbol.into(),
+            decimals,
+            transfer_tax_bps: 0,
+        }
+    }
+
+    /// Same token with a fee-on-transfer tax
+    pub fn with_transfer_tax_bps(mut self, transfer_tax_bps: u32) -> Self {
+        self.transfer_tax_bps = transfer_tax_bps;
+        self
+    }
+
     /// Get the address of the token
     pub fn address(&self) -> Address {
-        match self {
-            Token::WMNT(addr) | Token::MOE(addr) | Token::JOE(addr) => *addr,
-        }
+        self.address
     }
 
     /// Get the symbol of the token
-    pub fn symbol(&self) -> &'static str {
-        match self {
-            Token::WMNT(_) => "WMNT",
-            Token::MOE(_) => "MOE", 
-            Token::JOE(_) => "JOE",
-        }
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Get the token's decimals
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    /// Fee-on-transfer tax in basis points (0 for ordinary tokens)
+    pub fn transfer_tax_bps(&self) -> u32 {
+        self.transfer_tax_bps
     }
 
-    /// Create a token from address string (used for parsing from contracts)
+    /// Share of a received amount that actually arrives after the transfer tax
+    pub fn received_fraction(&self) -> f64 {
+        1.0 - (self.transfer_tax_bps as f64 / 10_000.0).min(1.0)
+    }
+
+    /// Look up a registered token by address (used for parsing from contracts)
     pub fn from_address(addr: Address) -> Option<Self> {
-        let addr_str = addr.to_checksum(None).to_lowercase();
-        match addr_str.as_str() {
-            crate::constants::WMNT_ADDRESS => Some(Token::WMNT(addr)),
-            crate::constants::MOE_ADDRESS => Some(Token::MOE(addr)),
-            crate::constants::JOE_ADDRESS => Some(Token::JOE(addr)),
-            _ => None,
-        }
+        crate::token_registry::by_address(addr)
+    }
+
+    /// Parse a registered token from its symbol (case-insensitive) or address
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        crate::token_registry::by_symbol(value)
+            .or_else(|| Self::from_address(value.parse().ok()?))
     }
 }
 
+// Tokens are identified by address alone; symbol, decimals and tax are metadata
+impl PartialEq for Token {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+impl Eq for Token {}
+
+impl std::hash::Hash for Token {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.address.hash(state);
+    }
+}
+
+/// One observed swap with the pool's reserves just before it (token units)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwapSample {
+    pub reserve_in: f64,
+    pub reserve_out: f64,
+    pub amount_in: f64,
+    pub amount_out: f64,
+}
+
+/// Concentrated-liquidity (Uniswap V3 style) pool state from `slot0` and `liquidity`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct V3PoolState {
+    /// sqrt(token1 / token0) in raw units, Q64.96 fixed point
+    pub sqrt_price_x96: U256,
+    /// Active liquidity in the current tick range
+    pub liquidity: u128,
+    pub tick: i32,
+}
+
+/// Pricing model of a pool
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PoolKind {
+    /// x*y=k pair priced from reserves
+    #[default]
+    ConstantProduct,
+    /// V3 pool quoted from price and active liquidity; token_a is token0
+    ConcentratedV3(V3PoolState),
+}
+
 /// Struct to hold reserves with token mapping for any DEX pool
 #[derive(Debug, Clone, PartialEq)]
 pub struct PoolReserves {
@@ -72,6 +151,46 @@ impl PoolReserves {
         }
     }
 
+    /// Copy with both reserves multiplied by `factor` (test environments only)
+    pub fn scaled(&self, factor: f64) -> Self {
+        use crate::math::f64_to_u256_with_decimals;
+
+        Self {
+            reserve_a: f64_to_u256_with_decimals(self.reserve_a_units() * factor, self.token_a.decimals()),
+            reserve_b: f64_to_u256_with_decimals(self.reserve_b_units() * factor, self.token_b.decimals()),
+            ..self.clone()
+        }
+    }
+
+    /// Error describing why these reserves cannot be priced: a zero side, or a side above
+    /// `MAX_PLAUSIBLE_RESERVE_UNITS` tokens
+    pub fn sanity_check(&self) -> Result<(), String> {
+        use crate::constants::MAX_PLAUSIBLE_RESERVE_UNITS;
+
+        for (token, raw, units) in [
+            (&self.token_a, self.reserve_a, self.reserve_a_units()),
+            (&self.token_b, self.reserve_b, self.reserve_b_units()),
+        ] {
+            if raw.is_zero() {
+                return Err(format!("zero {} reserve", token.symbol()));
+            }
+            if !units.is_finite() || units > MAX_PLAUSIBLE_RESERVE_UNITS {
+                return Err(format!("{} reserve of {:e} tokens exceeds {:e}", token.symbol(), units, MAX_PLAUSIBLE_RESERVE_UNITS));
+            }
+        }
+        Ok(())
+    }
+
+    /// Token A reserve in token units, using token A's decimals
+    pub fn reserve_a_units(&self) -> f64 {
+        crate::math::u256_to_f64_with_decimals(self.reserve_a, self.token_a.decimals())
+    }
+
+    /// Token B reserve in token units, using token B's decimals
+    pub fn reserve_b_units(&self) -> f64 {
+        crate::math::u256_to_f64_with_decimals(self.reserve_b, self.token_b.decimals())
+    }
+
     /// Get reserves for a specific token pair (returns None if tokens don't match)
     pub fn get_reserves_for_pair(&self, token_in: Token, token_out: Token) -> Option<(U256, U256)> {
         if self.token_a == token_in && self.token_b == token_out {
@@ -85,7 +204,7 @@ impl PoolReserves {
 }
 
 /// CSV record structure for arbitrage opportunities
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ArbitrageRecord {
     pub timestamp: String,
     pub block_number: u64,
@@ -103,6 +222,20 @@ pub struct ArbitrageRecord {
     pub joe_wmnt_reserve0: String,
     pub joe_wmnt_reserve1: String,
     pub fetch_time_ms: u64,
+    /// Net profit clears the configured minimum (absent from older logs)
+    #[serde(default)]
+    pub actionable: bool,
+    #[serde(default)]
+    pub price_impact_percent: f64,
+    #[serde(default)]
+    pub requires_flash_loan: bool,
+    #[serde(default)]
+    pub flash_loan_fee_wmnt: f64,
+    #[serde(default)]
+    pub expected_realized_profit_wmnt: f64,
+    /// JSON array of the amount held after each swap
+    #[serde(default)]
+    pub hop_outputs: String,
 }
 
 /// Enhanced CSV record for multi-path arbitrage
@@ -121,10 +254,27 @@ pub struct MultiPathArbitrageRecord {
     pub gas_units: u64,
     pub fetch_time_ms: u64,
     pub analysis_time_ms: u64,
+    /// Net profit clears the configured minimum
+    pub actionable: bool,
+    pub price_impact_percent: f64,
+    /// Input exceeds the wallet balance, so the shortfall is flash-borrowed
+    pub requires_flash_loan: bool,
+    pub flash_loan_fee_wmnt: f64,
+    /// Stable route id shared by every record of the same cycle (empty for legacy records)
+    pub opportunity_id: String,
+    /// Net profit at the same input re-priced at the next block (`None` unless verification is on)
+    pub verified_net_profit: Option<f64>,
+    /// Net profit if only `CAPTURE_RATIO` of the gross spread is captured
+    pub expected_realized_profit_wmnt: f64,
+    /// JSON array of the amount held after each swap (see `ArbitrageOpportunity::hop_outputs`)
+    pub hop_outputs: String,
+    /// Net profit in USD; the column is left out unless `USD_PRICES` quotes the start token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_profit_usd: Option<f64>,
 }
 
 /// Arbitrage opportunity result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ArbitrageOpportunity {
     pub optimal_input: f64,
     pub final_output: f64,
@@ -133,6 +283,26 @@ pub struct ArbitrageOpportunity {
     pub profit_percentage: f64,
     pub search_method: String,
     pub path: Option<ArbitragePath>,
+    /// Fraction of the input search range that is profitable (0-1)
+    pub robustness: f64,
+    /// Input chosen before applying input limits
+    pub unconstrained_input: f64,
+    /// Limit that reduced the input below the unconstrained optimum
+    pub binding_constraint: Option<InputConstraint>,
+    /// Worst per-hop price impact at the chosen input (%)
+    pub price_impact_percent: f64,
+    /// Input exceeds the wallet balance, so the shortfall is flash-borrowed
+    pub requires_flash_loan: bool,
+    /// Flash-loan fee (WMNT) already deducted from `net_profit`
+    pub flash_loan_fee: f64,
+    /// Shallowest hop's input reserve along the path, valued in WMNT (0 = unknown)
+    pub min_pool_depth: f64,
+    /// Net profit if only `CAPTURE_RATIO` of the gross spread survives competing bots
+    pub expected_realized_profit: f64,
+    /// Amount held after each swap at the chosen input, in path order (last = final output)
+    pub hop_outputs: Vec<f64>,
+    /// Odds (0-1) that the edge survives reserve drift over `EXECUTION_DELAY_BLOCKS`; 1 when not modeled
+    pub confidence: f64,
 }
 
 impl ArbitrageOpportunity {
@@ -141,6 +311,17 @@ impl ArbitrageOpportunity {
         self.net_profit > 0.0
     }
 
+    /// Check if the opportunity is profitable by at least `min` WMNT (worth acting on)
+    pub fn is_profitable_with_threshold(&self, min: f64) -> bool {
+        self.is_profitable() && self.net_profit >= min
+    }
+
+    /// Check whether the opportunity's path trades through the given token
+    pub fn touches_token(&self, token: &Token) -> bool {
+        self.path.as_ref()
+            .is_some_and(|p| p.tokens.contains(token))
+    }
+
     /// Get the number of hops in the arbitrage path
     pub fn hop_count(&self) -> usize {
         self.path.as_ref()
@@ -148,24 +329,46 @@ impl ArbitrageOpportunity {
             .unwrap_or(0)
     }
 
-    /// Get precise gas cost based on hop count and current gas price (result in MNT)
-    pub fn gas_cost(&self, gas_price_gwei: f64) -> f64 {
-        use crate::constants::{GAS_UNITS_3_HOPS, GAS_UNITS_4_HOPS, GWEI_TO_MNT_MULTIPLIER};
-        
-        let hop_count = self.hop_count();
-        let gas_units = match hop_count {
-            3 => GAS_UNITS_3_HOPS,
-            4 => GAS_UNITS_4_HOPS,
-            _ => GAS_UNITS_3_HOPS, // Default to 3-hops gas
+    /// Net profit in USD, valued in the route's start token (WMNT for legacy triangular results)
+    pub fn net_profit_usd(&self, prices: &dyn crate::price_source::PriceSource) -> Option<f64> {
+        let start_token = match &self.path {
+            Some(path) => path.tokens.first()?.clone(),
+            None => Token::parse("WMNT")?,
         };
-        
+        prices.value_usd(&start_token, self.net_profit)
+    }
+
+    /// `hop_outputs` as a JSON array, as stored in CSV/SQLite/Parquet records
+    pub fn hop_outputs_json(&self) -> String {
+        serde_json::to_string(&self.hop_outputs).unwrap_or_default()
+    }
+
+    /// Execution risk (lower is safer): price impact scaled by the inverse of the
+    /// shallowest pool's depth; infinite when the depth is unknown
+    pub fn risk_score(&self) -> f64 {
+        if self.min_pool_depth <= 0.0 {
+            return f64::INFINITY;
+        }
+        (1.0 + self.price_impact_percent / 100.0) / self.min_pool_depth
+    }
+
+    /// Gas cost (MNT) of the route's swaps plus approval/wrap `overhead` at the given gas price
+    pub fn gas_cost(&self, gas_price_gwei: f64, overhead: &GasOverhead) -> f64 {
+        use crate::constants::GWEI_TO_MNT_MULTIPLIER;
+
+        // Legacy results without a path are costed as 3 hops
+        let gas_units = match &self.path {
+            Some(path) => path.total_gas_units(overhead),
+            None => PathType::from_hops(0).gas_units(),
+        };
+
         // Direct calculation: gas_units * gas_price_gwei * gwei_to_mnt_multiplier
         gas_units as f64 * gas_price_gwei * GWEI_TO_MNT_MULTIPLIER
     }
 }
 
 /// Arbitrage path representation
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ArbitragePath {
     pub tokens: Vec<Token>,
     pub pools: Vec<Address>,
@@ -175,11 +378,9 @@ pub struct ArbitragePath {
 impl ArbitragePath {
     /// Create a new arbitrage path
     pub fn new(tokens: Vec<Token>, pools: Vec<Address>) -> Self {
-        let path_type = match tokens.len() {
-            3 => PathType::ThreeHop,
-            4 => PathType::FourHop,
-            _ => PathType::Custom(tokens.len()),
-        };
+        // One pool per hop; without pools, count the hops of a closed token path
+        let hops = if pools.is_empty() { tokens.len().saturating_sub(1) } else { pools.len() };
+        let path_type = PathType::from_hops(hops);
         
         Self {
             tokens,
@@ -188,6 +389,13 @@ impl ArbitragePath {
         }
     }
 
+    /// Stable id for this cycle: a hash of the ordered pool addresses, so the
+    /// same route gets the same id in every block and across runs
+    pub fn opportunity_id(&self) -> String {
+        let bytes: Vec<u8> = self.pools.iter().flat_map(|pool| pool.into_array()).collect();
+        alloy::hex::encode(&keccak256(bytes)[..8])
+    }
+
     /// Get path description as string
     pub fn description(&self) -> String {
         self.tokens
@@ -202,33 +410,221 @@ impl ArbitragePath {
         self.tokens.first() == self.tokens.last()
     }
 
+    /// Starts and ends in WMNT, so executing it wraps MNT in and unwraps it out
+    pub fn has_wmnt_endpoints(&self) -> bool {
+        let Ok(wmnt) = crate::constants::WMNT_ADDRESS.parse::<Address>() else {
+            return false;
+        };
+        [self.tokens.first(), self.tokens.last()]
+            .iter()
+            .all(|token| token.is_some_and(|token| token.address() == wmnt))
+    }
+
     /// Get expected gas cost for this path type
     pub fn expected_gas_units(&self) -> u64 {
-        use crate::constants::{GAS_UNITS_3_HOPS, GAS_UNITS_4_HOPS};
-        
-        match self.path_type {
-            PathType::ThreeHop => GAS_UNITS_3_HOPS,
-            PathType::FourHop => GAS_UNITS_4_HOPS,
-            PathType::Custom(_) => GAS_UNITS_3_HOPS + (self.tokens.len() as u64 * 10_000),
+        self.path_type.gas_units()
+    }
+
+    /// Swap gas plus approval/wrap overhead for executing this route
+    pub fn total_gas_units(&self, overhead: &GasOverhead) -> u64 {
+        self.expected_gas_units() + overhead.units_for(self)
+    }
+
+    /// Check that the path has one pool per hop and that each pool is in `graph`
+    /// and trades exactly the pair of tokens around it
+    pub fn validate(&self, graph: &TokenGraph) -> Result<(), String> {
+        if self.tokens.len() < 2 {
+            return Err(format!("path needs at least 2 tokens, got {}", self.tokens.len()));
+        }
+        if self.pools.len() != self.tokens.len() - 1 {
+            return Err(format!("{} pools for {} tokens (expected {})", self.pools.len(), self.tokens.len(), self.tokens.len() - 1));
+        }
+        for (hop, (pair, &pool_address)) in self.tokens.windows(2).zip(&self.pools).enumerate() {
+            let pool = graph.pool(pool_address).ok_or_else(|| format!("hop {}: pool {} is not in the graph", hop + 1, pool_address))?;
+            let connects = (pool.token_a == pair[0] && pool.token_b == pair[1]) || (pool.token_a == pair[1] && pool.token_b == pair[0]);
+            if !connects {
+                return Err(format!(
+                    "hop {} ({} -> {}): pool {} trades {}/{}",
+                    hop + 1, pair[0].symbol(), pair[1].symbol(), pool_address, pool.token_a.symbol(), pool.token_b.symbol()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Pool sequence of the cycle rotated to leave `start` first, so rotations
+    /// of the same cycle share one key (None if `start` is not on the cycle)
+    pub fn canonical_key(&self, start: &Token) -> Option<Vec<Address>> {
+        if !self.is_cycle() || self.pools.len() + 1 != self.tokens.len() {
+            return None;
         }
+        let offset = self.tokens[..self.pools.len()].iter().position(|t| t == start)?;
+        let mut pools = self.pools.clone();
+        pools.rotate_left(offset);
+        Some(pools)
+    }
+}
+
+/// Fixed gas overheads beyond the swaps themselves
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GasOverhead {
+    /// Gas for one token approval (charged once per hop)
+    pub approval_gas_units: u64,
+    /// Gas for one WMNT wrap or unwrap (charged for wrap in and unwrap out of WMNT-endpoint routes)
+    pub wrap_gas_units: u64,
+}
+
+impl GasOverhead {
+    /// Overhead gas units for a given route
+    pub fn units_for(&self, path: &ArbitragePath) -> u64 {
+        let wraps = if path.has_wmnt_endpoints() { 2 } else { 0 };
+        self.approval_gas_units * path.pools.len() as u64 + self.wrap_gas_units * wraps
+    }
+}
+
+/// Limit that can cap a route's input below its unconstrained optimum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum InputConstraint {
+    PriceImpact,
+    Capital,
+    ReserveCap,
+}
+
+impl InputConstraint {
+    pub fn label(&self) -> &'static str {
+        match self {
+            InputConstraint::PriceImpact => "price impact limit",
+            InputConstraint::Capital => "capital limit",
+            InputConstraint::ReserveCap => "reserve cap",
+        }
+    }
+}
+
+/// Caps on a route's input amount (0 disables a limit)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InputLimits {
+    /// Maximum price impact on any hop (fraction, e.g. 0.01 = 1%)
+    pub max_price_impact: f64,
+    /// Maximum WMNT committed to one trade
+    pub max_capital: f64,
+    /// Maximum input as a fraction of the first pool's input reserve
+    pub max_reserve_fraction: f64,
+}
+
+impl InputLimits {
+    /// Tightest active bound on the input and the constraint that sets it.
+    /// When bounds tie, precedence is price impact, then capital, then reserve cap.
+    pub fn bound(&self, pools: &[(f64, f64)], fees: &[f64], iterations: usize) -> Option<(f64, InputConstraint)> {
+        let mut bounds = Vec::new();
+        if self.max_price_impact > 0.0 {
+            let limit = crate::math::max_input_for_price_impact(pools, fees, self.max_price_impact, iterations);
+            bounds.push((limit, InputConstraint::PriceImpact));
+        }
+        if self.max_capital > 0.0 {
+            bounds.push((self.max_capital, InputConstraint::Capital));
+        }
+        if self.max_reserve_fraction > 0.0 && let Some(&(reserve_in, _)) = pools.first() {
+            bounds.push((reserve_in * self.max_reserve_fraction, InputConstraint::ReserveCap));
+        }
+
+        // min_by keeps the first of equal elements, so push order is the tie-break precedence
+        bounds.into_iter().min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+    }
+
+    /// Clamp an input to the limits, returning the constraint that bound it (if any)
+    pub fn apply(&self, input: f64, pools: &[(f64, f64)], fees: &[f64], iterations: usize) -> (f64, Option<InputConstraint>) {
+        match self.bound(pools, fees, iterations) {
+            Some((limit, constraint)) if input > limit => (limit, Some(constraint)),
+            _ => (input, None),
+        }
+    }
+}
+
+/// Flash-loan financing for inputs larger than the wallet holds
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FlashLoanTerms {
+    /// WMNT available without borrowing (0 = unlimited, never borrow)
+    pub wallet_balance: f64,
+    /// Fee on the borrowed amount, in basis points
+    pub fee_bps: f64,
+}
+
+impl FlashLoanTerms {
+    /// Fee (WMNT) for borrowing the part of `input` the wallet can't cover, or None if it can
+    pub fn fee_for(&self, input: f64) -> Option<f64> {
+        if self.wallet_balance <= 0.0 || input <= self.wallet_balance {
+            return None;
+        }
+        Some((input - self.wallet_balance) * self.fee_bps / 10_000.0)
     }
 }
 
 /// Path type classification
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum PathType {
     ThreeHop,
     FourHop,
+    /// Any other length, by hop count
     Custom(usize),
 }
 
+impl PathType {
+    /// Classify a route by its number of hops
+    pub fn from_hops(hops: usize) -> Self {
+        match hops {
+            3 => PathType::ThreeHop,
+            4 => PathType::FourHop,
+            hops => PathType::Custom(hops),
+        }
+    }
+
+    /// Number of hops (swaps) in the route
+    pub fn hops(&self) -> usize {
+        match self {
+            PathType::ThreeHop => 3,
+            PathType::FourHop => 4,
+            PathType::Custom(hops) => *hops,
+        }
+    }
+
+    /// Swap gas: the 3-hop cost plus `GAS_UNITS_PER_EXTRA_HOP` for each hop past three
+    pub fn gas_units(&self) -> u64 {
+        use crate::constants::{GAS_UNITS_3_HOPS, GAS_UNITS_PER_EXTRA_HOP};
+
+        GAS_UNITS_3_HOPS + self.hops().saturating_sub(3) as u64 * GAS_UNITS_PER_EXTRA_HOP
+    }
+}
+
+/// Breakdown of where time went during a multi-path analysis pass
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AnalysisTimings {
+    /// SPFA negative-cycle detection and path reconstruction
+    pub cycle_detection: Duration,
+    /// Wall-clock time of the (parallel) per-cycle analysis phase
+    pub cycle_analysis: Duration,
+    /// Sum of per-cycle analysis times across all worker threads
+    pub cycle_analysis_total: Duration,
+    /// Slowest single cycle analysis
+    pub cycle_analysis_max: Duration,
+    /// Sum of time spent converting cycles into pool reserves for the search
+    pub conversion_total: Duration,
+    /// Whole `find_all_opportunities` call
+    pub total: Duration,
+    pub cycles_analyzed: usize,
+    /// Cycles skipped this block because of the per-block cap
+    pub cycles_deferred: usize,
+    /// Cycle detection was skipped because no edge changed since the last search
+    pub cycles_reused: bool,
+}
+
 /// Multi-path arbitrage opportunity result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MultiPathOpportunity {
     pub opportunities: Vec<ArbitrageOpportunity>,
     pub best_opportunity: Option<ArbitrageOpportunity>,
     pub total_profit: f64,
     pub analysis_time_ms: u64,
+    pub timings: AnalysisTimings,
 }
 
 impl MultiPathOpportunity {
@@ -250,9 +646,16 @@ impl MultiPathOpportunity {
             best_opportunity,
             total_profit,
             analysis_time_ms,
+            timings: AnalysisTimings::default(),
         }
     }
 
+    /// Attach a detailed timing breakdown
+    pub fn with_timings(mut self, timings: AnalysisTimings) -> Self {
+        self.timings = timings;
+        self
+    }
+
     /// Get profitable opportunities only
     pub fn profitable_opportunities(&self) -> Vec<&ArbitrageOpportunity> {
         self.opportunities